@@ -20,7 +20,7 @@ pub struct DisasmView {
 
 impl DisasmView {
     pub fn new() -> Self {
-        let vfs = init_virtual_fs(
+        let (vfs, _) = init_virtual_fs(
             "F:\\SteamLibrary\\steamapps\\common\\Chinese Paladin 4",
             None,
         );
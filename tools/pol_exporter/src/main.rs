@@ -1,34 +1,307 @@
-use std::{io::Cursor, path::PathBuf};
+use std::{
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
 
-use fileformats::pol::read_pol;
-use shared::exporters::{obj_exporter::export_to_file, pol_obj_exporter::export_pol_to_obj};
+use anyhow::{anyhow, bail};
+use fileformats::{
+    binrw::BinRead,
+    mv3::read_mv3,
+    nif::NifModel,
+    pol::read_pol,
+    rwbs::{read_anm, read_bsp, read_dff},
+};
+use mini_fs::{LocalFs, MiniFs, StoreExt};
+use radiance::components::mesh::skinned_mesh::AnimKeyFrame;
+use shared::{
+    exporters::{
+        cvd_gltf_exporter::export_cvd_to_gltf,
+        gltf_exporter::GltfBuilder,
+        mv3_gltf_exporter::export_mv3_to_gltf,
+        mv3_obj_exporter::export_mv3_to_obj,
+        nif_gltf_exporter::export_nif_to_gltf,
+        obj_exporter::export_to_file,
+        pol_gltf_exporter::export_pol_to_gltf,
+        pol_obj_exporter::export_pol_to_obj,
+        rwbs_gltf_exporter::{export_bsp_to_gltf, export_dff_to_gltf},
+    },
+    fs::{init_virtual_fs, wildcard::expand},
+    loaders::anm::load_anm_action,
+    openpal3::loaders::cvd_loader::cvd_load_from_file,
+};
+
+const USAGE: &str = r#"Usage:
+    pol_exporter [options] <input>...
+
+Converts pol, cvd, mv3, dff, bsp and nif models. Inputs are paths relative to
+the root directory and may contain `*`, `?` and `**` wildcards.
+
+Options:
+    --format <obj|gltf|glb>   Output format, defaults to gltf. Only pol and
+                              mv3 models can be exported as obj.
+    --output <dir>            Output directory, defaults to the current one.
+    --root <dir>              Directory the inputs are relative to, defaults
+                              to the current one.
+    --game <dir>              Mounts the game folder with its packages as the
+                              root, so inputs are paths inside the archives.
+    --pkg-key <key>           Key of the encrypted packages (pal5, swd5...).
+    --anm <pattern>           Animations to attach to the dff models."#;
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Obj,
+    Gltf,
+    Glb,
+}
+
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Obj => "obj",
+            OutputFormat::Gltf => "gltf",
+            OutputFormat::Glb => "glb",
+        }
+    }
+}
+
+struct Options {
+    format: OutputFormat,
+    output: PathBuf,
+    root: PathBuf,
+    game: Option<PathBuf>,
+    pkg_key: Option<String>,
+    anm: Vec<String>,
+    inputs: Vec<String>,
+}
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 3 {
-        println!("使用方法: {} A.pol output.obj", args[0]);
-        return;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            println!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let vfs = match &options.game {
+        Some(game) => {
+            let (vfs, report) = init_virtual_fs(game, options.pkg_key.as_deref());
+            for record in report.failed() {
+                eprintln!(
+                    "warning: unable to mount {:?}: {:?}",
+                    record.path, record.status
+                );
+            }
+
+            vfs
+        }
+        None => MiniFs::new(false).mount("/", LocalFs::new(&options.root)),
+    };
+
+    let mut animations = vec![];
+    for pattern in &options.anm {
+        for path in expand(&vfs, pattern) {
+            match load_animations(&vfs, &path) {
+                Ok(anm) => animations.extend(anm),
+                Err(e) => eprintln!("warning: unable to load {:?}: {}", path, e),
+            }
+        }
+    }
+
+    let mut succeeded = 0;
+    let mut failed = vec![];
+    for pattern in &options.inputs {
+        let paths = expand(&vfs, pattern);
+        if paths.is_empty() {
+            failed.push((pattern.clone(), "no file matched".to_string()));
+            continue;
+        }
+
+        for path in paths {
+            let display = path.to_string_lossy().to_string();
+            match convert(&vfs, &path, &options, &animations) {
+                Ok(output) => {
+                    println!("ok      {} -> {}", display, output.to_string_lossy());
+                    succeeded += 1;
+                }
+                Err(e) => {
+                    println!("failed  {}: {}", display, e);
+                    failed.push((display, e.to_string()));
+                }
+            }
+        }
+    }
+
+    println!("{} converted, {} failed", succeeded, failed.len());
+    if !failed.is_empty() {
+        for (path, error) in &failed {
+            eprintln!("  {}: {}", path, error);
+        }
+
+        std::process::exit(1);
+    }
+}
+
+fn parse_args(args: &[String]) -> anyhow::Result<Options> {
+    let mut options = Options {
+        format: OutputFormat::Gltf,
+        output: PathBuf::from("."),
+        root: PathBuf::from("."),
+        game: None,
+        pkg_key: None,
+        anm: vec![],
+        inputs: vec![],
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| anyhow!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--format" => {
+                options.format = match value()?.to_lowercase().as_str() {
+                    "obj" => OutputFormat::Obj,
+                    "gltf" => OutputFormat::Gltf,
+                    "glb" => OutputFormat::Glb,
+                    f => bail!("unknown format {}", f),
+                }
+            }
+            "--output" => options.output = PathBuf::from(value()?),
+            "--root" => options.root = PathBuf::from(value()?),
+            "--game" => options.game = Some(PathBuf::from(value()?)),
+            "--pkg-key" => options.pkg_key = Some(value()?),
+            "--anm" => options.anm.push(value()?),
+            a if a.starts_with("--") => bail!("unknown option {}", a),
+            _ => options.inputs.push(arg.clone()),
+        }
+    }
+
+    if options.inputs.is_empty() {
+        bail!("no input");
+    }
+
+    Ok(options)
+}
+
+fn convert(
+    vfs: &MiniFs,
+    path: &Path,
+    options: &Options,
+    animations: &[(String, Vec<Vec<AnimKeyFrame>>)],
+) -> anyhow::Result<PathBuf> {
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let relative: PathBuf = path
+        .components()
+        .filter(|c| matches!(c, std::path::Component::Normal(_)))
+        .collect();
+    let output = options
+        .output
+        .join(relative)
+        .with_extension(options.format.extension());
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
     }
 
-    let data = std::fs::read(&args[1]).unwrap();
-    let mut reader = Cursor::new(data);
-    let pol = read_pol(&mut reader).unwrap();
+    let texture_loader = |texture_name: &str| load_texture(vfs, path, texture_name);
+    if options.format == OutputFormat::Obj {
+        let obj = match extension.as_str() {
+            "pol" => export_pol_to_obj(Some(&read_pol(&mut Cursor::new(read(vfs, path)?))?), &name),
+            "mv3" => export_mv3_to_obj(Some(&read_mv3(&mut Cursor::new(read(vfs, path)?))?), &name),
+            e => bail!("{} models can only be exported to glTF", e),
+        };
 
-    let name = PathBuf::from(&args[1])
-        .file_name()
-        .unwrap()
-        .to_string_lossy()
-        .to_string();
-    let obj = export_pol_to_obj(Some(&pol), &name);
-    if let Some(obj) = obj {
-        if let Ok(()) = export_to_file(&obj.0, &obj.1, &args[2]) {
-            println!("导出成功");
-            return;
+        let (obj, mtl) = obj.ok_or_else(|| anyhow!("the model has no mesh to export"))?;
+        export_to_file(&obj, &mtl, &output)?;
+        return Ok(output);
+    }
+
+    let builder: GltfBuilder = match extension.as_str() {
+        "pol" => {
+            let pol = read_pol(&mut Cursor::new(read(vfs, path)?))?;
+            export_pol_to_gltf(&pol, &name, &texture_loader)
+        }
+        "mv3" => {
+            let mv3 = read_mv3(&mut Cursor::new(read(vfs, path)?))?;
+            export_mv3_to_gltf(&mv3, &name, &texture_loader)
+        }
+        "cvd" => {
+            let cvd = cvd_load_from_file(vfs, path)?;
+            export_cvd_to_gltf(&cvd, &name, &texture_loader)
+        }
+        "dff" => {
+            let clumps = read_dff(&read(vfs, path)?)?;
+            export_dff_to_gltf(&clumps, &name, animations, &texture_loader)
+        }
+        "bsp" => {
+            let worlds = read_bsp(&read(vfs, path)?)?;
+            export_bsp_to_gltf(&worlds, &name, &texture_loader)
+        }
+        "nif" => {
+            let nif = NifModel::read(&mut Cursor::new(read(vfs, path)?))?;
+            export_nif_to_gltf(&nif, &name)
         }
+        e => bail!("unsupported model type {:?}", e),
+    };
 
-        println!("导出失败：无法导出 obj 文件");
-        return;
+    builder.write_to_file(&output)?;
+    Ok(output)
+}
+
+fn load_animations(
+    vfs: &MiniFs,
+    path: &Path,
+) -> anyhow::Result<Vec<(String, Vec<Vec<AnimKeyFrame>>)>> {
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let actions = read_anm(&read(vfs, path)?)?;
+    Ok(actions
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            let name = if actions.len() > 1 {
+                format!("{}_{}", name, i)
+            } else {
+                name.clone()
+            };
+            (name, load_anm_action(action))
+        })
+        .collect())
+}
+
+/// Looks for the texture next to the model, trying the usual image
+/// extensions when the referenced file doesn't exist.
+fn load_texture(vfs: &MiniFs, model_path: &Path, texture_name: &str) -> Option<Vec<u8>> {
+    if texture_name.is_empty() {
+        return None;
     }
 
-    println!("导出失败：转换为 obj 格式");
+    let path = model_path
+        .parent()
+        .unwrap_or(Path::new("/"))
+        .join(texture_name.replace('\\', "/"));
+    std::iter::once(path.clone())
+        .chain(["dds", "tga", "png"].iter().map(|e| path.with_extension(e)))
+        .find_map(|p| read(vfs, &p).ok())
+}
+
+fn read(vfs: &MiniFs, path: &Path) -> anyhow::Result<Vec<u8>> {
+    let mut data = vec![];
+    vfs.open(path)?.read_to_end(&mut data)?;
+    Ok(data)
 }
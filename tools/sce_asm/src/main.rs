@@ -0,0 +1,47 @@
+use std::io::{BufReader, Write};
+
+use shared::{
+    openpal3::loaders::sce_loader::{read_sce, write_sce},
+    scripting::sce::asm::{assemble, disassemble},
+};
+
+const USAGE: &str = r#"Usage:
+    sce-asm disasm <file.sce> [<output.txt>]
+    sce-asm asm <input.txt> <output.sce>"#;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.iter().map(|s| s.as_str()).collect::<Vec<&str>>()[..] {
+        ["disasm", input] => disasm(input, None),
+        ["disasm", input, output] => disasm(input, Some(output)),
+        ["asm", input, output] => asm(input, output),
+        _ => {
+            println!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {:?}", e);
+        std::process::exit(1);
+    }
+}
+
+fn disasm(input: &str, output: Option<&str>) -> anyhow::Result<()> {
+    let sce = read_sce(&mut BufReader::new(std::fs::File::open(input)?))?;
+    let text = disassemble(&sce);
+    match output {
+        Some(output) => std::fs::write(output, text)?,
+        None => std::io::stdout().lock().write_all(text.as_bytes())?,
+    }
+
+    Ok(())
+}
+
+fn asm(input: &str, output: &str) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(input)?;
+    let sce = assemble(&text)?;
+    write_sce(&sce, &mut std::fs::File::create(output)?)?;
+    eprintln!("{} proc(s) assembled", sce.proc_headers.len());
+    Ok(())
+}
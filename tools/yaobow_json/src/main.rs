@@ -0,0 +1,48 @@
+use std::{io::Write, path::Path};
+
+use fileformats::json::JsonFormat;
+
+const USAGE: &str = r#"Usage:
+    yaobow-json export <file.pol|nod|sce|dff|bsp|cam|npc|amf|bin> [<output.json>]
+    yaobow-json import <input.json> <output.pol|nod|sce|dff|bsp|cam|npc|amf|bin>"#;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.iter().map(|s| s.as_str()).collect::<Vec<&str>>()[..] {
+        ["export", input] => export(input, None),
+        ["export", input, output] => export(input, Some(output)),
+        ["import", input, output] => import(input, output),
+        _ => {
+            println!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {:?}", e);
+        std::process::exit(1);
+    }
+}
+
+fn format_of(path: &str) -> anyhow::Result<JsonFormat> {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| JsonFormat::from_extension(&ext.to_string_lossy()))
+        .ok_or_else(|| anyhow::anyhow!("unsupported file type: {}", path))
+}
+
+fn export(input: &str, output: Option<&str>) -> anyhow::Result<()> {
+    let json = format_of(input)?.to_json(&std::fs::read(input)?)?;
+    match output {
+        Some(output) => std::fs::write(output, json)?,
+        None => std::io::stdout().lock().write_all(json.as_bytes())?,
+    }
+
+    Ok(())
+}
+
+fn import(input: &str, output: &str) -> anyhow::Result<()> {
+    let data = format_of(output)?.from_json(&std::fs::read_to_string(input)?)?;
+    std::fs::write(output, data)?;
+    Ok(())
+}
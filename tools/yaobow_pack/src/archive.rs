@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use encoding::{EncoderTrap, Encoding};
+use shared::fs::{
+    cpk::{CpkArchive, CpkEntry},
+    fmb::fmb_archive::FmbArchive,
+    imd::imd_archive::ImdArchive,
+    pkg::pkg_archive::PkgArchive,
+    plain_fs::{PlainArchive, PlainFileInfo},
+    sfb::sfb_archive::SfbArchive,
+    shared_reader::create_shared_reader,
+    zpk::zpk_archive::ZpkArchive,
+    zpkg::{zpkg_archive::ZpkgArchive, zpkg_fs::map_cache_path},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum PackError {
+    #[error("Unsupported archive type: {0}")]
+    UnsupportedArchive(String),
+
+    #[error("A key is required to open pkg archives")]
+    PkgKeyRequired,
+
+    #[error("Size mismatch: expected {expected} bytes, got {actual}")]
+    SizeMismatch { expected: u64, actual: u64 },
+}
+
+/// Outcome of [`Archive::verify`] for an entry that could be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// The decompressed length matches the archive index.
+    Verified,
+
+    /// The entry was read but its length can't be checked against the index.
+    SizeUnchecked,
+}
+
+pub enum Archive {
+    Cpk(CpkArchive),
+    Pkg(PkgArchive),
+    Fmb(FmbArchive),
+    Imd(ImdArchive),
+    Sfb(SfbArchive),
+    Zpk(ZpkArchive),
+    Zpkg(ZpkgArchive),
+}
+
+impl Archive {
+    pub fn open<P: AsRef<Path>>(path: P, pkg_key: Option<&str>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let reader = create_shared_reader(path)?;
+        let archive = match ext.as_str() {
+            "cpk" => Archive::Cpk(CpkArchive::load(reader)?),
+            "pkg" => Archive::Pkg(PkgArchive::load(
+                reader,
+                pkg_key.ok_or(PackError::PkgKeyRequired)?,
+            )?),
+            "fmb" => Archive::Fmb(FmbArchive::load(reader)?),
+            "imd" => Archive::Imd(ImdArchive::load(reader)?),
+            "sfb" => Archive::Sfb(SfbArchive::load(reader)?),
+            "zpk" => Archive::Zpk(ZpkArchive::load(reader)?),
+            "zpkg" => {
+                let cache_content = std::fs::read(map_cache_path(path)?)?;
+                Archive::Zpkg(ZpkgArchive::load(reader, &cache_content)?)
+            }
+            _ => Err(PackError::UnsupportedArchive(ext))?,
+        };
+
+        Ok(archive)
+    }
+
+    /// Lists every file in the archive. Paths are always separated with `/`.
+    pub fn entries(&mut self) -> anyhow::Result<Vec<PlainFileInfo>> {
+        let mut entries = match self {
+            Archive::Cpk(cpk) => {
+                let mut entries = vec![];
+                let root = cpk.build_directory()?;
+                for child in root.children() {
+                    collect_cpk_entries(child, "", &mut entries);
+                }
+
+                entries
+            }
+            Archive::Pkg(pkg) => pkg
+                .entries
+                .file_entries
+                .iter()
+                .map(|e| PlainFileInfo {
+                    name: e.fullpath.trim_start_matches('\\').replace('\\', "/"),
+                    packed_size: e.size as u64,
+                    size: e.size2 as u64,
+                    is_compressed: e.size != e.size2,
+                })
+                .collect(),
+            Archive::Fmb(a) => a.file_infos(),
+            Archive::Imd(a) => a.file_infos(),
+            Archive::Sfb(a) => a.file_infos(),
+            Archive::Zpk(a) => a.file_infos(),
+            Archive::Zpkg(a) => a.file_infos(),
+        };
+
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    pub fn read(&mut self, path: &str) -> anyhow::Result<Vec<u8>> {
+        let file = match self {
+            Archive::Cpk(cpk) => {
+                // The crc hashes inside cpk are computed over lower-cased GBK paths
+                // with back-slash separators
+                let path = path.replace('/', "\\").to_lowercase();
+                let path = encoding::all::GBK
+                    .encode(&path, EncoderTrap::Ignore)
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
+                cpk.open(&path)?
+            }
+            Archive::Pkg(pkg) => pkg.open(path)?,
+            Archive::Fmb(a) => a.open(path)?,
+            Archive::Imd(a) => a.open(path)?,
+            Archive::Sfb(a) => a.open(path)?,
+            Archive::Zpk(a) => a.open(path)?,
+            Archive::Zpkg(a) => a.open(path)?,
+        };
+
+        Ok(file.content())
+    }
+
+    /// Reads and decompresses the entry, then validates its length against the
+    /// size recorded in the archive index.
+    pub fn verify(&mut self, entry: &PlainFileInfo) -> anyhow::Result<Verification> {
+        let data = self.read(&entry.name)?;
+        let expected = match self {
+            // Imd textures are decoded into RGBA on read, so the stored size
+            // doesn't match the output
+            Archive::Imd(_) => return Ok(Verification::SizeUnchecked),
+            _ => entry.size,
+        };
+
+        if data.len() as u64 != expected {
+            Err(PackError::SizeMismatch {
+                expected,
+                actual: data.len() as u64,
+            })?;
+        }
+
+        Ok(Verification::Verified)
+    }
+}
+
+fn collect_cpk_entries(entry: &CpkEntry, parent: &str, output: &mut Vec<PlainFileInfo>) {
+    let path = if parent.is_empty() {
+        entry.name().to_string()
+    } else {
+        format!("{}/{}", parent, entry.name())
+    };
+
+    if entry.is_dir() {
+        for child in entry.children() {
+            collect_cpk_entries(child, &path, output);
+        }
+    } else {
+        output.push(PlainFileInfo {
+            name: path,
+            packed_size: entry.packed_size() as u64,
+            size: entry.original_size() as u64,
+            is_compressed: entry.is_compressed(),
+        });
+    }
+}
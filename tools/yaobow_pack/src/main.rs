@@ -0,0 +1,193 @@
+use std::{
+    io::BufReader,
+    path::{Component, Path, PathBuf},
+};
+
+use archive::{Archive, Verification};
+use shared::fs::{init_virtual_fs, manifest::Manifest};
+
+mod archive;
+
+const USAGE: &str = r#"Usage:
+    yaobow-pack list <archive> [--pkg-key <key>]
+    yaobow-pack extract <archive> <output_dir> [<entry>] [--pkg-key <key>]
+    yaobow-pack verify <archive> [--pkg-key <key>]
+    yaobow-pack manifest <game_dir> [<output>] [--pkg-key <key>]
+    yaobow-pack diff <manifest> <manifest>
+
+Supported archives: cpk, pkg, fmb, imd, sfb, zpk, zpkg"#;
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let pkg_key = take_option(&mut args, "--pkg-key");
+
+    let result = match args.iter().map(|s| s.as_str()).collect::<Vec<&str>>()[..] {
+        ["list", archive] => list(archive, pkg_key.as_deref()),
+        ["extract", archive, output] => extract(archive, output, None, pkg_key.as_deref()),
+        ["extract", archive, output, entry] => {
+            extract(archive, output, Some(entry), pkg_key.as_deref())
+        }
+        ["verify", archive] => verify(archive, pkg_key.as_deref()),
+        ["manifest", game_dir] => manifest(game_dir, None, pkg_key.as_deref()),
+        ["manifest", game_dir, output] => manifest(game_dir, Some(output), pkg_key.as_deref()),
+        ["diff", old, new] => diff(old, new),
+        _ => {
+            println!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("error: {:?}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn list(archive_path: &str, pkg_key: Option<&str>) -> anyhow::Result<bool> {
+    let mut archive = Archive::open(archive_path, pkg_key)?;
+    let entries = archive.entries()?;
+
+    println!("{:>12} {:>12}  {}  {}", "packed", "size", "c", "path");
+    for entry in &entries {
+        println!(
+            "{:>12} {:>12}  {}  {}",
+            entry.packed_size,
+            entry.size,
+            if entry.is_compressed { "*" } else { " " },
+            entry.name
+        );
+    }
+
+    println!("{} file(s)", entries.len());
+    Ok(true)
+}
+
+fn extract(
+    archive_path: &str,
+    output_dir: &str,
+    entry: Option<&str>,
+    pkg_key: Option<&str>,
+) -> anyhow::Result<bool> {
+    let mut archive = Archive::open(archive_path, pkg_key)?;
+    let names = match entry {
+        Some(entry) => vec![entry.to_string()],
+        None => archive.entries()?.into_iter().map(|e| e.name).collect(),
+    };
+
+    let mut succeeded = true;
+    for name in &names {
+        if let Err(e) = extract_one(&mut archive, name, Path::new(output_dir)) {
+            eprintln!("failed: {}: {:?}", name, e);
+            succeeded = false;
+        } else {
+            println!("{}", name);
+        }
+    }
+
+    Ok(succeeded)
+}
+
+fn extract_one(archive: &mut Archive, name: &str, output_dir: &Path) -> anyhow::Result<()> {
+    // Entry names come from the archive, don't let them escape the output directory
+    let relative = Path::new(name.trim_start_matches('/'));
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        anyhow::bail!("Refusing to extract unsafe path: {}", name);
+    }
+
+    let data = archive.read(name)?;
+    let output: PathBuf = output_dir.join(relative);
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(output, data)?;
+    Ok(())
+}
+
+fn verify(archive_path: &str, pkg_key: Option<&str>) -> anyhow::Result<bool> {
+    let mut archive = Archive::open(archive_path, pkg_key)?;
+    let entries = archive.entries()?;
+
+    let mut failures = 0;
+    let mut unchecked = 0;
+    for entry in &entries {
+        match archive.verify(entry) {
+            Ok(Verification::Verified) => {}
+            Ok(Verification::SizeUnchecked) => {
+                println!("SKIP {}: decoded, size not checked", entry.name);
+                unchecked += 1;
+            }
+            Err(e) => {
+                println!("FAIL {}: {}", entry.name, e);
+                failures += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} file(s) checked, {} failure(s), {} size check(s) skipped",
+        entries.len(),
+        failures,
+        unchecked
+    );
+    Ok(failures == 0)
+}
+
+fn manifest(game_dir: &str, output: Option<&str>, pkg_key: Option<&str>) -> anyhow::Result<bool> {
+    let (vfs, report) = init_virtual_fs(game_dir, pkg_key);
+    for record in report.failed() {
+        eprintln!("failed to mount: {:?}", record.path);
+    }
+
+    let manifest = Manifest::build(&vfs, "/");
+    match output {
+        Some(output) => manifest.write(&mut std::fs::File::create(output)?)?,
+        None => manifest.write(&mut std::io::stdout().lock())?,
+    }
+
+    eprintln!("{} file(s) hashed", manifest.entries.len());
+    Ok(!report.has_failures())
+}
+
+fn diff(old_path: &str, new_path: &str) -> anyhow::Result<bool> {
+    let old = Manifest::read(&mut BufReader::new(std::fs::File::open(old_path)?))?;
+    let new = Manifest::read(&mut BufReader::new(std::fs::File::open(new_path)?))?;
+    let diff = old.diff(&new);
+
+    for path in &diff.added {
+        println!("+ {}", path);
+    }
+
+    for path in &diff.removed {
+        println!("- {}", path);
+    }
+
+    for path in &diff.changed {
+        println!("M {}", path);
+    }
+
+    println!(
+        "{} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+    Ok(diff.is_empty())
+}
+
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let index = args.iter().position(|a| a == name)?;
+    args.remove(index);
+    if index < args.len() {
+        Some(args.remove(index))
+    } else {
+        None
+    }
+}
@@ -1,17 +1,32 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 use encoding::{types::Encoding, DecoderTrap};
-use std::io::Read;
+use std::io::{ErrorKind, Read};
 
 #[derive(thiserror::Error, Debug)]
 pub enum FileReadError {
     #[error("Decode Error")]
     StringDecodeError,
+
+    #[error("Count {count} exceeds the remaining {remaining} bytes")]
+    CountTooLarge { count: u64, remaining: u64 },
+
+    #[error("Chunk size {size} exceeds the remaining {remaining} bytes")]
+    ChunkTooLarge { size: u64, remaining: u64 },
+
+    #[error("Index {index} is out of range, the length is {len}")]
+    IndexOutOfRange { index: u64, len: u64 },
 }
 
 pub trait ReadExt: Read {
     fn skip(&mut self, size: usize) -> std::io::Result<()> {
-        let mut buf = vec![0u8; size];
-        self.read_exact(&mut buf)?;
+        let skipped = std::io::copy(
+            &mut Read::take(&mut *self, size as u64),
+            &mut std::io::sink(),
+        )?;
+        if skipped != size as u64 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+
         Ok(())
     }
 
@@ -23,27 +38,36 @@ pub trait ReadExt: Read {
         self.read_u16::<LittleEndian>()
     }
 
+    /// The buffer grows while reading, so a bogus size coming from a broken
+    /// file fails with `UnexpectedEof` instead of allocating the whole size.
     fn read_u8_vec(&mut self, size: usize) -> std::io::Result<Vec<u8>> {
-        let mut buf = vec![0u8; size];
-        self.read_exact(&mut buf.as_mut_slice())?;
+        let mut buf = vec![];
+        Read::take(&mut *self, size as u64).read_to_end(&mut buf)?;
+        if buf.len() != size {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+
         Ok(buf)
     }
 
     fn read_dw_vec(&mut self, count: usize) -> std::io::Result<Vec<u32>> {
+        let bytes = self.read_u8_vec(byte_size(count, 4)?)?;
         let mut buf = vec![0u32; count];
-        self.read_u32_into::<LittleEndian>(&mut buf)?;
+        LittleEndian::read_u32_into(&bytes, &mut buf);
         Ok(buf)
     }
 
     fn read_w_vec(&mut self, count: usize) -> std::io::Result<Vec<u16>> {
+        let bytes = self.read_u8_vec(byte_size(count, 2)?)?;
         let mut buf = vec![0u16; count];
-        self.read_u16_into::<LittleEndian>(&mut buf)?;
+        LittleEndian::read_u16_into(&bytes, &mut buf);
         Ok(buf)
     }
 
     fn read_f32_vec(&mut self, count: usize) -> std::io::Result<Vec<f32>> {
+        let bytes = self.read_u8_vec(byte_size(count, 4)?)?;
         let mut buf = vec![0f32; count];
-        self.read_f32_into::<LittleEndian>(&mut buf)?;
+        LittleEndian::read_f32_into(&bytes, &mut buf);
         Ok(buf)
     }
 
@@ -81,3 +105,9 @@ pub trait ReadExt: Read {
 }
 
 impl<T: Read + ?Sized> ReadExt for T {}
+
+fn byte_size(count: usize, element_size: usize) -> std::io::Result<usize> {
+    count
+        .checked_mul(element_size)
+        .ok_or_else(|| ErrorKind::UnexpectedEof.into())
+}
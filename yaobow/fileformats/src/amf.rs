@@ -1,15 +1,18 @@
 use binrw::binrw;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::SizedString;
+use crate::utils::{checked_count, SizedString};
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AmfFile {
     header: SizedString,
+
+    #[bw(calc(data.len() as u32))]
     count: u32,
 
-    #[br(count = count)]
+    #[br(parse_with = checked_count(count))]
     data: Vec<AmfEvent>,
 }
 
@@ -21,20 +24,28 @@ impl AmfFile {
     pub fn events(&self) -> &Vec<AmfEvent> {
         &self.data
     }
+
+    pub fn events_mut(&mut self) -> &mut Vec<AmfEvent> {
+        &mut self.data
+    }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AmfEvent {
+    #[serde(with = "crate::utils::fixed_string")]
     model_name: [u8; 0x3c],
     unknown_cd: u32,
+    #[serde(with = "crate::utils::fixed_string")]
     action_name: [u8; 0x3c],
     unknown_cd2: u32,
+    #[serde(with = "crate::utils::fixed_string")]
     event_name: [u8; 0x3c],
     unknown_cd3: u32,
     tick: f32,
     unknown: u32,
+    #[serde(with = "crate::utils::fixed_bytes")]
     unknown_rest: [u8; 0x84],
 }
 
@@ -53,4 +64,8 @@ impl AmfEvent {
     pub fn get_tick(&self) -> f32 {
         self.tick
     }
+
+    pub fn set_tick(&mut self, tick: f32) {
+        self.tick = tick;
+    }
 }
@@ -1,11 +1,13 @@
 use binrw::BinRead;
 
+use crate::utils::checked_count;
+
 #[derive(Debug, BinRead)]
 #[brw(little)]
 pub struct C00 {
     pub header: C00Header,
 
-    #[br(count = header.packed_size as usize)]
+    #[br(parse_with = checked_count(header.packed_size))]
     pub data: Vec<u8>,
 }
 
@@ -1,32 +1,32 @@
 use binrw::binrw;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::{Pal4NodeSection, SizedString};
+use crate::utils::{checked_count, Pal4NodeSection, SizedString};
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CameraDataFile {
+    #[bw(calc(data.len() as u32))]
     count: u32,
 
-    #[br(count = count)]
+    #[br(parse_with = checked_count(count))]
     data: Vec<CameraData>,
 }
 
 impl CameraDataFile {
     pub fn get_camera_data(&self, name: &str) -> Option<&CameraData> {
-        self.data.iter().find(|d| {
-            if d.name.data().last() == Some(&0) {
-                &d.name.data()[..d.name.data().len() - 1] == name.as_bytes()
-            } else {
-                d.name.data() == name.as_bytes()
-            }
-        })
+        self.data.iter().find(|d| d.name_equals(name))
+    }
+
+    pub fn get_camera_data_mut(&mut self, name: &str) -> Option<&mut CameraData> {
+        self.data.iter_mut().find(|d| d.name_equals(name))
     }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CameraData {
     name: SizedString,
 
@@ -43,24 +43,61 @@ pub struct CameraData {
 }
 
 impl CameraData {
+    fn name_equals(&self, name: &str) -> bool {
+        if self.name.data().last() == Some(&0) {
+            &self.name.data()[..self.name.data().len() - 1] == name.as_bytes()
+        } else {
+            self.name.data() == name.as_bytes()
+        }
+    }
+
     pub fn get_look_at(&self) -> [f32; 3] {
         self.look_at
     }
 
-    pub fn get_position(&self) -> [f32; 3] {
-        let p = &self.data.root.as_ref().unwrap().children[0].properties;
-        [
-            p[0].f32().unwrap(),
-            p[1].f32().unwrap(),
-            p[2].f32().unwrap(),
-        ]
+    /// `None` when the node section doesn't hold a position.
+    pub fn get_position(&self) -> Option<[f32; 3]> {
+        let p = &self.data.root.as_ref()?.children.first()?.properties;
+        Some([p.first()?.f32()?, p.get(1)?.f32()?, p.get(2)?.f32()?])
+    }
+
+    pub fn set_look_at(&mut self, look_at: [f32; 3]) {
+        self.look_at = look_at;
+    }
+
+    /// Returns false and leaves the data untouched when the node section
+    /// doesn't hold a position.
+    pub fn set_position(&mut self, position: [f32; 3]) -> bool {
+        if self.get_position().is_none() {
+            return false;
+        }
+
+        let p = match self.data.root.as_mut().and_then(|r| r.children.first_mut()) {
+            Some(node) => &mut node.properties,
+            None => return false,
+        };
+        for (property, value) in p.iter_mut().zip(position) {
+            if let Some(v) = property.f32_mut() {
+                *v = value;
+            }
+        }
+
+        true
     }
 
     pub fn speed(&self) -> f32 {
         self.speed
     }
 
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
     pub fn is_instant(&self) -> bool {
         self.is_instant != 0
     }
+
+    pub fn set_instant(&mut self, is_instant: bool) {
+        self.is_instant = is_instant as i32;
+    }
 }
@@ -0,0 +1,315 @@
+use std::io::{Read, Seek};
+
+use binrw::{binread, BinRead, BinResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rwbs::{Quaternion, TexCoord, Vec3f},
+    utils::{check_count, checked_count, gbk_string_parser},
+};
+
+/// Positions are converted from the z-up coordinate system of the file.
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdVertex {
+    pub tex_coord: TexCoord,
+    pub normal: Vec3f,
+
+    #[br(map = |p: Vec3f| Vec3f { x: p.x, y: p.z, z: -p.y })]
+    pub position: Vec3f,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdTriangle {
+    pub indices: [u16; 3],
+}
+
+#[binread]
+#[br(little, import(unknown_float: f32))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdMaterial {
+    pub unknown_byte: u8,
+    pub color1: u32,
+    pub color2: u32,
+    pub color3: u32,
+    pub color4: u32,
+    pub unknown_float2: f32,
+
+    #[br(parse_with = gbk_string_parser, args(64))]
+    pub texture_name: String,
+    pub triangle_count: u32,
+
+    #[br(parse_with = non_empty_triangles_parser, args(triangle_count))]
+    pub triangles: Option<Vec<CvdTriangle>>,
+
+    #[br(if(unknown_float >= 0.5))]
+    pub unknown_data2_count: u32,
+
+    #[br(parse_with = checked_count(unknown_data2_count))]
+    pub unknown_data2: Vec<u32>,
+
+    #[br(parse_with = checked_count(unknown_data2_count))]
+    pub unknown_data3: Vec<[u8; 20]>,
+}
+
+#[binread]
+#[br(little, import(unknown_float: f32))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdMesh {
+    pub frame_count: u32,
+    pub vertex_count: u32,
+
+    #[br(parse_with = cvd_frames_parser, args(frame_count, vertex_count))]
+    pub frames: Vec<Vec<CvdVertex>>,
+
+    #[br(parse_with = checked_count(frame_count))]
+    pub unknown_data: Vec<f32>,
+    pub material_count: u32,
+
+    #[br(parse_with = checked_count(material_count), args(unknown_float))]
+    pub materials: Vec<CvdMaterial>,
+}
+
+#[binread]
+#[br(little, import(version: u8))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvdPositionKeyFrame {
+    pub timestamp: f32,
+    pub unknown1: f32,
+    pub unknown2: f32,
+    pub unknown3: f32,
+    pub unknown4: f32,
+    pub unknown5: f32,
+    pub unknown6: f32,
+    pub unknown7: f32,
+    pub unknown8: f32,
+    pub unknown9: f32,
+    pub unknown10: f32,
+
+    #[br(calc = match version {
+        1 => to_y_up(unknown7, unknown8, unknown9),
+        2 => to_y_up(unknown8, unknown9, unknown10),
+        _ => to_y_up(unknown2, unknown3, unknown4),
+    })]
+    pub position: Vec3f,
+}
+
+#[binread]
+#[br(little, import(count: usize))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvdPositionKeyFrames {
+    #[br(assert(
+        (1..=3).contains(&version),
+        "unsupported position keyframes version {}",
+        version
+    ))]
+    pub version: u8,
+
+    #[br(parse_with = checked_count(count), args(version))]
+    pub frames: Vec<CvdPositionKeyFrame>,
+}
+
+#[binread]
+#[br(little, import(version: u8))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvdRotationKeyFrame {
+    pub timestamp: f32,
+    pub unknown1: f32,
+    pub unknown2: f32,
+    pub unknown3: f32,
+    pub unknown4: f32,
+    pub unknown5: f32,
+    pub unknown6: f32,
+    pub unknown7: f32,
+    pub unknown8: f32,
+    pub unknown9: f32,
+    pub unknown10: f32,
+
+    #[br(calc = if version == 1 {
+        quaternion_from_axis_angle(unknown7, unknown8, unknown9, unknown10)
+    } else {
+        quaternion_to_y_up(unknown2, unknown3, unknown4, unknown5)
+    })]
+    pub quaternion: Quaternion,
+}
+
+#[binread]
+#[br(little, import(count: usize))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvdRotationKeyFrames {
+    #[br(assert(
+        (1..=3).contains(&version),
+        "unsupported rotation keyframes version {}",
+        version
+    ))]
+    pub version: u8,
+
+    #[br(parse_with = checked_count(count), args(version))]
+    pub frames: Vec<CvdRotationKeyFrame>,
+}
+
+#[binread]
+#[br(little, import(version: u8))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvdScaleKeyFrame {
+    pub timestamp: f32,
+    pub unknown: [f32; 14],
+
+    #[br(calc = {
+        let q = match version {
+            1 => &unknown[9..13],
+            2 => &unknown[10..14],
+            _ => &unknown[4..8],
+        };
+        quaternion_to_y_up(q[0], q[1], q[2], q[3])
+    })]
+    pub quaternion: Quaternion,
+
+    #[br(calc = {
+        let s = match version {
+            1 => &unknown[6..9],
+            2 => &unknown[7..10],
+            _ => &unknown[1..4],
+        };
+        Vec3f { x: s[0], y: s[2], z: s[1] }
+    })]
+    pub scale: Vec3f,
+}
+
+#[binread]
+#[br(little, import(count: usize))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CvdScaleKeyFrames {
+    #[br(assert(
+        (1..=3).contains(&version),
+        "unsupported scale keyframes version {}",
+        version
+    ))]
+    pub version: u8,
+
+    #[br(parse_with = checked_count(count), args(version))]
+    pub frames: Vec<CvdScaleKeyFrame>,
+}
+
+#[binread]
+#[br(little, import(unknown_byte: u8, unknown_float: f32))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdModel {
+    #[br(calc = unknown_byte)]
+    pub unknown_byte: u8,
+
+    #[br(temp)]
+    position_count: i32,
+
+    #[br(if(position_count > 0), args(position_count as usize))]
+    pub position_keyframes: Option<CvdPositionKeyFrames>,
+
+    #[br(temp)]
+    rotation_count: i32,
+
+    #[br(if(rotation_count > 0), args(rotation_count as usize))]
+    pub rotation_keyframes: Option<CvdRotationKeyFrames>,
+
+    #[br(temp)]
+    scale_count: i32,
+
+    #[br(if(scale_count > 0), args(scale_count as usize))]
+    pub scale_keyframes: Option<CvdScaleKeyFrames>,
+    pub scale_factor: f32,
+
+    #[br(args(unknown_float))]
+    pub mesh: CvdMesh,
+    pub matrix: [f32; 16],
+}
+
+#[binread]
+#[br(little, import(unknown_float: f32))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdModelNode {
+    #[br(temp)]
+    unknown_byte: u8,
+
+    #[br(if(unknown_byte > 0), args(unknown_byte, unknown_float))]
+    pub model: Option<CvdModel>,
+
+    #[br(temp)]
+    children_count: u32,
+
+    #[br(parse_with = cvd_children_parser, args(children_count, unknown_float))]
+    pub children: Option<Vec<CvdModelNode>>,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CvdFile {
+    #[br(assert(&magic == b"cvds" || &magic == b"cvdf", "not a valid cvd file"))]
+    pub magic: [u8; 4],
+    pub model_count: u32,
+
+    #[br(
+        parse_with = checked_count(model_count),
+        args(if &magic == b"cvds" { 0.5 } else { 0.4 })
+    )]
+    pub models: Vec<CvdModelNode>,
+}
+
+pub fn read_cvd(reader: &mut (impl Read + Seek)) -> BinResult<CvdFile> {
+    CvdFile::read(reader)
+}
+
+#[binrw::parser(reader, endian)]
+fn cvd_frames_parser(frame_count: u32, vertex_count: u32) -> BinResult<Vec<Vec<CvdVertex>>> {
+    check_count(reader, frame_count as u64 * vertex_count as u64)?;
+    (0..frame_count)
+        .map(|_| {
+            (0..vertex_count)
+                .map(|_| CvdVertex::read_options(reader, endian, ()))
+                .collect()
+        })
+        .collect()
+}
+
+#[binrw::parser(reader, endian)]
+fn non_empty_triangles_parser(count: u32) -> BinResult<Option<Vec<CvdTriangle>>> {
+    if count == 0 {
+        return Ok(None);
+    }
+
+    check_count(reader, count as u64)?;
+
+    (0..count)
+        .map(|_| CvdTriangle::read_options(reader, endian, ()))
+        .collect::<BinResult<_>>()
+        .map(Some)
+}
+
+#[binrw::parser(reader, endian)]
+fn cvd_children_parser(count: u32, unknown_float: f32) -> BinResult<Option<Vec<CvdModelNode>>> {
+    if count == 0 {
+        return Ok(None);
+    }
+
+    check_count(reader, count as u64)?;
+
+    (0..count)
+        .map(|_| CvdModelNode::read_options(reader, endian, (unknown_float,)))
+        .collect::<BinResult<_>>()
+        .map(Some)
+}
+
+fn to_y_up(x: f32, y: f32, z: f32) -> Vec3f {
+    Vec3f { x, y: z, z: -y }
+}
+
+fn quaternion_to_y_up(x: f32, y: f32, z: f32, w: f32) -> Quaternion {
+    Quaternion { x, y: z, z: -y, w }
+}
+
+fn quaternion_from_axis_angle(x: f32, y: f32, z: f32, angle: f32) -> Quaternion {
+    let (sin_half, cos_half) = (angle / 2.).sin_cos();
+    quaternion_to_y_up(x * sin_half, y * sin_half, z * sin_half, cos_half)
+}
@@ -0,0 +1,80 @@
+use std::io::Cursor;
+
+use binrw::{BinRead, BinWrite};
+
+use crate::{
+    amf::AmfFile,
+    cam::CameraDataFile,
+    nod::NodFile,
+    npc::NpcInfoFile,
+    pol::{read_pol, write_pol},
+    role_bin::RoleBinFile,
+    rwbs::{read_bsp, read_dff, write_bsp, write_dff},
+    sce::{read_sce, write_sce},
+};
+
+/// Formats that can be exported to json and rebuilt from it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JsonFormat {
+    Pol,
+    Nod,
+    Sce,
+    Dff,
+    Bsp,
+    Cam,
+    Npc,
+    Amf,
+    /// The Pal5 `role_xx.bin` asset tables.
+    RoleBin,
+}
+
+impl JsonFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "pol" => Some(Self::Pol),
+            "nod" => Some(Self::Nod),
+            "sce" => Some(Self::Sce),
+            "dff" => Some(Self::Dff),
+            "bsp" => Some(Self::Bsp),
+            "cam" => Some(Self::Cam),
+            "npc" => Some(Self::Npc),
+            "amf" => Some(Self::Amf),
+            "bin" => Some(Self::RoleBin),
+            _ => None,
+        }
+    }
+
+    pub fn to_json(&self, data: &[u8]) -> anyhow::Result<String> {
+        let mut reader = Cursor::new(data);
+        let json = match self {
+            Self::Pol => serde_json::to_string_pretty(&read_pol(&mut reader)?)?,
+            Self::Nod => serde_json::to_string_pretty(&NodFile::read(&mut reader)?)?,
+            Self::Sce => serde_json::to_string_pretty(&read_sce(&mut reader)?)?,
+            Self::Dff => serde_json::to_string_pretty(&read_dff(data)?)?,
+            Self::Bsp => serde_json::to_string_pretty(&read_bsp(data)?)?,
+            Self::Cam => serde_json::to_string_pretty(&CameraDataFile::read(&mut reader)?)?,
+            Self::Npc => serde_json::to_string_pretty(&NpcInfoFile::read(&mut reader)?)?,
+            Self::Amf => serde_json::to_string_pretty(&AmfFile::read(&mut reader)?)?,
+            Self::RoleBin => serde_json::to_string_pretty(&RoleBinFile::read(&mut reader)?)?,
+        };
+
+        Ok(json)
+    }
+
+    pub fn from_json(&self, json: &str) -> anyhow::Result<Vec<u8>> {
+        let mut writer = Cursor::new(vec![]);
+        match self {
+            Self::Pol => write_pol(&serde_json::from_str(json)?, &mut writer)?,
+            Self::Nod => serde_json::from_str::<NodFile>(json)?.write(&mut writer)?,
+            Self::Sce => write_sce(&serde_json::from_str(json)?, &mut writer)?,
+            Self::Dff => return write_dff(&serde_json::from_str::<Vec<_>>(json)?),
+            Self::Bsp => return write_bsp(&serde_json::from_str::<Vec<_>>(json)?),
+            Self::Cam => serde_json::from_str::<CameraDataFile>(json)?.write(&mut writer)?,
+            Self::Npc => serde_json::from_str::<NpcInfoFile>(json)?.write(&mut writer)?,
+            Self::Amf => serde_json::from_str::<AmfFile>(json)?.write(&mut writer)?,
+            Self::RoleBin => serde_json::from_str::<RoleBinFile>(json)?.write(&mut writer)?,
+        }
+
+        Ok(writer.into_inner())
+    }
+}
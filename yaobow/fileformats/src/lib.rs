@@ -3,13 +3,18 @@
 pub mod amf;
 pub mod c00;
 pub mod cam;
+pub mod cvd;
+pub mod json;
 pub mod mv3;
+pub mod nav;
 pub mod nif;
 pub mod nod;
 pub mod npc;
 pub mod pol;
 pub mod role_bin;
 pub mod rwbs;
+pub mod sce;
+pub mod scn;
 pub mod utils;
 
 pub use binrw;
@@ -1,13 +1,13 @@
 use binrw::BinRead;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::io::{Read, Seek};
 
 use crate::{
     rwbs::TexCoord,
-    utils::{SizedString, StringWithCapacity},
+    utils::{checked_count, checked_indices, SizedString, StringWithCapacity},
 };
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little)]
 pub struct Mv3Texture {
     #[br(count = 17)]
@@ -16,53 +16,55 @@ pub struct Mv3Texture {
     pub names: Vec<SizedString>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little)]
 pub struct Mv3Vertex {
-    #[br(map = |v: i16| -v)]
+    #[br(map = |v: i16| v.wrapping_neg())]
     pub x: i16,
     pub y: i16,
-    #[br(map = |v: i16| -v)]
+    #[br(map = |v: i16| v.wrapping_neg())]
     pub z: i16,
     pub normal_phi: i8,
     pub normal_theta: u8,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little, import(count: u32))]
 pub struct Mv3Frame {
     pub timestamp: u32,
-    #[br(count = count)]
+    #[br(parse_with = checked_count(count))]
     pub vertices: Vec<Mv3Vertex>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
-#[brw(little)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
+#[brw(little, import(vertex_count: u32))]
 pub struct Mv3Triangle {
+    #[br(parse_with = checked_indices(vertex_count))]
     pub indices: [u16; 3],
+    /// Not checked, retail files contain texcoord indices past the end.
     pub texcoord_indices: [u16; 3],
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little)]
 pub struct Mv3UnknownDataInMesh {
     pub u: u16,
     pub v: u16,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
-#[brw(little)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
+#[brw(little, import(vertex_count: u32))]
 pub struct Mv3Mesh {
     pub unknown: u32,
     pub triangle_count: u32,
-    #[br(count = triangle_count)]
+    #[br(parse_with = checked_count(triangle_count), args(vertex_count))]
     pub triangles: Vec<Mv3Triangle>,
     pub unknown_data_count: u32,
-    #[br(count = unknown_data_count)]
+    #[br(parse_with = checked_count(unknown_data_count))]
     pub unknown_data: Vec<Mv3UnknownDataInMesh>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little)]
 pub struct Mv3Model {
     #[br(count = 64)]
@@ -71,17 +73,17 @@ pub struct Mv3Model {
     pub aabb_min: [f32; 3],
     pub aabb_max: [f32; 3],
     pub frame_count: u32,
-    #[br(count = frame_count, args { inner: (vertex_per_frame,) })]
+    #[br(parse_with = checked_count(frame_count), args(vertex_per_frame))]
     pub frames: Vec<Mv3Frame>,
     pub texcoord_count: u32,
-    #[br(count = texcoord_count)]
+    #[br(parse_with = checked_count(texcoord_count))]
     pub texcoords: Vec<TexCoord>,
     pub mesh_count: u32,
-    #[br(count = mesh_count)]
+    #[br(parse_with = checked_count(mesh_count), args(vertex_per_frame))]
     pub meshes: Vec<Mv3Mesh>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little)]
 pub struct Mv3ActionDesc {
     pub tick: u32,
@@ -89,18 +91,18 @@ pub struct Mv3ActionDesc {
     pub name: StringWithCapacity,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little)]
 pub struct Mv3UnknownDataInFile {
     #[br(count = 64)]
     pub unknown0: Vec<u8>,
     pub unknown1: u32,
     pub unknown2_count: u32,
-    #[br(count = unknown2_count)]
+    #[br(parse_with = checked_count(unknown2_count))]
     pub unknown2: Vec<[f32; 17]>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[derive(BinRead, Debug, Serialize, Deserialize, Clone)]
 #[brw(little, magic = b"MV3\0")]
 pub struct Mv3File {
     pub version: u32,
@@ -111,13 +113,13 @@ pub struct Mv3File {
     pub model_count: u32,
     pub action_count: u32,
 
-    #[br(count = action_count)]
+    #[br(parse_with = checked_count(action_count))]
     pub action_desc: Vec<Mv3ActionDesc>,
-    #[br(count = unknown_data_count)]
+    #[br(parse_with = checked_count(unknown_data_count))]
     pub unknown_data: Vec<Mv3UnknownDataInFile>,
-    #[br(count = texture_count)]
+    #[br(parse_with = checked_count(texture_count))]
     pub textures: Vec<Mv3Texture>,
-    #[br(count = model_count)]
+    #[br(parse_with = checked_count(model_count))]
     pub models: Vec<Mv3Model>,
 }
 
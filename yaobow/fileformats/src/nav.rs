@@ -0,0 +1,113 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use binrw::{binread, BinRead, BinResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rwbs::Vec3f,
+    utils::{check_count, checked_count},
+};
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct NavMapPoint {
+    pub height: f32,
+    pub distance_to_border: u32,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NavUnknown22 {
+    pub unknown1: u16,
+    pub unknown2: u16,
+    pub unknown3: u16,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NavLayerTrigger {
+    pub nav_coord_min: (i32, i32),
+    pub nav_coord_max: (i32, i32),
+}
+
+#[binread]
+#[br(little, import(version: u32))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NavMap {
+    #[br(if(version == 2))]
+    pub layer_triggers: Option<[NavLayerTrigger; 8]>,
+    pub max_coord: Vec3f,
+    pub min_coord: Vec3f,
+    pub width: u32,
+    pub height: u32,
+
+    #[br(parse_with = nav_map_parser, args(width, height))]
+    pub map: Vec<Vec<NavMapPoint>>,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NavUnknown2 {
+    #[br(temp)]
+    count1: u16,
+
+    #[br(temp)]
+    count2: u16,
+
+    #[br(parse_with = checked_count(count1))]
+    pub unknown21: Vec<Vec3f>,
+
+    #[br(parse_with = checked_count(count2))]
+    pub unknown22: Vec<NavUnknown22>,
+}
+
+#[binread]
+#[br(little, magic = b"NAV\0")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NavFile {
+    #[br(
+        map = |v: u8| v as u32,
+        assert(version == 1 || version == 2, "nav version should be 1 or 2, found {}", version)
+    )]
+    pub version: u32,
+
+    #[br(temp)]
+    count: u8,
+
+    #[br(temp)]
+    maps_offset: u32,
+
+    #[br(temp)]
+    unknown2_offset: u32,
+
+    #[br(
+        seek_before = SeekFrom::Start(maps_offset as u64),
+        parse_with = checked_count(count),
+        args(version)
+    )]
+    pub maps: Vec<NavMap>,
+
+    #[br(seek_before = SeekFrom::Start(unknown2_offset as u64), parse_with = checked_count(count))]
+    pub unknown2: Vec<NavUnknown2>,
+}
+
+pub fn read_nav(reader: &mut (impl Read + Seek)) -> BinResult<NavFile> {
+    NavFile::read(reader)
+}
+
+/// Reads the `height` rows of `width` points.
+#[binrw::parser(reader, endian)]
+fn nav_map_parser(width: u32, height: u32) -> BinResult<Vec<Vec<NavMapPoint>>> {
+    check_count(reader, width as u64 * height as u64)?;
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| NavMapPoint::read_options(reader, endian, ()))
+                .collect()
+        })
+        .collect()
+}
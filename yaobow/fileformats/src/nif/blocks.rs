@@ -1,17 +1,20 @@
-use std::io::Cursor;
+use std::io::{Cursor, Seek};
 
 use binrw::{
     binrw,
     meta::{ReadEndian, WriteEndian},
     BinRead, BinResult, BinWrite, NamedArgs,
 };
-use common::read_ext::ReadExt;
+use common::read_ext::{FileReadError, ReadExt};
 use dashmap::DashMap;
 use yaobow_macros::NiObjectType;
 
-use crate::{nif::NiType, utils::SizedString};
+use crate::{
+    nif::NiType,
+    utils::{checked_count, SizedString},
+};
 
-use super::{Matrix33, NiObject, Vector3};
+use super::{Matrix33, NiObject, Quaternion, TexCoord, Vector3};
 
 lazy_static::lazy_static! {
     static ref NI_OBJECT_TYPES: DashMap<&'static str, &'static NiType> = init_ni_object_types();
@@ -27,6 +30,12 @@ fn init_ni_object_types() -> DashMap<&'static str, &'static NiType> {
     add(&NiTypeNiNode);
     add(&NiTypeNiMesh);
     add(&NiTypeNiDataStream);
+    add(&NiTypeNiSkinningMeshModifier);
+    add(&NiTypeNiTransformController);
+    add(&NiTypeNiTransformInterpolator);
+    add(&NiTypeNiTransformData);
+    add(&NiTypeNiTexturingProperty);
+    add(&NiTypeNiSourceTexture);
 
     map
 }
@@ -54,7 +63,18 @@ impl BinRead for NiBlocks {
 
         for i in 0..args.block_sizes.len() {
             let size = args.block_sizes[i];
-            let full_name: String = args.block_types[args.block_type_index[i] as usize]
+            let type_index = args.block_type_index[i] as usize;
+            let pos = reader.stream_position()?;
+            let full_name: String = args
+                .block_types
+                .get(type_index)
+                .ok_or_else(|| binrw::Error::Custom {
+                    pos,
+                    err: Box::new(FileReadError::IndexOutOfRange {
+                        index: type_index as u64,
+                        len: args.block_types.len() as u64,
+                    }),
+                })?
                 .clone()
                 .into();
 
@@ -163,30 +183,30 @@ impl BinWrite for UnknownBlock {
 #[brw(little)]
 #[derive(Debug)]
 pub struct NiObjectNET {
-    name: u32,
-    num_extra_data: u32,
+    pub name: u32,
+    pub num_extra_data: u32,
 
-    #[br(count = num_extra_data)]
-    extra_data: Vec<u32>,
+    #[br(parse_with = checked_count(num_extra_data))]
+    pub extra_data: Vec<u32>,
 
-    controller: u32,
+    pub controller: u32,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug)]
 pub struct NiAVObject {
-    object_net: NiObjectNET,
-    flags: u16,
-    translation: Vector3,
-    rotation: Matrix33,
-    scale: f32,
-    num_properties: u32,
+    pub object_net: NiObjectNET,
+    pub flags: u16,
+    pub translation: Vector3,
+    pub rotation: Matrix33,
+    pub scale: f32,
+    pub num_properties: u32,
 
-    #[br(count = num_properties)]
-    properties: Vec<u32>,
+    #[br(parse_with = checked_count(num_properties))]
+    pub properties: Vec<u32>,
 
-    collision_object: i32,
+    pub collision_object: i32,
 }
 
 #[binrw]
@@ -194,32 +214,32 @@ pub struct NiAVObject {
 #[br(import_raw(_args: NiObjectArgs))]
 #[derive(Debug, NiObjectType)]
 pub struct NiNode {
-    av_object: NiAVObject,
-    num_children: u32,
+    pub av_object: NiAVObject,
+    pub num_children: u32,
 
-    #[br(count = num_children)]
-    children: Vec<u32>,
+    #[br(parse_with = checked_count(num_children))]
+    pub children: Vec<u32>,
 
-    num_effects: u32,
+    pub num_effects: u32,
 
-    #[br(count = num_effects)]
-    effects: Vec<u32>,
+    #[br(parse_with = checked_count(num_effects))]
+    pub effects: Vec<u32>,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug)]
 pub struct MaterialData {
-    num_materials: u32,
+    pub num_materials: u32,
 
-    #[br(count = num_materials)]
-    material_names: Vec<u32>,
+    #[br(parse_with = checked_count(num_materials))]
+    pub material_names: Vec<u32>,
 
-    #[br(count = num_materials)]
-    material_extra_data: Vec<i32>,
+    #[br(parse_with = checked_count(num_materials))]
+    pub material_extra_data: Vec<i32>,
 
-    active_material: i32,
-    always_update: u8,
+    pub active_material: i32,
+    pub always_update: u8,
 }
 
 #[binrw]
@@ -227,8 +247,8 @@ pub struct MaterialData {
 #[br(import_raw(_args: NiObjectArgs))]
 #[derive(Debug, NiObjectType)]
 pub struct NiRenderObject {
-    av_object: NiAVObject,
-    material_data: MaterialData,
+    pub av_object: NiAVObject,
+    pub material_data: MaterialData,
 }
 
 #[binrw]
@@ -249,33 +269,33 @@ impl MeshPrimitiveType {
 #[br(import_raw(_args: NiObjectArgs))]
 #[derive(Debug, NiObjectType)]
 pub struct NiBound {
-    center: Vector3,
-    radius: f32,
+    pub center: Vector3,
+    pub radius: f32,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug)]
 pub struct SemanticData {
-    name: i32,
-    index: u32,
+    pub name: i32,
+    pub index: u32,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug)]
 pub struct DataStreamRef {
-    stream: i32,
-    per_instance: u8,
-    num_sub_meshes: u16,
+    pub stream: i32,
+    pub per_instance: u8,
+    pub num_sub_meshes: u16,
 
-    #[br(count = num_sub_meshes)]
-    sub_mesh_to_region_map: Vec<u16>,
+    #[br(parse_with = checked_count(num_sub_meshes))]
+    pub sub_mesh_to_region_map: Vec<u16>,
 
-    num_components: u32,
+    pub num_components: u32,
 
-    #[br(count = num_components)]
-    semantic_data: Vec<SemanticData>,
+    #[br(parse_with = checked_count(num_components))]
+    pub semantic_data: Vec<SemanticData>,
 }
 
 #[binrw]
@@ -283,20 +303,20 @@ pub struct DataStreamRef {
 #[br(import_raw(_args: NiObjectArgs))]
 #[derive(Debug, NiObjectType)]
 pub struct NiMesh {
-    render_object: NiRenderObject,
-    primitive_type: MeshPrimitiveType,
-    num_sub_meshes: u16,
-    instancing: u8,
-    bound: NiBound,
-    num_data_streams: u32,
+    pub render_object: NiRenderObject,
+    pub primitive_type: MeshPrimitiveType,
+    pub num_sub_meshes: u16,
+    pub instancing: u8,
+    pub bound: NiBound,
+    pub num_data_streams: u32,
 
-    #[br(count = num_data_streams)]
-    data_stream_ref: Vec<DataStreamRef>,
+    #[br(parse_with = checked_count(num_data_streams))]
+    pub data_stream_ref: Vec<DataStreamRef>,
 
-    num_modifiers: u32,
+    pub num_modifiers: u32,
 
-    #[br(count = num_modifiers)]
-    modifiers: Vec<i32>,
+    #[br(parse_with = checked_count(num_modifiers))]
+    pub modifiers: Vec<i32>,
 }
 
 #[binrw]
@@ -408,8 +428,8 @@ impl ComponentFormat {
 #[brw(little)]
 #[derive(Debug)]
 pub struct Region {
-    start_index: u32,
-    num_indices: u32,
+    pub start_index: u32,
+    pub num_indices: u32,
 }
 
 #[binrw]
@@ -418,25 +438,385 @@ pub struct Region {
 #[derive(Debug, NiObjectType)]
 pub struct NiDataStream {
     #[br(calc(DataStreamUsage(args.generic_value1)))]
-    usage: DataStreamUsage,
+    pub usage: DataStreamUsage,
 
     #[br(calc(DataStreamAccess(args.generic_value2)))]
-    access: DataStreamAccess,
+    pub access: DataStreamAccess,
 
-    num_bytes: u32,
-    cloning_behavior: CloningBehavior,
-    num_regions: u32,
+    pub num_bytes: u32,
+    pub cloning_behavior: CloningBehavior,
+    pub num_regions: u32,
 
-    #[br(count = num_regions)]
-    regions: Vec<Region>,
+    #[br(parse_with = checked_count(num_regions))]
+    pub regions: Vec<Region>,
 
-    num_components: u32,
+    pub num_components: u32,
 
-    #[br(count = num_components)]
-    component_formats: Vec<ComponentFormat>,
+    #[br(parse_with = checked_count(num_components))]
+    pub component_formats: Vec<ComponentFormat>,
 
-    #[br(count = num_bytes)]
-    data: Vec<u8>,
+    #[br(parse_with = checked_count(num_bytes))]
+    pub data: Vec<u8>,
+
+    pub streamable: u8,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+pub struct NiTransform {
+    pub rotation: Matrix33,
+    pub translation: Vector3,
+    pub scale: f32,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct NiMeshModifier {
+    pub num_submit_points: u32,
+
+    #[br(parse_with = checked_count(num_submit_points))]
+    pub submit_points: Vec<u16>,
+
+    pub num_complete_points: u32,
+
+    #[br(parse_with = checked_count(num_complete_points))]
+    pub complete_points: Vec<u16>,
+}
+
+/// Bone indices and weights of the skinned vertices are stored in the
+/// `BLENDINDICES` and `BLENDWEIGHT` streams of the mesh.
+#[binrw]
+#[brw(little)]
+#[br(import_raw(_args: NiObjectArgs))]
+#[derive(Debug, NiObjectType)]
+pub struct NiSkinningMeshModifier {
+    pub mesh_modifier: NiMeshModifier,
+    pub flags: u16,
+    pub root_node: i32,
+    pub root_node_transform: NiTransform,
+    pub num_bones: u32,
+
+    #[br(parse_with = checked_count(num_bones))]
+    pub bones: Vec<i32>,
+
+    #[br(parse_with = checked_count(num_bones))]
+    pub bone_transforms: Vec<NiTransform>,
+
+    #[br(parse_with = checked_count(if flags & 2 != 0 { num_bones } else { 0 }))]
+    pub bone_bounds: Vec<NiBound>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct NiTimeController {
+    pub next_controller: i32,
+    pub flags: u16,
+    pub frequency: f32,
+    pub phase: f32,
+    pub start_time: f32,
+    pub stop_time: f32,
+    pub target: i32,
+}
+
+#[binrw]
+#[brw(little)]
+#[br(import_raw(_args: NiObjectArgs))]
+#[derive(Debug, NiObjectType)]
+pub struct NiTransformController {
+    pub time_controller: NiTimeController,
+    pub interpolator: i32,
+}
+
+/// Components that are not animated are set to `-f32::MAX`.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+pub struct NiQuatTransform {
+    pub translation: Vector3,
+    pub rotation: Quaternion,
+    pub scale: f32,
+}
+
+#[binrw]
+#[brw(little)]
+#[br(import_raw(_args: NiObjectArgs))]
+#[derive(Debug, NiObjectType)]
+pub struct NiTransformInterpolator {
+    pub transform: NiQuatTransform,
+    pub data: i32,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyType(pub u32);
+impl KeyType {
+    pub const LINEAR: Self = Self(1);
+    pub const QUADRATIC: Self = Self(2);
+    pub const TBC: Self = Self(3);
+    pub const XYZ_ROTATION: Self = Self(4);
+    pub const CONST: Self = Self(5);
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+pub struct TbcParams {
+    pub tension: f32,
+    pub bias: f32,
+    pub continuity: f32,
+}
+
+#[binrw]
+#[brw(little)]
+#[br(import(key_type: KeyType))]
+#[derive(Debug)]
+pub struct NiFloatKey {
+    pub time: f32,
+    pub value: f32,
+
+    #[br(if(key_type == KeyType::QUADRATIC))]
+    pub tangents: Option<[f32; 2]>,
+
+    #[br(if(key_type == KeyType::TBC))]
+    pub tbc: Option<TbcParams>,
+}
+
+#[binrw]
+#[brw(little)]
+#[br(import(key_type: KeyType))]
+#[derive(Debug)]
+pub struct NiVector3Key {
+    pub time: f32,
+    pub value: Vector3,
+
+    #[br(if(key_type == KeyType::QUADRATIC))]
+    pub tangents: Option<[Vector3; 2]>,
+
+    #[br(if(key_type == KeyType::TBC))]
+    pub tbc: Option<TbcParams>,
+}
+
+#[binrw]
+#[brw(little)]
+#[br(import(key_type: KeyType))]
+#[derive(Debug)]
+pub struct NiQuatKey {
+    pub time: f32,
+    pub value: Quaternion,
+
+    #[br(if(key_type == KeyType::TBC))]
+    pub tbc: Option<TbcParams>,
+}
 
-    streamable: u8,
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct NiFloatKeyGroup {
+    pub num_keys: u32,
+
+    #[br(if(num_keys != 0))]
+    pub interpolation: Option<KeyType>,
+
+    #[br(
+        parse_with = checked_count(num_keys),
+        args(interpolation.unwrap_or(KeyType::LINEAR))
+    )]
+    pub keys: Vec<NiFloatKey>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct NiVector3KeyGroup {
+    pub num_keys: u32,
+
+    #[br(if(num_keys != 0))]
+    pub interpolation: Option<KeyType>,
+
+    #[br(
+        parse_with = checked_count(num_keys),
+        args(interpolation.unwrap_or(KeyType::LINEAR))
+    )]
+    pub keys: Vec<NiVector3Key>,
+}
+
+/// Rotations are stored either as quaternion keys or, with
+/// `KeyType::XYZ_ROTATION`, as one float key group per euler angle.
+#[binrw]
+#[brw(little)]
+#[br(import_raw(_args: NiObjectArgs))]
+#[derive(Debug, NiObjectType)]
+pub struct NiTransformData {
+    pub num_rotation_keys: u32,
+
+    #[br(if(num_rotation_keys != 0))]
+    pub rotation_type: Option<KeyType>,
+
+    #[br(
+        parse_with = checked_count(
+            if rotation_type == Some(KeyType::XYZ_ROTATION) { 0 } else { num_rotation_keys }
+        ),
+        args(rotation_type.unwrap_or(KeyType::LINEAR))
+    )]
+    pub quaternion_keys: Vec<NiQuatKey>,
+
+    #[br(if(rotation_type == Some(KeyType::XYZ_ROTATION)))]
+    pub xyz_rotations: Option<[NiFloatKeyGroup; 3]>,
+
+    pub translations: NiVector3KeyGroup,
+    pub scales: NiFloatKeyGroup,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct TexTransform {
+    pub translation: TexCoord,
+    pub scale: TexCoord,
+    pub rotation: f32,
+    pub transform_method: u32,
+    pub center: TexCoord,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct TexDesc {
+    pub source: i32,
+    pub flags: u16,
+    pub has_texture_transform: u8,
+
+    #[br(if(has_texture_transform != 0))]
+    pub texture_transform: Option<TexTransform>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct TexSlot {
+    pub has_texture: u8,
+
+    #[br(if(has_texture != 0))]
+    pub texture: Option<TexDesc>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct BumpMap {
+    pub texture: TexDesc,
+    pub luma_scale: f32,
+    pub luma_offset: f32,
+    pub matrix: [f32; 4],
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct BumpMapSlot {
+    pub has_texture: u8,
+
+    #[br(if(has_texture != 0))]
+    pub bump_map: Option<BumpMap>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct ParallaxMap {
+    pub texture: TexDesc,
+    pub offset: f32,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct ParallaxMapSlot {
+    pub has_texture: u8,
+
+    #[br(if(has_texture != 0))]
+    pub parallax_map: Option<ParallaxMap>,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct ShaderMap {
+    pub texture: TexDesc,
+    pub map_id: u32,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug)]
+pub struct ShaderTexSlot {
+    pub has_map: u8,
+
+    #[br(if(has_map != 0))]
+    pub map: Option<ShaderMap>,
+}
+
+/// The slots after glow only exist when `texture_count` is large enough.
+#[binrw]
+#[brw(little)]
+#[br(import_raw(_args: NiObjectArgs))]
+#[derive(Debug, NiObjectType)]
+pub struct NiTexturingProperty {
+    pub object_net: NiObjectNET,
+    pub flags: u16,
+    pub texture_count: u32,
+    pub base_texture: TexSlot,
+    pub dark_texture: TexSlot,
+    pub detail_texture: TexSlot,
+    pub gloss_texture: TexSlot,
+    pub glow_texture: TexSlot,
+
+    #[br(if(texture_count > 5))]
+    pub bump_map_texture: Option<BumpMapSlot>,
+
+    #[br(if(texture_count > 6))]
+    pub normal_texture: Option<TexSlot>,
+
+    #[br(if(texture_count > 7))]
+    pub parallax_texture: Option<ParallaxMapSlot>,
+
+    #[br(if(texture_count > 8))]
+    pub decal0_texture: Option<TexSlot>,
+
+    #[br(if(texture_count > 9))]
+    pub decal1_texture: Option<TexSlot>,
+
+    #[br(if(texture_count > 10))]
+    pub decal2_texture: Option<TexSlot>,
+
+    #[br(if(texture_count > 11))]
+    pub decal3_texture: Option<TexSlot>,
+
+    pub num_shader_textures: u32,
+
+    #[br(parse_with = checked_count(num_shader_textures))]
+    pub shader_textures: Vec<ShaderTexSlot>,
+}
+
+#[binrw]
+#[brw(little)]
+#[br(import_raw(_args: NiObjectArgs))]
+#[derive(Debug, NiObjectType)]
+pub struct NiSourceTexture {
+    pub object_net: NiObjectNET,
+    pub use_external: u8,
+    pub file_name: i32,
+
+    /// Unknown link for external textures, pixel data otherwise.
+    pub data: i32,
+    pub pixel_layout: u32,
+    pub use_mipmaps: u32,
+    pub alpha_format: u32,
+    pub is_static: u8,
+    pub direct_render: u8,
+    pub persist_render_data: u8,
 }
@@ -1,11 +1,13 @@
 use binrw::binrw;
 
+use crate::utils::checked_count;
+
 #[binrw]
 #[brw(little)]
 #[derive(Debug)]
 pub struct NiFooter {
     pub num_roots: u32,
 
-    #[br(count = num_roots)]
+    #[br(parse_with = checked_count(num_roots))]
     pub roots: Vec<i32>,
 }
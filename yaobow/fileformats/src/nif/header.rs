@@ -1,6 +1,6 @@
 use binrw::binrw;
 
-use crate::utils::SizedString;
+use crate::utils::{checked_count, SizedString};
 
 use super::HeaderString;
 
@@ -15,22 +15,22 @@ pub struct NiHeader {
     pub num_block: u32,
     pub num_block_types: u16,
 
-    #[br(count = num_block_types)]
+    #[br(parse_with = checked_count(num_block_types))]
     pub block_types: Vec<SizedString>,
 
-    #[br(count = num_block)]
+    #[br(parse_with = checked_count(num_block))]
     pub block_type_index: Vec<u16>,
 
-    #[br(count = num_block)]
+    #[br(parse_with = checked_count(num_block))]
     pub block_size: Vec<u32>,
 
     pub num_strings: u32,
     pub max_str_length: u32,
 
-    #[br(count = num_strings)]
+    #[br(parse_with = checked_count(num_strings))]
     pub strings: Vec<SizedString>,
     pub num_groups: u32,
 
-    #[br(count = num_groups)]
+    #[br(parse_with = checked_count(num_groups))]
     pub groups: Vec<u32>,
 }
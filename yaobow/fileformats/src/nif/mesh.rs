@@ -0,0 +1,188 @@
+use super::{
+    blocks::{ComponentFormat, NiDataStream, NiMesh},
+    NifModel,
+};
+
+/// Vertex data of one sub mesh, decoded from the data streams of a `NiMesh`.
+/// Streams that are missing in the mesh are left empty.
+#[derive(Debug, Default)]
+pub struct NiSubMeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub texcoords: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+    pub bone_indices: Vec<[u32; 4]>,
+    pub bone_weights: Vec<[f32; 4]>,
+}
+
+impl NiSubMeshData {
+    pub fn is_valid(&self) -> bool {
+        let vertex_count = self.positions.len() as u32;
+        vertex_count != 0 && self.indices.iter().all(|i| *i < vertex_count)
+    }
+}
+
+impl NifModel {
+    /// Decodes the data streams of the mesh, one entry per sub mesh.
+    pub fn sub_meshes(&self, mesh: &NiMesh) -> Vec<NiSubMeshData> {
+        (0..mesh.num_sub_meshes as usize)
+            .map(|sub_mesh| self.sub_mesh(mesh, sub_mesh))
+            .collect()
+    }
+
+    fn sub_mesh(&self, mesh: &NiMesh, sub_mesh: usize) -> NiSubMeshData {
+        let mut data = NiSubMeshData::default();
+        for stream_ref in &mesh.data_stream_ref {
+            let stream = match self.block::<NiDataStream>(stream_ref.stream) {
+                Some(stream) => stream,
+                None => continue,
+            };
+
+            let region = stream_ref
+                .sub_mesh_to_region_map
+                .get(sub_mesh)
+                .and_then(|r| stream.regions.get(*r as usize));
+            let (start, count) = match region {
+                Some(r) => (r.start_index as usize, r.num_indices as usize),
+                None => (0, stream.element_count()),
+            };
+
+            for (component, semantic) in stream_ref.semantic_data.iter().enumerate() {
+                if semantic.index != 0 {
+                    continue;
+                }
+
+                let values = stream.read_component(component, start, count);
+                let width = stream
+                    .component_formats
+                    .get(component)
+                    .map_or(1, |f| f.layout().0.max(1));
+                match self.string(semantic.name).as_deref() {
+                    Some("POSITION") => data.positions = to_vec3(&values),
+                    Some("NORMAL") => data.normals = to_vec3(&values),
+                    Some("TEXCOORD") => {
+                        data.texcoords = values.chunks_exact(2).map(|c| [c[0], c[1]]).collect()
+                    }
+                    Some("INDEX") => data.indices = values.iter().map(|v| *v as u32).collect(),
+                    Some("BLENDINDICES") => {
+                        data.bone_indices = values
+                            .chunks_exact(width)
+                            .map(|c| {
+                                let mut indices = [0; 4];
+                                for (i, v) in c.iter().take(4).enumerate() {
+                                    indices[i] = *v as u32;
+                                }
+                                indices
+                            })
+                            .collect()
+                    }
+                    Some("BLENDWEIGHT") => {
+                        data.bone_weights =
+                            values.chunks_exact(width).map(|c| to_weights(c)).collect()
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        data
+    }
+}
+
+impl ComponentFormat {
+    /// Value count and byte size of each value, encoded in the format.
+    pub fn layout(&self) -> (usize, usize) {
+        (
+            ((self.0 >> 16) & 0xff) as usize,
+            ((self.0 >> 8) & 0xff) as usize,
+        )
+    }
+
+    fn read_value(&self, data: &[u8]) -> f32 {
+        match (self.0 & 0xff, data.len()) {
+            // FLOAT16_*
+            (0x31..=0x34, 2) => half::f16::from_le_bytes([data[0], data[1]]).to_f32(),
+            // FLOAT32_*
+            (0x35..=0x38, 4) => f32::from_le_bytes(data.try_into().unwrap()),
+            // INT16_*
+            (0x11..=0x14, 2) => i16::from_le_bytes([data[0], data[1]]) as f32,
+            // NORMUINT8_*
+            (0x0d..=0x10, 1) => data[0] as f32 / 255.,
+            (_, 1) => data[0] as f32,
+            (_, 2) => u16::from_le_bytes([data[0], data[1]]) as f32,
+            (_, 4) => u32::from_le_bytes(data.try_into().unwrap()) as f32,
+            _ => 0.,
+        }
+    }
+}
+
+impl NiDataStream {
+    pub fn stride(&self) -> usize {
+        self.component_formats
+            .iter()
+            .map(|f| {
+                let (count, size) = f.layout();
+                count * size
+            })
+            .sum()
+    }
+
+    pub fn element_count(&self) -> usize {
+        match self.stride() {
+            0 => 0,
+            stride => self.data.len() / stride,
+        }
+    }
+
+    /// Reads the values of one component of the interleaved stream as floats.
+    /// Values that don't fit in the stream are dropped.
+    pub fn read_component(&self, component: usize, start: usize, count: usize) -> Vec<f32> {
+        let format = match self.component_formats.get(component) {
+            Some(format) => format,
+            None => return vec![],
+        };
+
+        let (value_count, value_size) = format.layout();
+        let offset: usize = self.component_formats[..component]
+            .iter()
+            .map(|f| {
+                let (count, size) = f.layout();
+                count * size
+            })
+            .sum();
+
+        let stride = self.stride();
+        let mut values = vec![];
+        for element in start..start.saturating_add(count) {
+            let base = element * stride + offset;
+            let end = base + value_count * value_size;
+            if value_size == 0 || end > self.data.len() {
+                break;
+            }
+
+            for chunk in self.data[base..end].chunks_exact(value_size) {
+                values.push(format.read_value(chunk));
+            }
+        }
+
+        values
+    }
+}
+
+fn to_vec3(values: &[f32]) -> Vec<[f32; 3]> {
+    values.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect()
+}
+
+/// Gamebryo often stores three weights and leaves the last one implicit.
+fn to_weights(values: &[f32]) -> [f32; 4] {
+    let mut weights = [0.; 4];
+    for (i, v) in values.iter().take(4).enumerate() {
+        weights[i] = *v;
+    }
+
+    if values.len() == 3 {
+        weights[3] = (1. - weights[0] - weights[1] - weights[2]).max(0.);
+    }
+
+    weights
+}
@@ -1,6 +1,7 @@
 pub mod blocks;
 pub mod footer;
 pub mod header;
+pub mod mesh;
 
 use std::{
     fmt::Debug,
@@ -24,9 +25,26 @@ use self::{
 #[bw(little)]
 #[derive(Debug)]
 pub struct NifModel {
-    header: NiHeader,
-    blocks: NiBlocks,
-    footer: NiFooter,
+    pub header: NiHeader,
+    pub blocks: NiBlocks,
+    pub footer: NiFooter,
+}
+
+impl NifModel {
+    /// Looks up a string referenced by index from the header string table.
+    pub fn string(&self, index: i32) -> Option<String> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| self.header.strings.get(i))
+            .map(|s| s.clone().into())
+    }
+
+    pub fn block<T: NiObject>(&self, index: i32) -> Option<&T> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| self.blocks.0.get(i))
+            .and_then(|b| b.as_any().downcast_ref::<T>())
+    }
 }
 
 impl ReadEndian for NifModel {
@@ -126,24 +144,43 @@ impl std::fmt::Debug for HeaderString {
 #[brw(little)]
 #[derive(Debug, Clone)]
 pub struct Vector3 {
-    x: f32,
-    y: f32,
-    z: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
 }
 
 #[binrw]
 #[brw(little)]
 #[derive(Debug, Clone)]
 pub struct Matrix33 {
-    m11: f32,
-    m21: f32,
-    m31: f32,
+    pub m11: f32,
+    pub m21: f32,
+    pub m31: f32,
+
+    pub m12: f32,
+    pub m22: f32,
+    pub m32: f32,
 
-    m12: f32,
-    m22: f32,
-    m32: f32,
+    pub m13: f32,
+    pub m23: f32,
+    pub m33: f32,
+}
 
-    m13: f32,
-    m23: f32,
-    m33: f32,
+/// Stored in w, x, y, z order.
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone)]
+pub struct TexCoord {
+    pub u: f32,
+    pub v: f32,
 }
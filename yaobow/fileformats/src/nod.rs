@@ -1,17 +1,19 @@
 use binrw::binrw;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::StringWithCapacity;
+use crate::utils::{checked_count, StringWithCapacity};
 
 #[binrw]
 #[brw(little, magic = 0x0001e240u32)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NodFile {
     pub version: u32,
-    pub node_count: u32,
+
+    #[bw(calc(nodes.len() as u32))]
+    node_count: u32,
 
     // version < 9 is not supported
-    #[br(if(version >= 9), count = node_count)]
+    #[br(if(version >= 9), parse_with = checked_count(node_count))]
     pub nodes: Vec<Node>,
 
     pub unknown: u32,
@@ -20,7 +22,7 @@ pub struct NodFile {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Node {
     #[brw(args(100))]
     pub name: StringWithCapacity,
@@ -1,22 +1,24 @@
 use std::borrow::Cow;
 
 use binrw::binrw;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::{Pal4NodeSection, SizedString};
+use crate::utils::{checked_count, Pal4NodeSection, SizedString};
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NpcInfoFile {
+    #[bw(calc(data.len() as u32))]
     count: u32,
 
-    #[br(count = count)]
+    #[br(parse_with = checked_count(count))]
     pub data: Vec<NpcInfo>,
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NpcInfo {
     pub name: SizedString,
     pub model_name: SizedString,
@@ -1,14 +1,15 @@
-use binrw::BinRead;
-use serde::Serialize;
-use std::io::{Read, Seek};
+use binrw::{binrw, BinRead, BinWrite};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, Write};
 
 use crate::{
     rwbs::{Matrix44f, TexCoord, Vec3f},
-    utils::{SizedString, StringWithCapacity},
+    utils::{checked_count, checked_indices, SizedString, StringWithCapacity},
 };
 
-#[derive(BinRead, Debug, Serialize, Clone, Copy)]
+#[binrw]
 #[brw(little)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub struct PolVertexComponents(u32);
 impl PolVertexComponents {
     pub const POSITION: Self = PolVertexComponents(0b1);
@@ -26,16 +27,19 @@ impl PolVertexComponents {
     }
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
 #[brw(little)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PolVertexPosition {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
-#[brw(little, import(t: PolVertexComponents))]
+#[binrw]
+#[brw(little)]
+#[br(import(t: PolVertexComponents))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PolVertex {
     pub position: Vec3f,
     #[br(if(t.has(PolVertexComponents::NORMAL)))]
@@ -55,31 +59,45 @@ pub struct PolVertex {
     pub unknown100: Option<[f32; 4]>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
 #[brw(little)]
+#[br(import(vertex_count: u32))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PolMaterialInfo {
     pub use_alpha: u32,
     #[br(count = 16)]
     pub unknown_68: Vec<f32>,
     pub unknown_float: f32,
-    pub texture_count: u32,
-    #[br(count = texture_count, args { inner: (64,) })]
+
+    #[bw(calc(texture_names.len() as u32))]
+    texture_count: u32,
+
+    #[br(parse_with = checked_count(texture_count), args(64))]
+    #[bw(args(64))]
     pub texture_names: Vec<StringWithCapacity>,
+
     pub unknown2: u32,
     pub unknown3: u32,
     pub unknown4: u32,
-    pub triangle_count: u32,
-    #[br(count = triangle_count)]
+
+    #[bw(calc(triangles.len() as u32))]
+    triangle_count: u32,
+
+    #[br(parse_with = checked_count(triangle_count), args(vertex_count))]
     pub triangles: Vec<PolTriangle>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
 #[brw(little)]
+#[br(import(vertex_count: u32))]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PolTriangle {
+    #[br(parse_with = checked_indices(vertex_count))]
     pub indices: [u16; 3],
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[brw(
     little,
     assert(
@@ -92,16 +110,23 @@ pub struct PolMesh {
     pub aabb_min: Vec3f,
     pub aabb_max: Vec3f,
     pub vertex_type: PolVertexComponents,
-    pub vertex_count: u32,
-    #[br(count = vertex_count as usize, args { inner: (vertex_type,) })]
+
+    #[bw(calc(vertices.len() as u32))]
+    vertex_count: u32,
+
+    #[br(parse_with = checked_count(vertex_count), args(vertex_type))]
     pub vertices: Vec<PolVertex>,
-    pub material_info_count: u32,
-    #[br(count = material_info_count)]
+
+    #[bw(calc(material_info.len() as u32))]
+    material_info_count: u32,
+
+    #[br(parse_with = checked_count(material_info_count), args(vertex_count))]
     pub material_info: Vec<PolMaterialInfo>,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
 #[brw(little)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UnknownData {
     #[br(count = 32)]
     pub unknown: Vec<u8>, // size: 32
@@ -110,28 +135,49 @@ pub struct UnknownData {
     pub ddd_str: SizedString,
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
 #[brw(little)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GeomNodeDesc {
     #[br(count = 26)]
     pub unknown: Vec<u16>, // size: 52
 }
 
-#[derive(BinRead, Debug, Serialize, Clone)]
+#[binrw]
 #[brw(little, magic = b"POLY")]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PolFile {
     pub some_flag: u32,
-    pub mesh_count: u32,
-    #[br(count = mesh_count)]
+
+    #[bw(calc(meshes.len() as u32))]
+    mesh_count: u32,
+
+    /// One per mesh, they share the mesh count.
+    #[br(parse_with = checked_count(mesh_count))]
+    #[bw(assert(
+        geom_node_descs.len() == meshes.len(),
+        "POL has {} geom node descs for {} meshes",
+        geom_node_descs.len(),
+        meshes.len()
+    ))]
     pub geom_node_descs: Vec<GeomNodeDesc>,
+
     #[br(if(some_flag > 100))]
-    pub unknown_count: u32,
-    #[br(if(some_flag > 100), count = unknown_count)]
+    #[bw(if(*some_flag > 100), calc(unknown_data.len() as u32))]
+    unknown_count: u32,
+
+    #[br(if(some_flag > 100), parse_with = checked_count(unknown_count))]
+    #[bw(if(*some_flag > 100))]
     pub unknown_data: Vec<UnknownData>,
-    #[br(count = mesh_count)]
+
+    #[br(parse_with = checked_count(mesh_count))]
     pub meshes: Vec<PolMesh>,
 }
 
 pub fn read_pol(reader: &mut (impl Read + Seek)) -> anyhow::Result<PolFile> {
     Ok(PolFile::read(reader)?)
 }
+
+pub fn write_pol(pol: &PolFile, writer: &mut (impl Write + Seek)) -> anyhow::Result<()> {
+    Ok(pol.write(writer)?)
+}
@@ -1,27 +1,36 @@
 use binrw::{binrw, NullString};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::checked_count;
 
 #[binrw]
 #[brw(little, magic = 0x87654321u32)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct RoleBinFile {
     pub version: u32,
-    pub item_count: u32,
+
+    #[bw(calc(items.len() as u32))]
+    item_count: u32,
 
     // version < 105 is not supported
-    #[br(if(version >= 105), count = item_count)]
+    #[br(if(version >= 105), parse_with = checked_count(item_count))]
     pub items: Vec<AssetItem>,
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AssetItem {
     pub id: u32,
     pub unknown: [u32; 17],
     pub unknown_f32: [f32; 3],
 
+    #[serde(with = "crate::utils::null_string")]
     pub file_path: NullString,
+    #[serde(with = "crate::utils::null_string")]
     pub folder_folder: NullString,
+    #[serde(with = "crate::utils::null_string")]
     pub empty_string: NullString,
+    #[serde(with = "crate::utils::null_string")]
     pub empty_string2: NullString,
 }
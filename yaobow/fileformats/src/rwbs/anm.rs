@@ -1,11 +1,11 @@
 use binrw::binread;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::{Vec3f, Vec4f};
 
 #[binread]
 #[brw(little)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AnmAction {
     version: u32,
     pub kf_type: u32,
@@ -22,7 +22,7 @@ pub struct AnmAction {
 #[binread]
 #[brw(little)]
 #[br(import(kf_type: u32))]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AnmKeyFrame {
     pub ts: f32,
 
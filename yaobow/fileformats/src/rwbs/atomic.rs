@@ -1,11 +1,14 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
+use byteorder::{LittleEndian, WriteBytesExt};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::rwbs::{check_ty, extension::Extension, ChunkHeader, ChunkType};
+use crate::rwbs::{
+    check_ty, extension::Extension, write_chunk, ChunkHeader, ChunkType, ChunkVersion,
+};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Atomic {
     pub frame: u32,
     pub geometry: u32,
@@ -35,6 +38,18 @@ impl Atomic {
         })
     }
 
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.frame)?;
+            w.write_u32::<LittleEndian>(self.geometry)?;
+            w.write_u32::<LittleEndian>(self.unknown)?;
+            w.write_u32::<LittleEndian>(self.unknown2)?;
+            Ok(())
+        })?;
+
+        Extension::write(&self.extensions, writer, version)
+    }
+
     pub fn contains_right_to_render(&self) -> bool {
         self.extensions
             .iter()
@@ -1,13 +1,16 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
+use byteorder::{LittleEndian, WriteBytesExt};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::rwbs::{check_ty, extension::Extension, ChunkHeader, ChunkType};
+use crate::rwbs::{
+    check_ty, extension::Extension, write_chunk, ChunkHeader, ChunkType, ChunkVersion,
+};
 
 use super::{atomic::Atomic, frame::Frame, geometry::Geometry};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Clump {
     pub header: ChunkHeader,
     pub light_count: u32,
@@ -59,6 +62,31 @@ impl Clump {
         })
     }
 
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.atomics.len() as u32)?;
+            w.write_u32::<LittleEndian>(self.light_count)?;
+            w.write_u32::<LittleEndian>(self.camera_count)?;
+            Ok(())
+        })?;
+
+        write_chunk(writer, ChunkType::FRAME_LIST, version, |w| {
+            self.write_frame_list(w, version)
+        })?;
+
+        write_chunk(writer, ChunkType::GEOMETRY_LIST, version, |w| {
+            self.write_geometry_list(w, version)
+        })?;
+
+        for atomic in &self.atomics {
+            write_chunk(writer, ChunkType::ATOMIC, version, |w| {
+                atomic.write(w, version)
+            })?;
+        }
+
+        Extension::write(&self.extensions, writer, version)
+    }
+
     fn read_frame_list(cursor: &mut dyn Read) -> anyhow::Result<Vec<Frame>> {
         let header = ChunkHeader::read(cursor)?;
         check_ty!(header.ty, ChunkType::STRUCT);
@@ -95,4 +123,44 @@ impl Clump {
 
         Ok(geometries)
     }
+
+    fn write_frame_list(
+        &self,
+        writer: &mut dyn Write,
+        version: ChunkVersion,
+    ) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.frames.len() as u32)?;
+            for frame in &self.frames {
+                frame.write(w)?;
+            }
+
+            Ok(())
+        })?;
+
+        for frame in &self.frames {
+            Extension::write(frame.extensions(), writer, version)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_geometry_list(
+        &self,
+        writer: &mut dyn Write,
+        version: ChunkVersion,
+    ) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.geometries.len() as u32)?;
+            Ok(())
+        })?;
+
+        for geometry in &self.geometries {
+            write_chunk(writer, ChunkType::GEOMETRY, version, |w| {
+                geometry.write(w, version)
+            })?;
+        }
+
+        Ok(())
+    }
 }
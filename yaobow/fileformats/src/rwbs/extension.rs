@@ -1,15 +1,17 @@
-use std::{collections::HashMap, io::Read};
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
-use common::read_ext::ReadExt;
-use encoding::{types::Encoding, DecoderTrap};
-use serde::Serialize;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use common::read_ext::{FileReadError, ReadExt};
+use serde::{Deserialize, Serialize};
 
-use crate::rwbs::{check_ty, ChunkHeader, ChunkType};
+use crate::{
+    rwbs::{check_ty, write_chunk, ChunkHeader, ChunkType, ChunkVersion},
+    utils::SizedString,
+};
 
-use super::{plugins::hanim::HAnimPlugin, Matrix44f};
+use super::{plugins::hanim::HAnimPlugin, write_f32_slice, Matrix44f};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Extension {
     RightToRender(RightToRenderPlugin),
     HAnimPlugin(HAnimPlugin),
@@ -36,7 +38,15 @@ impl Extension {
         let mut extensions = vec![];
         while chunk_len > 0 {
             let ext_header = ChunkHeader::read(cursor)?;
-            chunk_len -= 12 + ext_header.length;
+            let size = 12 + ext_header.length as u64;
+            if size > chunk_len as u64 {
+                return Err(FileReadError::ChunkTooLarge {
+                    size,
+                    remaining: chunk_len as u64,
+                })?;
+            }
+
+            chunk_len -= size as u32;
 
             let ext = match ext_header.ty {
                 ChunkType::RIGHT_TO_RENDER => {
@@ -76,9 +86,51 @@ impl Extension {
         let header = Self::read_header(cursor)?;
         Self::read_data(cursor, header.length, vertices_count)
     }
+
+    /// Writes the extension chunk holding all the plugins.
+    pub fn write(
+        extensions: &[Self],
+        writer: &mut dyn Write,
+        version: ChunkVersion,
+    ) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::EXTENSION, version, |w| {
+            for ext in extensions {
+                ext.write_plugin(w, version)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn write_plugin(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        match self {
+            Extension::RightToRender(p) => {
+                write_chunk(writer, ChunkType::RIGHT_TO_RENDER, version, |w| p.write(w))
+            }
+            Extension::HAnimPlugin(p) => {
+                write_chunk(writer, ChunkType::PLUGIN_HANIM, version, |w| p.write(w))
+            }
+            Extension::SkinPlugin(p) => {
+                write_chunk(writer, ChunkType::PLUGIN_SKIN, version, |w| p.write(w))
+            }
+            Extension::NodeNamePlugin(p) => {
+                write_chunk(writer, ChunkType::PLUGIN_NODENAME, version, |w| p.write(w))
+            }
+            Extension::UserDataPlugin(p) => {
+                write_chunk(writer, ChunkType::PLUGIN_USERDATA, version, |w| p.write(w))
+            }
+            Extension::BinMeshPlugin(p) => {
+                write_chunk(writer, ChunkType::PLUGIN_BINMESH, version, |w| p.write(w))
+            }
+            Extension::UnknownPlugin(p) => write_chunk(writer, p.ty, version, |w| {
+                w.write_all(&p.unknown)?;
+                Ok(())
+            }),
+        }
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RightToRenderPlugin {
     pub target_plugin_id: u32,
     pub param: u32,
@@ -93,10 +145,18 @@ impl RightToRenderPlugin {
             param,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_u32::<LittleEndian>(self.target_plugin_id)?;
+        writer.write_u32::<LittleEndian>(self.param)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkinPlugin {
+    pub max_weights_per_vertex: u8,
+    unknown: u8,
     pub used_bones: Vec<u8>,
     pub bone_indices: Vec<[u8; 4]>,
     pub weights: Vec<[f32; 4]>,
@@ -110,6 +170,8 @@ impl SkinPlugin {
         let bone_count = count & 0xFF;
 
         let used_bone_count = (count & 0xFF00) >> 8;
+        let max_weights_per_vertex = ((count & 0xFF0000) >> 16) as u8;
+        let unknown = (count >> 24) as u8;
         let used_bones = cursor.read_u8_vec(used_bone_count as usize)?;
 
         let mut bone_indices = vec![];
@@ -133,6 +195,8 @@ impl SkinPlugin {
         let unknown2 = cursor.read_u8_vec(12)?;
 
         Ok(Self {
+            max_weights_per_vertex,
+            unknown,
             used_bones,
             bone_indices,
             weights,
@@ -140,50 +204,91 @@ impl SkinPlugin {
             unknown2,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(&[
+            self.matrix.len() as u8,
+            self.used_bones.len() as u8,
+            self.max_weights_per_vertex,
+            self.unknown,
+        ])?;
+        writer.write_all(&self.used_bones)?;
+
+        for indices in &self.bone_indices {
+            writer.write_all(indices)?;
+        }
+
+        for weight in &self.weights {
+            write_f32_slice(writer, weight)?;
+        }
+
+        for m in &self.matrix {
+            m.write(writer)?;
+        }
+
+        writer.write_all(&self.unknown2)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize)]
+/// The name fills the whole chunk and is kept as raw bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeNamePlugin {
-    name: String,
+    name: SizedString,
 }
 
 impl NodeNamePlugin {
     pub fn read(cursor: &mut dyn Read, header: ChunkHeader) -> anyhow::Result<Self> {
         let name = cursor.read_u8_vec(header.length as usize)?;
-        let name = encoding::all::UTF_8
-            .decode(&name, DecoderTrap::Ignore)
-            .or(Ok::<String, ()>("DEC_ERR".to_string()))
-            .unwrap();
-        Ok(Self { name })
+        Ok(Self {
+            name: SizedString::from_data(name),
+        })
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(self.name.data())?;
+        Ok(())
+    }
+
+    pub fn name(&self) -> &SizedString {
+        &self.name
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum UserData {
     Integer(i32),
     Float(f32),
-    String(String),
+    String(SizedString),
 }
 
 impl UserData {
     pub fn get_string(&self) -> Option<String> {
         if let Self::String(s) = self {
-            Some(s.clone())
+            s.as_str().ok()
         } else {
             None
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDataEntry {
+    pub name: SizedString,
+    pub ty: u32,
+    pub items: Vec<UserData>,
+}
+
+/// Entries are kept in file order so that they can be written back as is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserDataPlugin {
-    data: HashMap<String, Vec<UserData>>,
+    entries: Vec<UserDataEntry>,
 }
 
 impl UserDataPlugin {
     pub fn read(cursor: &mut dyn Read, _: ChunkHeader) -> anyhow::Result<Self> {
         let entry_count = cursor.read_u32_le()?;
-        let mut data = HashMap::new();
+        let mut entries = vec![];
         for _ in 0..entry_count {
             let entry_name = Self::read_string(cursor)?;
             let ty = cursor.read_u32_le()?;
@@ -199,40 +304,76 @@ impl UserDataPlugin {
                 }
             }
 
-            data.insert(entry_name, items);
+            entries.push(UserDataEntry {
+                name: entry_name,
+                ty,
+                items,
+            });
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_u32::<LittleEndian>(self.entries.len() as u32)?;
+        for entry in &self.entries {
+            Self::write_string(writer, &entry.name)?;
+            writer.write_u32::<LittleEndian>(entry.ty)?;
+            writer.write_u32::<LittleEndian>(entry.items.len() as u32)?;
+
+            for item in &entry.items {
+                match item {
+                    UserData::Integer(i) => writer.write_i32::<LittleEndian>(*i)?,
+                    UserData::Float(f) => writer.write_f32::<LittleEndian>(*f)?,
+                    UserData::String(s) => Self::write_string(writer, s)?,
+                }
+            }
         }
 
-        Ok(Self { data })
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[UserDataEntry] {
+        &self.entries
     }
 
-    pub fn data(&self) -> &HashMap<String, Vec<UserData>> {
-        &self.data
+    pub fn get(&self, name: &str) -> Option<&[UserData]> {
+        self.entries
+            .iter()
+            .find(|e| e.name.as_str().is_ok_and(|n| n == name))
+            .map(|e| e.items.as_slice())
     }
 
-    fn read_string(cursor: &mut dyn Read) -> anyhow::Result<String> {
+    /// Strings keep their terminator, null strings have a zero length.
+    fn read_string(cursor: &mut dyn Read) -> anyhow::Result<SizedString> {
         let length = cursor.read_u32_le()?;
-        cursor.read_string(length as usize)
+        Ok(SizedString::from_data(cursor.read_u8_vec(length as usize)?))
+    }
+
+    fn write_string(writer: &mut dyn Write, string: &SizedString) -> anyhow::Result<()> {
+        writer.write_u32::<LittleEndian>(string.data().len() as u32)?;
+        writer.write_all(string.data())?;
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinMesh {
     pub material: u32,
     pub indices: Vec<u32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinMeshPlugin {
-    flag: u32,
-    total_indices_count: u32,
-    meshes: Vec<BinMesh>,
+    pub flag: u32,
+    pub meshes: Vec<BinMesh>,
 }
 
 impl BinMeshPlugin {
     pub fn read(cursor: &mut dyn Read, _: ChunkHeader) -> anyhow::Result<Self> {
         let flag = cursor.read_u32_le()?;
         let mesh_count = cursor.read_u32_le()?;
-        let total_indices_count = cursor.read_u32_le()?;
+        let _total_indices_count = cursor.read_u32_le()?;
 
         let mut meshes = vec![];
         for _ in 0..mesh_count {
@@ -245,15 +386,28 @@ impl BinMeshPlugin {
             });
         }
 
-        Ok(Self {
-            flag,
-            total_indices_count,
-            meshes,
-        })
+        Ok(Self { flag, meshes })
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        let total_indices_count: usize = self.meshes.iter().map(|m| m.indices.len()).sum();
+        writer.write_u32::<LittleEndian>(self.flag)?;
+        writer.write_u32::<LittleEndian>(self.meshes.len() as u32)?;
+        writer.write_u32::<LittleEndian>(total_indices_count as u32)?;
+
+        for mesh in &self.meshes {
+            writer.write_u32::<LittleEndian>(mesh.indices.len() as u32)?;
+            writer.write_u32::<LittleEndian>(mesh.material)?;
+            for index in &mesh.indices {
+                writer.write_u32::<LittleEndian>(*index)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnknownPlugin {
     ty: ChunkType,
     unknown: Vec<u8>,
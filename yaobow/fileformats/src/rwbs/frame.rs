@@ -1,14 +1,14 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::rwbs::Vec3f;
 
 use super::{extension::Extension, plugins::hanim::HAnimPlugin};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Frame {
     pub right: Vec3f,
     pub up: Vec3f,
@@ -40,6 +40,17 @@ impl Frame {
         })
     }
 
+    /// Writes the frame struct. Extensions are written by the frame list.
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        self.right.write(writer)?;
+        self.up.write(writer)?;
+        self.at.write(writer)?;
+        self.pos.write(writer)?;
+        writer.write_i32::<LittleEndian>(self.parent)?;
+        writer.write_u32::<LittleEndian>(self.unknown)?;
+        Ok(())
+    }
+
     pub fn set_extensions(&mut self, ext: Vec<Extension>) {
         self.extensions = ext;
     }
@@ -64,7 +75,7 @@ impl Frame {
     pub fn name(&self) -> Option<String> {
         for e in &self.extensions {
             if let Extension::UserDataPlugin(u) = e {
-                if let Some(names) = u.data().get("name") {
+                if let Some(names) = u.get("name") {
                     return names.get(0).and_then(|s| s.get_string());
                 }
             }
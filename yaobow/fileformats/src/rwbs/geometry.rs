@@ -1,14 +1,17 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::rwbs::{check_ty, ChunkHeader, ChunkType, FormatFlag, TexCoord, Triangle, Vec3f};
+use crate::rwbs::{
+    check_ty, write_chunk, ChunkHeader, ChunkType, ChunkVersion, FormatFlag, TexCoord, Triangle,
+    Vec3f,
+};
 
 use super::{extension::Extension, material::Material};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GeometryMorphTarget {
     pub bounding_sphere_x: f32,
     pub bounding_sphere_y: f32,
@@ -61,9 +64,24 @@ impl GeometryMorphTarget {
             normals,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_f32::<LittleEndian>(self.bounding_sphere_x)?;
+        writer.write_f32::<LittleEndian>(self.bounding_sphere_y)?;
+        writer.write_f32::<LittleEndian>(self.bounding_sphere_z)?;
+        writer.write_f32::<LittleEndian>(self.radius)?;
+        writer.write_u32::<LittleEndian>(self.vertices.is_some() as u32)?;
+        writer.write_u32::<LittleEndian>(self.normals.is_some() as u32)?;
+
+        for v in self.vertices.iter().chain(self.normals.iter()).flatten() {
+            v.write(writer)?;
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Geometry {
     pub flags: FormatFlag,
     pub vertices_count: u32,
@@ -71,6 +89,8 @@ pub struct Geometry {
     pub texcoord_sets: Vec<Vec<TexCoord>>,
     pub triangles: Vec<Triangle>,
     pub materials: Vec<Material>,
+    #[serde(default)]
+    pub material_instances: Vec<i32>,
     pub morph_targets: Vec<GeometryMorphTarget>,
     pub extensions: Vec<Extension>,
 }
@@ -122,7 +142,7 @@ impl Geometry {
         }
 
         let _ = super::material::read_material_list_header(cursor)?;
-        let materials = super::material::read_material_list(cursor)?;
+        let (materials, material_instances) = super::material::read_material_list(cursor)?;
 
         let extensions = Extension::read(cursor, vertices_count)?;
 
@@ -133,8 +153,49 @@ impl Geometry {
             texcoord_sets,
             triangles,
             materials,
+            material_instances,
             morph_targets,
             extensions,
         })
     }
+
+    /// Writes the geometry struct, material list and extensions. Optional
+    /// data is written when present, so the flags must agree with it.
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.flags.0)?;
+            w.write_u32::<LittleEndian>(self.triangles.len() as u32)?;
+            w.write_u32::<LittleEndian>(self.vertices_count)?;
+            w.write_u32::<LittleEndian>(self.morph_targets.len() as u32)?;
+
+            for color in self.prelit.iter().flatten() {
+                w.write_u32::<LittleEndian>(*color)?;
+            }
+
+            for tex_coord in self.texcoord_sets.iter().flatten() {
+                tex_coord.write(w)?;
+            }
+
+            for triangle in &self.triangles {
+                w.write_u16::<LittleEndian>(triangle.index[1])?;
+                w.write_u16::<LittleEndian>(triangle.index[0])?;
+                w.write_u16::<LittleEndian>(triangle.material)?;
+                w.write_u16::<LittleEndian>(triangle.index[2])?;
+            }
+
+            for morph_target in &self.morph_targets {
+                morph_target.write(w)?;
+            }
+
+            Ok(())
+        })?;
+
+        super::material::write_material_list(
+            &self.materials,
+            &self.material_instances,
+            writer,
+            version,
+        )?;
+        Extension::write(&self.extensions, writer, version)
+    }
 }
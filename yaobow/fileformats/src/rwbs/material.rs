@@ -1,18 +1,26 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
-use common::read_ext::ReadExt;
-use serde::Serialize;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use common::read_ext::{FileReadError, ReadExt};
+use serde::{Deserialize, Serialize};
 
-use crate::rwbs::{check_ty, ChunkHeader, ChunkType};
+use crate::{
+    rwbs::{check_ty, write_chunk, ChunkHeader, ChunkType, ChunkVersion},
+    utils::StringWithCapacity,
+};
 
-#[derive(Debug, Serialize)]
+use super::extension::Extension;
+
+/// Names are kept as the raw zero padded chunk data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Texture {
     pub filter_mode: u32,
     pub address_mode_u: u32,
     pub address_mode_v: u32,
-    pub name: String,
-    pub mask_name: String,
+    pub unknown: u32,
+    pub name: StringWithCapacity,
+    pub mask_name: StringWithCapacity,
+    pub extensions: Vec<Extension>,
 }
 
 impl Texture {
@@ -25,33 +33,47 @@ impl Texture {
         let filter_mode = modes & _private::TEXTURE_FILTER_MODE_MASK;
         let address_mode_u = (modes & _private::TEXTURE_ADDRESS_MODE_U_MASK) >> 8;
         let address_mode_v = (modes & _private::TEXTURE_ADDRESS_MODE_V_MASK) >> 12;
+        let unknown = modes >> 16;
 
         let header = ChunkHeader::read(cursor)?;
         check_ty!(header.ty, ChunkType::STRING);
 
-        let name = cursor.read_gbk_string(header.length as usize)?;
+        let name = StringWithCapacity::from_data(cursor.read_u8_vec(header.length as usize)?);
 
         let header = ChunkHeader::read(cursor)?;
         check_ty!(header.ty, ChunkType::STRING);
 
-        let mask_name = cursor.read_gbk_string(header.length as usize)?;
-
-        let header = ChunkHeader::read(cursor)?;
-        check_ty!(header.ty, ChunkType::EXTENSION);
-
-        cursor.skip(header.length as usize)?;
+        let mask_name = StringWithCapacity::from_data(cursor.read_u8_vec(header.length as usize)?);
+        let extensions = Extension::read(cursor, 0)?;
 
         Ok(Self {
             filter_mode,
             address_mode_u,
             address_mode_v,
+            unknown,
             name,
             mask_name,
+            extensions,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            let modes = (self.filter_mode & _private::TEXTURE_FILTER_MODE_MASK)
+                | ((self.address_mode_u << 8) & _private::TEXTURE_ADDRESS_MODE_U_MASK)
+                | ((self.address_mode_v << 12) & _private::TEXTURE_ADDRESS_MODE_V_MASK)
+                | (self.unknown << 16);
+            w.write_u32::<LittleEndian>(modes)?;
+            Ok(())
+        })?;
+
+        write_string_chunk(writer, &self.name, version)?;
+        write_string_chunk(writer, &self.mask_name, version)?;
+        Extension::write(&self.extensions, writer, version)
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Material {
     pub unknown: u32,
     pub color: u32,
@@ -60,6 +82,7 @@ pub struct Material {
     pub ambient: f32,
     pub specular: f32,
     pub diffuse: f32,
+    pub extensions: Vec<Extension>,
 }
 
 impl Material {
@@ -81,10 +104,7 @@ impl Material {
             texture = Some(Texture::read(cursor)?);
         }
 
-        let header = ChunkHeader::read(cursor)?;
-        check_ty!(header.ty, ChunkType::EXTENSION);
-
-        cursor.skip(header.length as usize)?;
+        let extensions = Extension::read(cursor, 0)?;
 
         Ok(Self {
             unknown,
@@ -94,8 +114,30 @@ impl Material {
             ambient,
             specular,
             diffuse,
+            extensions,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.unknown)?;
+            w.write_u32::<LittleEndian>(self.color)?;
+            w.write_u32::<LittleEndian>(self.unknown2)?;
+            w.write_u32::<LittleEndian>(self.texture.is_some() as u32)?;
+            w.write_f32::<LittleEndian>(self.ambient)?;
+            w.write_f32::<LittleEndian>(self.specular)?;
+            w.write_f32::<LittleEndian>(self.diffuse)?;
+            Ok(())
+        })?;
+
+        if let Some(texture) = &self.texture {
+            write_chunk(writer, ChunkType::TEXTURE, version, |w| {
+                texture.write(w, version)
+            })?;
+        }
+
+        Extension::write(&self.extensions, writer, version)
+    }
 }
 
 pub fn read_material_list_header(cursor: &mut dyn Read) -> anyhow::Result<ChunkHeader> {
@@ -105,24 +147,90 @@ pub fn read_material_list_header(cursor: &mut dyn Read) -> anyhow::Result<ChunkH
     Ok(header)
 }
 
-pub fn read_material_list(cursor: &mut dyn Read) -> anyhow::Result<Vec<Material>> {
+/// Reads the materials of the list along with the instance index of each
+/// entry. Entries with an index other than -1 reuse an earlier material and
+/// are returned as a copy of it.
+pub fn read_material_list(cursor: &mut dyn Read) -> anyhow::Result<(Vec<Material>, Vec<i32>)> {
     let header = ChunkHeader::read(cursor)?;
     check_ty!(header.ty, ChunkType::STRUCT);
 
-    let mut material_vec = vec![];
-
     let material_count = cursor.read_u32_le()?;
-    if material_count > 0 {
-        let _unknown = cursor.read_dw_vec(material_count as usize)?;
-        for _ in 0..material_count {
+    let mut instances = vec![];
+    for _ in 0..material_count {
+        instances.push(cursor.read_i32::<LittleEndian>()?);
+    }
+
+    let mut material_vec: Vec<Material> = vec![];
+    for &instance in &instances {
+        let material = if instance < 0 {
             let header = ChunkHeader::read(cursor)?;
             check_ty!(header.ty, ChunkType::MATERIAL);
 
-            material_vec.push(Material::read(cursor)?);
+            Material::read(cursor)?
+        } else {
+            material_vec
+                .get(instance as usize)
+                .ok_or(FileReadError::IndexOutOfRange {
+                    index: instance as u64,
+                    len: material_vec.len() as u64,
+                })?
+                .clone()
+        };
+
+        material_vec.push(material);
+    }
+
+    Ok((material_vec, instances))
+}
+
+/// Writes the whole material list chunk. Materials without an instance index
+/// are written as new materials, the ones instancing an earlier material are
+/// only written as the index.
+pub fn write_material_list(
+    materials: &[Material],
+    instances: &[i32],
+    writer: &mut dyn Write,
+    version: ChunkVersion,
+) -> anyhow::Result<()> {
+    let instance = |i: usize| instances.get(i).copied().unwrap_or(-1);
+    write_chunk(writer, ChunkType::MATERIAL_LIST, version, |w| {
+        write_chunk(w, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(materials.len() as u32)?;
+            for i in 0..materials.len() {
+                w.write_i32::<LittleEndian>(instance(i))?;
+            }
+
+            Ok(())
+        })?;
+
+        for (i, material) in materials.iter().enumerate() {
+            if instance(i) < 0 {
+                write_chunk(w, ChunkType::MATERIAL, version, |w| {
+                    material.write(w, version)
+                })?;
+            }
         }
+
+        Ok(())
+    })
+}
+
+/// Strings are null terminated and padded to 4 bytes. Strings read from a
+/// file already carry their padding and are written as they are.
+fn write_string_chunk(
+    writer: &mut dyn Write,
+    string: &StringWithCapacity,
+    version: ChunkVersion,
+) -> anyhow::Result<()> {
+    let mut data = string.data().to_vec();
+    if data.len() % 4 != 0 || data.last() != Some(&0) {
+        data.resize((data.len() / 4 + 1) * 4, 0);
     }
 
-    Ok(material_vec)
+    write_chunk(writer, ChunkType::STRING, version, |w| {
+        w.write_all(&data)?;
+        Ok(())
+    })
 }
 
 mod _private {
@@ -9,13 +9,13 @@ pub mod plugins;
 pub mod sector;
 pub mod world;
 
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Write};
 
 use binrw::{binrw, BinRead, BinResult};
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 use clump::Clump;
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use world::World;
 
 /**
@@ -27,7 +27,7 @@ use world::World;
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
 pub struct ChunkType(pub u32);
 
 impl ChunkType {
@@ -59,7 +59,7 @@ impl ChunkType {
     pub const PLUGIN_NODENAME: Self = Self(0x0253f2fe);
 }
 
-#[derive(Copy, Clone, Debug, Serialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct FormatFlag(pub u32);
 impl FormatFlag {
     pub const TRISTRIP: Self = Self(0x1);
@@ -99,7 +99,7 @@ fn float_parser(half_float: bool) -> BinResult<f32> {
 #[binrw]
 #[brw(little)]
 #[brw(import{half_float: bool = false})]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Vec4f {
     #[br(parse_with = float_parser)]
     #[br(args(half_float))]
@@ -121,7 +121,7 @@ pub struct Vec4f {
 #[binrw]
 #[brw(little)]
 #[brw(import{half_float: bool = false})]
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Vec3f {
     #[br(parse_with = float_parser)]
     #[br(args(half_float))]
@@ -143,11 +143,18 @@ impl Vec3f {
         let z = cursor.read_f32::<LittleEndian>()?;
         Ok(Self { x, y, z })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_f32::<LittleEndian>(self.x)?;
+        writer.write_f32::<LittleEndian>(self.y)?;
+        writer.write_f32::<LittleEndian>(self.z)?;
+        Ok(())
+    }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Matrix44f(pub [f32; 16]);
 
 impl Matrix44f {
@@ -155,12 +162,16 @@ impl Matrix44f {
         let m = cursor.read_f32_vec(16)?;
         Ok(Self(m.try_into().unwrap()))
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        write_f32_slice(writer, &self.0)
+    }
 }
 
 #[binrw]
 #[brw(little)]
 #[brw(import{half_float: bool = false})]
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Quaternion {
     #[br(parse_with = float_parser)]
     #[br(args(half_float))]
@@ -179,7 +190,7 @@ pub struct Quaternion {
     pub w: f32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PrelitColor {
     pub r: u8,
     pub g: u8,
@@ -195,8 +206,14 @@ impl PrelitColor {
         let a = cursor.read_u8()?;
         Ok(Self { r, g, b, a })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(&[self.r, self.g, self.b, self.a])?;
+        Ok(())
+    }
 }
-#[derive(Debug, Serialize)]
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Normal {
     pub x: u8,
     pub y: u8,
@@ -212,11 +229,16 @@ impl Normal {
         let p = cursor.read_u8()?;
         Ok(Self { x, y, z, p })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_all(&[self.x, self.y, self.z, self.p])?;
+        Ok(())
+    }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TexCoord {
     pub u: f32,
     pub v: f32,
@@ -228,9 +250,15 @@ impl TexCoord {
         let v = cursor.read_f32::<LittleEndian>()?;
         Ok(Self { u, v })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_f32::<LittleEndian>(self.u)?;
+        writer.write_f32::<LittleEndian>(self.v)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Triangle {
     pub index: [u16; 3],
     pub material: u16,
@@ -238,7 +266,7 @@ pub struct Triangle {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ChunkHeader {
     pub ty: ChunkType,
     pub length: u32,
@@ -260,6 +288,57 @@ impl ChunkHeader {
             version,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        writer.write_u32::<LittleEndian>(self.ty.0)?;
+        writer.write_u32::<LittleEndian>(self.length)?;
+        writer.write_u16::<LittleEndian>(self.build_number)?;
+        writer.write_u16::<LittleEndian>(self.version)?;
+        Ok(())
+    }
+
+    pub fn chunk_version(&self) -> ChunkVersion {
+        ChunkVersion {
+            build_number: self.build_number,
+            version: self.version,
+        }
+    }
+}
+
+/// The library stamp written into every chunk header.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub struct ChunkVersion {
+    pub build_number: u16,
+    pub version: u16,
+}
+
+/// Writes a chunk whose length is known only after `data` has been written.
+pub(crate) fn write_chunk(
+    writer: &mut dyn Write,
+    ty: ChunkType,
+    version: ChunkVersion,
+    data: impl FnOnce(&mut Vec<u8>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut buf = vec![];
+    data(&mut buf)?;
+
+    ChunkHeader {
+        ty,
+        length: buf.len() as u32,
+        build_number: version.build_number,
+        version: version.version,
+    }
+    .write(writer)?;
+    writer.write_all(&buf)?;
+    Ok(())
+}
+
+pub(crate) fn write_f32_slice(writer: &mut dyn Write, values: &[f32]) -> anyhow::Result<()> {
+    for v in values {
+        writer.write_f32::<LittleEndian>(*v)?;
+    }
+
+    Ok(())
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -326,6 +405,34 @@ pub fn read_bsp(data: &[u8]) -> anyhow::Result<Vec<World>> {
     Ok(world)
 }
 
+/// Writes the clumps back into a dff file. Every chunk is stamped with the
+/// version of the clump it belongs to.
+pub fn write_dff(clumps: &[Clump]) -> anyhow::Result<Vec<u8>> {
+    let mut data = vec![];
+    for clump in clumps {
+        let version = clump.header.chunk_version();
+        write_chunk(&mut data, ChunkType::CLUMP, version, |w| {
+            clump.write(w, version)
+        })?;
+    }
+
+    Ok(data)
+}
+
+/// Writes the worlds back into a bsp file. Every chunk is stamped with the
+/// version of the world it belongs to.
+pub fn write_bsp(worlds: &[World]) -> anyhow::Result<Vec<u8>> {
+    let mut data = vec![];
+    for world in worlds {
+        let version = world.header.chunk_version();
+        write_chunk(&mut data, ChunkType::WORLD, version, |w| {
+            world.write(w, version)
+        })?;
+    }
+
+    Ok(data)
+}
+
 pub fn read_anm(data: &[u8]) -> anyhow::Result<Vec<AnmAction>> {
     let mut cursor = Cursor::new(data);
     let mut anim = vec![];
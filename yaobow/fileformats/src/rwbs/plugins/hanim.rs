@@ -1,21 +1,21 @@
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Write};
 
-use binrw::{binrw, BinRead};
+use binrw::{binrw, BinRead, BinWrite};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::rwbs::ChunkHeader;
+use crate::{rwbs::ChunkHeader, utils::checked_count};
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HAnimPlugin {
     pub header: HAnimHeader,
 
     #[br(if(header.bone_count > 0))]
     pub unknown: Option<HAnimUnknown>,
 
-    #[br(count = header.bone_count)]
+    #[br(parse_with = checked_count(header.bone_count))]
     pub bones: Vec<HAnimBone>,
 }
 
@@ -24,11 +24,18 @@ impl HAnimPlugin {
         let buf = cursor.read_u8_vec(header.length as usize)?;
         Ok(<Self as BinRead>::read(&mut Cursor::new(buf))?)
     }
+
+    pub fn write(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        let mut buf = Cursor::new(vec![]);
+        <Self as BinWrite>::write(self, &mut buf)?;
+        writer.write_all(&buf.into_inner())?;
+        Ok(())
+    }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HAnimHeader {
     pub version: u32,
     pub id: u32,
@@ -37,7 +44,7 @@ pub struct HAnimHeader {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HAnimUnknown {
     unknown1: u32,
     unknown2: u32,
@@ -45,7 +52,7 @@ pub struct HAnimUnknown {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HAnimBone {
     pub id: u32,
     pub index: u32,
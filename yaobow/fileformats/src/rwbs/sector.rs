@@ -1,21 +1,39 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::rwbs::{
-    check_ty, extension::Extension, ChunkHeader, ChunkType, FormatFlag, Normal, PrelitColor,
-    TexCoord, Triangle, Vec3f,
+    check_ty, extension::Extension, write_chunk, ChunkHeader, ChunkType, ChunkVersion, FormatFlag,
+    Normal, PrelitColor, TexCoord, Triangle, Vec3f,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Sector {
     PlaneSector(PlaneSector),
     AtomicSector(AtomicSector),
 }
 
-#[derive(Debug, Serialize)]
+impl Sector {
+    /// Writes the sector chunk including its header.
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        match self {
+            Sector::PlaneSector(plane) => {
+                write_chunk(writer, ChunkType::PLANE_SECTOR, version, |w| {
+                    plane.write(w, version)
+                })
+            }
+            Sector::AtomicSector(atomic) => {
+                write_chunk(writer, ChunkType::ATOMIC_SECTOR, version, |w| {
+                    atomic.write(w, version)
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PlaneSector {
     pub sector_type: u32,
     pub unknown: u32,
@@ -83,10 +101,27 @@ impl PlaneSector {
             right_child,
         })
     }
+
+    /// The plane struct is written back in the same raw form it is read in.
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        writer.write_u32::<LittleEndian>(self.sector_type)?;
+        writer.write_u32::<LittleEndian>(self.unknown)?;
+        writer.write_f32::<LittleEndian>(self.unknown2)?;
+        writer.write_u32::<LittleEndian>(self.unknown3)?;
+        writer.write_f32::<LittleEndian>(self.value)?;
+        writer.write_u32::<LittleEndian>(self.left_child_type)?;
+        writer.write_u32::<LittleEndian>(self.right_child_type)?;
+        writer.write_f32::<LittleEndian>(self.left_value)?;
+        writer.write_f32::<LittleEndian>(self.right_value)?;
+
+        self.left_child.write(writer, version)?;
+        self.right_child.write(writer, version)
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AtomicSector {
+    pub material_id_base: u32,
     pub bbox_min: Vec3f,
     pub bbox_max: Vec3f,
     pub vertices: Vec<Vec3f>,
@@ -96,6 +131,8 @@ pub struct AtomicSector {
     pub texcoords2: Option<Vec<TexCoord>>,
     pub triangles: Vec<Triangle>,
     pub extensions: Vec<Extension>,
+    unknown: u32,
+    unknown2: u32,
 }
 
 impl AtomicSector {
@@ -110,13 +147,13 @@ impl AtomicSector {
         let header = ChunkHeader::read(cursor)?;
         check_ty!(header.ty, ChunkType::STRUCT);
 
-        let _material_id_base = cursor.read_u32_le()?;
+        let material_id_base = cursor.read_u32_le()?;
         let triangle_count = cursor.read_u32_le()?;
         let vertices_count = cursor.read_u32_le()?;
         let bbox_min = Vec3f::read(cursor)?;
         let bbox_max = Vec3f::read(cursor)?;
-        let _unknown = cursor.read_u32_le()?;
-        let _unknown2 = cursor.read_u32_le()?;
+        let unknown = cursor.read_u32_le()?;
+        let unknown2 = cursor.read_u32_le()?;
 
         let mut vertices = vec![];
         for _ in 0..vertices_count {
@@ -183,6 +220,7 @@ impl AtomicSector {
         let extensions = Extension::read(cursor, vertices_count)?;
 
         Ok(Self {
+            material_id_base,
             bbox_min,
             bbox_max,
             vertices,
@@ -192,6 +230,54 @@ impl AtomicSector {
             texcoords2,
             triangles,
             extensions,
+            unknown,
+            unknown2,
         })
     }
+
+    /// Writes the sector struct and extensions. Optional data is written when
+    /// present, so the world flags must agree with it.
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.material_id_base)?;
+            w.write_u32::<LittleEndian>(self.triangles.len() as u32)?;
+            w.write_u32::<LittleEndian>(self.vertices.len() as u32)?;
+            self.bbox_min.write(w)?;
+            self.bbox_max.write(w)?;
+            w.write_u32::<LittleEndian>(self.unknown)?;
+            w.write_u32::<LittleEndian>(self.unknown2)?;
+
+            for vertex in &self.vertices {
+                vertex.write(w)?;
+            }
+
+            for color in self.prelit_colors.iter().flatten() {
+                color.write(w)?;
+            }
+
+            for normal in self.normals.iter().flatten() {
+                normal.write(w)?;
+            }
+
+            for tex_coord in self
+                .texcoords
+                .iter()
+                .chain(self.texcoords2.iter())
+                .flatten()
+            {
+                tex_coord.write(w)?;
+            }
+
+            for triangle in &self.triangles {
+                w.write_u16::<LittleEndian>(triangle.index[0])?;
+                w.write_u16::<LittleEndian>(triangle.index[1])?;
+                w.write_u16::<LittleEndian>(triangle.index[2])?;
+                w.write_u16::<LittleEndian>(triangle.material)?;
+            }
+
+            Ok(())
+        })?;
+
+        Extension::write(&self.extensions, writer, version)
+    }
 }
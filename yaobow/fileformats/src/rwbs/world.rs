@@ -1,18 +1,18 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use common::read_ext::ReadExt;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::{
     check_ty,
     extension::Extension,
     material::Material,
     sector::{AtomicSector, PlaneSector, Sector},
-    ChunkHeader, ChunkType, FormatFlag, Vec3f,
+    write_chunk, ChunkHeader, ChunkType, ChunkVersion, FormatFlag, Vec3f,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct World {
     pub header: ChunkHeader,
     root_type: u32,
@@ -29,6 +29,8 @@ pub struct World {
     pub bbox_min: Vec3f,
 
     pub materials: Vec<Material>,
+    #[serde(default)]
+    pub material_instances: Vec<i32>,
     pub sector: Sector,
     pub extensions: Vec<Extension>,
 }
@@ -54,7 +56,7 @@ impl World {
         let bbox_min = Vec3f::read(cursor)?;
 
         let _ = super::material::read_material_list_header(cursor)?;
-        let materials = super::material::read_material_list(cursor)?;
+        let (materials, material_instances) = super::material::read_material_list(cursor)?;
 
         let sector = if root_type == 0 {
             let _ = PlaneSector::read_header(cursor)?;
@@ -84,8 +86,35 @@ impl World {
             bbox_min,
 
             materials,
+            material_instances,
             sector,
             extensions,
         })
     }
+
+    pub fn write(&self, writer: &mut dyn Write, version: ChunkVersion) -> anyhow::Result<()> {
+        write_chunk(writer, ChunkType::STRUCT, version, |w| {
+            w.write_u32::<LittleEndian>(self.root_type)?;
+            w.write_f32::<LittleEndian>(-self.world_origin_x)?;
+            w.write_f32::<LittleEndian>(-self.world_origin_y)?;
+            w.write_f32::<LittleEndian>(-self.world_origin_z)?;
+            w.write_u32::<LittleEndian>(self.triangle_count)?;
+            w.write_u32::<LittleEndian>(self.vertices_count)?;
+            w.write_u32::<LittleEndian>(self.count_plane)?;
+            w.write_u32::<LittleEndian>(self.count_sector)?;
+            w.write_u32::<LittleEndian>(self.unknown2)?;
+            w.write_u32::<LittleEndian>(self.flag.0)?;
+            self.bbox_max.write(w)?;
+            self.bbox_min.write(w)
+        })?;
+
+        super::material::write_material_list(
+            &self.materials,
+            &self.material_instances,
+            writer,
+            version,
+        )?;
+        self.sector.write(writer, version)?;
+        Extension::write(&self.extensions, writer, version)
+    }
 }
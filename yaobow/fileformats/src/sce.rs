@@ -0,0 +1,264 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Read, Seek, Write},
+};
+
+use binrw::{binread, BinRead, BinResult};
+use byteorder::{LittleEndian, WriteBytesExt};
+use encoding::{EncoderTrap, Encoding};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{checked_count, gbk_string_parser, to_gbk_string};
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceLocalVar {
+    pub unknown: u8,
+
+    #[br(temp)]
+    size: u16,
+
+    #[br(parse_with = checked_count(size))]
+    pub unknown_vec: Vec<u8>,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceProc {
+    pub id: u32,
+
+    #[br(temp)]
+    name_len: u16,
+
+    #[br(restore_position, parse_with = gbk_string_parser, args(name_len as usize))]
+    pub name: String,
+
+    /// The name as stored in the file, including the NUL terminator. It is
+    /// written back as is unless `name` has been changed.
+    #[br(parse_with = checked_count(name_len))]
+    #[serde(default)]
+    pub raw_name: Vec<u8>,
+
+    #[br(temp)]
+    local_var_num: u16,
+
+    #[br(parse_with = checked_count(local_var_num))]
+    pub local_vars: Vec<SceLocalVar>,
+
+    #[br(temp)]
+    inst_size: u32,
+
+    #[br(parse_with = checked_count(inst_size))]
+    pub inst: Vec<u8>,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceProcHeader {
+    pub id: u32,
+    pub offset: u32,
+
+    #[br(restore_position, parse_with = gbk_string_parser, args(SCE_PROC_HEADER_NAME_SIZE))]
+    pub name: String,
+
+    /// The 64 byte name field as stored in the file, padding included. It is
+    /// written back as is unless `name` has been changed.
+    #[br(count = SCE_PROC_HEADER_NAME_SIZE)]
+    #[serde(default)]
+    pub raw_name: Vec<u8>,
+}
+
+#[binread]
+#[br(little, magic = b"SCE\0")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SceFile {
+    #[br(temp, assert(version == 1, "unsupported sce version {}", version))]
+    version: u8,
+    pub proc_num: u16,
+
+    #[br(parse_with = checked_count(proc_num))]
+    pub proc_headers: Vec<SceProcHeader>,
+
+    #[br(
+        parse_with = checked_count(proc_num),
+        map = |procs: Vec<SceProc>| procs.into_iter().map(|p| (p.id, p)).collect()
+    )]
+    #[serde(serialize_with = "serialize_procs")]
+    pub procs: HashMap<u32, SceProc>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SceWriteError {
+    #[error("Sce proc {0} is missing")]
+    MissingProc(u32),
+
+    #[error("Unable to encode {0} in GBK")]
+    Encoding(String),
+
+    #[error("Sce proc header name {0} is longer than 64 bytes")]
+    NameTooLong(String),
+
+    #[error("Sce {0} of length {1} doesn't fit in its length field")]
+    LengthOverflow(&'static str, usize),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+const SCE_MAGIC: [u8; 4] = [0x53, 0x43, 0x45, 0x00]; // "SCE"
+const SCE_PROC_HEADER_NAME_SIZE: usize = 64;
+
+pub fn read_sce(reader: &mut (impl Read + Seek)) -> BinResult<SceFile> {
+    SceFile::read(reader)
+}
+
+/// Writes the headers in the order of `proc_headers` and the proc bodies in
+/// the order of the header offsets, so procs stored out of header order keep
+/// their place. Header offsets are recomputed. Names keep the raw bytes read
+/// from the file, terminator and padding included; changed names are written
+/// NUL terminated and zero padded. Header names that don't fit in their 64
+/// byte field are rejected.
+pub fn write_sce(sce: &SceFile, writer: &mut dyn Write) -> Result<(), SceWriteError> {
+    let offsets = sce.proc_offsets()?;
+    writer.write_all(&SCE_MAGIC)?;
+    writer.write_u8(1)?;
+    writer.write_u16::<LittleEndian>(checked_len("proc list", sce.proc_headers.len())?)?;
+    for (header, offset) in sce.proc_headers.iter().zip(&offsets) {
+        writer.write_u32::<LittleEndian>(header.id)?;
+        writer.write_u32::<LittleEndian>(*offset)?;
+        writer.write_all(&header.name_bytes()?)?;
+    }
+
+    for header in sce.layout_order() {
+        let proc = sce
+            .procs
+            .get(&header.id)
+            .ok_or(SceWriteError::MissingProc(header.id))?;
+        let name = proc.name_bytes()?;
+        writer.write_u32::<LittleEndian>(proc.id)?;
+        writer.write_u16::<LittleEndian>(checked_len("proc name", name.len())?)?;
+        writer.write_all(&name)?;
+        writer.write_u16::<LittleEndian>(checked_len("local var list", proc.local_vars.len())?)?;
+        for var in &proc.local_vars {
+            writer.write_u8(var.unknown)?;
+            writer.write_u16::<LittleEndian>(checked_len("local var", var.unknown_vec.len())?)?;
+            writer.write_all(&var.unknown_vec)?;
+        }
+
+        let inst_len = u32::try_from(proc.inst.len())
+            .map_err(|_| SceWriteError::LengthOverflow("proc body", proc.inst.len()))?;
+        writer.write_u32::<LittleEndian>(inst_len)?;
+        writer.write_all(&proc.inst)?;
+    }
+
+    Ok(())
+}
+
+impl SceFile {
+    /// File offsets of the procs as laid out by `write_sce`, in header order.
+    pub fn proc_offsets(&self) -> Result<Vec<u32>, SceWriteError> {
+        let mut offset =
+            SCE_MAGIC.len() + 3 + self.proc_headers.len() * (8 + SCE_PROC_HEADER_NAME_SIZE);
+        let mut offsets = HashMap::new();
+        for header in self.layout_order() {
+            let proc = self
+                .procs
+                .get(&header.id)
+                .ok_or(SceWriteError::MissingProc(header.id))?;
+            offsets.insert(
+                header.id,
+                u32::try_from(offset).map_err(|_| SceWriteError::LengthOverflow("file", offset))?,
+            );
+            offset += 4 + 2 + proc.name_bytes()?.len() + 2 + 4 + proc.inst.len();
+            offset += proc
+                .local_vars
+                .iter()
+                .map(|v| 3 + v.unknown_vec.len())
+                .sum::<usize>();
+        }
+
+        Ok(self
+            .proc_headers
+            .iter()
+            .map(|header| offsets[&header.id])
+            .collect())
+    }
+
+    /// The headers in the order their proc bodies are stored, by offset.
+    pub fn layout_order(&self) -> Vec<&SceProcHeader> {
+        let mut headers: Vec<&SceProcHeader> = self.proc_headers.iter().collect();
+        headers.sort_by_key(|header| header.offset);
+        headers
+    }
+}
+
+impl SceProc {
+    /// The name bytes `write_sce` stores for this proc.
+    pub fn name_bytes(&self) -> Result<Vec<u8>, SceWriteError> {
+        if !self.raw_name.is_empty()
+            && decode_name(&self.raw_name).as_deref() == Some(self.name.as_str())
+        {
+            return Ok(self.raw_name.clone());
+        }
+
+        self.default_name_bytes()
+    }
+
+    /// `name` NUL terminated, as written when there are no raw bytes to keep.
+    pub fn default_name_bytes(&self) -> Result<Vec<u8>, SceWriteError> {
+        let mut name = encode_gbk(&self.name)?;
+        name.push(0);
+        Ok(name)
+    }
+}
+
+impl SceProcHeader {
+    /// The 64 byte name field `write_sce` stores for this header.
+    pub fn name_bytes(&self) -> Result<Vec<u8>, SceWriteError> {
+        if self.raw_name.len() == SCE_PROC_HEADER_NAME_SIZE
+            && decode_name(&self.raw_name).as_deref() == Some(self.name.as_str())
+        {
+            return Ok(self.raw_name.clone());
+        }
+
+        self.default_name_bytes()
+    }
+
+    /// `name` zero padded, as written when there are no raw bytes to keep.
+    pub fn default_name_bytes(&self) -> Result<Vec<u8>, SceWriteError> {
+        let mut name = encode_gbk(&self.name)?;
+        if name.len() > SCE_PROC_HEADER_NAME_SIZE {
+            return Err(SceWriteError::NameTooLong(self.name.clone()));
+        }
+
+        name.resize(SCE_PROC_HEADER_NAME_SIZE, 0);
+        Ok(name)
+    }
+}
+
+/// Sorts the procs by id to keep the json output stable.
+fn serialize_procs<S: serde::Serializer>(
+    procs: &HashMap<u32, SceProc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(procs.iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Decodes a stored name the way the reader does, up to the first NUL.
+fn decode_name(raw: &[u8]) -> Option<String> {
+    let end = raw.iter().position(|c| *c == 0).unwrap_or(raw.len());
+    to_gbk_string(&raw[..end]).ok()
+}
+
+fn checked_len(what: &'static str, len: usize) -> Result<u16, SceWriteError> {
+    u16::try_from(len).map_err(|_| SceWriteError::LengthOverflow(what, len))
+}
+
+fn encode_gbk(text: &str) -> Result<Vec<u8>, SceWriteError> {
+    encoding::all::GBK
+        .encode(text, EncoderTrap::Strict)
+        .map_err(|_| SceWriteError::Encoding(text.to_string()))
+}
@@ -0,0 +1,145 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use binrw::{binread, BinRead, BinResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    rwbs::Vec3f,
+    utils::{checked_count, gbk_string_parser},
+};
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScnNode {
+    pub index: u16,
+    pub w2: u16,
+
+    #[br(parse_with = gbk_string_parser, args(32))]
+    pub name: String,
+    pub w24: u16,
+    pub w26: u16,
+    pub position: Vec3f,
+    pub rotation: f32,
+    pub nav_trigger_coord_min: (i32, i32),
+    pub nav_trigger_coord_max: (i32, i32),
+    pub node_type: u16,
+    pub nav_layer: u16,
+    pub ladder_nav_coord1: (i32, i32),
+    pub ladder_nav_coord2: (i32, i32),
+    pub ladder_switch_layer: i32,
+
+    #[br(count = 6)]
+    pub b49: Vec<u8>,
+
+    #[br(count = 4)]
+    pub w66: Vec<u16>,
+
+    #[br(count = 18)]
+    pub b6e: Vec<u8>,
+    pub sce_proc_id: u32,
+    pub d84: u32,
+
+    #[br(count = 192)]
+    pub b88: Vec<u8>,
+    pub w148: u16,
+
+    #[br(count = 34)]
+    pub b14a: Vec<u8>,
+    pub aabb_trigger_coord1: Vec3f,
+    pub aabb_trigger_coord2: Vec3f,
+
+    #[br(count = 6)]
+    pub dw184: Vec<u32>,
+
+    #[br(count = 208)]
+    pub b: Vec<u8>,
+}
+
+#[binread]
+#[br(little)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScnRole {
+    pub index: u8,
+    pub b1: u8,
+
+    #[br(parse_with = gbk_string_parser, args(64))]
+    pub name: String,
+    pub w42: u16,
+    pub dw44: f32,
+    pub dw48: u32,
+    pub position_x: f32,
+    pub position_z: f32,
+    pub position_y: f32,
+    pub dw58: u32,
+    pub sce_proc_id: u32,
+    pub dw60: u32,
+
+    #[br(parse_with = gbk_string_parser, args(16))]
+    pub action_name: String,
+    pub dw74: u32,
+    pub dw78: u32,
+    pub dw7c: u32,
+
+    #[br(count = 4)]
+    pub b80: Vec<u8>,
+
+    #[br(count = 49)]
+    pub dw84: Vec<u32>,
+    pub dw148: u32,
+    pub dw14c: u32,
+    pub dw150: u32,
+
+    #[br(count = 29)]
+    pub dw154: Vec<u32>,
+}
+
+#[binread]
+#[br(little, magic = b"SCN\0")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScnFile {
+    #[br(temp, assert(version == 1, "unsupported scn version {}", version))]
+    version: u16,
+
+    #[br(temp)]
+    role_num: u16,
+
+    #[br(temp)]
+    role_offset: u32,
+
+    #[br(temp)]
+    node_num: u16,
+
+    #[br(temp)]
+    node_offset: u32,
+
+    #[br(parse_with = gbk_string_parser, args(32))]
+    pub cpk_name: String,
+
+    #[br(parse_with = gbk_string_parser, args(32))]
+    pub scn_name: String,
+
+    #[br(parse_with = gbk_string_parser, args(32))]
+    pub scn_base_name: String,
+    pub global_scene_index: u32,
+
+    #[br(map = |v: u32| v == 1)]
+    pub is_night: bool,
+    pub skybox_id: u32,
+
+    #[br(
+        seek_before = SeekFrom::Start(role_offset as u64),
+        parse_with = checked_count(role_num)
+    )]
+    pub roles: Vec<ScnRole>,
+
+    #[br(
+        seek_before = SeekFrom::Start(node_offset as u64),
+        parse_with = checked_count(node_num)
+    )]
+    pub nodes: Vec<ScnNode>,
+}
+
+pub fn read_scn(reader: &mut (impl Read + Seek)) -> BinResult<ScnFile> {
+    ScnFile::read(reader)
+}
@@ -1,9 +1,12 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    io::{Read, Seek, SeekFrom},
+};
 
-use binrw::{binrw, BinRead, BinWrite};
+use binrw::{binrw, BinRead, BinResult, BinWrite, Endian, VecArgs};
 use common::read_ext::FileReadError;
-use encoding::{DecoderTrap, Encoding};
-use serde::Serialize;
+use encoding::{DecoderTrap, EncoderTrap, Encoding};
+use serde::{Deserialize, Serialize};
 
 pub fn to_gbk_string(v: &[u8]) -> Result<String, FileReadError> {
     let str = encoding::all::GBK
@@ -12,6 +15,196 @@ pub fn to_gbk_string(v: &[u8]) -> Result<String, FileReadError> {
     Ok(str)
 }
 
+fn encode_gbk(text: &str) -> Option<Vec<u8>> {
+    encoding::all::GBK.encode(text, EncoderTrap::Strict).ok()
+}
+
+/// Json representation of the string types below. Strings that don't survive
+/// a GBK round trip are kept as raw bytes so that nothing is lost when the
+/// json is written back to binary.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SerializedString {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl SerializedString {
+    fn new(text: Result<String, FileReadError>, data: &[u8]) -> Self {
+        match text {
+            Ok(text) if encode_gbk(&text).as_deref() == Some(data) => Self::Text(text),
+            _ => Self::Bytes(data.to_vec()),
+        }
+    }
+
+    fn into_bytes<E: serde::de::Error>(self) -> Result<Vec<u8>, E> {
+        match self {
+            Self::Text(text) => encode_gbk(&text)
+                .ok_or_else(|| E::custom(format!("unable to encode {} in GBK", text))),
+            Self::Bytes(bytes) => Ok(bytes),
+        }
+    }
+}
+
+/// Trailing zeros are dropped as they are padding that the writer restores.
+/// The string is kept as text when it ends at the first zero, bytes left
+/// after the first zero make it fall back to raw bytes.
+fn serialize_padded<S: serde::Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let end = data.iter().rposition(|x| *x != 0).map_or(0, |p| p + 1);
+    let text_end = data.iter().position(|x| *x == 0).unwrap_or(data.len());
+    SerializedString::new(to_gbk_string(&data[..text_end]), &data[..end]).serialize(serializer)
+}
+
+/// Serde helpers for zero padded strings stored in byte arrays, serialized
+/// like `StringWithCapacity`.
+pub(crate) mod fixed_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::SerializedString;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        data: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        super::serialize_padded(data, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let mut data = SerializedString::deserialize(deserializer)?.into_bytes()?;
+        if data.len() > N {
+            return Err(serde::de::Error::custom(format!(
+                "string longer than {} bytes",
+                N
+            )));
+        }
+
+        data.resize(N, 0);
+        Ok(data.try_into().unwrap())
+    }
+}
+
+/// Serde helpers for byte arrays longer than the 32 elements serde supports.
+pub(crate) mod fixed_bytes {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        data: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        data.as_slice().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let data = Vec::<u8>::deserialize(deserializer)?;
+        let len = data.len();
+        data.try_into()
+            .map_err(|_| serde::de::Error::custom(format!("expected {} bytes, found {}", N, len)))
+    }
+}
+
+/// Serde helpers for `NullString`, serialized like `SizedString`.
+pub(crate) mod null_string {
+    use binrw::NullString;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{to_gbk_string, SerializedString};
+
+    pub fn serialize<S: Serializer>(string: &NullString, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedString::new(to_gbk_string(&string.0), &string.0).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NullString, D::Error> {
+        let data = SerializedString::deserialize(deserializer)?.into_bytes()?;
+        if data.contains(&0) {
+            return Err(serde::de::Error::custom("null string contains a zero"));
+        }
+
+        Ok(NullString(data))
+    }
+}
+
+/// Fails with `FileReadError::CountTooLarge` when `count` elements can't fit
+/// in the rest of the stream. Every element takes at least one byte, so this
+/// rejects bogus counts before anything is allocated for them.
+pub(crate) fn check_count<R: Read + Seek>(reader: &mut R, count: u64) -> BinResult<()> {
+    let pos = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(pos))?;
+
+    let remaining = end.saturating_sub(pos);
+    if count > remaining {
+        return Err(binrw::Error::Custom {
+            pos,
+            err: Box::new(FileReadError::CountTooLarge { count, remaining }),
+        });
+    }
+
+    Ok(())
+}
+
+/// Replacement of `#[br(count = ..)]` for counts read from the file, see
+/// `check_count`. Arguments of the elements are passed with `args(..)`.
+pub(crate) fn checked_count<R, T, Arg, N>(
+    count: N,
+) -> impl Fn(&mut R, Endian, Arg) -> BinResult<Vec<T>>
+where
+    R: Read + Seek,
+    T: for<'a> BinRead<Args<'a> = Arg> + 'static,
+    Arg: Clone,
+    N: TryInto<u64>,
+{
+    let count = count.try_into().unwrap_or(u64::MAX);
+    move |reader, endian, inner| {
+        check_count(reader, count)?;
+        Vec::<T>::read_options(
+            reader,
+            endian,
+            VecArgs {
+                count: count as usize,
+                inner,
+            },
+        )
+    }
+}
+
+/// Reads three `u16` indices and fails with `FileReadError::IndexOutOfRange`
+/// when one of them isn't below `len`.
+pub(crate) fn checked_indices<R: Read + Seek>(
+    len: u32,
+) -> impl Fn(&mut R, Endian, ()) -> BinResult<[u16; 3]> {
+    move |reader, endian, _| {
+        let pos = reader.stream_position()?;
+        let indices = <[u16; 3]>::read_options(reader, endian, ())?;
+        match indices.iter().find(|i| **i as u32 >= len) {
+            Some(index) => Err(binrw::Error::Custom {
+                pos,
+                err: Box::new(FileReadError::IndexOutOfRange {
+                    index: *index as u64,
+                    len: len as u64,
+                }),
+            }),
+            None => Ok(indices),
+        }
+    }
+}
+
+/// Reads a zero padded GBK string stored in a fixed size buffer.
+#[binrw::parser(reader)]
+pub(crate) fn gbk_string_parser(size: usize) -> BinResult<String> {
+    let pos = reader.stream_position()?;
+    let mut buf = vec![0u8; size];
+    reader.read_exact(&mut buf)?;
+    let end = buf.iter().position(|c| *c == 0).unwrap_or(size);
+    to_gbk_string(&buf[..end]).map_err(|e| binrw::Error::Custom {
+        pos,
+        err: Box::new(e),
+    })
+}
+
 #[binrw]
 #[brw(little)]
 #[derive(Clone)]
@@ -19,11 +212,15 @@ pub struct SizedString {
     #[bw(calc(string.len() as u32))]
     size: u32,
 
-    #[br(count = size)]
+    #[br(parse_with = checked_count(size))]
     string: Vec<u8>,
 }
 
 impl SizedString {
+    pub fn from_data(string: Vec<u8>) -> Self {
+        Self { string }
+    }
+
     pub fn data(&self) -> &[u8] {
         &self.string
     }
@@ -74,8 +271,17 @@ impl Serialize for SizedString {
     where
         S: serde::Serializer,
     {
-        let str = to_gbk_string(self.data());
-        serializer.serialize_str(&str.unwrap())
+        SerializedString::new(to_gbk_string(self.data()), self.data()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SizedString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = SerializedString::deserialize(deserializer)?.into_bytes()?;
+        Ok(Self { string })
     }
 }
 
@@ -85,10 +291,18 @@ impl Serialize for SizedString {
 #[brw(import(capacity: u32))]
 pub struct StringWithCapacity {
     #[br(count = capacity)]
+    #[bw(
+        assert(string.len() <= capacity as usize, "string longer than {} bytes", capacity),
+        pad_size_to = capacity
+    )]
     string: Vec<u8>,
 }
 
 impl StringWithCapacity {
+    pub fn from_data(string: Vec<u8>) -> Self {
+        Self { string }
+    }
+
     pub fn data(&self) -> &[u8] {
         &self.string
     }
@@ -138,14 +352,23 @@ impl Serialize for StringWithCapacity {
     where
         S: serde::Serializer,
     {
-        let str = self.as_str().unwrap_or_default();
-        serializer.serialize_str(&str)
+        serialize_padded(&self.string, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StringWithCapacity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let string = SerializedString::deserialize(deserializer)?.into_bytes()?;
+        Ok(Self { string })
     }
 }
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Pal4NodeSection {
     version1: u32,
     version2: u32,
@@ -156,17 +379,17 @@ pub struct Pal4NodeSection {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Pal4Node {
     pub name: SizedString,
     pub property_count: u32,
 
-    #[br(count = property_count)]
+    #[br(parse_with = checked_count(property_count))]
     pub properties: Vec<Pal4NodeProperty>,
 
     pub children_count: u32,
 
-    #[br(count = children_count)]
+    #[br(parse_with = checked_count(children_count))]
     pub children: Vec<Box<Pal4Node>>,
 }
 
@@ -185,12 +408,12 @@ impl Pal4Node {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Pal4NodeProperty {
-    #[br(magic(2u32))]
+    #[brw(magic(2u32))]
     Float(Pal4NodePropertyValue<f32>),
 
-    #[br(magic(3u32))]
+    #[brw(magic(3u32))]
     String(Pal4NodePropertyValue<SizedString>),
 }
 
@@ -210,6 +433,14 @@ impl Pal4NodeProperty {
         }
     }
 
+    pub fn f32_mut(&mut self) -> Option<&mut f32> {
+        if let Self::Float(v) = self {
+            Some(&mut v.value)
+        } else {
+            None
+        }
+    }
+
     pub fn string(&self) -> Option<Cow<str>> {
         if let Self::String(v) = self {
             Some(String::from_utf8_lossy(v.value.data()))
@@ -221,7 +452,7 @@ impl Pal4NodeProperty {
 
 #[binrw]
 #[brw(little)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Pal4NodePropertyValue<
     T: for<'a> BinRead<Args<'a> = ()> + for<'a> BinWrite<Args<'a> = ()>,
 > {
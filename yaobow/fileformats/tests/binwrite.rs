@@ -0,0 +1,429 @@
+mod common;
+
+use std::io::Cursor;
+
+use crate::common::{malformed, Bytes};
+use ::common::read_ext::FileReadError;
+use fileformats::{
+    amf::AmfFile,
+    binrw::{self, BinRead, BinWrite},
+    cam::CameraDataFile,
+    json::JsonFormat,
+    nod::NodFile,
+    npc::NpcInfoFile,
+    pol::{read_pol, write_pol},
+    role_bin::RoleBinFile,
+    sce::read_sce,
+    utils::StringWithCapacity,
+};
+use proptest::prelude::*;
+
+fn round_trip<T>(data: &[u8]) -> T
+where
+    T: for<'a> BinRead<Args<'a> = ()> + for<'a> BinWrite<Args<'a> = ()>,
+{
+    let file = T::read_le(&mut Cursor::new(data)).unwrap();
+    assert_eq!(write(&file), data);
+    file
+}
+
+fn write<T: for<'a> BinWrite<Args<'a> = ()>>(file: &T) -> Vec<u8> {
+    let mut writer = Cursor::new(vec![]);
+    file.write_le(&mut writer).unwrap();
+    writer.into_inner()
+}
+
+fn pal4_float(b: Bytes, name: &str, v: f32) -> Bytes {
+    b.u32(2).sized(name).f32(v)
+}
+
+fn pal4_string(b: Bytes, name: &str, v: &str) -> Bytes {
+    b.u32(3).sized(name).sized(v)
+}
+
+fn pol_data() -> Vec<u8> {
+    let mut b = Bytes::default()
+        .raw(b"POLY")
+        .u32(101)
+        .u32(1)
+        .raw(&[7; 52])
+        .u32(1)
+        .raw(&[1; 32])
+        .f32s(&[0.5; 16])
+        .u32(3)
+        .sized("ddd")
+        .f32s(&[-1., -1., -1., 1., 1., 1.])
+        .u32(0b10011)
+        .u32(2);
+    for i in 0..2 {
+        b = b
+            .f32s(&[i as f32, 1., 2.])
+            .f32s(&[0., 1., 0.])
+            .f32s(&[0.25, 0.75]);
+    }
+
+    b = b
+        .u32(1)
+        .u32(1)
+        .f32s(&[1.; 16])
+        .f32(0.5)
+        .u32(1)
+        .padded("tex.tga", 64)
+        .u32(4)
+        .u32(5)
+        .u32(6)
+        .u32(1);
+    b.u16(0).u16(1).u16(0).0
+}
+
+#[test]
+fn pol_round_trip() {
+    let data = pol_data();
+    let pol = read_pol(&mut Cursor::new(&data)).unwrap();
+    assert_eq!(pol.meshes[0].vertices.len(), 2);
+    assert_eq!(pol.meshes[0].material_info[0].texture_names[0], "tex.tga");
+
+    let mut writer = Cursor::new(vec![]);
+    write_pol(&pol, &mut writer).unwrap();
+    assert_eq!(writer.into_inner(), data);
+
+    let json = JsonFormat::Pol.to_json(&data).unwrap();
+    assert_eq!(JsonFormat::Pol.from_json(&json).unwrap(), data);
+}
+
+#[test]
+fn pol_rejects_out_of_range_indices() {
+    let mut data = pol_data();
+    let len = data.len();
+    data[len - 4..len - 2].copy_from_slice(&2u16.to_le_bytes());
+
+    let err = read_pol(&mut Cursor::new(&data)).unwrap_err();
+    let err = err.downcast_ref::<binrw::Error>().unwrap();
+    assert!(matches!(
+        err.custom_err::<FileReadError>(),
+        Some(FileReadError::IndexOutOfRange { index: 2, len: 2 })
+    ));
+}
+
+#[test]
+fn pol_write_recomputes_counts() {
+    let data = pol_data();
+    let mut pol = read_pol(&mut Cursor::new(&data)).unwrap();
+    pol.meshes[0].material_info[0].triangles.clear();
+
+    let mut writer = Cursor::new(vec![]);
+    write_pol(&pol, &mut writer).unwrap();
+
+    let mut expected = data[..data.len() - 6].to_vec();
+    let len = expected.len();
+    expected[len - 4..].copy_from_slice(&0u32.to_le_bytes());
+    assert_eq!(writer.into_inner(), expected);
+}
+
+#[test]
+fn pol_without_unknown_data_round_trip() {
+    let b = Bytes::default()
+        .raw(b"POLY")
+        .u32(100)
+        .u32(1)
+        .raw(&[0; 52])
+        .f32s(&[0.; 6])
+        .u32(0b10001)
+        .u32(1)
+        .f32s(&[1., 2., 3.])
+        .f32s(&[0.5, 0.5])
+        .u32(0);
+
+    let pol = read_pol(&mut Cursor::new(&b.0)).unwrap();
+    let mut writer = Cursor::new(vec![]);
+    write_pol(&pol, &mut writer).unwrap();
+    assert_eq!(writer.into_inner(), b.0);
+}
+
+fn nod_data(name: &str) -> Vec<u8> {
+    Bytes::default()
+        .u32(0x0001e240)
+        .u32(9)
+        .u32(1)
+        .padded(name, 100)
+        .f32s(&[1., 2., 3., 1., 1., 1., 0., 0.5, 0., 0., 0., 0.])
+        .u32(10)
+        .u32(11)
+        .u32(12)
+        .f32s(&[-1., -1., -1., 1., 1., 1.])
+        .raw(&[3; 28])
+        .u32(13)
+        .u32(14)
+        .0
+}
+
+#[test]
+fn nod_round_trip() {
+    let nod: NodFile = round_trip(&nod_data("node"));
+    assert_eq!(nod.nodes[0].name, "node");
+}
+
+#[test]
+fn nod_pads_renamed_node() {
+    let mut nod: NodFile = round_trip(&nod_data("node"));
+    nod.nodes[0].name = StringWithCapacity::from("renamed");
+    assert_eq!(write(&nod), nod_data("renamed"));
+}
+
+#[test]
+fn nod_write_recomputes_node_count() {
+    let mut nod: NodFile = round_trip(&nod_data("node"));
+    nod.nodes.clear();
+
+    let mut expected = nod_data("node");
+    expected[8..12].copy_from_slice(&0u32.to_le_bytes());
+    expected.drain(12..expected.len() - 8);
+    assert_eq!(write(&nod), expected);
+}
+
+#[test]
+fn nod_json_round_trip() {
+    let mut data = nod_data("node");
+    let json = JsonFormat::Nod.to_json(&data).unwrap();
+    assert!(json.contains("\"node\""));
+    assert_eq!(JsonFormat::Nod.from_json(&json).unwrap(), data);
+
+    // Bytes after the terminating zero are kept as raw bytes
+    data[20] = 7;
+    let json = JsonFormat::Nod.to_json(&data).unwrap();
+    assert!(!json.contains("\"node\""));
+    assert_eq!(JsonFormat::Nod.from_json(&json).unwrap(), data);
+}
+
+#[test]
+fn nod_json_edit() {
+    let json = JsonFormat::Nod.to_json(&nod_data("node")).unwrap();
+    let json = json.replace("\"node\"", "\"renamed\"");
+    assert_eq!(
+        JsonFormat::Nod.from_json(&json).unwrap(),
+        nod_data("renamed")
+    );
+}
+
+fn sce_data() -> Vec<u8> {
+    let mut b = Bytes::default().raw(b"SCE\0").u8(1).u16(2);
+    for (id, offset) in [(2, 151), (1, 169)] {
+        b = b.u32(id).u32(offset).padded(&format!("proc{}", id), 64);
+    }
+
+    for id in [2, 1] {
+        b = b
+            .u32(id)
+            .u16(5)
+            .raw(format!("proc{}", id).as_bytes())
+            .u16(0)
+            .u32(1)
+            .u8(id as u8);
+    }
+
+    b.0
+}
+
+#[test]
+fn sce_json_round_trip() {
+    let data = sce_data();
+    let json = JsonFormat::Sce.to_json(&data).unwrap();
+    assert!(json.find("\"1\"").unwrap() < json.find("\"2\"").unwrap());
+    assert_eq!(JsonFormat::Sce.from_json(&json).unwrap(), data);
+}
+
+fn role_bin_data() -> Vec<u8> {
+    Bytes::default()
+        .u32(0x87654321)
+        .u32(105)
+        .u32(1)
+        .u32(42)
+        .raw(&[9; 68])
+        .f32s(&[1., 2., 3.])
+        .null_terminated("model/a.dff")
+        .null_terminated("model")
+        .null_terminated("")
+        .null_terminated("")
+        .0
+}
+
+#[test]
+fn role_bin_round_trip() {
+    let role: RoleBinFile = round_trip(&role_bin_data());
+    assert_eq!(role.items[0].id, 42);
+}
+
+fn cam_data(position: [f32; 3]) -> Vec<u8> {
+    let mut b = Bytes::default()
+        .u32(1)
+        .sized("cam1\0")
+        .f32s(&[1., 2., 3.])
+        .f32s(&[0.; 3])
+        .u32(1)
+        .u32(2)
+        .f32(0.5)
+        .u32(3)
+        .f32(2.)
+        .u32(1)
+        .u32(0)
+        .u32(0)
+        .u32(0)
+        .sized("root")
+        .u32(0)
+        .u32(1)
+        .sized("position")
+        .u32(3);
+    for (name, v) in ["x", "y", "z"].iter().zip(position) {
+        b = pal4_float(b, name, v);
+    }
+
+    b.u32(0).0
+}
+
+#[test]
+fn cam_round_trip() {
+    let mut cam: CameraDataFile = round_trip(&cam_data([4., 5., 6.]));
+    let data = cam.get_camera_data_mut("cam1").unwrap();
+    assert_eq!(data.get_position(), Some([4., 5., 6.]));
+
+    assert!(data.set_position([7., 8., 9.]));
+    assert_eq!(write(&cam), cam_data([7., 8., 9.]));
+}
+
+fn npc_data() -> Vec<u8> {
+    let mut b = Bytes::default()
+        .u32(1)
+        .sized("npc")
+        .sized("model")
+        .sized("")
+        .f32s(&[1., 2., 3., 0., 90., 0.])
+        .u32(1)
+        .u32(2)
+        .f32(3.)
+        .u32(2)
+        .u32(2)
+        .u32(0)
+        .u32(0)
+        .sized("NPCINFO_BufferCache")
+        .u32(0)
+        .u32(1)
+        .sized("NPCINFO_BufferCache_Attr")
+        .u32(1);
+    b = pal4_string(b, "NPCINFO_BufferCache_Attr_defaultAct", "stand");
+    b.u32(0).0
+}
+
+#[test]
+fn npc_round_trip() {
+    let npc: NpcInfoFile = round_trip(&npc_data());
+    assert_eq!(npc.data[0].get_default_act().unwrap(), "stand");
+}
+
+fn amf_data(ticks: &[f32]) -> Vec<u8> {
+    let mut b = Bytes::default().sized("AMF").u32(ticks.len() as u32);
+    for tick in ticks {
+        b = b
+            .padded("model", 0x3c)
+            .u32(0xcdcdcdcd)
+            .padded("action", 0x3c)
+            .u32(0xcdcdcdcd)
+            .padded("hit", 0x3c)
+            .u32(0xcdcdcdcd)
+            .f32(*tick)
+            .u32(0)
+            .raw(&[0xcd; 0x84]);
+    }
+
+    b.0
+}
+
+#[test]
+fn amf_round_trip() {
+    let mut amf: AmfFile = round_trip(&amf_data(&[1., 2.]));
+    assert_eq!(amf.get_event("hit").unwrap().get_tick(), 1.);
+
+    amf.events_mut()[0].set_tick(3.);
+    amf.events_mut().pop();
+    assert_eq!(write(&amf), amf_data(&[3.]));
+}
+
+#[test]
+fn pal4_json_round_trip() {
+    for (format, data) in [
+        (JsonFormat::RoleBin, role_bin_data()),
+        (JsonFormat::Cam, cam_data([4., 5., 6.])),
+        (JsonFormat::Npc, npc_data()),
+        (JsonFormat::Amf, amf_data(&[1., 2.])),
+    ] {
+        let json = format.to_json(&data).unwrap();
+        assert_eq!(format.from_json(&json).unwrap(), data, "{:?}", format);
+    }
+}
+
+#[test]
+fn amf_json_edit() {
+    let json = JsonFormat::Amf.to_json(&amf_data(&[1.])).unwrap();
+    assert!(json.contains("\"hit\""));
+
+    let mut data = amf_data(&[1.]);
+    let pos = data.windows(4).position(|w| w == b"hit\0").unwrap();
+    data[pos..pos + 3].copy_from_slice(b"hut");
+    let json = json.replace("\"hit\"", "\"hut\"");
+    assert_eq!(JsonFormat::Amf.from_json(&json).unwrap(), data);
+}
+
+#[test]
+fn nod_rejects_huge_node_count() {
+    let mut data = nod_data("node");
+    data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let err = NodFile::read_le(&mut Cursor::new(&data)).unwrap_err();
+    assert!(matches!(
+        err.custom_err::<FileReadError>(),
+        Some(FileReadError::CountTooLarge {
+            count: 0xffffffff,
+            ..
+        })
+    ));
+}
+
+fn parse<T: for<'a> BinRead<Args<'a> = ()>>(data: &[u8]) {
+    let _ = T::read_le(&mut Cursor::new(data));
+}
+
+proptest! {
+    #[test]
+    fn pol_survives_malformed_input(data in malformed(pol_data())) {
+        let _ = read_pol(&mut Cursor::new(&data));
+    }
+
+    #[test]
+    fn nod_survives_malformed_input(data in malformed(nod_data("node"))) {
+        parse::<NodFile>(&data);
+    }
+
+    #[test]
+    fn sce_survives_malformed_input(data in malformed(sce_data())) {
+        let _ = read_sce(&mut Cursor::new(&data));
+    }
+
+    #[test]
+    fn role_bin_survives_malformed_input(data in malformed(role_bin_data())) {
+        parse::<RoleBinFile>(&data);
+    }
+
+    #[test]
+    fn cam_survives_malformed_input(data in malformed(cam_data([4., 5., 6.]))) {
+        parse::<CameraDataFile>(&data);
+    }
+
+    #[test]
+    fn npc_survives_malformed_input(data in malformed(npc_data())) {
+        parse::<NpcInfoFile>(&data);
+    }
+
+    #[test]
+    fn amf_survives_malformed_input(data in malformed(amf_data(&[1., 2.]))) {
+        parse::<AmfFile>(&data);
+    }
+}
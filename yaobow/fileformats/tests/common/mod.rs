@@ -0,0 +1,101 @@
+#![allow(dead_code)]
+
+use proptest::prelude::*;
+
+/// Little endian byte builder for the synthetic test files.
+#[derive(Default)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn u8(mut self, v: u8) -> Self {
+        self.0.push(v);
+        self
+    }
+
+    pub fn u32(mut self, v: u32) -> Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    pub fn u16(mut self, v: u16) -> Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    pub fn f32(mut self, v: f32) -> Self {
+        self.0.extend(v.to_le_bytes());
+        self
+    }
+
+    pub fn f32s(self, v: &[f32]) -> Self {
+        v.iter().fold(self, |b, v| b.f32(*v))
+    }
+
+    pub fn raw(mut self, v: &[u8]) -> Self {
+        self.0.extend(v);
+        self
+    }
+
+    pub fn padded(mut self, v: &str, capacity: usize) -> Self {
+        let start = self.0.len();
+        self.0.extend(v.as_bytes());
+        self.0.resize(start + capacity, 0);
+        self
+    }
+
+    pub fn sized(self, v: &str) -> Self {
+        self.u32(v.len() as u32).raw(v.as_bytes())
+    }
+
+    /// Appends a RenderWare chunk with the given body.
+    pub fn chunk(self, ty: u32, body: Bytes) -> Self {
+        self.u32(ty)
+            .u32(body.0.len() as u32)
+            .u16(0)
+            .u16(0x1803)
+            .raw(&body.0)
+    }
+
+    pub fn null_terminated(self, v: &str) -> Self {
+        self.raw(v.as_bytes()).raw(&[0])
+    }
+}
+
+/// Mutates a well formed sample the way a damaged or hostile file would:
+/// truncates it, flips a few bytes, or overwrites a dword with a huge count.
+pub fn malformed(sample: Vec<u8>) -> impl Strategy<Value = Vec<u8>> {
+    let len = sample.len();
+    let truncated = {
+        let sample = sample.clone();
+        (0..len.max(1)).prop_map(move |n| sample[..n.min(len)].to_vec())
+    };
+    let flipped = {
+        let sample = sample.clone();
+        prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..8).prop_map(
+            move |edits| {
+                let mut data = sample.clone();
+                if !data.is_empty() {
+                    for (i, b) in edits {
+                        let i = i.index(data.len());
+                        data[i] = b;
+                    }
+                }
+                data
+            },
+        )
+    };
+    let splatted = (
+        any::<prop::sample::Index>(),
+        prop_oneof![Just(u32::MAX), Just(0x7fff_ffff), any::<u32>()],
+    )
+        .prop_map(move |(i, v)| {
+            let mut data = sample.clone();
+            if data.len() >= 4 {
+                let i = i.index(data.len() - 3);
+                data[i..i + 4].copy_from_slice(&v.to_le_bytes());
+            }
+            data
+        });
+
+    prop_oneof![truncated, flipped, splatted]
+}
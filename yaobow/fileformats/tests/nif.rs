@@ -0,0 +1,196 @@
+mod common;
+
+use std::io::Cursor;
+
+use crate::common::{malformed, Bytes};
+use fileformats::{
+    binrw::{BinRead, BinWrite},
+    nif::blocks::{
+        CloningBehavior, ComponentFormat, DataStreamAccess, DataStreamUsage, KeyType, NiDataStream,
+        NiObjectArgs, NiSkinningMeshModifier, NiSourceTexture, NiTexturingProperty,
+        NiTransformData,
+    },
+};
+use proptest::prelude::*;
+
+fn round_trip<T>(data: &[u8]) -> T
+where
+    T: for<'a> BinRead<Args<'a> = NiObjectArgs> + for<'a> BinWrite<Args<'a> = ()>,
+{
+    let block = T::read_le_args(&mut Cursor::new(data), NiObjectArgs::default()).unwrap();
+
+    let mut writer = Cursor::new(vec![]);
+    block.write_le(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), data);
+    block
+}
+
+fn object_net(b: Bytes) -> Bytes {
+    b.u32(0).u32(0).u32(0xffffffff)
+}
+
+fn transform(b: Bytes, translation: [f32; 3]) -> Bytes {
+    b.f32s(&[1., 0., 0., 0., 1., 0., 0., 0., 1.])
+        .f32s(&translation)
+        .f32(1.)
+}
+
+fn transform_data_sample() -> Vec<u8> {
+    Bytes::default()
+        .u32(2)
+        .u32(KeyType::TBC.0)
+        .f32(0.)
+        .f32s(&[1., 0., 0., 0.])
+        .f32s(&[0., 0., 0.])
+        .f32(1.)
+        .f32s(&[0., 1., 0., 0.])
+        .f32s(&[0., 0., 0.])
+        .u32(1)
+        .u32(KeyType::QUADRATIC.0)
+        .f32(0.5)
+        .f32s(&[1., 2., 3.])
+        .f32s(&[0.; 6])
+        .u32(0)
+        .0
+}
+
+#[test]
+fn transform_data() {
+    let data: NiTransformData = round_trip(&transform_data_sample());
+    assert_eq!(data.quaternion_keys.len(), 2);
+    assert_eq!(data.quaternion_keys[1].value.x, 1.);
+    assert!(data.xyz_rotations.is_none());
+    assert_eq!(data.translations.keys[0].value.z, 3.);
+    assert!(data.translations.keys[0].tangents.is_some());
+    assert!(data.scales.interpolation.is_none());
+}
+
+#[test]
+fn transform_data_with_xyz_rotations() {
+    let mut b = Bytes::default().u32(1).u32(KeyType::XYZ_ROTATION.0);
+    for angle in [0.5, 1., 1.5] {
+        b = b.u32(1).u32(KeyType::LINEAR.0).f32(0.).f32(angle);
+    }
+    b = b.u32(0).u32(0);
+
+    let data: NiTransformData = round_trip(&b.0);
+    assert!(data.quaternion_keys.is_empty());
+    assert_eq!(data.xyz_rotations.unwrap()[2].keys[0].value, 1.5);
+}
+
+fn tex_desc(b: Bytes, source: i32) -> Bytes {
+    b.u32(source as u32).u16(0).u8(0)
+}
+
+fn texturing_property_sample() -> Vec<u8> {
+    let mut b = object_net(Bytes::default()).u16(0).u32(7);
+    b = tex_desc(b.u8(1), 3);
+    b = b.u8(0).u8(0).u8(0).u8(0).u8(0);
+    b = tex_desc(b.u8(1), 4);
+    b = b.u32(1).u8(1);
+    tex_desc(b, 5).u32(2).0
+}
+
+#[test]
+fn texturing_property() {
+    let property: NiTexturingProperty = round_trip(&texturing_property_sample());
+    let base = property.base_texture.texture.as_ref().unwrap();
+    assert_eq!(base.source, 3);
+    assert!(property.dark_texture.texture.is_none());
+    assert_eq!(property.bump_map_texture.unwrap().has_texture, 0);
+
+    let normal = property.normal_texture.unwrap().texture.unwrap();
+    assert_eq!(normal.source, 4);
+    assert!(property.parallax_texture.is_none());
+
+    let shader_map = property.shader_textures[0].map.as_ref().unwrap();
+    assert_eq!(shader_map.map_id, 2);
+}
+
+#[test]
+fn source_texture() {
+    let b = object_net(Bytes::default())
+        .u8(1)
+        .u32(2)
+        .u32(0xffffffff)
+        .u32(6)
+        .u32(1)
+        .u32(3)
+        .u8(1)
+        .u8(1)
+        .u8(0);
+
+    let texture: NiSourceTexture = round_trip(&b.0);
+    assert_eq!(texture.file_name, 2);
+    assert_eq!(texture.data, -1);
+}
+
+fn skinning_mesh_modifier_sample() -> Vec<u8> {
+    let mut b = Bytes::default().u32(1).u16(1).u32(0).u16(2).u32(0);
+    b = transform(b, [0., 0., 0.]).u32(2).u32(3).u32(4);
+    b = transform(b, [1., 0., 0.]);
+    b = transform(b, [2., 0., 0.]);
+    b.f32s(&[0., 0., 0., 1., 0., 0., 0., 2.]).0
+}
+
+#[test]
+fn skinning_mesh_modifier() {
+    let skin: NiSkinningMeshModifier = round_trip(&skinning_mesh_modifier_sample());
+    assert_eq!(skin.bones, [3, 4]);
+    assert_eq!(skin.bone_transforms[1].translation.x, 2.);
+    assert_eq!(skin.bone_bounds[1].radius, 2.);
+}
+
+#[test]
+fn data_stream_components() {
+    let mut data = vec![];
+    for (x, i) in [(1.5f32, 7u16), (-2., 9)] {
+        data.extend(half::f16::from_f32(x).to_le_bytes());
+        data.extend(half::f16::from_f32(0.).to_le_bytes());
+        data.extend(half::f16::from_f32(1.).to_le_bytes());
+        data.extend(i.to_le_bytes());
+    }
+
+    let stream = NiDataStream {
+        usage: DataStreamUsage::USAGE_VERTEX,
+        access: DataStreamAccess::GPU_READ,
+        num_bytes: data.len() as u32,
+        cloning_behavior: CloningBehavior::CLONING_SHARE,
+        num_regions: 0,
+        regions: vec![],
+        num_components: 2,
+        component_formats: vec![ComponentFormat::FLOAT16_3, ComponentFormat::UINT16_1],
+        data,
+        streamable: 1,
+    };
+
+    assert_eq!(stream.element_count(), 2);
+    assert_eq!(stream.read_component(0, 1, 1), [-2., 0., 1.]);
+    assert_eq!(stream.read_component(1, 0, 2), [7., 9.]);
+    assert_eq!(stream.read_component(1, 1, 5), [9.]);
+}
+
+fn parse<T: for<'a> BinRead<Args<'a> = NiObjectArgs>>(data: &[u8]) {
+    let _ = T::read_le_args(&mut Cursor::new(data), NiObjectArgs::default());
+}
+
+proptest! {
+    #[test]
+    fn transform_data_survives_malformed_input(data in malformed(transform_data_sample())) {
+        parse::<NiTransformData>(&data);
+    }
+
+    #[test]
+    fn texturing_property_survives_malformed_input(
+        data in malformed(texturing_property_sample())
+    ) {
+        parse::<NiTexturingProperty>(&data);
+    }
+
+    #[test]
+    fn skinning_mesh_modifier_survives_malformed_input(
+        data in malformed(skinning_mesh_modifier_sample())
+    ) {
+        parse::<NiSkinningMeshModifier>(&data);
+    }
+}
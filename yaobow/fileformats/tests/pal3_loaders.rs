@@ -0,0 +1,330 @@
+mod common;
+
+use std::io::Cursor;
+
+use crate::common::{malformed, Bytes};
+use ::common::read_ext::FileReadError;
+use fileformats::{
+    binrw, cvd::read_cvd, mv3::read_mv3, nav::read_nav, sce::read_sce, scn::read_scn,
+};
+use proptest::prelude::*;
+
+fn nav_data(version: u8) -> Vec<u8> {
+    let mut b = Bytes::default()
+        .raw(b"NAV\0")
+        .u8(version)
+        .u8(1)
+        .u32(14)
+        .u32(0);
+    if version == 2 {
+        for i in 0..8 {
+            b = b.u32(i).u32(i).u32(i + 1).u32(i + 1);
+        }
+    }
+
+    b = b
+        .f32s(&[10., 1., 20.])
+        .f32s(&[-10., 0., -20.])
+        .u32(2)
+        .u32(1)
+        .f32(0.5)
+        .u32(3)
+        .f32(1.5)
+        .u32(4);
+
+    let unknown2_offset = b.0.len() as u32;
+    b.0[10..14].copy_from_slice(&unknown2_offset.to_le_bytes());
+    b.u16(1).u16(1).f32s(&[1., 2., 3.]).u16(4).u16(5).u16(6).0
+}
+
+#[test]
+fn nav_read() {
+    let nav = read_nav(&mut Cursor::new(nav_data(2))).unwrap();
+    assert_eq!(nav.version, 2);
+
+    let map = &nav.maps[0];
+    assert_eq!(
+        map.layer_triggers.as_ref().unwrap()[7].nav_coord_max,
+        (8, 8)
+    );
+    assert_eq!(map.min_coord.z, -20.);
+    assert_eq!((map.width, map.height), (2, 1));
+    assert_eq!(map.map[0][1].height, 1.5);
+    assert_eq!(map.map[0][1].distance_to_border, 4);
+    assert_eq!(nav.unknown2[0].unknown22[0].unknown3, 6);
+}
+
+#[test]
+fn nav_v1_has_no_layer_triggers() {
+    let nav = read_nav(&mut Cursor::new(nav_data(1))).unwrap();
+    assert!(nav.maps[0].layer_triggers.is_none());
+    assert_eq!(nav.maps[0].map[0][0].height, 0.5);
+}
+
+#[test]
+fn nav_rejects_bad_input() {
+    assert!(read_nav(&mut Cursor::new(nav_data(3))).is_err());
+
+    let mut data = nav_data(2);
+    data.truncate(100);
+    assert!(read_nav(&mut Cursor::new(data)).is_err());
+}
+
+fn scn_data() -> Vec<u8> {
+    let mut b = Bytes::default()
+        .raw(b"SCN\0")
+        .u16(1)
+        .u16(0)
+        .u32(0)
+        .u16(1)
+        .u32(126)
+        .padded("Q01", 32)
+        .padded("Q01a", 32)
+        .padded("Q01", 32)
+        .u32(7)
+        .u32(1)
+        .u32(3);
+    assert_eq!(b.0.len(), 126);
+
+    b = b
+        .u16(5)
+        .u16(0)
+        .padded("_tree", 32)
+        .u16(0)
+        .u16(0)
+        .f32s(&[1., 2., 3.])
+        .f32(90.)
+        .u32(1)
+        .u32(2)
+        .u32(3)
+        .u32(4)
+        .u16(17)
+        .u16(1)
+        .raw(&[0; 20])
+        .raw(&[0; 6])
+        .raw(&[0; 8])
+        .raw(&[0; 18])
+        .u32(1001)
+        .u32(0)
+        .raw(&[0; 192])
+        .u16(0)
+        .raw(&[0; 34])
+        .f32s(&[-1., -1., -1., 1., 1., 1.])
+        .raw(&[0; 24])
+        .raw(&[0; 208]);
+
+    b.0
+}
+
+#[test]
+fn scn_read() {
+    let scn = read_scn(&mut Cursor::new(scn_data())).unwrap();
+    assert_eq!(scn.scn_name, "Q01a");
+    assert_eq!(scn.global_scene_index, 7);
+    assert!(scn.is_night);
+    assert!(scn.roles.is_empty());
+
+    let node = &scn.nodes[0];
+    assert_eq!(node.index, 5);
+    assert_eq!(node.name, "_tree");
+    assert_eq!(node.position.z, 3.);
+    assert_eq!(node.nav_trigger_coord_max, (3, 4));
+    assert_eq!(node.sce_proc_id, 1001);
+    assert_eq!(node.aabb_trigger_coord2.x, 1.);
+}
+
+#[test]
+fn scn_rejects_bad_input() {
+    let mut data = scn_data();
+    data[4] = 2;
+    assert!(read_scn(&mut Cursor::new(&data)).is_err());
+
+    let mut data = scn_data();
+    data.truncate(300);
+    assert!(read_scn(&mut Cursor::new(data)).is_err());
+}
+
+fn cvd_data(position_version: u8) -> Vec<u8> {
+    Bytes::default()
+        .raw(b"cvds")
+        .u32(1)
+        .u8(1)
+        .u32(1)
+        .u8(position_version)
+        .f32(0.)
+        .f32s(&[0., 1., 2., 3., 0., 0., 0., 0., 0., 0.])
+        .u32(0)
+        .u32(0)
+        .f32(2.)
+        .u32(1)
+        .u32(1)
+        .f32s(&[0.25, 0.75])
+        .f32s(&[0., 0., 1.])
+        .f32s(&[4., 5., 6.])
+        .f32(0.)
+        .u32(1)
+        .u8(0)
+        .u32(0)
+        .u32(0)
+        .u32(0)
+        .u32(0)
+        .f32(0.)
+        .padded("tex.tga", 64)
+        .u32(1)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u32(0)
+        .f32s(&[0.; 16])
+        .u32(0)
+        .0
+}
+
+#[test]
+fn cvd_read() {
+    let cvd = read_cvd(&mut Cursor::new(cvd_data(3))).unwrap();
+    let model = cvd.models[0].model.as_ref().unwrap();
+    assert!(cvd.models[0].children.is_none());
+    assert_eq!(model.scale_factor, 2.);
+
+    // Converted to y-up
+    let position = &model.position_keyframes.as_ref().unwrap().frames[0].position;
+    assert_eq!((position.x, position.y, position.z), (1., 3., -2.));
+    let vertex = &model.mesh.frames[0][0];
+    assert_eq!(
+        (vertex.position.x, vertex.position.y, vertex.position.z),
+        (4., 6., -5.)
+    );
+    assert_eq!(vertex.tex_coord.v, 0.75);
+
+    let material = &model.mesh.materials[0];
+    assert_eq!(material.texture_name, "tex.tga");
+    assert_eq!(material.triangles.as_ref().unwrap().len(), 1);
+}
+
+#[test]
+fn cvd_rejects_bad_input() {
+    assert!(read_cvd(&mut Cursor::new(cvd_data(4))).is_err());
+
+    let mut data = cvd_data(3);
+    data[3] = b'x';
+    assert!(read_cvd(&mut Cursor::new(&data)).is_err());
+
+    let mut data = cvd_data(3);
+    data.truncate(80);
+    assert!(read_cvd(&mut Cursor::new(data)).is_err());
+}
+
+#[test]
+fn sce_rejects_bad_input() {
+    let data = Bytes::default().raw(b"SCE\0").u8(1).u16(1).u32(1).0;
+    assert!(read_sce(&mut Cursor::new(data)).is_err());
+    assert!(read_sce(&mut Cursor::new(b"SCN\0\x01\0\0")).is_err());
+}
+
+fn mv3_data() -> Vec<u8> {
+    let mut b = Bytes::default()
+        .raw(b"MV3\0")
+        .u32(1)
+        .u32(100)
+        .u32(1)
+        .u32(0)
+        .u32(1)
+        .u32(1)
+        .u32(50)
+        .padded("walk", 16)
+        .f32s(&[0.; 17]);
+    for name in ["tex.tga", "", "", ""] {
+        b = b.sized(name);
+    }
+
+    b.raw(&[0; 64])
+        .u32(1)
+        .f32s(&[-1., -1., -1., 1., 1., 1.])
+        .u32(1)
+        .u32(0)
+        .u16(1)
+        .u16(2)
+        .u16(0x8000)
+        .u8(0)
+        .u8(0)
+        .u32(1)
+        .f32s(&[0.5, 0.5])
+        .u32(1)
+        .u32(0)
+        .u32(1)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u16(0)
+        .u32(0)
+        .0
+}
+
+#[test]
+fn mv3_read() {
+    let mv3 = read_mv3(&mut Cursor::new(mv3_data())).unwrap();
+    assert_eq!(mv3.action_desc[0].name, "walk");
+    assert_eq!(mv3.textures[0].names[0], "tex.tga");
+
+    let vertex = &mv3.models[0].frames[0].vertices[0];
+    assert_eq!((vertex.x, vertex.y, vertex.z), (-1, 2, i16::MIN));
+}
+
+#[test]
+fn mv3_rejects_huge_vertex_count() {
+    let mut data = mv3_data();
+    data[203..207].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(read_mv3(&mut Cursor::new(data)).is_err());
+}
+
+#[test]
+fn mv3_rejects_out_of_range_indices() {
+    let mut data = mv3_data();
+    let len = data.len();
+    data[len - 16..len - 14].copy_from_slice(&1u16.to_le_bytes());
+
+    let err = read_mv3(&mut Cursor::new(data)).unwrap_err();
+    let err = err.downcast_ref::<binrw::Error>().unwrap();
+    assert!(matches!(
+        err.custom_err::<FileReadError>(),
+        Some(FileReadError::IndexOutOfRange { index: 1, len: 1 })
+    ));
+}
+
+#[test]
+fn mv3_keeps_out_of_range_texcoord_indices() {
+    let mut data = mv3_data();
+    let len = data.len();
+    data[len - 10..len - 8].copy_from_slice(&5u16.to_le_bytes());
+
+    let mv3 = read_mv3(&mut Cursor::new(data)).unwrap();
+    assert_eq!(
+        mv3.models[0].meshes[0].triangles[0].texcoord_indices,
+        [5, 0, 0]
+    );
+}
+
+proptest! {
+    #[test]
+    fn nav_survives_malformed_input(data in malformed(nav_data(2))) {
+        let _ = read_nav(&mut Cursor::new(&data));
+    }
+
+    #[test]
+    fn scn_survives_malformed_input(data in malformed(scn_data())) {
+        let _ = read_scn(&mut Cursor::new(&data));
+    }
+
+    #[test]
+    fn cvd_survives_malformed_input(data in malformed(cvd_data(3))) {
+        let _ = read_cvd(&mut Cursor::new(&data));
+    }
+
+    #[test]
+    fn mv3_survives_malformed_input(data in malformed(mv3_data())) {
+        let _ = read_mv3(&mut Cursor::new(&data));
+    }
+}
@@ -0,0 +1,362 @@
+mod common;
+
+use crate::common::{malformed, Bytes};
+use fileformats::{
+    json::JsonFormat,
+    rwbs::{extension::Extension, read_bsp, read_dff, sector::Sector, write_bsp, write_dff},
+};
+use proptest::prelude::*;
+
+const STRUCT: u32 = 0x1;
+const STRING: u32 = 0x2;
+const EXTENSION: u32 = 0x3;
+const TEXTURE: u32 = 0x6;
+const MATERIAL: u32 = 0x7;
+const MATERIAL_LIST: u32 = 0x8;
+const ATOMIC_SECTOR: u32 = 0x9;
+const WORLD: u32 = 0xb;
+const FRAME_LIST: u32 = 0xe;
+const GEOMETRY: u32 = 0xf;
+const CLUMP: u32 = 0x10;
+const ATOMIC: u32 = 0x14;
+const GEOMETRY_LIST: u32 = 0x1a;
+const RIGHT_TO_RENDER: u32 = 0x1f;
+const PLUGIN_SKIN: u32 = 0x116;
+const PLUGIN_HANIM: u32 = 0x11e;
+const PLUGIN_USERDATA: u32 = 0x11f;
+const PLUGIN_MATFX: u32 = 0x120;
+const PLUGIN_BINMESH: u32 = 0x50e;
+const PLUGIN_NODENAME: u32 = 0x0253f2fe;
+
+// "主角" in GBK, which isn't valid UTF-8
+const GBK_NAME: &[u8] = &[0xd6, 0xf7, 0xbd, 0xc7];
+
+fn string(b: Bytes, v: &str) -> Bytes {
+    b.u32(v.len() as u32 + 1).null_terminated(v)
+}
+
+fn material(texture_name: &[u8]) -> Bytes {
+    let texture = Bytes::default()
+        .chunk(STRUCT, Bytes::default().u32(0x11106))
+        .chunk(STRING, Bytes::default().raw(texture_name))
+        .chunk(STRING, Bytes::default().padded("", 4))
+        .chunk(EXTENSION, Bytes::default());
+
+    Bytes::default()
+        .chunk(
+            STRUCT,
+            Bytes::default()
+                .u32(0)
+                .u32(0xffffffff)
+                .u32(1)
+                .u32(1)
+                .f32s(&[1., 0.5, 1.]),
+        )
+        .chunk(TEXTURE, texture)
+        .chunk(
+            EXTENSION,
+            Bytes::default().chunk(PLUGIN_MATFX, Bytes::default().u32(0).u32(7)),
+        )
+}
+
+fn material_list() -> Bytes {
+    Bytes::default().chunk(
+        MATERIAL_LIST,
+        Bytes::default()
+            .chunk(STRUCT, Bytes::default().u32(1).u32(0xffffffff))
+            .chunk(MATERIAL, material(b"tex\0")),
+    )
+}
+
+fn dff_data(vertex: [f32; 3]) -> Vec<u8> {
+    dff_data_with(vertex, b"root", material_list())
+}
+
+fn dff_data_with(vertex: [f32; 3], frame_name: &[u8], material_list: Bytes) -> Vec<u8> {
+    let user_data = string(Bytes::default().u32(2), "name").u32(3).u32(1);
+    let user_data = user_data
+        .u32(frame_name.len() as u32 + 1)
+        .raw(frame_name)
+        .raw(&[0]);
+    let user_data = string(user_data, "id").u32(1).u32(1).u32(42);
+    let frame_ext = Bytes::default()
+        .chunk(
+            PLUGIN_HANIM,
+            Bytes::default()
+                .u32(0x100)
+                .u32(1)
+                .u32(1)
+                .u32(0)
+                .u32(36)
+                .u32(1)
+                .u32(0)
+                .u32(0),
+        )
+        .chunk(PLUGIN_USERDATA, user_data)
+        .chunk(PLUGIN_NODENAME, Bytes::default().raw(frame_name));
+
+    let frame_list = Bytes::default()
+        .chunk(
+            STRUCT,
+            Bytes::default()
+                .u32(1)
+                .f32s(&[1., 0., 0., 0., 1., 0., 0., 0., 1., 0., 0., 0.])
+                .u32(0xffffffff)
+                .u32(0),
+        )
+        .chunk(EXTENSION, frame_ext);
+
+    let mut geometry_struct = Bytes::default()
+        .u32(0x00010016)
+        .u32(1)
+        .u32(3)
+        .u32(1)
+        .f32s(&[0., 0., 1., 1., 1., 0.])
+        .u16(1)
+        .u16(0)
+        .u16(0)
+        .u16(2)
+        .f32s(&[0., 0., 0., 2.])
+        .u32(1)
+        .u32(1)
+        .f32s(&vertex)
+        .f32s(&[1., 0., 0., 0., 1., 0.]);
+    for _ in 0..3 {
+        geometry_struct = geometry_struct.f32s(&[0., 0., 1.]);
+    }
+
+    let skin = Bytes::default()
+        .raw(&[1, 1, 4, 0])
+        .raw(&[0])
+        .raw(&[0; 12])
+        .f32s(&[1., 0., 0., 0., 1., 0., 0., 0., 1., 0., 0., 0.])
+        .f32s(&[1.; 16])
+        .raw(&[0; 12]);
+    let geometry_ext = Bytes::default()
+        .chunk(
+            PLUGIN_BINMESH,
+            Bytes::default()
+                .u32(0)
+                .u32(1)
+                .u32(3)
+                .u32(3)
+                .u32(0)
+                .u32(0)
+                .u32(1)
+                .u32(2),
+        )
+        .chunk(PLUGIN_SKIN, skin);
+
+    let geometry = Bytes::default()
+        .chunk(STRUCT, geometry_struct)
+        .raw(&material_list.0)
+        .chunk(EXTENSION, geometry_ext);
+
+    let geometry_list = Bytes::default()
+        .chunk(STRUCT, Bytes::default().u32(1))
+        .chunk(GEOMETRY, geometry);
+
+    let atomic = Bytes::default()
+        .chunk(STRUCT, Bytes::default().u32(0).u32(0).u32(5).u32(0))
+        .chunk(
+            EXTENSION,
+            Bytes::default().chunk(RIGHT_TO_RENDER, Bytes::default().u32(PLUGIN_SKIN).u32(1)),
+        );
+
+    let clump = Bytes::default()
+        .chunk(STRUCT, Bytes::default().u32(1).u32(0).u32(0))
+        .chunk(FRAME_LIST, frame_list)
+        .chunk(GEOMETRY_LIST, geometry_list)
+        .chunk(ATOMIC, atomic)
+        .chunk(EXTENSION, Bytes::default());
+
+    Bytes::default().chunk(CLUMP, clump).0
+}
+
+#[test]
+fn dff_round_trip() {
+    let data = dff_data([1., 2., 3.]);
+    let clumps = read_dff(&data).unwrap();
+    assert_eq!(clumps[0].frames[0].name().as_deref(), Some("root"));
+    assert!(clumps[0].atomics[0].contains_right_to_render());
+
+    let material = &clumps[0].geometries[0].materials[0];
+    assert_eq!(material.texture.as_ref().unwrap().name, "tex");
+    assert!(matches!(
+        material.extensions[0],
+        Extension::UnknownPlugin(_)
+    ));
+
+    assert_eq!(write_dff(&clumps).unwrap(), data);
+}
+
+/// Names that aren't valid UTF-8, texture names padded past the next 4 bytes
+/// and a material instancing an earlier one are all written back as read.
+fn raw_names_dff_data() -> Vec<u8> {
+    let mut texture_name = GBK_NAME.to_vec();
+    texture_name.resize(12, 0);
+    let material_list = Bytes::default().chunk(
+        MATERIAL_LIST,
+        Bytes::default()
+            .chunk(
+                STRUCT,
+                Bytes::default()
+                    .u32(3)
+                    .u32(0xffffffff)
+                    .u32(0)
+                    .u32(0xffffffff),
+            )
+            .chunk(MATERIAL, material(&texture_name))
+            .chunk(MATERIAL, material(b"tex\0")),
+    );
+
+    dff_data_with([1., 2., 3.], GBK_NAME, material_list)
+}
+
+#[test]
+fn dff_round_trip_raw_names_and_instances() {
+    let data = raw_names_dff_data();
+    let clumps = read_dff(&data).unwrap();
+    assert_eq!(clumps[0].frames[0].name().as_deref(), Some("主角"));
+    assert!(clumps[0].frames[0].extensions.iter().any(|e| matches!(
+        e,
+        Extension::NodeNamePlugin(p) if p.name().data() == GBK_NAME
+    )));
+
+    let geometry = &clumps[0].geometries[0];
+    assert_eq!(geometry.material_instances, vec![-1, 0, -1]);
+    let names: Vec<String> = geometry
+        .materials
+        .iter()
+        .map(|m| m.texture.as_ref().unwrap().name.as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["主角", "主角", "tex"]);
+
+    assert_eq!(write_dff(&clumps).unwrap(), data);
+}
+
+#[test]
+fn dff_material_instance_out_of_range() {
+    let material_list = Bytes::default().chunk(
+        MATERIAL_LIST,
+        Bytes::default()
+            .chunk(STRUCT, Bytes::default().u32(2).u32(1).u32(0xffffffff))
+            .chunk(MATERIAL, material(b"tex\0")),
+    );
+
+    assert!(read_dff(&dff_data_with([1., 2., 3.], b"root", material_list)).is_err());
+}
+
+#[test]
+fn dff_write_edited_geometry() {
+    let mut clumps = read_dff(&dff_data([1., 2., 3.])).unwrap();
+    let vertices = clumps[0].geometries[0].morph_targets[0]
+        .vertices
+        .as_mut()
+        .unwrap();
+    vertices[0].y = 5.;
+
+    assert_eq!(write_dff(&clumps).unwrap(), dff_data([1., 5., 3.]));
+}
+
+#[test]
+fn dff_json_round_trip() {
+    let data = dff_data([1., 2., 3.]);
+    let json = JsonFormat::Dff.to_json(&data).unwrap();
+    assert_eq!(JsonFormat::Dff.from_json(&json).unwrap(), data);
+}
+
+#[test]
+fn dff_json_round_trip_raw_names() {
+    let material_list = Bytes::default().chunk(
+        MATERIAL_LIST,
+        Bytes::default()
+            .chunk(STRUCT, Bytes::default().u32(2).u32(0xffffffff).u32(0))
+            .chunk(MATERIAL, material(&[GBK_NAME, &[0; 4]].concat())),
+    );
+    let data = dff_data_with([1., 2., 3.], GBK_NAME, material_list);
+    let json = JsonFormat::Dff.to_json(&data).unwrap();
+    assert_eq!(JsonFormat::Dff.from_json(&json).unwrap(), data);
+}
+
+fn bsp_data(vertex_x: f32) -> Vec<u8> {
+    let mut sector_struct = Bytes::default()
+        .u32(0)
+        .u32(1)
+        .u32(3)
+        .f32s(&[0., 0., 0., 1., 1., 1.])
+        .u32(0)
+        .u32(0)
+        .f32s(&[vertex_x, 0., 0., 0., 1., 0., 0., 0., 1.]);
+    for _ in 0..3 {
+        sector_struct = sector_struct.raw(&[0, 0, 127, 0]);
+    }
+    sector_struct = sector_struct
+        .f32s(&[0., 0., 1., 0., 0., 1.])
+        .u16(0)
+        .u16(1)
+        .u16(2)
+        .u16(0);
+
+    let world_struct = Bytes::default()
+        .u32(1)
+        .f32s(&[-1., -2., -3.])
+        .u32(1)
+        .u32(3)
+        .u32(0)
+        .u32(1)
+        .u32(0)
+        .u32(0x40000014)
+        .f32s(&[1., 1., 1., 0., 0., 0.]);
+
+    let world = Bytes::default()
+        .chunk(STRUCT, world_struct)
+        .raw(&material_list().0)
+        .chunk(
+            ATOMIC_SECTOR,
+            Bytes::default()
+                .chunk(STRUCT, sector_struct)
+                .chunk(EXTENSION, Bytes::default()),
+        )
+        .chunk(EXTENSION, Bytes::default());
+
+    Bytes::default().chunk(WORLD, world).0
+}
+
+#[test]
+fn bsp_round_trip() {
+    let data = bsp_data(1.);
+    let worlds = read_bsp(&data).unwrap();
+    assert_eq!(worlds[0].world_origin_z, 3.);
+    assert_eq!(write_bsp(&worlds).unwrap(), data);
+}
+
+#[test]
+fn bsp_write_edited_sector() {
+    let mut worlds = read_bsp(&bsp_data(1.)).unwrap();
+    match &mut worlds[0].sector {
+        Sector::AtomicSector(sector) => sector.vertices[0].x = 4.,
+        _ => unreachable!(),
+    }
+
+    assert_eq!(write_bsp(&worlds).unwrap(), bsp_data(4.));
+}
+
+#[test]
+fn bsp_json_round_trip() {
+    let data = bsp_data(1.);
+    let json = JsonFormat::Bsp.to_json(&data).unwrap();
+    assert_eq!(JsonFormat::Bsp.from_json(&json).unwrap(), data);
+}
+
+proptest! {
+    #[test]
+    fn dff_survives_malformed_input(data in malformed(dff_data([1., 2., 3.]))) {
+        let _ = read_dff(&data);
+    }
+
+    #[test]
+    fn bsp_survives_malformed_input(data in malformed(bsp_data(1.))) {
+        let _ = read_bsp(&data);
+    }
+}
@@ -1,11 +1,28 @@
+use std::path::PathBuf;
+
 use serde::Deserialize;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct YaobowConfig {
     pub asset_path: String,
+
+    /// Directories layered on top of the game data. Loose files in them
+    /// override the packed assets; earlier entries take precedence.
+    #[serde(default)]
+    pub mod_paths: Vec<String>,
+
+    /// Directory of `<edition>.manifest` reference manifests used to detect
+    /// the game edition at startup. Relative paths are resolved against
+    /// `asset_path`.
+    #[serde(default)]
+    pub edition_manifest_path: Option<String>,
 }
 
 impl YaobowConfig {
+    pub fn mod_paths(&self) -> Vec<PathBuf> {
+        self.mod_paths.iter().map(PathBuf::from).collect()
+    }
+
     pub fn load(config_name: &str, app_name: &str) -> anyhow::Result<YaobowConfig> {
         use crate::ydirs;
 
@@ -0,0 +1,193 @@
+use crate::openpal3::loaders::cvd_loader::{CvdFile, CvdModel, CvdModelNode};
+
+use super::gltf_exporter::{GltfAnimationPath, GltfBuilder, GltfChannel, GltfNode, GltfPrimitive};
+
+/// Exports the cvd node tree. Position, rotation and scale keyframes become
+/// node animations, and the extra vertex frames of a mesh become morph
+/// targets. The timing of the vertex frames is unknown, so the morph targets
+/// are not animated.
+pub fn export_cvd_to_gltf(
+    cvd_file: &CvdFile,
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> GltfBuilder {
+    let mut builder = GltfBuilder::new();
+    let mut channels = vec![];
+    for node in &cvd_file.models {
+        let index = export_node(&mut builder, &mut channels, node, name, texture_loader);
+        builder.add_root(index);
+    }
+
+    builder.add_animation(name, &channels);
+    builder
+}
+
+fn export_node(
+    builder: &mut GltfBuilder,
+    channels: &mut Vec<GltfChannel>,
+    node: &CvdModelNode,
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> usize {
+    let name = format!("{}_{}", name, builder.node_count());
+    let mut gltf_node = GltfNode {
+        name: name.clone(),
+        ..Default::default()
+    };
+
+    if let Some(model) = &node.model {
+        gltf_node.mesh = export_mesh(builder, model, &name, texture_loader);
+        gltf_node.translation = model
+            .position_keyframes
+            .as_ref()
+            .and_then(|k| k.frames.first())
+            .map(|f| [f.position.x, f.position.y, f.position.z]);
+        gltf_node.rotation = model
+            .rotation_keyframes
+            .as_ref()
+            .and_then(|k| k.frames.first())
+            .map(|f| {
+                normalize_quaternion(
+                    f.quaternion.x,
+                    f.quaternion.y,
+                    f.quaternion.z,
+                    f.quaternion.w,
+                )
+            });
+        gltf_node.scale = Some(scale_at(model, 0));
+    }
+
+    let index = builder.add_node(gltf_node);
+    if let Some(model) = &node.model {
+        add_channels(channels, index, model);
+    }
+
+    let children: Vec<usize> = node
+        .children
+        .iter()
+        .flatten()
+        .map(|child| export_node(builder, channels, child, &name, texture_loader))
+        .collect();
+    builder.set_children(index, &children);
+    index
+}
+
+fn export_mesh(
+    builder: &mut GltfBuilder,
+    model: &CvdModel,
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Option<usize> {
+    let frames = &model.mesh.frames;
+    let base = frames.first()?;
+    let primitives: Vec<GltfPrimitive> = model
+        .mesh
+        .materials
+        .iter()
+        .filter_map(|m| {
+            let triangles = m.triangles.as_ref()?;
+            let material =
+                builder.add_material(&m.texture_name, texture_loader(&m.texture_name), false);
+
+            Some(GltfPrimitive {
+                positions: base
+                    .iter()
+                    .map(|v| [v.position.x, v.position.y, v.position.z])
+                    .collect(),
+                texcoords: Some(
+                    base.iter()
+                        .map(|v| [v.tex_coord.u, v.tex_coord.v])
+                        .collect(),
+                ),
+                indices: triangles
+                    .iter()
+                    .flat_map(|t| t.indices.map(|i| i as u32))
+                    .collect(),
+                material: Some(material),
+                morph_targets: frames[1..]
+                    .iter()
+                    .map(|f| {
+                        f.iter()
+                            .map(|v| [v.position.x, v.position.y, v.position.z])
+                            .collect()
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    if primitives.is_empty() {
+        None
+    } else {
+        Some(builder.add_mesh(name, &primitives))
+    }
+}
+
+fn add_channels(channels: &mut Vec<GltfChannel>, node: usize, model: &CvdModel) {
+    if let Some(keyframes) = &model.position_keyframes {
+        channels.push(GltfChannel {
+            node,
+            path: GltfAnimationPath::Translation,
+            times: keyframes.frames.iter().map(|f| f.timestamp).collect(),
+            values: keyframes
+                .frames
+                .iter()
+                .flat_map(|f| [f.position.x, f.position.y, f.position.z])
+                .collect(),
+            step: false,
+        });
+    }
+
+    if let Some(keyframes) = &model.rotation_keyframes {
+        channels.push(GltfChannel {
+            node,
+            path: GltfAnimationPath::Rotation,
+            times: keyframes.frames.iter().map(|f| f.timestamp).collect(),
+            values: keyframes
+                .frames
+                .iter()
+                .flat_map(|f| {
+                    let q = &f.quaternion;
+                    normalize_quaternion(q.x, q.y, q.z, q.w)
+                })
+                .collect(),
+            step: false,
+        });
+    }
+
+    if let Some(keyframes) = &model.scale_keyframes {
+        channels.push(GltfChannel {
+            node,
+            path: GltfAnimationPath::Scale,
+            times: keyframes.frames.iter().map(|f| f.timestamp).collect(),
+            values: (0..keyframes.frames.len())
+                .flat_map(|i| scale_at(model, i))
+                .collect(),
+            step: false,
+        });
+    }
+}
+
+/// The axis rotation of the scale keyframes can't be expressed in a glTF
+/// node, so only the scale itself is kept.
+fn scale_at(model: &CvdModel, index: usize) -> [f32; 3] {
+    let s = model.scale_factor;
+    match model
+        .scale_keyframes
+        .as_ref()
+        .and_then(|k| k.frames.get(index))
+    {
+        Some(f) => [f.scale.x * s, f.scale.y * s, f.scale.z * s],
+        None => [s, s, s],
+    }
+}
+
+fn normalize_quaternion(x: f32, y: f32, z: f32, w: f32) -> [f32; 4] {
+    let len = (x * x + y * y + z * z + w * w).sqrt();
+    if len > 0. {
+        [x / len, y / len, z / len, w / len]
+    } else {
+        [0., 0., 0., 1.]
+    }
+}
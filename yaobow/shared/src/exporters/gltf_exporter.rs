@@ -0,0 +1,530 @@
+use std::{collections::HashMap, io::Cursor, path::Path};
+
+use serde_json::{json, Value};
+
+const GLB_MAGIC: u32 = 0x46546C67; // "glTF"
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A; // "JSON"
+const GLB_CHUNK_BIN: u32 = 0x004E4942; // "BIN\0"
+
+const COMPONENT_UNSIGNED_SHORT: u32 = 5123;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+const COMPONENT_FLOAT: u32 = 5126;
+
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+#[derive(Debug, Clone, Default)]
+pub struct GltfPrimitive {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Option<Vec<[f32; 3]>>,
+    pub texcoords: Option<Vec<[f32; 2]>>,
+    pub joints: Option<Vec<[u16; 4]>>,
+    pub weights: Option<Vec<[f32; 4]>>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+
+    /// Absolute vertex positions of each morph target. They are stored as
+    /// offsets from `positions` as required by glTF.
+    pub morph_targets: Vec<Vec<[f32; 3]>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GltfNode {
+    pub name: String,
+    pub mesh: Option<usize>,
+    pub skin: Option<usize>,
+    pub children: Vec<usize>,
+    pub matrix: Option<[f32; 16]>,
+    pub translation: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 4]>,
+    pub scale: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfAnimationPath {
+    Translation,
+    Rotation,
+    Scale,
+    Weights,
+}
+
+impl GltfAnimationPath {
+    fn name(&self) -> &'static str {
+        match self {
+            GltfAnimationPath::Translation => "translation",
+            GltfAnimationPath::Rotation => "rotation",
+            GltfAnimationPath::Scale => "scale",
+            GltfAnimationPath::Weights => "weights",
+        }
+    }
+
+    fn accessor_type(&self) -> &'static str {
+        match self {
+            GltfAnimationPath::Translation | GltfAnimationPath::Scale => "VEC3",
+            GltfAnimationPath::Rotation => "VEC4",
+            GltfAnimationPath::Weights => "SCALAR",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfChannel {
+    pub node: usize,
+    pub path: GltfAnimationPath,
+    pub times: Vec<f32>,
+
+    /// Flattened output values, `times.len()` groups of the path's component
+    /// count, or of the morph target count for weights.
+    pub values: Vec<f32>,
+    pub step: bool,
+}
+
+impl GltfChannel {
+    /// glTF requires strictly increasing keyframe times, so keyframes that
+    /// don't move time forward are dropped.
+    fn increasing_keyframes(&self) -> (Vec<f32>, Vec<f32>) {
+        let stride = self.values.len() / self.times.len();
+        let mut times = vec![];
+        let mut values = vec![];
+        for (i, t) in self.times.iter().enumerate() {
+            if times.last().map_or(true, |last| t > last) {
+                times.push(*t);
+                values.extend_from_slice(&self.values[i * stride..(i + 1) * stride]);
+            }
+        }
+
+        (times, values)
+    }
+}
+
+/// Builds a glTF 2.0 document with all the binary data in a single buffer,
+/// which is saved either as a `.glb` or as a `.gltf` with a `.bin` sidecar.
+#[derive(Debug, Default)]
+pub struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    roots: Vec<usize>,
+    material_map: HashMap<String, usize>,
+}
+
+impl GltfBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a textured material, reusing an earlier one with the same name.
+    /// Images the `image` crate can decode (including dds) are embedded as
+    /// png, anything else is referenced by `name` relative to the output.
+    pub fn add_material(&mut self, name: &str, image: Option<Vec<u8>>, use_alpha: bool) -> usize {
+        if let Some(index) = self.material_map.get(name) {
+            return *index;
+        }
+
+        let mut material = json!({
+            "name": name,
+            "pbrMetallicRoughness": {
+                "metallicFactor": 0.0,
+                "roughnessFactor": 1.0,
+            },
+            "doubleSided": true,
+        });
+
+        if !name.is_empty() {
+            let image = match image.and_then(|data| encode_png(&data)) {
+                Some(png) => {
+                    let view = self.add_buffer_view(&png, None);
+                    json!({ "name": name, "bufferView": view, "mimeType": "image/png" })
+                }
+                None => json!({ "name": name, "uri": name }),
+            };
+
+            self.images.push(image);
+            self.textures
+                .push(json!({ "source": self.images.len() - 1 }));
+            material["pbrMetallicRoughness"]["baseColorTexture"] =
+                json!({ "index": self.textures.len() - 1 });
+        }
+
+        if use_alpha {
+            material["alphaMode"] = json!("BLEND");
+        }
+
+        self.materials.push(material);
+        let index = self.materials.len() - 1;
+        self.material_map.insert(name.to_string(), index);
+        index
+    }
+
+    pub fn add_mesh(&mut self, name: &str, primitives: &[GltfPrimitive]) -> usize {
+        let mut gltf_primitives = vec![];
+        let mut target_count = 0;
+        for p in primitives {
+            let mut attributes = json!({
+                "POSITION": self.add_vec3_accessor(&p.positions, true),
+            });
+
+            if let Some(normals) = &p.normals {
+                attributes["NORMAL"] = json!(self.add_vec3_accessor(normals, false));
+            }
+
+            if let Some(texcoords) = &p.texcoords {
+                let data: Vec<f32> = texcoords.iter().flatten().copied().collect();
+                attributes["TEXCOORD_0"] =
+                    json!(self.add_float_accessor(&data, "VEC2", Some(TARGET_ARRAY_BUFFER), false));
+            }
+
+            if let Some(joints) = &p.joints {
+                let data: Vec<u8> = joints
+                    .iter()
+                    .flatten()
+                    .flat_map(|j| j.to_le_bytes())
+                    .collect();
+                let view = self.add_buffer_view(&data, Some(TARGET_ARRAY_BUFFER));
+                attributes["JOINTS_0"] = json!(self.add_accessor(json!({
+                    "bufferView": view,
+                    "componentType": COMPONENT_UNSIGNED_SHORT,
+                    "count": joints.len(),
+                    "type": "VEC4",
+                })));
+            }
+
+            if let Some(weights) = &p.weights {
+                let data: Vec<f32> = weights.iter().flatten().copied().collect();
+                attributes["WEIGHTS_0"] =
+                    json!(self.add_float_accessor(&data, "VEC4", Some(TARGET_ARRAY_BUFFER), false));
+            }
+
+            let data: Vec<u8> = p.indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+            let view = self.add_buffer_view(&data, Some(TARGET_ELEMENT_ARRAY_BUFFER));
+            let indices = self.add_accessor(json!({
+                "bufferView": view,
+                "componentType": COMPONENT_UNSIGNED_INT,
+                "count": p.indices.len(),
+                "type": "SCALAR",
+            }));
+
+            let mut primitive = json!({
+                "attributes": attributes,
+                "indices": indices,
+                "mode": 4,
+            });
+
+            if let Some(material) = p.material {
+                primitive["material"] = json!(material);
+            }
+
+            if !p.morph_targets.is_empty() {
+                let targets: Vec<Value> = p
+                    .morph_targets
+                    .iter()
+                    .map(|target| {
+                        let deltas: Vec<[f32; 3]> = target
+                            .iter()
+                            .zip(&p.positions)
+                            .map(|(t, b)| [t[0] - b[0], t[1] - b[1], t[2] - b[2]])
+                            .collect();
+                        json!({ "POSITION": self.add_vec3_accessor(&deltas, true) })
+                    })
+                    .collect();
+                target_count = target_count.max(targets.len());
+                primitive["targets"] = json!(targets);
+            }
+
+            gltf_primitives.push(primitive);
+        }
+
+        let mut mesh = json!({ "name": name, "primitives": gltf_primitives });
+        if target_count > 0 {
+            mesh["weights"] = json!(vec![0.0; target_count]);
+        }
+
+        self.meshes.push(mesh);
+        self.meshes.len() - 1
+    }
+
+    pub fn add_node(&mut self, node: GltfNode) -> usize {
+        let mut value = json!({ "name": node.name });
+        if let Some(mesh) = node.mesh {
+            value["mesh"] = json!(mesh);
+        }
+
+        if let Some(skin) = node.skin {
+            value["skin"] = json!(skin);
+        }
+
+        if !node.children.is_empty() {
+            value["children"] = json!(node.children);
+        }
+
+        if let Some(matrix) = node.matrix {
+            value["matrix"] = json!(matrix);
+        }
+
+        if let Some(translation) = node.translation {
+            value["translation"] = json!(translation);
+        }
+
+        if let Some(rotation) = node.rotation {
+            value["rotation"] = json!(rotation);
+        }
+
+        if let Some(scale) = node.scale {
+            value["scale"] = json!(scale);
+        }
+
+        self.nodes.push(value);
+        self.nodes.len() - 1
+    }
+
+    pub fn set_children(&mut self, node: usize, children: &[usize]) {
+        if !children.is_empty() {
+            self.nodes[node]["children"] = json!(children);
+        }
+    }
+
+    pub fn add_root(&mut self, node: usize) {
+        self.roots.push(node);
+    }
+
+    /// `inverse_bind_matrices` are column-major, one per joint.
+    pub fn add_skin(
+        &mut self,
+        joints: &[usize],
+        inverse_bind_matrices: &[[f32; 16]],
+        skeleton: Option<usize>,
+    ) -> usize {
+        let data: Vec<f32> = inverse_bind_matrices.iter().flatten().copied().collect();
+        let matrices = self.add_float_accessor(&data, "MAT4", None, false);
+        let mut skin = json!({ "joints": joints, "inverseBindMatrices": matrices });
+        if let Some(skeleton) = skeleton {
+            skin["skeleton"] = json!(skeleton);
+        }
+
+        self.skins.push(skin);
+        self.skins.len() - 1
+    }
+
+    pub fn add_animation(&mut self, name: &str, channels: &[GltfChannel]) -> Option<usize> {
+        let mut samplers = vec![];
+        let mut gltf_channels = vec![];
+        for channel in channels.iter().filter(|c| !c.times.is_empty()) {
+            let (times, values) = channel.increasing_keyframes();
+            let input = self.add_float_accessor(&times, "SCALAR", None, true);
+            let output =
+                self.add_float_accessor(&values, channel.path.accessor_type(), None, false);
+            samplers.push(json!({
+                "input": input,
+                "output": output,
+                "interpolation": if channel.step { "STEP" } else { "LINEAR" },
+            }));
+            gltf_channels.push(json!({
+                "sampler": samplers.len() - 1,
+                "target": { "node": channel.node, "path": channel.path.name() },
+            }));
+        }
+
+        if gltf_channels.is_empty() {
+            return None;
+        }
+
+        self.animations.push(json!({
+            "name": name,
+            "samplers": samplers,
+            "channels": gltf_channels,
+        }));
+        Some(self.animations.len() - 1)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The glTF json. `buffer_uri` is the sidecar file name, or `None` when the
+    /// buffer is stored in a glb.
+    pub fn to_json(&self, buffer_uri: Option<&str>) -> Value {
+        let mut buffer = json!({ "byteLength": self.buffer.len() });
+        if let Some(uri) = buffer_uri {
+            buffer["uri"] = json!(uri);
+        }
+
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "yaobow" },
+            "scene": 0,
+            "scenes": [{ "nodes": self.roots }],
+            "buffers": [buffer],
+        });
+
+        let arrays = [
+            ("bufferViews", &self.buffer_views),
+            ("accessors", &self.accessors),
+            ("images", &self.images),
+            ("textures", &self.textures),
+            ("materials", &self.materials),
+            ("meshes", &self.meshes),
+            ("nodes", &self.nodes),
+            ("skins", &self.skins),
+            ("animations", &self.animations),
+        ];
+
+        for (name, array) in arrays {
+            if !array.is_empty() {
+                root[name] = json!(array);
+            }
+        }
+
+        root
+    }
+
+    pub fn to_glb(&self) -> Vec<u8> {
+        let mut json = serde_json::to_vec(&self.to_json(None)).unwrap();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+
+        let mut bin = self.buffer.clone();
+        while bin.len() % 4 != 0 {
+            bin.push(0);
+        }
+
+        let total = 12 + 8 + json.len() + 8 + bin.len();
+        let mut glb = Vec::with_capacity(total);
+        glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        glb.extend_from_slice(&bin);
+        glb
+    }
+
+    /// Saves as glb when `path` ends with `.glb`, otherwise as a `.gltf` with
+    /// the buffer next to it.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let is_glb = path
+            .extension()
+            .map_or(false, |ext| ext.eq_ignore_ascii_case("glb"));
+        if is_glb {
+            return std::fs::write(path, self.to_glb());
+        }
+
+        let bin_path = path.with_extension("bin");
+        let bin_name = bin_path.file_name().unwrap().to_string_lossy().to_string();
+        std::fs::write(&bin_path, &self.buffer)?;
+        std::fs::write(
+            path,
+            serde_json::to_vec_pretty(&self.to_json(Some(&bin_name))).unwrap(),
+        )
+    }
+
+    fn add_buffer_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": data.len(),
+        });
+
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn add_accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn add_vec3_accessor(&mut self, data: &[[f32; 3]], with_bounds: bool) -> usize {
+        let data: Vec<f32> = data.iter().flatten().copied().collect();
+        self.add_float_accessor(&data, "VEC3", Some(TARGET_ARRAY_BUFFER), with_bounds)
+    }
+
+    fn add_float_accessor(
+        &mut self,
+        data: &[f32],
+        ty: &str,
+        target: Option<u32>,
+        with_bounds: bool,
+    ) -> usize {
+        let components = match ty {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            "MAT4" => 16,
+            _ => unreachable!(),
+        };
+
+        let bytes: Vec<u8> = data.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let view = self.add_buffer_view(&bytes, target);
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": COMPONENT_FLOAT,
+            "count": data.len() / components,
+            "type": ty,
+        });
+
+        // Required for positions and animation inputs
+        if with_bounds && !data.is_empty() {
+            let mut min = vec![f32::MAX; components];
+            let mut max = vec![f32::MIN; components];
+            for chunk in data.chunks(components) {
+                for (i, v) in chunk.iter().enumerate() {
+                    min[i] = min[i].min(*v);
+                    max[i] = max[i].max(*v);
+                }
+            }
+
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+
+        self.add_accessor(accessor)
+    }
+}
+
+/// One-hot morph weights so that keyframe `i` shows target `i - 1`, and the
+/// first keyframe shows the base mesh.
+pub fn morph_weights_channel(node: usize, times: &[f32]) -> GltfChannel {
+    let target_count = times.len().saturating_sub(1);
+    let mut values = vec![0.; times.len() * target_count];
+    for i in 1..times.len() {
+        values[i * target_count + i - 1] = 1.;
+    }
+
+    GltfChannel {
+        node,
+        path: GltfAnimationPath::Weights,
+        times: times.to_vec(),
+        values,
+        step: false,
+    }
+}
+
+fn encode_png(data: &[u8]) -> Option<Vec<u8>> {
+    let image = image::load_from_memory(data).ok()?;
+    let mut png = Cursor::new(vec![]);
+    image
+        .write_to(&mut png, image::ImageOutputFormat::Png)
+        .ok()?;
+    Some(png.into_inner())
+}
@@ -1,3 +1,9 @@
+pub mod cvd_gltf_exporter;
+pub mod gltf_exporter;
+pub mod mv3_gltf_exporter;
 pub mod mv3_obj_exporter;
+pub mod nif_gltf_exporter;
 pub mod obj_exporter;
+pub mod pol_gltf_exporter;
 pub mod pol_obj_exporter;
+pub mod rwbs_gltf_exporter;
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use fileformats::mv3::{Mv3File, Mv3Model};
+
+use super::gltf_exporter::{morph_weights_channel, GltfBuilder, GltfNode, GltfPrimitive};
+
+const MV3_SCALE: f32 = 0.01562;
+const MV3_TICKS_PER_SECOND: f32 = 4580.;
+
+/// Exports every model in the mv3 as a mesh whose morph targets are the
+/// animation frames, plus one animation playing through all the frames.
+pub fn export_mv3_to_gltf(
+    mv3_file: &Mv3File,
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> GltfBuilder {
+    let mut builder = GltfBuilder::new();
+    let mut channels = vec![];
+    for (model_index, model) in mv3_file.models.iter().enumerate() {
+        if model.frames.is_empty() {
+            log::warn!("Skipped mv3 model {} without frames", model_index);
+            continue;
+        }
+
+        let texture_index = if model_index < mv3_file.textures.len() {
+            model_index
+        } else {
+            0
+        };
+
+        let texture_name = mv3_file
+            .textures
+            .get(texture_index)
+            .and_then(|t| t.names[0].as_str().ok())
+            .unwrap_or_default();
+        let material = builder.add_material(&texture_name, texture_loader(&texture_name), false);
+
+        let primitives: Vec<GltfPrimitive> = (0..model.meshes.len())
+            .map(|mesh_index| create_primitive(model, mesh_index, material))
+            .collect();
+        let mesh = builder.add_mesh(&format!("{}_{}", name, model_index), &primitives);
+        let node = builder.add_node(GltfNode {
+            name: format!("{}_{}", name, model_index),
+            mesh: Some(mesh),
+            ..Default::default()
+        });
+        builder.add_root(node);
+
+        if model.frames.len() > 1 {
+            let times: Vec<f32> = model
+                .frames
+                .iter()
+                .map(|f| f.timestamp as f32 / MV3_TICKS_PER_SECOND)
+                .collect();
+            channels.push(morph_weights_channel(node, &times));
+        }
+    }
+
+    builder.add_animation(name, &channels);
+    builder
+}
+
+/// Splits the vertices the same way as the renderer, since mv3 triangles
+/// index positions and texcoords separately. Triangles referring to a vertex
+/// missing from any frame are skipped, while texcoord indices past the end,
+/// which retail files contain, fall back to (0, 0) like the renderer. `model`
+/// must have at least one frame.
+fn create_primitive(model: &Mv3Model, mesh_index: usize, material: usize) -> GltfPrimitive {
+    let mesh = &model.meshes[mesh_index];
+    let mut index_map = HashMap::new();
+    let mut frame_positions = vec![vec![]; model.frames.len()];
+    let mut texcoords = vec![];
+    let mut indices = vec![];
+    let vertex_count = model
+        .frames
+        .iter()
+        .map(|f| f.vertices.len())
+        .min()
+        .unwrap_or(0);
+
+    let mut skipped = 0;
+    for t in &mesh.triangles {
+        if t.indices.iter().any(|i| *i as usize >= vertex_count) {
+            skipped += 1;
+            continue;
+        }
+
+        for (&i, &j) in t.indices.iter().zip(&t.texcoord_indices) {
+            let next = index_map.len() as u32;
+            let index = *index_map.entry((i, j)).or_insert_with(|| {
+                for (k, frame) in model.frames.iter().enumerate() {
+                    let v = &frame.vertices[i as usize];
+                    frame_positions[k].push([
+                        v.x as f32 * MV3_SCALE,
+                        v.y as f32 * MV3_SCALE,
+                        v.z as f32 * MV3_SCALE,
+                    ]);
+                }
+
+                texcoords.push(
+                    model
+                        .texcoords
+                        .get(j as usize)
+                        .map_or([0., 0.], |t| [t.u, -t.v]),
+                );
+                next
+            });
+
+            indices.push(index);
+        }
+    }
+
+    if skipped > 0 {
+        log::warn!(
+            "Skipped {} mv3 triangles with invalid vertex indices in mesh {}",
+            skipped,
+            mesh_index
+        );
+    }
+
+    let positions = frame_positions.remove(0);
+    GltfPrimitive {
+        positions,
+        texcoords: Some(texcoords),
+        indices,
+        material: Some(material),
+        morph_targets: frame_positions,
+        ..Default::default()
+    }
+}
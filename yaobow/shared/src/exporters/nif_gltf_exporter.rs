@@ -0,0 +1,114 @@
+use fileformats::nif::{
+    blocks::{MeshPrimitiveType, NiAVObject, NiMesh, NiNode},
+    NifModel,
+};
+
+use super::gltf_exporter::{GltfBuilder, GltfNode, GltfPrimitive};
+
+/// Exports the NiNode tree and the triangle NiMeshes in it. Each sub mesh
+/// becomes a primitive.
+pub fn export_nif_to_gltf(nif: &NifModel, name: &str) -> GltfBuilder {
+    let mut builder = GltfBuilder::new();
+    let material = builder.add_material("", None, false);
+    for root in &nif.footer.roots {
+        if let Some(node) = export_block(&mut builder, nif, *root, name, material, 0) {
+            builder.add_root(node);
+        }
+    }
+
+    builder
+}
+
+// Guards against reference cycles in corrupted files
+const MAX_DEPTH: usize = 64;
+
+fn export_block(
+    builder: &mut GltfBuilder,
+    nif: &NifModel,
+    index: i32,
+    name: &str,
+    material: usize,
+    depth: usize,
+) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    if let Some(node) = nif.block::<NiNode>(index) {
+        let children: Vec<usize> = node
+            .children
+            .iter()
+            .filter_map(|c| export_block(builder, nif, *c as i32, name, material, depth + 1))
+            .collect();
+        let mut gltf_node = create_node(nif, &node.av_object, name, index);
+        gltf_node.children = children;
+        Some(builder.add_node(gltf_node))
+    } else if let Some(mesh) = nif.block::<NiMesh>(index) {
+        let av_object = &mesh.render_object.av_object;
+        let mut gltf_node = create_node(nif, av_object, name, index);
+        let primitives = create_primitives(nif, mesh, material);
+        if !primitives.is_empty() {
+            gltf_node.mesh = Some(builder.add_mesh(&gltf_node.name, &primitives));
+        }
+
+        Some(builder.add_node(gltf_node))
+    } else {
+        None
+    }
+}
+
+fn create_node(nif: &NifModel, av_object: &NiAVObject, name: &str, index: i32) -> GltfNode {
+    let r = &av_object.rotation;
+    let t = &av_object.translation;
+    let s = av_object.scale;
+    GltfNode {
+        name: nif
+            .string(av_object.object_net.name as i32)
+            .unwrap_or(format!("{}_{}", name, index)),
+        matrix: Some([
+            r.m11 * s,
+            r.m21 * s,
+            r.m31 * s,
+            0.,
+            r.m12 * s,
+            r.m22 * s,
+            r.m32 * s,
+            0.,
+            r.m13 * s,
+            r.m23 * s,
+            r.m33 * s,
+            0.,
+            t.x,
+            t.y,
+            t.z,
+            1.,
+        ]),
+        ..Default::default()
+    }
+}
+
+fn create_primitives(nif: &NifModel, mesh: &NiMesh, material: usize) -> Vec<GltfPrimitive> {
+    if mesh.primitive_type.0 != MeshPrimitiveType::MESH_PRIMITIVE_TRIANGLES.0 {
+        log::warn!("Unsupported nif primitive type {}", mesh.primitive_type.0);
+        return vec![];
+    }
+
+    let mut primitives = vec![];
+    for (sub_mesh, data) in nif.sub_meshes(mesh).into_iter().enumerate() {
+        if !data.is_valid() {
+            log::warn!("Skipped invalid nif sub mesh {}", sub_mesh);
+            continue;
+        }
+
+        primitives.push(GltfPrimitive {
+            positions: data.positions,
+            normals: (!data.normals.is_empty()).then_some(data.normals),
+            texcoords: (!data.texcoords.is_empty()).then_some(data.texcoords),
+            indices: data.indices,
+            material: Some(material),
+            ..Default::default()
+        });
+    }
+
+    primitives
+}
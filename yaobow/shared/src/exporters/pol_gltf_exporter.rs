@@ -0,0 +1,81 @@
+use fileformats::pol::{PolFile, PolVertexComponents};
+
+use super::gltf_exporter::{GltfBuilder, GltfNode, GltfPrimitive};
+
+/// Exports each pol mesh as a node, with one primitive per material.
+pub fn export_pol_to_gltf(
+    pol_file: &PolFile,
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> GltfBuilder {
+    let mut builder = GltfBuilder::new();
+    for (mesh_index, mesh) in pol_file.meshes.iter().enumerate() {
+        let positions: Vec<[f32; 3]> = mesh
+            .vertices
+            .iter()
+            .map(|v| [v.position.x, v.position.y, v.position.z])
+            .collect();
+
+        let normals = if mesh.vertex_type.has(PolVertexComponents::NORMAL) {
+            Some(
+                mesh.vertices
+                    .iter()
+                    .map(|v| v.normal.as_ref().map_or([0., 1., 0.], |n| [n.x, n.y, n.z]))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        // Same as the obj exporter, the second set is the diffuse one when
+        // both exist
+        let texcoords = mesh
+            .vertices
+            .iter()
+            .map(|v| {
+                let t = v.tex_coord2.as_ref().unwrap_or(&v.tex_coord);
+                [t.u, t.v]
+            })
+            .collect::<Vec<[f32; 2]>>();
+
+        let primitives: Vec<GltfPrimitive> = mesh
+            .material_info
+            .iter()
+            .map(|m| {
+                let texture_name = m
+                    .texture_names
+                    .last()
+                    .and_then(|t| t.as_str().ok())
+                    .unwrap_or_default();
+                let material = builder.add_material(
+                    &texture_name,
+                    texture_loader(&texture_name),
+                    m.use_alpha != 0,
+                );
+
+                GltfPrimitive {
+                    positions: positions.clone(),
+                    normals: normals.clone(),
+                    texcoords: Some(texcoords.clone()),
+                    indices: m
+                        .triangles
+                        .iter()
+                        .flat_map(|t| t.indices.map(|i| i as u32))
+                        .collect(),
+                    material: Some(material),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let mesh = builder.add_mesh(&format!("{}_{}", name, mesh_index), &primitives);
+        let node = builder.add_node(GltfNode {
+            name: format!("{}_{}", name, mesh_index),
+            mesh: Some(mesh),
+            ..Default::default()
+        });
+        builder.add_root(node);
+    }
+
+    builder
+}
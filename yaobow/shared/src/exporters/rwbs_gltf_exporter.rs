@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+
+use fileformats::rwbs::{
+    clump::Clump,
+    extension::{Extension, SkinPlugin},
+    frame::Frame,
+    geometry::Geometry,
+    material::Material,
+    sector::{AtomicSector, Sector},
+    world::World,
+    Matrix44f, TexCoord, Triangle, Vec3f,
+};
+use radiance::components::mesh::skinned_mesh::AnimKeyFrame;
+
+use super::gltf_exporter::{GltfAnimationPath, GltfBuilder, GltfChannel, GltfNode, GltfPrimitive};
+
+/// Exports dff clumps with their frame hierarchy. Skinned geometries are bound
+/// to the HAnim bones, and each animation, as returned by `load_anm`, targets
+/// the bones in HAnim index order.
+pub fn export_dff_to_gltf(
+    clumps: &[Clump],
+    name: &str,
+    animations: &[(String, Vec<Vec<AnimKeyFrame>>)],
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> GltfBuilder {
+    let mut builder = GltfBuilder::new();
+    let mut all_joints = vec![];
+    for (clump_index, clump) in clumps.iter().enumerate() {
+        let joints = export_clump(
+            &mut builder,
+            clump,
+            &format!("{}_{}", name, clump_index),
+            texture_loader,
+        );
+        all_joints.push(joints);
+    }
+
+    for (anim_name, keyframes) in animations {
+        let channels: Vec<GltfChannel> = all_joints
+            .iter()
+            .flat_map(|joints| {
+                joints
+                    .iter()
+                    .zip(keyframes)
+                    .flat_map(|(joint, frames)| bone_channels(*joint, frames))
+            })
+            .collect();
+        builder.add_animation(anim_name, &channels);
+    }
+
+    builder
+}
+
+/// Exports the geometries of all the atomic sectors in the bsp worlds.
+pub fn export_bsp_to_gltf(
+    worlds: &[World],
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> GltfBuilder {
+    let mut builder = GltfBuilder::new();
+    for (world_index, world) in worlds.iter().enumerate() {
+        let mut sectors = vec![];
+        collect_atomic_sectors(&world.sector, &mut sectors);
+
+        let mut children = vec![];
+        for (i, sector) in sectors.iter().enumerate() {
+            let texcoords = sector
+                .texcoords
+                .as_ref()
+                .map(|t| t.iter().map(|t| [t.u, t.v]).collect());
+            let primitives = create_primitives(
+                &mut builder,
+                &sector.vertices,
+                texcoords,
+                &sector.triangles,
+                &world.materials,
+                texture_loader,
+            );
+
+            if primitives.is_empty() {
+                continue;
+            }
+
+            let sector_name = format!("{}_{}_{}", name, world_index, i);
+            let mesh = builder.add_mesh(&sector_name, &primitives);
+            children.push(builder.add_node(GltfNode {
+                name: sector_name,
+                mesh: Some(mesh),
+                ..Default::default()
+            }));
+        }
+
+        let root = builder.add_node(GltfNode {
+            name: format!("{}_{}", name, world_index),
+            children,
+            ..Default::default()
+        });
+        builder.add_root(root);
+    }
+
+    builder
+}
+
+/// Returns the joint nodes in HAnim index order.
+fn export_clump(
+    builder: &mut GltfBuilder,
+    clump: &Clump,
+    name: &str,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Vec<usize> {
+    let nodes: Vec<usize> = clump
+        .frames
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            builder.add_node(GltfNode {
+                name: f.name().unwrap_or(format!("{}_frame{}", name, i)),
+                matrix: Some(frame_matrix(f)),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let mut children = vec![vec![]; nodes.len()];
+    for (i, frame) in clump.frames.iter().enumerate() {
+        if frame.parent < 0 || frame.parent as usize >= nodes.len() {
+            builder.add_root(nodes[i]);
+        } else if frame.parent as usize != i {
+            children[frame.parent as usize].push(nodes[i]);
+        } else {
+            log::warn!("Ignored orphan frame");
+        }
+    }
+
+    let joints = hanim_joints(clump, &nodes);
+    let mut skin = None;
+    for atomic in &clump.atomics {
+        let geometry = match clump.geometries.get(atomic.geometry as usize) {
+            Some(geometry) if (atomic.frame as usize) < nodes.len() => geometry,
+            _ => continue,
+        };
+
+        let skin_plugin = geometry.extensions.iter().find_map(|e| match e {
+            Extension::SkinPlugin(skin) => Some(skin),
+            _ => None,
+        });
+
+        let primitives = create_geometry_primitives(builder, geometry, skin_plugin, texture_loader);
+        if primitives.is_empty() {
+            continue;
+        }
+
+        let geom_name = format!(
+            "{}_geom",
+            clump.frames[atomic.frame as usize]
+                .name()
+                .unwrap_or_default()
+        );
+        let mesh = builder.add_mesh(&geom_name, &primitives);
+        let node_skin = match skin_plugin {
+            Some(plugin) if !joints.is_empty() => {
+                Some(*skin.get_or_insert_with(|| create_skin(builder, &joints, plugin)))
+            }
+            _ => None,
+        };
+
+        let node = builder.add_node(GltfNode {
+            name: geom_name,
+            mesh: Some(mesh),
+            skin: node_skin,
+            ..Default::default()
+        });
+        children[atomic.frame as usize].push(node);
+    }
+
+    for (node, children) in nodes.iter().zip(&children) {
+        builder.set_children(*node, children);
+    }
+
+    joints
+}
+
+fn hanim_joints(clump: &Clump, nodes: &[usize]) -> Vec<usize> {
+    let mut bone_frames = BTreeMap::new();
+    for (i, frame) in clump.frames.iter().enumerate() {
+        if let Some(hanim) = frame.hanim_plugin() {
+            bone_frames.insert(hanim.header.id, i);
+        }
+    }
+
+    // The root bone lists the whole hierarchy
+    let root = match clump
+        .frames
+        .iter()
+        .filter_map(|f| f.hanim_plugin())
+        .find(|h| !h.bones.is_empty())
+    {
+        Some(root) => root,
+        None => return vec![],
+    };
+
+    let mut bones = root.bones.clone();
+    bones.sort_by_key(|b| b.index);
+    bones
+        .iter()
+        .filter_map(|b| bone_frames.get(&b.id).map(|i| nodes[*i]))
+        .collect()
+}
+
+fn create_skin(builder: &mut GltfBuilder, joints: &[usize], plugin: &SkinPlugin) -> usize {
+    let matrices: Vec<[f32; 16]> = (0..joints.len())
+        .map(|i| plugin.matrix.get(i).map_or(IDENTITY, inverse_bind_matrix))
+        .collect();
+    builder.add_skin(joints, &matrices, joints.first().copied())
+}
+
+fn create_geometry_primitives(
+    builder: &mut GltfBuilder,
+    geometry: &Geometry,
+    skin: Option<&SkinPlugin>,
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Vec<GltfPrimitive> {
+    let vertices = match geometry
+        .morph_targets
+        .first()
+        .and_then(|t| t.vertices.as_ref())
+    {
+        Some(vertices) => vertices,
+        None => return vec![],
+    };
+
+    let texcoords = geometry
+        .texcoord_sets
+        .first()
+        .map(|t| t.iter().map(|t: &TexCoord| [t.u, t.v]).collect());
+    let mut primitives = create_primitives(
+        builder,
+        vertices,
+        texcoords,
+        &geometry.triangles,
+        &geometry.materials,
+        texture_loader,
+    );
+
+    let normals: Option<Vec<[f32; 3]>> = geometry.morph_targets[0]
+        .normals
+        .as_ref()
+        .map(|n| n.iter().map(|n| [n.x, n.y, n.z]).collect());
+    for p in &mut primitives {
+        p.normals = normals.clone();
+        if let Some(skin) = skin {
+            p.joints = Some(
+                skin.bone_indices
+                    .iter()
+                    .map(|b| b.map(|b| b as u16))
+                    .collect(),
+            );
+            p.weights = Some(skin.weights.iter().map(normalize_weights).collect());
+        }
+    }
+
+    primitives
+}
+
+/// One primitive per material, all sharing the vertices.
+fn create_primitives(
+    builder: &mut GltfBuilder,
+    vertices: &[Vec3f],
+    texcoords: Option<Vec<[f32; 2]>>,
+    triangles: &[Triangle],
+    materials: &[Material],
+    texture_loader: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Vec<GltfPrimitive> {
+    let mut groups: BTreeMap<u16, Vec<u32>> = BTreeMap::new();
+    for t in triangles {
+        groups
+            .entry(t.material)
+            .or_default()
+            .extend(t.index.map(|i| i as u32));
+    }
+
+    let positions: Vec<[f32; 3]> = vertices.iter().map(|v| [v.x, v.y, v.z]).collect();
+    groups
+        .into_iter()
+        .map(|(material, indices)| {
+            let texture_name = materials
+                .get(material as usize)
+                .and_then(|m| m.texture.as_ref())
+                .and_then(|t| t.name.as_str().ok())
+                .unwrap_or_default();
+            let material =
+                builder.add_material(&texture_name, texture_loader(&texture_name), false);
+
+            GltfPrimitive {
+                positions: positions.clone(),
+                texcoords: texcoords.clone(),
+                indices,
+                material: Some(material),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn collect_atomic_sectors<'a>(sector: &'a Sector, sectors: &mut Vec<&'a AtomicSector>) {
+    match sector {
+        Sector::AtomicSector(a) => sectors.push(a),
+        Sector::PlaneSector(p) => {
+            collect_atomic_sectors(&p.left_child, sectors);
+            collect_atomic_sectors(&p.right_child, sectors);
+        }
+    }
+}
+
+fn bone_channels(node: usize, frames: &[AnimKeyFrame]) -> Vec<GltfChannel> {
+    let times: Vec<f32> = frames.iter().map(|f| f.timestamp).collect();
+    vec![
+        GltfChannel {
+            node,
+            path: GltfAnimationPath::Rotation,
+            times: times.clone(),
+            values: frames
+                .iter()
+                .flat_map(|f| [f.rotation.x, f.rotation.y, f.rotation.z, f.rotation.w])
+                .collect(),
+            step: false,
+        },
+        GltfChannel {
+            node,
+            path: GltfAnimationPath::Translation,
+            times,
+            values: frames
+                .iter()
+                .flat_map(|f| [f.position.x, f.position.y, f.position.z])
+                .collect(),
+            step: false,
+        },
+    ]
+}
+
+const IDENTITY: [f32; 16] = [
+    1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+];
+
+/// Frame axes are the columns of the local transform.
+fn frame_matrix(frame: &Frame) -> [f32; 16] {
+    [
+        frame.right.x,
+        frame.right.y,
+        frame.right.z,
+        0.,
+        frame.up.x,
+        frame.up.y,
+        frame.up.z,
+        0.,
+        frame.at.x,
+        frame.at.y,
+        frame.at.z,
+        0.,
+        frame.pos.x,
+        frame.pos.y,
+        frame.pos.z,
+        1.,
+    ]
+}
+
+/// Skin matrices are stored column-major with padding in the last row.
+fn inverse_bind_matrix(m: &Matrix44f) -> [f32; 16] {
+    let mut matrix = m.0;
+    matrix[3] = 0.;
+    matrix[7] = 0.;
+    matrix[11] = 0.;
+    matrix[15] = 1.;
+    matrix
+}
+
+fn normalize_weights(weights: &[f32; 4]) -> [f32; 4] {
+    let sum: f32 = weights.iter().sum();
+    if sum > 0. {
+        weights.map(|w| w / sum)
+    } else {
+        [1., 0., 0., 0.]
+    }
+}
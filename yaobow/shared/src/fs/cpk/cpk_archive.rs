@@ -1,23 +1,32 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use common::read_ext::ReadExt;
 use radiance::utils::SeekRead;
+use std::{clone::Clone, path::Path};
 use std::{
-    cell::RefCell,
-    collections::HashMap,
-    io::{Cursor, Read},
-    rc::Rc,
+    collections::{HashMap, HashSet},
+    io::{Cursor, Read, Seek, Write},
 };
-use std::{clone::Clone, path::Path};
 
-use crate::fs::memory_file::MemoryFile;
+use crate::fs::{
+    memory_file::MemoryFile,
+    shared_reader::{ReaderCursor, SharedReader},
+    streaming_file::{PackedFile, StreamingFile, STREAMING_THRESHOLD},
+};
 
 type IoResult<T> = std::io::Result<T>;
 type IoError = std::io::Error;
 type IoErrorKind = std::io::ErrorKind;
 
+pub(super) const CPK_LABEL: u32 = 0x1A545352;
+pub(super) const CPK_HEADER_SIZE: u32 = 0x80;
+pub(super) const CPK_MAX_FILE_NUM: u32 = 0x8000;
+pub(super) const PAL4_DATA_START: u32 = 0x00100080;
+pub(super) const PAL4_ENCRYPTED_TABLE_SIZE: usize = 0x1000;
+pub(super) const PAL4_TABLE_KEY: &str = "Vampire.C.J at Softstar Technology (ShangHai) Co., Ltd";
+
 #[allow(dead_code)]
 pub struct CpkArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     header: CpkHeader,
     pub entries: Vec<CpkTable>,
     crc_to_index: HashMap<u32, usize>,
@@ -25,27 +34,36 @@ pub struct CpkArchive {
 }
 
 impl CpkArchive {
-    pub fn load(mut reader: Box<dyn SeekRead>) -> IoResult<CpkArchive> {
+    pub fn load(source: SharedReader) -> IoResult<CpkArchive> {
+        let mut reader = ReaderCursor::new(source.clone());
         let header = CpkHeader::read(&mut reader)?;
 
+        // Reject truncated packages before allocating the index buffer
+        let table_entry_size = std::mem::size_of::<CpkTable>() + header.is_pal4() as usize * 4;
+        let buffer_len = table_entry_size * header.file_num as usize;
+        let table_start = reader.stream_position()?;
+        let stream_len = reader.seek(std::io::SeekFrom::End(0))?;
+        if table_start + buffer_len as u64 > stream_len {
+            return Err(IoError::new(
+                IoErrorKind::InvalidData,
+                "cpk index exceeds the package size",
+            ));
+        }
+
+        reader.seek(std::io::SeekFrom::Start(table_start))?;
         let buffer = if header.is_pal4() {
-            let buffer_len = (std::mem::size_of::<CpkTable>() + 4) * header.file_num as usize;
-            let mut encrypted_buffer = vec![0; 0x1000];
+            let mut encrypted_buffer = vec![0; PAL4_ENCRYPTED_TABLE_SIZE];
             reader.read_exact(&mut encrypted_buffer)?;
-            let mut decrypted_buffer = xxtea::decrypt_raw(
-                &encrypted_buffer,
-                "Vampire.C.J at Softstar Technology (ShangHai) Co., Ltd",
-            );
+            let mut decrypted_buffer = xxtea::decrypt_raw(&encrypted_buffer, PAL4_TABLE_KEY);
 
-            if buffer_len > 0x1000 {
-                let mut extra_buffer = vec![0; buffer_len - 0x1000];
+            if buffer_len > PAL4_ENCRYPTED_TABLE_SIZE {
+                let mut extra_buffer = vec![0; buffer_len - PAL4_ENCRYPTED_TABLE_SIZE];
                 reader.read_exact(&mut extra_buffer)?;
                 decrypted_buffer.append(&mut extra_buffer);
             }
 
             decrypted_buffer
         } else {
-            let buffer_len = std::mem::size_of::<CpkTable>() * header.file_num as usize;
             let mut buffer = vec![0; buffer_len];
             reader.read_exact(&mut buffer)?;
 
@@ -64,7 +82,7 @@ impl CpkArchive {
         let lzo = minilzo_rs::LZO::init().unwrap();
 
         Ok(CpkArchive {
-            reader,
+            reader: source,
             header,
             entries,
             crc_to_index,
@@ -72,7 +90,7 @@ impl CpkArchive {
         })
     }
 
-    pub fn open(&mut self, file_name: &[u8]) -> IoResult<MemoryFile> {
+    pub fn open(&self, file_name: &[u8]) -> IoResult<MemoryFile> {
         let hash = super::crc_checksum(file_name);
         let index = self
             .get_entry_index_by_crc(hash)
@@ -81,24 +99,47 @@ impl CpkArchive {
         self.open_entry(index)
     }
 
-    pub fn open_str(&mut self, file_name: &str) -> IoResult<MemoryFile> {
+    /// Opens the file without loading it into memory as a whole when it is
+    /// large and stored uncompressed, e.g. movies.
+    pub fn open_streaming(&self, file_name: &[u8]) -> IoResult<PackedFile> {
+        let hash = super::crc_checksum(file_name);
+        let index = self
+            .get_entry_index_by_crc(hash)
+            .ok_or(IoError::from(IoErrorKind::NotFound))?;
+
+        let entry = &self.entries[index];
+        if entry.is_file()
+            && !entry.is_compressed()
+            && entry.packed_size as u64 >= STREAMING_THRESHOLD
+        {
+            Ok(PackedFile::Stream(StreamingFile::new(
+                self.reader.clone(),
+                entry.start_pos as u64,
+                entry.packed_size as u64,
+            )))
+        } else {
+            // Lzo can only decompress the whole entry at once
+            Ok(PackedFile::Memory(self.open_entry(index)?))
+        }
+    }
+
+    pub fn open_str(&self, file_name: &str) -> IoResult<MemoryFile> {
         self.open(file_name.to_lowercase().as_bytes())
     }
 
-    pub fn open_first(&mut self) -> IoResult<MemoryFile> {
+    pub fn open_first(&self) -> IoResult<MemoryFile> {
         self.open_entry(0)
     }
 
-    fn open_entry(&mut self, index: usize) -> IoResult<MemoryFile> {
+    fn open_entry(&self, index: usize) -> IoResult<MemoryFile> {
         let entry = &self.entries[index];
         if entry.is_dir() {
             return Err(IoError::from(IoErrorKind::IsADirectory));
         }
 
-        self.reader
-            .seek(std::io::SeekFrom::Start(entry.start_pos as u64))?;
         let mut content = vec![0; entry.packed_size as usize];
-        self.reader.read(&mut content)?;
+        self.reader
+            .read_exact_at(entry.start_pos as u64, &mut content)?;
         if !entry.is_compressed() {
             Ok(MemoryFile::new(Cursor::new(content)))
         } else {
@@ -110,13 +151,14 @@ impl CpkArchive {
         }
     }
 
-    pub fn build_directory(&mut self) -> CpkEntry {
-        let file_names = Self::read_file_names(&mut self.reader, &self.entries).unwrap();
-        Self::build_directory_internal(&self.entries, &file_names)
+    pub fn build_directory(&self) -> IoResult<CpkEntry> {
+        let mut reader = ReaderCursor::new(self.reader.clone());
+        let file_names = Self::read_file_names(&mut reader, &self.entries)?;
+        Ok(Self::build_directory_internal(&self.entries, &file_names))
     }
 
     pub fn is_pal4(&self) -> bool {
-        self.header.data_start == 0x00100080
+        self.header.is_pal4()
     }
 
     fn build_directory_internal(entries: &[CpkTable], file_names: &[String]) -> CpkEntry {
@@ -130,29 +172,51 @@ impl CpkArchive {
             extra_info_size: 0,
         };
         let mut root = CpkEntry::new(root_table, "".to_string());
-        let mut crc_to_cpk_entry = HashMap::new();
-        for (i, entry) in entries.iter().enumerate() {
-            if i < file_names.len() {
-                let cpk_entry = CpkEntry::new(*entry, file_names[i].clone());
-                crc_to_cpk_entry.insert(entry.crc, Rc::new(RefCell::new(cpk_entry)));
-            }
+        let mut children: HashMap<u32, Vec<usize>> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate().take(file_names.len()) {
+            children.entry(entry.father_crc).or_default().push(i);
         }
 
-        for (_, cpk_entry) in &crc_to_cpk_entry {
-            if cpk_entry.borrow().raw_entry.father_crc == 0 {
-                root.add_child(cpk_entry);
-            } else if let Some(parent_entry) =
-                crc_to_cpk_entry.get(&cpk_entry.borrow().raw_entry.father_crc)
-            {
-                parent_entry.borrow_mut().add_child(cpk_entry);
-            } else {
-                // Orphan entries.. ignore them
-            }
-        }
+        // Entries whose parent is missing are orphans and never get visited
+        let mut visited = HashSet::new();
+        Self::add_children(&mut root, 0, entries, file_names, &children, &mut visited);
 
         root
     }
 
+    fn add_children(
+        parent: &mut CpkEntry,
+        parent_crc: u32,
+        entries: &[CpkTable],
+        file_names: &[String],
+        children: &HashMap<u32, Vec<usize>>,
+        visited: &mut HashSet<u32>,
+    ) {
+        for &i in children
+            .get(&parent_crc)
+            .map(|c| c.as_slice())
+            .unwrap_or(&[])
+        {
+            let entry = &entries[i];
+
+            // Guard against malformed tables where an entry is its own ancestor
+            if !visited.insert(entry.crc) {
+                continue;
+            }
+
+            let mut cpk_entry = CpkEntry::new(*entry, file_names[i].clone());
+            Self::add_children(
+                &mut cpk_entry,
+                entry.crc,
+                entries,
+                file_names,
+                children,
+                visited,
+            );
+            parent.add_child(cpk_entry);
+        }
+    }
+
     fn build_index_map(entries: &Vec<CpkTable>) -> HashMap<u32, usize> {
         let mut map = HashMap::new();
         for (i, entry) in entries.iter().enumerate() {
@@ -199,40 +263,40 @@ impl CpkArchive {
 
 #[allow(dead_code)]
 #[derive(Debug)]
-struct CpkHeader {
-    label: u32,
-    version: u32,
-    table_start: u32,
-    data_start: u32,
-    max_file_num: u32,
-    file_num: u32,
-    is_formatted: u32,
-    size_of_header: u32,
-    valid_table_num: u32,
-    max_table_num: u32,
-    fragment_num: u32,
-    package_size: u32,
-    reserved: [u32; 20],
+pub(super) struct CpkHeader {
+    pub(super) label: u32,
+    pub(super) version: u32,
+    pub(super) table_start: u32,
+    pub(super) data_start: u32,
+    pub(super) max_file_num: u32,
+    pub(super) file_num: u32,
+    pub(super) is_formatted: u32,
+    pub(super) size_of_header: u32,
+    pub(super) valid_table_num: u32,
+    pub(super) max_table_num: u32,
+    pub(super) fragment_num: u32,
+    pub(super) package_size: u32,
+    pub(super) reserved: [u32; 20],
 }
 
 impl CpkHeader {
     pub fn read(cursor: &mut dyn SeekRead) -> IoResult<CpkHeader> {
-        let label = cursor.read_u32::<LittleEndian>().unwrap();
-        let version = cursor.read_u32::<LittleEndian>().unwrap();
-        let table_start = cursor.read_u32::<LittleEndian>().unwrap();
-        let data_start = cursor.read_u32::<LittleEndian>().unwrap();
-        let max_file_num = cursor.read_u32::<LittleEndian>().unwrap();
-        let file_num = cursor.read_u32::<LittleEndian>().unwrap();
-        let is_formatted = cursor.read_u32::<LittleEndian>().unwrap();
-        let size_of_header = cursor.read_u32::<LittleEndian>().unwrap();
-        let valid_table_num = cursor.read_u32::<LittleEndian>().unwrap();
-        let max_table_num = cursor.read_u32::<LittleEndian>().unwrap();
-        let fragment_num = cursor.read_u32::<LittleEndian>().unwrap();
-        let package_size = cursor.read_u32::<LittleEndian>().unwrap();
+        let label = cursor.read_u32::<LittleEndian>()?;
+        let version = cursor.read_u32::<LittleEndian>()?;
+        let table_start = cursor.read_u32::<LittleEndian>()?;
+        let data_start = cursor.read_u32::<LittleEndian>()?;
+        let max_file_num = cursor.read_u32::<LittleEndian>()?;
+        let file_num = cursor.read_u32::<LittleEndian>()?;
+        let is_formatted = cursor.read_u32::<LittleEndian>()?;
+        let size_of_header = cursor.read_u32::<LittleEndian>()?;
+        let valid_table_num = cursor.read_u32::<LittleEndian>()?;
+        let max_table_num = cursor.read_u32::<LittleEndian>()?;
+        let fragment_num = cursor.read_u32::<LittleEndian>()?;
+        let package_size = cursor.read_u32::<LittleEndian>()?;
         let mut reserved: [u32; 20] = Default::default();
-        reserved.copy_from_slice(&cursor.read_dw_vec(20).unwrap());
+        reserved.copy_from_slice(&cursor.read_dw_vec(20)?);
 
-        if label != 0x1A545352 {
+        if label != CPK_LABEL {
             return Err(IoError::from(IoErrorKind::InvalidData));
         }
 
@@ -253,25 +317,45 @@ impl CpkHeader {
         })
     }
 
+    pub fn write(&self, writer: &mut dyn Write) -> IoResult<()> {
+        writer.write_u32::<LittleEndian>(self.label)?;
+        writer.write_u32::<LittleEndian>(self.version)?;
+        writer.write_u32::<LittleEndian>(self.table_start)?;
+        writer.write_u32::<LittleEndian>(self.data_start)?;
+        writer.write_u32::<LittleEndian>(self.max_file_num)?;
+        writer.write_u32::<LittleEndian>(self.file_num)?;
+        writer.write_u32::<LittleEndian>(self.is_formatted)?;
+        writer.write_u32::<LittleEndian>(self.size_of_header)?;
+        writer.write_u32::<LittleEndian>(self.valid_table_num)?;
+        writer.write_u32::<LittleEndian>(self.max_table_num)?;
+        writer.write_u32::<LittleEndian>(self.fragment_num)?;
+        writer.write_u32::<LittleEndian>(self.package_size)?;
+        for r in self.reserved {
+            writer.write_u32::<LittleEndian>(r)?;
+        }
+
+        Ok(())
+    }
+
     pub fn is_pal4(&self) -> bool {
-        self.data_start == 0x00100080
+        self.data_start == PAL4_DATA_START
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct CpkTable {
-    crc: u32,
-    flag: u32,
-    father_crc: u32,
-    start_pos: u32,
-    packed_size: u32,
-    origin_size: u32,
-    extra_info_size: u32,
+    pub(super) crc: u32,
+    pub(super) flag: u32,
+    pub(super) father_crc: u32,
+    pub(super) start_pos: u32,
+    pub(super) packed_size: u32,
+    pub(super) origin_size: u32,
+    pub(super) extra_info_size: u32,
 }
 
 #[allow(dead_code)]
 #[derive(Debug)]
-enum CpkTableFlag {
+pub(super) enum CpkTableFlag {
     None = 0x0,
     IsFile = 0x1,
     IsDir = 0x2,
@@ -283,13 +367,13 @@ enum CpkTableFlag {
 
 impl CpkTable {
     pub fn read(cursor: &mut dyn SeekRead, extra_ending: bool) -> IoResult<CpkTable> {
-        let crc = cursor.read_u32::<LittleEndian>().unwrap();
-        let flag = cursor.read_u32::<LittleEndian>().unwrap();
-        let father_crc = cursor.read_u32::<LittleEndian>().unwrap();
-        let start_pos = cursor.read_u32::<LittleEndian>().unwrap();
-        let packed_size = cursor.read_u32::<LittleEndian>().unwrap();
-        let origin_size = cursor.read_u32::<LittleEndian>().unwrap();
-        let extra_info_size = cursor.read_u32::<LittleEndian>().unwrap();
+        let crc = cursor.read_u32::<LittleEndian>()?;
+        let flag = cursor.read_u32::<LittleEndian>()?;
+        let father_crc = cursor.read_u32::<LittleEndian>()?;
+        let start_pos = cursor.read_u32::<LittleEndian>()?;
+        let packed_size = cursor.read_u32::<LittleEndian>()?;
+        let origin_size = cursor.read_u32::<LittleEndian>()?;
+        let extra_info_size = cursor.read_u32::<LittleEndian>()?;
         if extra_ending {
             let _ = cursor.read_u32::<LittleEndian>();
         }
@@ -305,6 +389,21 @@ impl CpkTable {
         })
     }
 
+    pub fn write(&self, writer: &mut dyn Write, extra_ending: bool) -> IoResult<()> {
+        writer.write_u32::<LittleEndian>(self.crc)?;
+        writer.write_u32::<LittleEndian>(self.flag)?;
+        writer.write_u32::<LittleEndian>(self.father_crc)?;
+        writer.write_u32::<LittleEndian>(self.start_pos)?;
+        writer.write_u32::<LittleEndian>(self.packed_size)?;
+        writer.write_u32::<LittleEndian>(self.origin_size)?;
+        writer.write_u32::<LittleEndian>(self.extra_info_size)?;
+        if extra_ending {
+            writer.write_u32::<LittleEndian>(0)?;
+        }
+
+        Ok(())
+    }
+
     pub fn is_compressed(&self) -> bool {
         (self.flag & CpkTableFlag::IsNotCompressed as u32) == 0
     }
@@ -322,7 +421,7 @@ impl CpkTable {
 pub struct CpkEntry {
     raw_entry: CpkTable,
     name: String,
-    children: Vec<Rc<RefCell<CpkEntry>>>,
+    children: Vec<CpkEntry>,
 }
 
 impl CpkEntry {
@@ -338,11 +437,11 @@ impl CpkEntry {
         &self.name
     }
 
-    pub fn add_child(&mut self, child: &Rc<RefCell<CpkEntry>>) {
-        self.children.push(child.clone());
+    pub fn add_child(&mut self, child: CpkEntry) {
+        self.children.push(child);
     }
 
-    pub fn children(&self) -> &[Rc<RefCell<CpkEntry>>] {
+    pub fn children(&self) -> &[CpkEntry] {
         self.children.as_slice()
     }
 
@@ -350,7 +449,19 @@ impl CpkEntry {
         (self.raw_entry.flag & CpkTableFlag::IsDir as u32) != 0
     }
 
-    pub fn ls<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<Rc<RefCell<CpkEntry>>>> {
+    pub fn is_compressed(&self) -> bool {
+        self.raw_entry.is_compressed()
+    }
+
+    pub fn packed_size(&self) -> u32 {
+        self.raw_entry.packed_size
+    }
+
+    pub fn original_size(&self) -> u32 {
+        self.raw_entry.origin_size
+    }
+
+    pub fn ls<P: AsRef<Path>>(&self, path: P) -> std::io::Result<&[CpkEntry]> {
         let mut components = path.as_ref().components();
         let first = components.next();
         match first {
@@ -359,13 +470,13 @@ impl CpkEntry {
                 let child = self
                     .children
                     .iter()
-                    .find(|e| e.borrow().name == component.as_os_str().to_str().unwrap());
+                    .find(|e| e.name == component.as_os_str().to_str().unwrap());
                 match child {
-                    Some(c) => c.borrow().ls(rest),
+                    Some(c) => c.ls(rest),
                     None => Err(std::io::Error::from(std::io::ErrorKind::NotFound)),
                 }
             }
-            None => Ok(self.children.clone()),
+            None => Ok(&self.children),
         }
     }
 }
@@ -1,22 +1,22 @@
-use crate::fs::{create_reader, memory_file::MemoryFile};
+use crate::fs::{shared_reader::create_shared_reader, streaming_file::PackedFile};
 
 use super::{CpkArchive, CpkEntry};
 use encoding::{EncoderTrap, Encoding};
 use mini_fs::{Entries, Entry, EntryKind, Store};
-use std::{cell::RefCell, ffi::OsString, path::Path, rc::Rc};
+use std::{ffi::OsString, path::Path};
 
 pub struct CpkFs {
-    cpk_archive: RefCell<CpkArchive>,
+    cpk_archive: CpkArchive,
     entry: Option<CpkEntry>,
 }
 
 impl CpkFs {
     pub fn new<P: AsRef<Path>>(cpk_path: P) -> anyhow::Result<CpkFs> {
-        let reader = create_reader(cpk_path)?;
-        let cpk_archive = RefCell::new(CpkArchive::load(reader)?);
+        let reader = create_shared_reader(cpk_path)?;
+        let cpk_archive = CpkArchive::load(reader)?;
 
         #[cfg(any(windows, linux, macos))]
-        let entry = Some(cpk_archive.borrow_mut().build_directory());
+        let entry = Some(cpk_archive.build_directory()?);
 
         #[cfg(any(android, vita))]
         let entry = None;
@@ -26,14 +26,14 @@ impl CpkFs {
 }
 
 impl Store for CpkFs {
-    type File = MemoryFile;
+    type File = PackedFile;
 
     fn open_path(&self, path: &Path) -> std::io::Result<Self::File> {
         // need ad-hoc conversion to windows path
         // since the crc hashed path was hard-coded with back-slash dir separator
         let path = path.to_string_lossy().replace("/", r"\");
         let path = Path::new(path.chars().as_str());
-        self.cpk_archive.borrow_mut().open(
+        self.cpk_archive.open_streaming(
             &encoding::all::GBK
                 .encode(&path.to_str().unwrap().to_lowercase(), EncoderTrap::Ignore)
                 .unwrap(),
@@ -43,9 +43,9 @@ impl Store for CpkFs {
     fn entries_path(&self, p: &Path) -> std::io::Result<Entries> {
         if let Some(entry) = self.entry.as_ref() {
             let entries = entry.ls(p)?;
-            Ok(Entries::new(CpkEntryIter::new(Box::new(
-                entries.into_iter(),
-            ))))
+            let list: Vec<std::io::Result<Entry>> =
+                CpkEntryIter::new(Box::new(entries.iter())).collect();
+            Ok(Entries::new(list))
         } else {
             Ok(Entries::new(vec![]))
         }
@@ -53,11 +53,11 @@ impl Store for CpkFs {
 }
 
 pub struct CpkEntryIter<'a> {
-    entries: Box<dyn Iterator<Item = Rc<RefCell<CpkEntry>>> + 'a>,
+    entries: Box<dyn Iterator<Item = &'a CpkEntry> + 'a>,
 }
 
 impl<'a> CpkEntryIter<'a> {
-    pub fn new(entries: Box<dyn Iterator<Item = Rc<RefCell<CpkEntry>>> + 'a>) -> Self {
+    pub fn new(entries: Box<dyn Iterator<Item = &'a CpkEntry> + 'a>) -> Self {
         Self { entries }
     }
 }
@@ -68,8 +68,8 @@ impl<'a> Iterator for CpkEntryIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.entries.next().and_then(|e| {
             Some(Ok(Entry {
-                name: OsString::from(e.borrow().name()),
-                kind: if e.borrow().is_dir() {
+                name: OsString::from(e.name()),
+                kind: if e.is_dir() {
                     EntryKind::Dir
                 } else {
                     EntryKind::File
@@ -0,0 +1,282 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::Path,
+};
+
+use encoding::{EncoderTrap, Encoding};
+
+use super::{
+    cpk_archive::{
+        CpkHeader, CpkTable, CpkTableFlag, CPK_HEADER_SIZE, CPK_LABEL, CPK_MAX_FILE_NUM,
+        PAL4_ENCRYPTED_TABLE_SIZE, PAL4_TABLE_KEY,
+    },
+    CpkArchive, CpkEntry,
+};
+
+type IoResult<T> = std::io::Result<T>;
+type IoError = std::io::Error;
+type IoErrorKind = std::io::ErrorKind;
+
+/// Builds cpk packages that the original PAL3 and PAL4 executables are able to load.
+///
+/// Paths are stored relative to the package root, separated with back-slashes
+/// as the original engines expect. The crc of every entry is computed over its
+/// lower-cased GBK-encoded path.
+pub struct CpkWriter {
+    pal4: bool,
+    compress: bool,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl CpkWriter {
+    pub fn new(pal4: bool) -> Self {
+        Self {
+            pal4,
+            compress: true,
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn add_file(&mut self, path: &str, data: Vec<u8>) {
+        let path = path
+            .split(|c| c == '/' || c == '\\')
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<&str>>()
+            .join("\\");
+        self.files.insert(path, data);
+    }
+
+    pub fn add_dir<P: AsRef<Path>>(&mut self, root: P) -> IoResult<()> {
+        self.add_dir_internal(root.as_ref(), "")
+    }
+
+    pub fn add_archive(&mut self, archive: &CpkArchive) -> IoResult<()> {
+        let root = archive.build_directory()?;
+        self.add_archive_entries(archive, &root, "")
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> IoResult<()> {
+        let mut lzo = minilzo_rs::LZO::init().unwrap();
+        let nodes = self.collect_nodes()?;
+        if nodes.len() > CPK_MAX_FILE_NUM as usize {
+            return Err(IoError::new(
+                IoErrorKind::InvalidInput,
+                format!("Too many entries for cpk: {}", nodes.len()),
+            ));
+        }
+
+        let table_entry_size = std::mem::size_of::<CpkTable>() as u32 + self.pal4 as u32 * 4;
+        let data_start = CPK_HEADER_SIZE + table_entry_size * CPK_MAX_FILE_NUM;
+
+        let mut tables = vec![];
+        let mut blocks = vec![];
+        let mut position = data_start;
+        for node in &nodes {
+            let name = encode_gbk(&node.name)?;
+            let (flag, data, origin_size) = match node.data {
+                None => (
+                    CpkTableFlag::IsDir as u32 | CpkTableFlag::IsNotCompressed as u32,
+                    vec![],
+                    0,
+                ),
+                Some(data) => {
+                    let compressed = if self.compress && !data.is_empty() {
+                        lzo.compress(data)
+                            .ok()
+                            .filter(|compressed| compressed.len() < data.len())
+                    } else {
+                        None
+                    };
+
+                    match compressed {
+                        Some(compressed) => (CpkTableFlag::IsFile as u32, compressed, data.len()),
+                        None => (
+                            CpkTableFlag::IsFile as u32 | CpkTableFlag::IsNotCompressed as u32,
+                            data.clone(),
+                            data.len(),
+                        ),
+                    }
+                }
+            };
+
+            let mut extra_info = name;
+            extra_info.push(0);
+
+            tables.push(CpkTable {
+                crc: node.crc,
+                flag,
+                father_crc: node.father_crc,
+                start_pos: position,
+                packed_size: size_u32(data.len())?,
+                origin_size: size_u32(origin_size)?,
+                extra_info_size: size_u32(extra_info.len())?,
+            });
+
+            let block_size = size_u32(data.len())?
+                .checked_add(size_u32(extra_info.len())?)
+                .ok_or_else(too_large)?;
+            position = position.checked_add(block_size).ok_or_else(too_large)?;
+            blocks.push(data);
+            blocks.push(extra_info);
+        }
+
+        // The original engines binary search the index by crc
+        tables.sort_by_key(|t| t.crc);
+        if let Some(w) = tables.windows(2).find(|w| w[0].crc == w[1].crc) {
+            return Err(IoError::new(
+                IoErrorKind::InvalidInput,
+                format!("Crc collision in cpk index: {:#x}", w[0].crc),
+            ));
+        }
+
+        let mut table_buffer = vec![];
+        for table in &tables {
+            table.write(&mut table_buffer, self.pal4)?;
+        }
+
+        table_buffer.resize((table_entry_size * CPK_MAX_FILE_NUM) as usize, 0);
+        if self.pal4 {
+            let encrypted =
+                xxtea::encrypt_raw(&table_buffer[..PAL4_ENCRYPTED_TABLE_SIZE], PAL4_TABLE_KEY);
+            table_buffer[..PAL4_ENCRYPTED_TABLE_SIZE].copy_from_slice(&encrypted);
+        }
+
+        let header = CpkHeader {
+            label: CPK_LABEL,
+            version: 1,
+            table_start: CPK_HEADER_SIZE,
+            data_start,
+            max_file_num: CPK_MAX_FILE_NUM,
+            file_num: tables.len() as u32,
+            is_formatted: 0,
+            size_of_header: CPK_HEADER_SIZE,
+            valid_table_num: tables.len() as u32,
+            max_table_num: CPK_MAX_FILE_NUM,
+            fragment_num: 0,
+            package_size: position,
+            reserved: [0; 20],
+        };
+
+        header.write(writer)?;
+        writer.write_all(&table_buffer)?;
+        for block in &blocks {
+            writer.write_all(block)?;
+        }
+
+        Ok(())
+    }
+
+    fn collect_nodes(&self) -> IoResult<Vec<CpkWriterNode>> {
+        let mut dirs = BTreeSet::new();
+        for path in self.files.keys() {
+            let mut parent = path.as_str();
+            while let Some(index) = parent.rfind('\\') {
+                parent = &parent[..index];
+                dirs.insert(parent);
+            }
+        }
+
+        let mut nodes = vec![];
+        for dir in dirs {
+            nodes.push(CpkWriterNode::new(dir, None)?);
+        }
+
+        for (path, data) in &self.files {
+            nodes.push(CpkWriterNode::new(path, Some(data))?);
+        }
+
+        Ok(nodes)
+    }
+
+    fn add_dir_internal(&mut self, dir: &Path, prefix: &str) -> IoResult<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}\\{}", prefix, name)
+            };
+
+            if entry.file_type()?.is_dir() {
+                self.add_dir_internal(&entry.path(), &path)?;
+            } else {
+                self.add_file(&path, std::fs::read(entry.path())?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_archive_entries(
+        &mut self,
+        archive: &CpkArchive,
+        entry: &CpkEntry,
+        prefix: &str,
+    ) -> IoResult<()> {
+        for child in entry.children() {
+            let path = if prefix.is_empty() {
+                child.name().to_string()
+            } else {
+                format!("{}\\{}", prefix, child.name())
+            };
+
+            if child.is_dir() {
+                self.add_archive_entries(archive, child, &path)?;
+            } else {
+                let file = archive.open(&encode_gbk(&path.to_lowercase())?)?;
+                self.add_file(&path, file.content());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct CpkWriterNode<'a> {
+    name: String,
+    crc: u32,
+    father_crc: u32,
+    data: Option<&'a Vec<u8>>,
+}
+
+impl<'a> CpkWriterNode<'a> {
+    fn new(path: &str, data: Option<&'a Vec<u8>>) -> IoResult<Self> {
+        let (father_crc, name) = match path.rfind('\\') {
+            Some(index) => (path_crc(&path[..index])?, &path[index + 1..]),
+            None => (0, path),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            crc: path_crc(path)?,
+            father_crc,
+            data,
+        })
+    }
+}
+
+/// Cpk offsets and sizes are 32 bits, an archive can't grow beyond 4 GiB.
+fn size_u32(size: usize) -> IoResult<u32> {
+    u32::try_from(size).map_err(|_| too_large())
+}
+
+fn too_large() -> IoError {
+    IoError::new(IoErrorKind::InvalidInput, "Cpk archive exceeds 4 GiB")
+}
+
+fn path_crc(path: &str) -> IoResult<u32> {
+    Ok(super::crc_checksum(&encode_gbk(&path.to_lowercase())?))
+}
+
+fn encode_gbk(s: &str) -> IoResult<Vec<u8>> {
+    encoding::all::GBK
+        .encode(s, EncoderTrap::Strict)
+        .map_err(|e| IoError::new(IoErrorKind::InvalidInput, e.to_string()))
+}
@@ -1,22 +1,27 @@
 use std::{
     collections::HashMap,
-    io::{Cursor, SeekFrom},
+    io::{Cursor, Seek, SeekFrom},
     path::Path,
 };
 
 use common::read_ext::ReadExt;
 use radiance::utils::SeekRead;
 
-use crate::fs::{memory_file::MemoryFile, plain_fs::PlainArchive};
+use crate::fs::{
+    memory_file::MemoryFile,
+    plain_fs::{PlainArchive, PlainFileInfo},
+    shared_reader::{ReaderCursor, SharedReader},
+};
 
 pub struct FmbArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     _meta: FmbMeta,
     pub files: HashMap<String, FmbFile>,
 }
 
 impl FmbArchive {
-    pub fn load(mut reader: Box<dyn SeekRead>) -> anyhow::Result<FmbArchive> {
+    pub fn load(source: SharedReader) -> anyhow::Result<FmbArchive> {
+        let mut reader = ReaderCursor::new(source.clone());
         let meta = FmbMeta::read(&mut reader)?;
         let mut files = HashMap::new();
 
@@ -27,7 +32,7 @@ impl FmbArchive {
         }
 
         Ok(Self {
-            reader,
+            reader: source,
             _meta: meta,
             files,
         })
@@ -35,13 +40,13 @@ impl FmbArchive {
 }
 
 impl PlainArchive for FmbArchive {
-    fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile> {
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let mut reader = ReaderCursor::new(self.reader.clone());
         let path = path.as_ref().to_str().unwrap();
 
         if let Some(file) = self.files.get(path) {
-            self.reader
-                .seek(SeekFrom::Start(file.start_position as u64))?;
-            let mut data = self.reader.read_u8_vec(file.compressed_size as usize)?;
+            reader.seek(SeekFrom::Start(file.start_position as u64))?;
+            let mut data = reader.read_u8_vec(file.compressed_size as usize)?;
 
             if file.is_compressed == 1 {
                 let lzo = minilzo_rs::LZO::init().unwrap();
@@ -63,6 +68,18 @@ impl PlainArchive for FmbArchive {
     fn files(&self) -> Vec<String> {
         self.files.keys().map(|s| s.to_string()).collect()
     }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        self.files
+            .values()
+            .map(|f| PlainFileInfo {
+                name: f.name.clone(),
+                packed_size: f.compressed_size as u64,
+                size: f.uncompressed_size as u64,
+                is_compressed: f.is_compressed == 1,
+            })
+            .collect()
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -1,4 +1,4 @@
-use crate::fs::{create_reader, plain_fs::PlainFs};
+use crate::fs::{plain_fs::PlainFs, shared_reader::create_shared_reader};
 use std::path::Path;
 
 use super::fmb_archive::FmbArchive;
@@ -7,7 +7,7 @@ pub type FmbFs = PlainFs<FmbArchive>;
 
 impl FmbFs {
     pub fn create<P: AsRef<Path>>(fmb_path: P) -> anyhow::Result<Self> {
-        let reader = create_reader(fmb_path)?;
+        let reader = create_shared_reader(fmb_path)?;
         let sfb_archive = FmbArchive::load(reader)?;
         Ok(Self::new(sfb_archive))
     }
@@ -1,6 +1,6 @@
 use std::{
     collections::HashMap,
-    io::{Cursor, SeekFrom},
+    io::{Cursor, Seek, SeekFrom},
     path::Path,
 };
 
@@ -8,16 +8,21 @@ use byteorder::{LittleEndian, WriteBytesExt};
 use common::read_ext::ReadExt;
 use radiance::utils::SeekRead;
 
-use crate::fs::{memory_file::MemoryFile, plain_fs::PlainArchive};
+use crate::fs::{
+    memory_file::MemoryFile,
+    plain_fs::{PlainArchive, PlainFileInfo},
+    shared_reader::{ReaderCursor, SharedReader},
+};
 
 pub struct ImdArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     _meta: ImdMeta,
     pub files: HashMap<String, ImdFile>,
 }
 
 impl ImdArchive {
-    pub fn load(mut reader: Box<dyn SeekRead>) -> anyhow::Result<ImdArchive> {
+    pub fn load(source: SharedReader) -> anyhow::Result<ImdArchive> {
+        let mut reader = ReaderCursor::new(source.clone());
         let meta = ImdMeta::read(&mut reader)?;
         let mut files = HashMap::new();
 
@@ -28,7 +33,7 @@ impl ImdArchive {
         }
 
         Ok(Self {
-            reader,
+            reader: source,
             _meta: meta,
             files,
         })
@@ -36,33 +41,21 @@ impl ImdArchive {
 }
 
 impl PlainArchive for ImdArchive {
-    fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile> {
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let mut reader = ReaderCursor::new(self.reader.clone());
         let path = path.as_ref().to_str().unwrap();
 
         if let Some(file) = self.files.get(path) {
-            self.reader
-                .seek(SeekFrom::Start(file.start_position as u64))?;
-            let data = self.reader.read_u8_vec(file.file_size as usize)?;
+            reader.seek(SeekFrom::Start(file.start_position as u64))?;
+            let data = reader.read_u8_vec(file.file_size as usize)?;
 
+            // Rle encoded textures are decoded into RGBA with the size
+            // prepended, the others are complete image files
             let data = match file.file_type {
-                3 => {
-                    let mut output = vec![];
-                    output.write_u32::<LittleEndian>(file.width)?;
-                    output.write_u32::<LittleEndian>(file.height)?;
-                    let mut data = decode_03(&data)?;
-                    output.append(&mut data);
-                    output
-                }
-                5 => {
-                    let mut output = vec![];
-                    output.write_u32::<LittleEndian>(file.width)?;
-                    output.write_u32::<LittleEndian>(file.height)?;
-                    let mut data = decode_05(&data)?;
-                    output.append(&mut data);
-                    output
-                }
+                3 => with_size_header(file, decode_03(&data, pixel_count(file))?)?,
+                5 => with_size_header(file, decode_05(&data, pixel_count(file))?)?,
                 7 | 9 => data,
-                i => unimplemented!("Unsupported imd file type: {}", i),
+                i => Err(ImdReadError::UnsupportedFileType(i))?,
             };
 
             Ok(MemoryFile::new(Cursor::new(data)))
@@ -74,59 +67,85 @@ impl PlainArchive for ImdArchive {
     fn files(&self) -> Vec<String> {
         self.files.keys().map(|s| s.to_string()).collect()
     }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        self.files
+            .values()
+            .map(|f| PlainFileInfo {
+                name: f.name.clone(),
+                packed_size: f.file_size as u64,
+                size: f.file_size as u64,
+                is_compressed: matches!(f.file_type, 3 | 5),
+            })
+            .collect()
+    }
 }
 
-fn decode_03(mut data: &[u8]) -> anyhow::Result<Vec<u8>> {
-    let data = data.read_w_vec(data.len() / 2)?;
+fn with_size_header(file: &ImdFile, mut pixels: Vec<u8>) -> anyhow::Result<Vec<u8>> {
     let mut output = vec![];
-    let mut i = 0;
-    while i < data.len() {
-        let len = (data[i] & 0x7fff) + 1;
-        let ty = data[i] & 0x8000;
-        i += 1;
-        if ty == 0 {
-            for j in 0..len as usize {
-                let pixel = transform_rgb565(data[i + j]);
-                output.append(&mut pixel.to_vec());
-            }
-
-            i += len as usize;
-        } else {
-            let pixel = transform_rgb565(data[i]);
-            for _ in 0..len as usize {
-                output.append(&mut pixel.to_vec());
-            }
+    output.write_u32::<LittleEndian>(file.width)?;
+    output.write_u32::<LittleEndian>(file.height)?;
+    output.append(&mut pixels);
+    Ok(output)
+}
 
-            i += 1;
-        }
-    }
+fn pixel_count(file: &ImdFile) -> usize {
+    (file.width as usize).saturating_mul(file.height as usize)
+}
 
-    Ok(output)
+/// Rle encoded RGB565. Each run starts with a u16 whose lower 15 bits are
+/// the pixel count minus one. If the highest bit is set, the following pixel
+/// is repeated, otherwise that many literal pixels follow.
+fn decode_03(mut data: &[u8], pixel_count: usize) -> anyhow::Result<Vec<u8>> {
+    let data = data.read_w_vec(data.len() / 2)?;
+    decode_rle(&data, 0x8000, pixel_count, |pixel| {
+        transform_rgb565(pixel as u16)
+    })
 }
 
-fn decode_05(mut data: &[u8]) -> anyhow::Result<Vec<u8>> {
+/// Rle encoded BGRA8888, with the same layout as `decode_03` except that
+/// both the run headers and the pixels are u32.
+fn decode_05(mut data: &[u8], pixel_count: usize) -> anyhow::Result<Vec<u8>> {
     let data = data.read_dw_vec(data.len() / 4)?;
+    decode_rle(&data, 0x80000000, pixel_count, |pixel| {
+        let [b, g, r, a] = pixel.to_le_bytes();
+        [r, g, b, a]
+    })
+}
+
+/// Runs that would go past `pixel_count` pixels are rejected, so a corrupted
+/// run header cannot make the output grow without bound.
+fn decode_rle<T: Copy + Into<u32>>(
+    data: &[T],
+    repeat_flag: u32,
+    pixel_count: usize,
+    transform: impl Fn(u32) -> [u8; 4],
+) -> anyhow::Result<Vec<u8>> {
     let mut output = vec![];
+    let mut decoded = 0;
     let mut i = 0;
     while i < data.len() {
-        let len = (data[i] & 0x7fffffff) + 1;
-        let ty = data[i] & 0x80000000;
+        let header: u32 = data[i].into();
+        let len = (header & !repeat_flag) as usize + 1;
+        decoded += len;
+        if decoded > pixel_count {
+            Err(ImdReadError::TooManyPixels(pixel_count))?;
+        }
+
         i += 1;
-        if ty == 0 {
-            for j in 0..len as usize {
-                let mut v = vec![];
-                v.write_u32::<LittleEndian>(data[i + j])?;
-                v.swap(0, 2);
-                output.append(&mut v);
+        if header & repeat_flag == 0 {
+            let pixels = data
+                .get(i..i.saturating_add(len))
+                .ok_or(ImdReadError::CorruptedData(i))?;
+            for pixel in pixels {
+                output.extend_from_slice(&transform((*pixel).into()));
             }
 
-            i += len as usize;
+            i += len;
         } else {
-            let mut v = vec![];
-            v.write_u32::<LittleEndian>(data[i])?;
-            v.swap(0, 2);
-            for _ in 0..len as usize {
-                output.append(&mut v.clone());
+            let pixel = transform((*data.get(i).ok_or(ImdReadError::CorruptedData(i))?).into());
+            for _ in 0..len {
+                output.extend_from_slice(&pixel);
             }
 
             i += 1;
@@ -145,8 +164,17 @@ fn transform_rgb565(short: u16) -> [u8; 4] {
 
 #[derive(thiserror::Error, Debug)]
 pub enum ImdReadError {
-    #[error("Incorrect magic found for fmb. Should be: 0x464d4220, found {0}")]
+    #[error("Incorrect magic found for imd. Should be: 0x20444d49, found {0}")]
     IncorrectMagic(u32),
+
+    #[error("Unsupported imd texture type: {0}")]
+    UnsupportedFileType(u32),
+
+    #[error("Corrupted imd texture data at {0}")]
+    CorruptedData(usize),
+
+    #[error("Imd texture data decodes to more than {0} pixels")]
+    TooManyPixels(usize),
 }
 
 #[derive(Debug, Clone)]
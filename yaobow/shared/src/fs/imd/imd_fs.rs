@@ -1,4 +1,4 @@
-use crate::fs::{create_reader, plain_fs::PlainFs};
+use crate::fs::{plain_fs::PlainFs, shared_reader::create_shared_reader};
 use std::path::Path;
 
 use super::imd_archive::ImdArchive;
@@ -7,7 +7,7 @@ pub type ImdFs = PlainFs<ImdArchive>;
 
 impl ImdFs {
     pub fn create<P: AsRef<Path>>(imd_path: P) -> anyhow::Result<Self> {
-        let reader = create_reader(imd_path)?;
+        let reader = create_shared_reader(imd_path)?;
         let sfb_archive = ImdArchive::load(reader)?;
         Ok(Self::new(sfb_archive))
     }
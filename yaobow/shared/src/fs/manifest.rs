@@ -0,0 +1,214 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Read, Write},
+    path::Path,
+};
+
+use mini_fs::{EntryKind, MiniFs, StoreExt};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ManifestError {
+    #[error("Invalid manifest line {0}: {1}")]
+    InvalidLine(usize, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub md5: String,
+}
+
+/// Size and md5 of every file in a virtual file system, keyed by the vfs
+/// path. Saved as one `<md5>\t<size>\t<path>` line per file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ManifestDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Manifest {
+    /// Hashes every file under `root`. Files that can't be read are logged
+    /// and left out.
+    pub fn build<P: AsRef<Path>>(vfs: &MiniFs, root: P) -> Manifest {
+        let mut manifest = Manifest::default();
+        manifest.add_dir(vfs, root.as_ref());
+        manifest
+    }
+
+    pub fn read(reader: &mut dyn BufRead) -> anyhow::Result<Manifest> {
+        let mut manifest = Manifest::default();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(3, '\t');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(md5), Some(size), Some(path)) => {
+                    let size = size
+                        .parse()
+                        .map_err(|_| ManifestError::InvalidLine(i + 1, line.clone()))?;
+                    manifest.entries.insert(
+                        path.to_string(),
+                        ManifestEntry {
+                            size,
+                            md5: md5.to_string(),
+                        },
+                    );
+                }
+                _ => Err(ManifestError::InvalidLine(i + 1, line.clone()))?,
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn write(&self, writer: &mut dyn Write) -> std::io::Result<()> {
+        for (path, entry) in &self.entries {
+            writeln!(writer, "{}\t{}\t{}", entry.md5, entry.size, path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the files only in `other` as added, and the ones missing from
+    /// it as removed.
+    pub fn diff(&self, other: &Manifest) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+        for (path, entry) in &self.entries {
+            match other.entries.get(path) {
+                None => diff.removed.push(path.clone()),
+                Some(e) if e != entry => diff.changed.push(path.clone()),
+                _ => {}
+            }
+        }
+
+        for path in other.entries.keys() {
+            if !self.entries.contains_key(path) {
+                diff.added.push(path.clone());
+            }
+        }
+
+        diff
+    }
+
+    /// Hashes only the files listed in this manifest, so a small reference
+    /// manifest of key files is cheap to check at startup.
+    pub fn check(&self, vfs: &MiniFs) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+        for (path, entry) in &self.entries {
+            match hash_file(vfs, Path::new(path)) {
+                Ok(actual) if actual == *entry => {}
+                Ok(_) => diff.changed.push(path.clone()),
+                Err(_) => diff.removed.push(path.clone()),
+            }
+        }
+
+        diff
+    }
+
+    fn add_dir(&mut self, vfs: &MiniFs, path: &Path) {
+        let entries = match vfs.entries(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Unable to list {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let entry_path = path.join(&entry.name);
+            match entry.kind {
+                EntryKind::Dir => self.add_dir(vfs, &entry_path),
+                EntryKind::File => match hash_file(vfs, &entry_path) {
+                    Ok(e) => {
+                        let key = entry_path.to_string_lossy().replace('\\', "/");
+                        self.entries.insert(key, e);
+                    }
+                    Err(e) => log::warn!("Unable to hash {:?}: {}", entry_path, e),
+                },
+            }
+        }
+    }
+}
+
+/// Loads every `<edition>.manifest` file in `dir` as a reference manifest
+/// named after the file. Manifests that can't be read are logged and skipped.
+pub fn load_editions<P: AsRef<Path>>(dir: P) -> Vec<(String, Manifest)> {
+    let entries = match std::fs::read_dir(dir.as_ref()) {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::warn!(
+                "Unable to list edition manifests in {:?}: {}",
+                dir.as_ref(),
+                e
+            );
+            return vec![];
+        }
+    };
+
+    let mut editions = vec![];
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("manifest") {
+            continue;
+        }
+
+        let name = match path.file_stem() {
+            Some(stem) => stem.to_string_lossy().to_string(),
+            None => continue,
+        };
+
+        let manifest = std::fs::File::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| Manifest::read(&mut std::io::BufReader::new(file)));
+        match manifest {
+            Ok(manifest) => editions.push((name, manifest)),
+            Err(e) => log::warn!("Unable to read edition manifest {:?}: {}", path, e),
+        }
+    }
+
+    editions.sort_by(|a, b| a.0.cmp(&b.0));
+    editions
+}
+
+/// Picks the first edition whose reference manifest fully matches the data.
+pub fn detect_edition<'a>(vfs: &MiniFs, editions: &'a [(String, Manifest)]) -> Option<&'a str> {
+    editions
+        .iter()
+        .find(|(_, manifest)| manifest.check(vfs).is_empty())
+        .map(|(name, _)| name.as_str())
+}
+
+fn hash_file(vfs: &MiniFs, path: &Path) -> std::io::Result<ManifestEntry> {
+    let mut file = vfs.open(path)?;
+    let mut context = md5::Context::new();
+    let mut buf = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        context.consume(&buf[..read]);
+        size += read as u64;
+    }
+
+    Ok(ManifestEntry {
+        size,
+        md5: format!("{:x}", context.compute()),
+    })
+}
@@ -1,10 +1,17 @@
 pub mod cpk;
 pub mod fmb;
 pub mod imd;
+pub mod manifest;
 pub mod memory_file;
+pub mod mount_report;
+pub mod overlay_fs;
+pub mod path_index;
 pub mod pkg;
 pub mod plain_fs;
 pub mod sfb;
+pub mod shared_reader;
+pub mod streaming_file;
+pub mod wildcard;
 pub mod zpk;
 pub mod zpkg;
 
@@ -14,95 +21,220 @@ use std::{
 };
 
 use mini_fs::{LocalFs, MiniFs};
-use radiance::utils::SeekRead;
 
 use crate::fs::{
-    cpk::CpkFs, fmb::fmb_fs::FmbFs, imd::imd_fs::ImdFs, pkg::pkg_fs::PkgFs, sfb::sfb_fs::SfbFs,
-    zpk::zpk_fs::ZpkFs, zpkg::zpkg_fs::ZpkgFs,
+    cpk::CpkFs, fmb::fmb_fs::FmbFs, imd::imd_fs::ImdFs, mount_report::MountReport,
+    overlay_fs::OverlayFs, pkg::pkg_fs::PkgFs, sfb::sfb_fs::SfbFs, zpk::zpk_fs::ZpkFs,
+    zpkg::zpkg_fs::ZpkgFs,
 };
 
-pub fn init_virtual_fs<P: AsRef<Path>>(local_asset_path: P, pkg_key: Option<&str>) -> MiniFs {
-    let local = LocalFs::new(local_asset_path.as_ref());
-    let vfs = MiniFs::new(false).mount("/", local);
-    let vfs = mount_packages_recursive(
-        vfs,
-        local_asset_path.as_ref(),
+pub fn init_virtual_fs<P: AsRef<Path>>(
+    local_asset_path: P,
+    pkg_key: Option<&str>,
+) -> (MiniFs, MountReport) {
+    init_virtual_fs_with_overlays(local_asset_path, &[], pkg_key)
+}
+
+/// Mounts the game folder, with every directory in `overlay_paths` layered on
+/// top of it. Overlays are consulted before the game data in the given order,
+/// so loose files in them override the packed assets. Relative overlay paths
+/// are resolved against `local_asset_path`.
+///
+/// Packages that cannot be mounted are skipped and listed in the returned
+/// report.
+pub fn init_virtual_fs_with_overlays<P: AsRef<Path>>(
+    local_asset_path: P,
+    overlay_paths: &[PathBuf],
+    pkg_key: Option<&str>,
+) -> (MiniFs, MountReport) {
+    let mut report = MountReport::default();
+    let vfs = mount_local_dir(local_asset_path.as_ref(), pkg_key, &mut report);
+    if overlay_paths.is_empty() {
+        return (vfs, report);
+    }
+
+    let mut overlay = OverlayFs::new();
+    for path in overlay_paths {
+        let path = local_asset_path.as_ref().join(path);
+        if !path.is_dir() {
+            report.add_skipped(&path, "overlay is not a directory");
+            continue;
+        }
+
+        log::debug!("Mounting overlay {:?}", &path);
+        overlay = overlay.push_layer(
+            &path.to_string_lossy(),
+            mount_local_dir(&path, pkg_key, &mut report),
+        );
+    }
+
+    let overlay = overlay.push_layer(&local_asset_path.as_ref().to_string_lossy(), vfs);
+    log::info!("Overlay order: {:?}", overlay.layer_names());
+    (MiniFs::new(false).mount("/", overlay), report)
+}
+
+fn mount_local_dir(local_path: &Path, pkg_key: Option<&str>, report: &mut MountReport) -> MiniFs {
+    let mut packages = vec![];
+    find_packages(
+        local_path,
         &PathBuf::from("./"),
         pkg_key,
+        &mut packages,
+        report,
     );
 
+    let mut vfs = MiniFs::new(false).mount("/", LocalFs::new(local_path));
+    for (package, store) in packages.iter().zip(open_packages(&packages, pkg_key)) {
+        match store {
+            Ok(store) => {
+                log::debug!("Mounting {:?} <- {:?}", &package.vfs_path, &package.path);
+                report.add_mounted(&package.path, &package.vfs_path);
+                vfs = store.mount(vfs, &package.vfs_path);
+            }
+            Err(e) => report.add_failed(&package.path, &format!("{:?}", e)),
+        }
+    }
+
     vfs
 }
 
-fn mount_packages_recursive(
-    mut vfs: MiniFs,
+fn find_packages(
     local_path: &Path,
     relative_path: &Path,
     pkg_key: Option<&str>,
-) -> MiniFs {
+    packages: &mut Vec<Package>,
+    report: &mut MountReport,
+) {
     let path = local_path.join(relative_path);
     if path.is_dir() {
-        for entry in fs::read_dir(path).unwrap() {
-            let entry = entry.unwrap();
+        let entries = match fs::read_dir(&path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                report.add_failed(&path, &e.to_string());
+                return;
+            }
+        };
+
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    report.add_failed(&path, &e.to_string());
+                    continue;
+                }
+            };
+
             if entry.file_name().eq_ignore_ascii_case("PALSound") {
+                report.add_skipped(&entry.path(), "PALSound is not used");
                 continue;
             }
 
             let new_path = relative_path.join(entry.file_name());
-            vfs = mount_packages_recursive(vfs, local_path, &new_path, pkg_key.clone());
+            find_packages(local_path, &new_path, pkg_key, packages, report);
         }
     } else {
         let vfs_path = PathBuf::from("/").join(relative_path.with_extension(""));
-        match path.extension().and_then(|ext| ext.to_str()) {
-            Some("cpk") => {
-                log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, CpkFs::new(path).unwrap())
+        let parent_path = vfs_path.parent().unwrap_or(Path::new("/")).to_owned();
+        let (kind, vfs_path) = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("cpk") => (PackageKind::Cpk, vfs_path),
+            Some("fmb") => (PackageKind::Fmb, parent_path.join("Model")),
+            Some("imd") => (PackageKind::Imd, parent_path.join("Texture")),
+            Some("sfb") => (PackageKind::Sfb, vfs_path),
+            Some("pkg") if pkg_key.is_none() => {
+                report.add_skipped(&path, "pkg key is not provided");
+                return;
             }
-            Some("fmb") => {
-                let vfs_path = vfs_path.parent().unwrap().join("Model");
-                log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, FmbFs::create(path).unwrap())
-            }
-            Some("imd") => {
-                let vfs_path = vfs_path.parent().unwrap().join("Texture");
-                log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, ImdFs::create(path).unwrap())
-            }
-            Some("sfb") => {
-                log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, SfbFs::create(path).unwrap())
-            }
-            Some("pkg") => match pkg_key {
-                None => log::debug!("Didn't mount {:?} as pkg key is not provided", &path),
-                Some(key) => {
-                    log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                    vfs = vfs.mount(vfs_path, PkgFs::new(path, key).unwrap())
-                }
-            },
-            Some("zpk") => {
-                log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, ZpkFs::create(path).unwrap())
-            }
-            Some("zpkg") => {
-                log::debug!("Mounting {:?} <- {:?}", &vfs_path, &path);
-                vfs = vfs.mount(vfs_path, ZpkgFs::create(path).unwrap())
-            }
-            _ => {}
-        }
+            Some("pkg") => (PackageKind::Pkg, vfs_path),
+            Some("zpk") => (PackageKind::Zpk, vfs_path),
+            Some("zpkg") => (PackageKind::Zpkg, vfs_path),
+            _ => return,
+        };
+
+        packages.push(Package {
+            path,
+            vfs_path,
+            kind,
+        });
     }
+}
 
-    vfs
+/// Opening a package reads and indexes its whole entry table, so the
+/// packages are opened on all the available cores. The stores come back in
+/// the order of `packages` to keep the mount order stable.
+fn open_packages(packages: &[Package], pkg_key: Option<&str>) -> Vec<anyhow::Result<PackageStore>> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = packages.len().div_ceil(threads).max(1);
+    std::thread::scope(|s| {
+        let handles: Vec<_> = packages
+            .chunks(chunk_size)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|package| package.open(pkg_key))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+enum PackageKind {
+    Cpk,
+    Fmb,
+    Imd,
+    Sfb,
+    Pkg,
+    Zpk,
+    Zpkg,
+}
+
+struct Package {
+    path: PathBuf,
+    vfs_path: PathBuf,
+    kind: PackageKind,
 }
 
-#[cfg(vita)]
-fn create_reader<P: AsRef<Path>>(path: P) -> anyhow::Result<Box<dyn SeekRead>> {
-    let file = std::fs::File::open(path.as_ref())?;
-    Ok(Box::new(file))
+impl Package {
+    fn open(&self, pkg_key: Option<&str>) -> anyhow::Result<PackageStore> {
+        let path = &self.path;
+        Ok(match self.kind {
+            PackageKind::Cpk => PackageStore::Cpk(CpkFs::new(path)?),
+            PackageKind::Fmb => PackageStore::Fmb(FmbFs::create(path)?),
+            PackageKind::Imd => PackageStore::Imd(ImdFs::create(path)?),
+            PackageKind::Sfb => PackageStore::Sfb(SfbFs::create(path)?),
+            PackageKind::Pkg => PackageStore::Pkg(PkgFs::new(path, pkg_key.unwrap_or_default())?),
+            PackageKind::Zpk => PackageStore::Zpk(ZpkFs::create(path)?),
+            PackageKind::Zpkg => PackageStore::Zpkg(ZpkgFs::create(path)?),
+        })
+    }
+}
+
+enum PackageStore {
+    Cpk(CpkFs),
+    Fmb(FmbFs),
+    Imd(ImdFs),
+    Sfb(SfbFs),
+    Pkg(PkgFs),
+    Zpk(ZpkFs),
+    Zpkg(ZpkgFs),
 }
 
-#[cfg(any(windows, linux, macos, android))]
-fn create_reader<P: AsRef<Path>>(path: P) -> anyhow::Result<Box<dyn SeekRead>> {
-    let file = std::fs::File::open(path.as_ref())?;
-    let mem = unsafe { memmap::MmapOptions::new().map(&file)? };
-    Ok(Box::new(std::io::Cursor::new(mem)))
+impl PackageStore {
+    fn mount(self, vfs: MiniFs, vfs_path: &Path) -> MiniFs {
+        match self {
+            PackageStore::Cpk(store) => vfs.mount(vfs_path, store),
+            PackageStore::Fmb(store) => vfs.mount(vfs_path, store),
+            PackageStore::Imd(store) => vfs.mount(vfs_path, store),
+            PackageStore::Sfb(store) => vfs.mount(vfs_path, store),
+            PackageStore::Pkg(store) => vfs.mount(vfs_path, store),
+            PackageStore::Zpk(store) => vfs.mount(vfs_path, store),
+            PackageStore::Zpkg(store) => vfs.mount(vfs_path, store),
+        }
+    }
 }
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub enum MountStatus {
+    Mounted,
+    Skipped(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct MountRecord {
+    pub path: PathBuf,
+    pub vfs_path: Option<PathBuf>,
+    pub status: MountStatus,
+}
+
+/// Outcome of mounting every package found under the game folder. A broken
+/// package is recorded here instead of aborting the whole mount.
+#[derive(Debug, Clone, Default)]
+pub struct MountReport {
+    pub records: Vec<MountRecord>,
+}
+
+impl MountReport {
+    pub fn add_mounted(&mut self, path: &Path, vfs_path: &Path) {
+        self.records.push(MountRecord {
+            path: path.to_owned(),
+            vfs_path: Some(vfs_path.to_owned()),
+            status: MountStatus::Mounted,
+        });
+    }
+
+    pub fn add_skipped(&mut self, path: &Path, reason: &str) {
+        log::debug!("Skipped {:?}: {}", path, reason);
+        self.records.push(MountRecord {
+            path: path.to_owned(),
+            vfs_path: None,
+            status: MountStatus::Skipped(reason.to_string()),
+        });
+    }
+
+    pub fn add_failed(&mut self, path: &Path, reason: &str) {
+        log::error!("Failed to mount {:?}: {}", path, reason);
+        self.records.push(MountRecord {
+            path: path.to_owned(),
+            vfs_path: None,
+            status: MountStatus::Failed(reason.to_string()),
+        });
+    }
+
+    pub fn mounted(&self) -> impl Iterator<Item = &MountRecord> {
+        self.records
+            .iter()
+            .filter(|r| matches!(r.status, MountStatus::Mounted))
+    }
+
+    pub fn skipped(&self) -> impl Iterator<Item = &MountRecord> {
+        self.records
+            .iter()
+            .filter(|r| matches!(r.status, MountStatus::Skipped(_)))
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &MountRecord> {
+        self.records
+            .iter()
+            .filter(|r| matches!(r.status, MountStatus::Failed(_)))
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.failed().next().is_some()
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    collections::HashSet,
+    io::{self},
+    path::Path,
+};
+
+use mini_fs::{Entries, Entry, File, MiniFs, Store};
+
+/// Stacks several virtual file systems on top of each other. Layers are
+/// consulted in order, so a loose file in a mod layer wins over the
+/// copy packed in the game archives.
+pub struct OverlayFs {
+    layers: Vec<OverlayLayer>,
+}
+
+struct OverlayLayer {
+    name: String,
+    vfs: MiniFs,
+}
+
+impl Default for OverlayFs {
+    fn default() -> Self {
+        Self { layers: vec![] }
+    }
+}
+
+impl OverlayFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_layer(mut self, name: &str, vfs: MiniFs) -> Self {
+        self.layers.push(OverlayLayer {
+            name: name.to_string(),
+            vfs,
+        });
+        self
+    }
+
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers.iter().map(|l| l.name.as_str()).collect()
+    }
+}
+
+impl Store for OverlayFs {
+    type File = File;
+
+    fn open_path(&self, path: &Path) -> io::Result<Self::File> {
+        for layer in &self.layers {
+            if let Ok(file) = layer.vfs.open_path(path) {
+                log::debug!("Serving {:?} from {}", path, &layer.name);
+                return Ok(file);
+            }
+        }
+
+        Err(io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    fn entries_path(&self, path: &Path) -> io::Result<Entries> {
+        let mut names = HashSet::new();
+        let mut list: Vec<io::Result<Entry>> = vec![];
+        let mut found = false;
+        for layer in &self.layers {
+            if let Ok(entries) = layer.vfs.entries_path(path) {
+                found = true;
+                for entry in entries.flatten() {
+                    if names.insert(entry.name.clone()) {
+                        list.push(Ok(entry));
+                    }
+                }
+            }
+        }
+
+        if found {
+            Ok(Entries::new(list))
+        } else {
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{collections::HashMap, path::Path};
+
+/// Case-insensitive lookup from archive paths to entry indices. Both `/` and
+/// `\` are accepted as separators, and leading separators are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct PathIndex {
+    map: HashMap<String, usize>,
+}
+
+impl PathIndex {
+    pub fn new<'a, I: IntoIterator<Item = &'a str>>(paths: I) -> Self {
+        let mut map = HashMap::new();
+        for (i, path) in paths.into_iter().enumerate() {
+            // Keep the first one to match the behaviour of a linear scan
+            map.entry(Self::normalize(path)).or_insert(i);
+        }
+
+        Self { map }
+    }
+
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<usize> {
+        self.map
+            .get(&Self::normalize(&path.as_ref().to_string_lossy()))
+            .copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn normalize(path: &str) -> String {
+        path.replace('\\', "/")
+            .trim_start_matches('/')
+            .to_lowercase()
+    }
+}
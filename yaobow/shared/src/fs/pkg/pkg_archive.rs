@@ -1,56 +1,86 @@
 use std::{
-    io::{Cursor, Read},
+    io::{Cursor, Read, Seek},
     path::Path,
 };
 
 use byteorder::ReadBytesExt;
 use common::read_ext::ReadExt;
 use encoding::Encoding;
-use radiance::utils::SeekRead;
 
-use crate::fs::memory_file::MemoryFile;
+use crate::fs::{
+    memory_file::MemoryFile,
+    path_index::PathIndex,
+    shared_reader::{ReaderCursor, SharedReader},
+    streaming_file::{InflateFile, PackedFile, StreamingFile, STREAMING_THRESHOLD},
+};
 
 pub struct PkgArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     _header: PkgHeader,
     pub entries: PkgEntries,
+    index: PathIndex,
 }
 
 impl PkgArchive {
-    pub fn load(mut reader: Box<dyn SeekRead>, decrypt_key: &str) -> anyhow::Result<PkgArchive> {
+    pub fn load(source: SharedReader, decrypt_key: &str) -> anyhow::Result<PkgArchive> {
+        let mut reader = ReaderCursor::new(source.clone());
         let _header = PkgHeader::read(&mut reader)?;
         reader.seek(std::io::SeekFrom::Start(_header.entries_start as u64))?;
 
         let entries = PkgEntries::read(&mut reader, &decrypt_key)?;
+        let index = PathIndex::new(entries.file_entries.iter().map(|e| e.fullpath.as_str()));
+
         Ok(Self {
-            reader,
+            reader: source,
             _header,
             entries,
+            index,
         })
     }
 
-    pub fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile> {
-        let path = path.as_ref().as_os_str().to_string_lossy().to_lowercase();
-        let mut path = path.replace('/', "\\");
-        if !path.starts_with("\\") {
-            path = format!("\\{}", path);
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let entry = self.find_entry(path)?;
+        let mut data = vec![0; entry.size as usize];
+        self.reader
+            .read_exact_at(entry.start_position as u64, &mut data)?;
+        if entry.is_compressed() {
+            data = miniz_oxide::inflate::decompress_to_vec_zlib(&data).map_err(|_| {
+                PkgReadError::DecompressionError("Unable to decompress file".to_string())
+            })?;
         }
 
-        for entry in &self.entries.file_entries {
-            if entry.fullpath.to_lowercase() == path {
-                self.reader
-                    .seek(std::io::SeekFrom::Start(entry.start_position as u64))?;
-                let mut data = self.reader.read_u8_vec(entry.size as usize)?;
-                if entry.size != entry.size2 {
-                    data = miniz_oxide::inflate::decompress_to_vec_zlib(&data).map_err(|_| {
-                        PkgReadError::DecompressionError("Unable to decompress file".to_string())
-                    })?;
-                }
-                return Ok(MemoryFile::new(Cursor::new(data)));
-            }
+        Ok(MemoryFile::new(Cursor::new(data)))
+    }
+
+    /// Opens the file without loading it into memory as a whole when it is
+    /// large. Compressed entries are inflated while being read.
+    pub fn open_streaming<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<PackedFile> {
+        let entry = self.find_entry(path.as_ref())?;
+        if (entry.size2 as u64) < STREAMING_THRESHOLD {
+            return Ok(PackedFile::Memory(self.open(path)?));
+        }
+
+        let source = StreamingFile::new(
+            self.reader.clone(),
+            entry.start_position as u64,
+            entry.size as u64,
+        );
+
+        if entry.is_compressed() {
+            Ok(PackedFile::Inflate(InflateFile::new(
+                source,
+                entry.size2 as u64,
+            )))
+        } else {
+            Ok(PackedFile::Stream(source))
         }
+    }
 
-        Err(std::io::Error::from(std::io::ErrorKind::NotFound))?
+    fn find_entry<P: AsRef<Path>>(&self, path: P) -> std::io::Result<PkgFileEntry> {
+        self.index
+            .get(path)
+            .map(|i| self.entries.file_entries[i].clone())
+            .ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))
     }
 }
 
@@ -136,6 +166,10 @@ impl PkgFileEntry {
             size2,
         }
     }
+
+    pub fn is_compressed(&self) -> bool {
+        self.size != self.size2
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,7 +1,6 @@
-use crate::fs::{create_reader, memory_file::MemoryFile};
+use crate::fs::{shared_reader::create_shared_reader, streaming_file::PackedFile};
 use mini_fs::{Entries, Entry, EntryKind, Store};
 use std::{
-    cell::RefCell,
     ffi::OsString,
     io::{self},
     path::Path,
@@ -10,32 +9,28 @@ use std::{
 use super::pkg_archive::{PkgArchive, PkgEntry};
 
 pub struct PkgFs {
-    pkg_archive: RefCell<PkgArchive>,
+    pkg_archive: PkgArchive,
 }
 
 impl PkgFs {
     pub fn new<P: AsRef<Path>>(pkg_path: P, decrypt_key: &str) -> anyhow::Result<PkgFs> {
-        let reader = create_reader(pkg_path)?;
-        let pkg_archive = RefCell::new(PkgArchive::load(reader, decrypt_key)?);
+        let reader = create_shared_reader(pkg_path)?;
+        let pkg_archive = PkgArchive::load(reader, decrypt_key)?;
         Ok(PkgFs { pkg_archive })
     }
 }
 
 impl Store for PkgFs {
-    type File = MemoryFile;
+    type File = PackedFile;
 
     fn open_path(&self, path: &Path) -> std::io::Result<Self::File> {
-        let path = path.to_string_lossy().replace("/", r"\");
-        let path = Path::new(path.chars().as_str());
         self.pkg_archive
-            .borrow_mut()
-            .open(path)
+            .open_streaming(path)
             .map_err(|_| std::io::Error::from(std::io::ErrorKind::Unsupported))
     }
 
     fn entries_path(&self, p: &Path) -> io::Result<Entries> {
-        let archive = self.pkg_archive.borrow();
-        let list = archive.entries.root_entry.list_content(p)?;
+        let list = self.pkg_archive.entries.root_entry.list_content(p)?;
         let entries = list.into_iter().map(|e| {
             Ok(Entry {
                 name: OsString::from(e.name()),
@@ -1,21 +1,108 @@
 use crate::fs::memory_file::MemoryFile;
 use mini_fs::{Entries, Entry, EntryKind, Store};
-use std::{cell::RefCell, ffi::OsString, path::Path};
+use std::{collections::BTreeMap, ffi::OsString, path::Path};
+
+#[derive(Debug, Clone)]
+pub struct PlainFileInfo {
+    pub name: String,
+    pub packed_size: u64,
+    pub size: u64,
+    pub is_compressed: bool,
+}
 
 pub trait PlainArchive {
-    fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile>;
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile>;
     fn files(&self) -> Vec<String>;
+    fn file_infos(&self) -> Vec<PlainFileInfo>;
+
+    fn directory(&self) -> PlainDirectory {
+        PlainDirectory::from_files(self.files())
+    }
+}
+
+/// Directory tree rebuilt from the flat file list of an archive. Both `/` and
+/// `\` are treated as separators.
+#[derive(Debug, Clone, Default)]
+pub struct PlainDirectory {
+    dirs: BTreeMap<String, PlainDirectory>,
+    files: BTreeMap<String, String>,
+}
+
+impl PlainDirectory {
+    pub fn from_files<I: IntoIterator<Item = String>>(files: I) -> Self {
+        let mut root = Self::default();
+        for file in files {
+            let mut components: Vec<&str> = file
+                .split(|c| c == '/' || c == '\\')
+                .filter(|c| !c.is_empty())
+                .collect();
+
+            let name = match components.pop() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            let mut dir = &mut root;
+            for component in components {
+                dir = dir.dirs.entry(component.to_string()).or_default();
+            }
+
+            dir.files.insert(name, file);
+        }
+
+        root
+    }
+
+    pub fn ls<P: AsRef<Path>>(&self, path: P) -> std::io::Result<&PlainDirectory> {
+        let mut dir = self;
+        for component in Self::components(path.as_ref()) {
+            dir = dir
+                .dirs
+                .get(&component)
+                .ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        }
+
+        Ok(dir)
+    }
+
+    /// Returns the name of the file as stored in the archive.
+    pub fn find_file<P: AsRef<Path>>(&self, path: P) -> Option<&str> {
+        let mut components = Self::components(path.as_ref());
+        let name = components.pop()?;
+        let mut dir = self;
+        for component in components {
+            dir = dir.dirs.get(&component)?;
+        }
+
+        dir.files.get(&name).map(|f| f.as_str())
+    }
+
+    pub fn dirs(&self) -> impl Iterator<Item = &str> {
+        self.dirs.keys().map(|d| d.as_str())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|f| f.as_str())
+    }
+
+    fn components(path: &Path) -> Vec<String> {
+        path.to_string_lossy()
+            .split(|c| c == '/' || c == '\\')
+            .filter(|c| !c.is_empty() && *c != ".")
+            .map(|c| c.to_string())
+            .collect()
+    }
 }
 
 pub struct PlainFs<TArchive: PlainArchive> {
-    archive: RefCell<TArchive>,
+    archive: TArchive,
+    root: PlainDirectory,
 }
 
 impl<TArchive: PlainArchive> PlainFs<TArchive> {
     pub fn new(archive: TArchive) -> Self {
-        Self {
-            archive: RefCell::new(archive),
-        }
+        let root = archive.directory();
+        Self { archive, root }
     }
 }
 
@@ -23,26 +110,31 @@ impl<TArchive: PlainArchive> Store for PlainFs<TArchive> {
     type File = MemoryFile;
 
     fn open_path(&self, path: &Path) -> std::io::Result<Self::File> {
-        let path = path.to_string_lossy().to_string().replace('_', "/");
-        self.archive
-            .borrow_mut()
-            .open(path)
-            .map_err(|_| std::io::Error::from(std::io::ErrorKind::Unsupported))
-    }
-
-    fn entries_path(&self, _: &Path) -> std::io::Result<Entries> {
-        let archive = self.archive.borrow();
-        let list: Vec<Result<Entry, std::io::Error>> = archive
-            .files()
-            .into_iter()
-            .map(|name| {
-                Ok(Entry {
-                    name: OsString::from(name.replace('/', "_")),
-                    kind: EntryKind::File,
-                })
+        let result = match self.root.find_file(path) {
+            Some(name) => self.archive.open(name),
+            None => self.archive.open(path),
+        };
+
+        result.map_err(|_| std::io::Error::from(std::io::ErrorKind::NotFound))
+    }
+
+    fn entries_path(&self, path: &Path) -> std::io::Result<Entries> {
+        let dir = self.root.ls(path)?;
+        let dirs = dir.dirs().map(|name| {
+            Ok(Entry {
+                name: OsString::from(name),
+                kind: EntryKind::Dir,
+            })
+        });
+
+        let files = dir.files().map(|name| {
+            Ok(Entry {
+                name: OsString::from(name),
+                kind: EntryKind::File,
             })
-            .collect();
+        });
 
+        let list: Vec<std::io::Result<Entry>> = dirs.chain(files).collect();
         Ok(Entries::new(list))
     }
 }
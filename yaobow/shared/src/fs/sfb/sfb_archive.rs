@@ -1,22 +1,27 @@
 use std::{
     collections::HashMap,
-    io::{Cursor, SeekFrom},
+    io::{Cursor, Seek, SeekFrom},
     path::Path,
 };
 
 use common::read_ext::ReadExt;
 use radiance::utils::SeekRead;
 
-use crate::fs::{memory_file::MemoryFile, plain_fs::PlainArchive};
+use crate::fs::{
+    memory_file::MemoryFile,
+    plain_fs::{PlainArchive, PlainFileInfo},
+    shared_reader::{ReaderCursor, SharedReader},
+};
 
 pub struct SfbArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     _meta: SfbMeta,
     pub files: HashMap<String, SfbFile>,
 }
 
 impl SfbArchive {
-    pub fn load(mut reader: Box<dyn SeekRead>) -> anyhow::Result<SfbArchive> {
+    pub fn load(source: SharedReader) -> anyhow::Result<SfbArchive> {
+        let mut reader = ReaderCursor::new(source.clone());
         let meta = SfbMeta::read(&mut reader)?;
         let mut files = HashMap::new();
 
@@ -27,7 +32,7 @@ impl SfbArchive {
         }
 
         Ok(Self {
-            reader,
+            reader: source,
             _meta: meta,
             files,
         })
@@ -35,13 +40,13 @@ impl SfbArchive {
 }
 
 impl PlainArchive for SfbArchive {
-    fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile> {
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let mut reader = ReaderCursor::new(self.reader.clone());
         let path = path.as_ref().to_str().unwrap();
 
         if let Some(file) = self.files.get(path) {
-            self.reader
-                .seek(SeekFrom::Start(file.start_position as u64))?;
-            let data = self.reader.read_u8_vec(file.file_size as usize)?;
+            reader.seek(SeekFrom::Start(file.start_position as u64))?;
+            let data = reader.read_u8_vec(file.file_size as usize)?;
 
             Ok(MemoryFile::new(Cursor::new(data)))
         } else {
@@ -52,6 +57,18 @@ impl PlainArchive for SfbArchive {
     fn files(&self) -> Vec<String> {
         self.files.keys().map(|s| s.to_string()).collect()
     }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        self.files
+            .iter()
+            .map(|(name, f)| PlainFileInfo {
+                name: name.clone(),
+                packed_size: f.file_size as u64,
+                size: f.file_size as u64,
+                is_compressed: false,
+            })
+            .collect()
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
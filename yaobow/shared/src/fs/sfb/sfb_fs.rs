@@ -1,4 +1,4 @@
-use crate::fs::{create_reader, plain_fs::PlainFs};
+use crate::fs::{plain_fs::PlainFs, shared_reader::create_shared_reader};
 use std::path::Path;
 
 use super::sfb_archive::SfbArchive;
@@ -7,7 +7,7 @@ pub type SfbFs = PlainFs<SfbArchive>;
 
 impl SfbFs {
     pub fn create<P: AsRef<Path>>(sfb_path: P) -> anyhow::Result<SfbFs> {
-        let reader = create_reader(sfb_path)?;
+        let reader = create_shared_reader(sfb_path)?;
         let sfb_archive = SfbArchive::load(reader)?;
         Ok(Self::new(sfb_archive))
     }
@@ -0,0 +1,135 @@
+use std::{
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    sync::Arc,
+};
+
+/// Positional reads over an archive. Implementations don't keep a cursor, so
+/// one source can be shared by all the files opened from the archive, across
+/// threads.
+pub trait ReadAt: Send + Sync {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize>;
+    fn size(&self) -> u64;
+
+    fn read_exact_at(&self, mut offset: u64, mut buf: &mut [u8]) -> std::io::Result<()> {
+        while !buf.is_empty() {
+            let read = self.read_at(offset, buf)?;
+            if read == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+            }
+
+            offset += read as u64;
+            buf = &mut buf[read..];
+        }
+
+        Ok(())
+    }
+}
+
+pub type SharedReader = Arc<dyn ReadAt>;
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        Ok(read_slice_at(self, offset, buf))
+    }
+
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+#[cfg(any(windows, linux, macos, android))]
+impl ReadAt for memmap::Mmap {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        Ok(read_slice_at(self, offset, buf))
+    }
+
+    fn size(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+/// Mmap isn't available on vita, so the file handle is shared behind a lock.
+#[cfg(vita)]
+pub struct LockedFile {
+    file: std::sync::Mutex<std::fs::File>,
+    size: u64,
+}
+
+#[cfg(vita)]
+impl ReadAt for LockedFile {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(offset))?;
+        file.read(buf)
+    }
+
+    fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+#[cfg(vita)]
+pub fn create_shared_reader<P: AsRef<Path>>(path: P) -> anyhow::Result<SharedReader> {
+    let file = std::fs::File::open(path.as_ref())?;
+    let size = file.metadata()?.len();
+    Ok(Arc::new(LockedFile {
+        file: std::sync::Mutex::new(file),
+        size,
+    }))
+}
+
+#[cfg(any(windows, linux, macos, android))]
+pub fn create_shared_reader<P: AsRef<Path>>(path: P) -> anyhow::Result<SharedReader> {
+    let file = std::fs::File::open(path.as_ref())?;
+    let mem = unsafe { memmap::MmapOptions::new().map(&file)? };
+    Ok(Arc::new(mem))
+}
+
+/// A cursor over a shared source. Each thread or opened file gets its own
+/// cursor, so reading never needs to coordinate with other users.
+pub struct ReaderCursor {
+    source: SharedReader,
+    position: u64,
+}
+
+impl ReaderCursor {
+    pub fn new(source: SharedReader) -> Self {
+        Self {
+            source,
+            position: 0,
+        }
+    }
+}
+
+impl Read for ReaderCursor {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.source.read_at(self.position, buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for ReaderCursor {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => offset as i64,
+            SeekFrom::End(offset) => self.source.size() as i64 + offset,
+            SeekFrom::Current(offset) => self.position as i64 + offset,
+        };
+
+        if target < 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput));
+        }
+
+        self.position = target as u64;
+        Ok(self.position)
+    }
+}
+
+fn read_slice_at(data: &[u8], offset: u64, buf: &mut [u8]) -> usize {
+    let start = offset.min(data.len() as u64) as usize;
+    let size = buf.len().min(data.len() - start);
+    buf[..size].copy_from_slice(&data[start..start + size]);
+    size
+}
@@ -0,0 +1,211 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use mini_fs::UserFile;
+use miniz_oxide::{
+    inflate::stream::{inflate, InflateState},
+    DataFormat, MZError, MZFlush, MZStatus,
+};
+
+use crate::fs::{memory_file::MemoryFile, shared_reader::SharedReader};
+
+/// Entries smaller than this are read into memory at once, since parsers
+/// usually issue lots of tiny reads on them.
+pub const STREAMING_THRESHOLD: u64 = 1024 * 1024;
+
+const INFLATE_INPUT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A file returned by the archives. Small entries are fully loaded while the
+/// large ones are read from the package on demand.
+pub enum PackedFile {
+    Memory(MemoryFile),
+    Stream(StreamingFile),
+    Inflate(InflateFile),
+}
+
+impl UserFile for PackedFile {}
+
+impl Read for PackedFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            PackedFile::Memory(f) => f.read(buf),
+            PackedFile::Stream(f) => f.read(buf),
+            PackedFile::Inflate(f) => f.read(buf),
+        }
+    }
+}
+
+impl Seek for PackedFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            PackedFile::Memory(f) => f.seek(pos),
+            PackedFile::Stream(f) => f.seek(pos),
+            PackedFile::Inflate(f) => f.seek(pos),
+        }
+    }
+}
+
+/// A read-only window over `[start, start + len)` of a shared package reader.
+pub struct StreamingFile {
+    reader: SharedReader,
+    start: u64,
+    len: u64,
+    position: u64,
+}
+
+impl StreamingFile {
+    pub fn new(reader: SharedReader, start: u64, len: u64) -> Self {
+        Self {
+            reader,
+            start,
+            len,
+            position: 0,
+        }
+    }
+}
+
+impl UserFile for StreamingFile {}
+
+impl Read for StreamingFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.position);
+        let size = (buf.len() as u64).min(remaining) as usize;
+        if size == 0 {
+            return Ok(0);
+        }
+
+        let read = self
+            .reader
+            .read_at(self.start + self.position, &mut buf[..size])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for StreamingFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = resolve_seek(pos, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+/// Inflates a zlib stream while it is being read. Seeking forward decodes and
+/// drops the skipped data, seeking backward restarts from the beginning.
+pub struct InflateFile {
+    source: StreamingFile,
+    state: Box<InflateState>,
+    input: Vec<u8>,
+    input_start: usize,
+    input_end: usize,
+    position: u64,
+    size: u64,
+    finished: bool,
+}
+
+impl InflateFile {
+    pub fn new(source: StreamingFile, size: u64) -> Self {
+        Self {
+            source,
+            state: InflateState::new_boxed(DataFormat::Zlib),
+            input: vec![0; INFLATE_INPUT_BUFFER_SIZE],
+            input_start: 0,
+            input_end: 0,
+            position: 0,
+            size,
+            finished: false,
+        }
+    }
+
+    fn restart(&mut self) -> std::io::Result<()> {
+        self.source.seek(SeekFrom::Start(0))?;
+        self.state = InflateState::new_boxed(DataFormat::Zlib);
+        self.input_start = 0;
+        self.input_end = 0;
+        self.position = 0;
+        self.finished = false;
+        Ok(())
+    }
+
+    fn skip(&mut self, mut count: u64) -> std::io::Result<()> {
+        let mut buf = vec![0; INFLATE_INPUT_BUFFER_SIZE];
+        while count > 0 {
+            let size = (buf.len() as u64).min(count) as usize;
+            let read = self.read(&mut buf[..size])?;
+            if read == 0 {
+                break;
+            }
+
+            count -= read as u64;
+        }
+
+        Ok(())
+    }
+}
+
+impl UserFile for InflateFile {}
+
+impl Read for InflateFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while !self.finished {
+            if self.input_start == self.input_end {
+                self.input_start = 0;
+                self.input_end = self.source.read(&mut self.input)?;
+            }
+
+            let input = &self.input[self.input_start..self.input_end];
+            let result = inflate(&mut self.state, input, buf, MZFlush::None);
+            self.input_start += result.bytes_consumed;
+
+            match result.status {
+                Ok(MZStatus::StreamEnd) => self.finished = true,
+                Ok(_) => {}
+                Err(MZError::Buf) if input.is_empty() => {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))
+                }
+                Err(MZError::Buf) => {}
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Unable to inflate: {:?}", e),
+                    ))
+                }
+            }
+
+            if result.bytes_written > 0 {
+                self.position += result.bytes_written as u64;
+                return Ok(result.bytes_written);
+            }
+        }
+
+        Ok(0)
+    }
+}
+
+impl Seek for InflateFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = resolve_seek(pos, self.position, self.size)?;
+        if target < self.position {
+            self.restart()?;
+        }
+
+        self.skip(target - self.position)?;
+        Ok(self.position)
+    }
+}
+
+fn resolve_seek(pos: SeekFrom, current: u64, len: u64) -> std::io::Result<u64> {
+    let target = match pos {
+        SeekFrom::Start(offset) => offset as i64,
+        SeekFrom::End(offset) => len as i64 + offset,
+        SeekFrom::Current(offset) => current as i64 + offset,
+    };
+
+    if target < 0 {
+        Err(std::io::Error::from(std::io::ErrorKind::InvalidInput))
+    } else {
+        Ok(target as u64)
+    }
+}
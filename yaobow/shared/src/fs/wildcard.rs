@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use mini_fs::{EntryKind, MiniFs, StoreExt};
+
+/// Lists the files matching the pattern. Patterns without wildcards are
+/// returned as they are so that a missing file is reported by the caller.
+pub fn expand(vfs: &MiniFs, pattern: &str) -> Vec<PathBuf> {
+    let pattern = pattern.replace('\\', "/");
+    let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    if !components.iter().any(|c| c.contains(['*', '?'])) {
+        return vec![PathBuf::from("/").join(pattern.trim_start_matches('/'))];
+    }
+
+    let mut paths = vec![];
+    walk(vfs, &PathBuf::from("/"), &components, &mut paths);
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn walk(vfs: &MiniFs, dir: &Path, components: &[&str], paths: &mut Vec<PathBuf>) {
+    let (component, rest) = match components.split_first() {
+        Some(c) => c,
+        None => return,
+    };
+
+    let entries: Vec<(String, EntryKind)> = match vfs.entries(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| (e.name.to_string_lossy().to_string(), e.kind))
+            .collect(),
+        Err(_) => return,
+    };
+
+    if *component == "**" {
+        // A trailing `**` matches every file below `dir`
+        walk(vfs, dir, rest, paths);
+        for (name, kind) in &entries {
+            match kind {
+                EntryKind::Dir => walk(vfs, &dir.join(name), components, paths),
+                EntryKind::File if rest.is_empty() => paths.push(dir.join(name)),
+                _ => {}
+            }
+        }
+
+        return;
+    }
+
+    for (name, kind) in entries {
+        if !wildcard_match(component, &name) {
+            continue;
+        }
+
+        match kind {
+            EntryKind::File if rest.is_empty() => paths.push(dir.join(name)),
+            EntryKind::Dir if !rest.is_empty() => walk(vfs, &dir.join(name), rest, paths),
+            _ => {}
+        }
+    }
+}
+
+/// Case insensitive match supporting `*` and `?`.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((bp, bn)) = backtrack {
+            p = bp + 1;
+            n = bn + 1;
+            backtrack = Some((bp, bn + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
@@ -1,5 +1,5 @@
 use std::{
-    io::{Cursor, SeekFrom},
+    io::{Cursor, Seek, SeekFrom},
     path::Path,
 };
 
@@ -8,7 +8,12 @@ use cipher::{generic_array::GenericArray, BlockDecrypt, KeyInit};
 use common::read_ext::ReadExt;
 use radiance::utils::SeekRead;
 
-use crate::fs::{memory_file::MemoryFile, plain_fs::PlainArchive};
+use crate::fs::{
+    memory_file::MemoryFile,
+    path_index::PathIndex,
+    plain_fs::{PlainArchive, PlainFileInfo},
+    shared_reader::{ReaderCursor, SharedReader},
+};
 
 use super::{blowfish, tea::Tea, xtea::XTea};
 
@@ -19,16 +24,24 @@ pub enum ZpkReadError {
 
     #[error("Unsupported zpk compression type: {0}")]
     UnsupportedCompressionType(u8),
+
+    #[error("Invalid zpk entry chunk size: {0}")]
+    InvalidChunkSize(u16),
+
+    #[error("Invalid zpk key length: {0}")]
+    InvalidKeyLength(usize),
 }
 
 pub struct ZpkArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     header: ZpkHeader,
     entries: Vec<ZpkEntry>,
+    index: PathIndex,
 }
 
 impl ZpkArchive {
-    pub fn load(mut reader: Box<dyn SeekRead>) -> anyhow::Result<ZpkArchive> {
+    pub fn load(source: SharedReader) -> anyhow::Result<ZpkArchive> {
+        let mut reader = ReaderCursor::new(source.clone());
         let header = ZpkHeader::read(&mut reader)?;
 
         let entry_start = reader.read_u32_le()?;
@@ -55,10 +68,13 @@ impl ZpkArchive {
             )?);
         }
 
+        let index = PathIndex::new(entries.iter().map(|e| e.name.as_str()));
+
         Ok(Self {
-            reader,
+            reader: source,
             header,
             entries,
+            index,
         })
     }
 
@@ -81,8 +97,6 @@ impl ZpkArchive {
     fn decompress_data(&self, file: &ZpkEntry, data: &[u8]) -> anyhow::Result<Vec<u8>> {
         if file.compression_type == 0 {
             Ok(data.to_vec())
-        } else if file.compression_type == 1 {
-            unimplemented!("compression type 1 not implemented for zpk");
         } else if file.compression_type == 2 {
             let mut output = vec![];
             lzma_rs::lzma_decompress(&mut Cursor::new(&data[4..]), &mut output)?;
@@ -96,23 +110,19 @@ impl ZpkArchive {
 }
 
 impl PlainArchive for ZpkArchive {
-    fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile> {
-        let path = path.as_ref().to_str().unwrap();
-
-        if let Some(file) = self
-            .entries
-            .iter()
-            .find(|item| item.name == path.to_string())
-        {
-            self.reader.seek(SeekFrom::Start(file.offset as u64))?;
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let mut reader = ReaderCursor::new(self.reader.clone());
+        if let Some(index) = self.index.get(path) {
+            let file = &self.entries[index];
+            reader.seek(SeekFrom::Start(file.offset as u64))?;
 
             let mut content = vec![];
 
             while content.len() < file.original_size as usize {
-                let data_size = self.reader.read_u32::<BigEndian>()?;
-                let chunk_size = self.reader.read_u32::<LittleEndian>()?;
+                let data_size = reader.read_u32::<BigEndian>()?;
+                let chunk_size = reader.read_u32::<LittleEndian>()?;
 
-                let data = self.reader.read_u8_vec(chunk_size as usize)?;
+                let data = reader.read_u8_vec(chunk_size as usize)?;
                 let data = self.decrypt_data(file, &data)?;
                 let data = self.decompress_data(file, &data)?;
 
@@ -128,6 +138,18 @@ impl PlainArchive for ZpkArchive {
     fn files(&self) -> Vec<String> {
         self.entries.iter().map(|s| s.name.clone()).collect()
     }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        self.entries
+            .iter()
+            .map(|e| PlainFileInfo {
+                name: e.name.clone(),
+                packed_size: e.packed_size as u64,
+                size: e.original_size as u64,
+                is_compressed: e.compression_type != 0,
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -166,10 +188,15 @@ impl ZpkEntry {
         chunk_size: u16,
         key: Vec<u8>,
     ) -> anyhow::Result<Vec<Self>> {
+        if chunk_size % 8 != 0 {
+            Err(ZpkReadError::InvalidChunkSize(chunk_size))?;
+        }
+
         let mut data = reader.read_u8_vec(chunk_size as usize)?;
         let mut output = vec![0; data.len()];
 
-        let bf = blowfish::Blowfish::<BigEndian>::new_from_slice(&key).unwrap();
+        let bf = blowfish::Blowfish::<BigEndian>::new_from_slice(&key)
+            .map_err(|_| ZpkReadError::InvalidKeyLength(key.len()))?;
 
         for i in 0..(chunk_size as usize + 7) / 8 {
             let p = &mut data[i * 8..(i + 1) * 8];
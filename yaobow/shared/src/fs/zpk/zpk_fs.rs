@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use crate::fs::{create_reader, plain_fs::PlainFs};
+use crate::fs::{plain_fs::PlainFs, shared_reader::create_shared_reader};
 
 use super::zpk_archive::ZpkArchive;
 
@@ -8,7 +8,7 @@ pub type ZpkFs = PlainFs<ZpkArchive>;
 
 impl ZpkFs {
     pub fn create<P: AsRef<Path>>(zpk_path: P) -> anyhow::Result<ZpkFs> {
-        let reader = create_reader(zpk_path)?;
+        let reader = create_shared_reader(zpk_path)?;
         let sfb_archive = ZpkArchive::load(reader)?;
         Ok(Self::new(sfb_archive))
     }
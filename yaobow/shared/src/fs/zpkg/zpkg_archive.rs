@@ -1,22 +1,36 @@
-use std::{io::Cursor, path::Path};
+use std::{
+    io::{Cursor, Seek},
+    path::Path,
+};
 
 use common::read_ext::ReadExt;
-use radiance::utils::SeekRead;
 
-use crate::fs::{memory_file::MemoryFile, plain_fs::PlainArchive};
+use crate::fs::{
+    memory_file::MemoryFile,
+    path_index::PathIndex,
+    plain_fs::{PlainArchive, PlainFileInfo},
+    shared_reader::{ReaderCursor, SharedReader},
+};
 
 use super::tr_cache::TrCacheFile;
 
 pub struct ZpkgArchive {
-    reader: Box<dyn SeekRead>,
+    reader: SharedReader,
     tr_cache: TrCacheFile,
+    index: PathIndex,
 }
 
 impl ZpkgArchive {
-    pub fn load(reader: Box<dyn SeekRead>, cache_content: &[u8]) -> anyhow::Result<ZpkgArchive> {
+    pub fn load(reader: SharedReader, cache_content: &[u8]) -> anyhow::Result<ZpkgArchive> {
         let tr_cache = TrCacheFile::read(cache_content, "Gef9d(y2f^q0e9%fni2$sd8$0u")?;
 
-        Ok(Self { reader, tr_cache })
+        let index = PathIndex::new(tr_cache.entries.iter().map(|e| e.filename.as_str()));
+
+        Ok(Self {
+            reader,
+            tr_cache,
+            index,
+        })
     }
 
     fn decrypt_data(data: &[u8], cipher_id: u32, key1: &[u8], key2: &[u8]) -> Vec<u8> {
@@ -41,18 +55,13 @@ impl ZpkgArchive {
 }
 
 impl PlainArchive for ZpkgArchive {
-    fn open<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<MemoryFile> {
-        let path = path.as_ref().to_str().unwrap();
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let mut reader = ReaderCursor::new(self.reader.clone());
+        if let Some(index) = self.index.get(path) {
+            let file = &self.tr_cache.entries[index];
+            reader.seek(std::io::SeekFrom::Start(file.offset))?;
 
-        if let Some(file) = self
-            .tr_cache
-            .entries
-            .iter()
-            .find(|item| item.filename == path.to_string())
-        {
-            self.reader.seek(std::io::SeekFrom::Start(file.offset))?;
-
-            let data = self.reader.read_u8_vec(file.packed_size as usize)?;
+            let data = reader.read_u8_vec(file.packed_size as usize)?;
             let data =
                 Self::decrypt_data(&data, file.cipher, &file.file_key, &self.tr_cache.zpkg_key);
 
@@ -79,4 +88,17 @@ impl PlainArchive for ZpkgArchive {
             .map(|s| s.filename.clone())
             .collect()
     }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        self.tr_cache
+            .entries
+            .iter()
+            .map(|e| PlainFileInfo {
+                name: e.filename.clone(),
+                packed_size: e.packed_size,
+                size: e.unpacked_size,
+                is_compressed: e.packed_size != e.unpacked_size,
+            })
+            .collect()
+    }
 }
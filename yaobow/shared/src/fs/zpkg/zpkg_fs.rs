@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use crate::fs::{create_reader, plain_fs::PlainFs};
+use crate::fs::{plain_fs::PlainFs, shared_reader::create_shared_reader};
 
 use super::zpkg_archive::ZpkgArchive;
 
@@ -11,7 +11,7 @@ impl ZpkgFs {
         let cache_path = map_cache_path(zpkg_path.as_ref())?;
         let cache_content = std::fs::read(cache_path)?;
 
-        let reader = create_reader(zpkg_path)?;
+        let reader = create_shared_reader(zpkg_path)?;
         let archive = ZpkgArchive::load(reader, &cache_content)?;
         Ok(Self::new(archive))
     }
@@ -26,7 +26,7 @@ pub enum ZpkgReadError {
     UnknownFolderStructure(PathBuf),
 }
 
-fn map_cache_path(zpkg_path: &Path) -> anyhow::Result<PathBuf> {
+pub fn map_cache_path(zpkg_path: &Path) -> anyhow::Result<PathBuf> {
     let filename = zpkg_path
         .file_name()
         .ok_or(ZpkgReadError::UnknownZpkgFileName(zpkg_path.to_owned()))?
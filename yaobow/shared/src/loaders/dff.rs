@@ -192,7 +192,7 @@ fn load_clump(
 fn check_frame_name(frame: &Frame) -> bool {
     for e in frame.extensions() {
         if let Extension::UserDataPlugin(plugin) = e {
-            if let Some(prt) = plugin.data().get("prt") {
+            if let Some(prt) = plugin.get("prt") {
                 if prt.len() > 0 {
                     if let Some(prt) = prt[0].get_string() {
                         if prt.starts_with('[') {
@@ -321,11 +321,12 @@ pub(crate) fn create_geometry_internal(
         let group = material_to_indices.entry(t.material).or_insert_with(|| {
             let material = &materials[t.material as usize];
             let md = if let Some(texture) = material.texture.as_ref() {
-                let data = texture_resolver.resolve_texture(vfs, path.as_ref(), &texture.name);
+                let name = texture.name.as_str().unwrap_or_default();
+                let data = texture_resolver.resolve_texture(vfs, path.as_ref(), &name);
                 if data.is_none() {
-                    log::warn!("Failed to resolve texture {} for {:?}", texture.name, path);
+                    log::warn!("Failed to resolve texture {} for {:?}", name, path);
                 }
-                radiance::rendering::SimpleMaterialDef::create2(&texture.name, data, true)
+                radiance::rendering::SimpleMaterialDef::create2(&name, data, true)
             } else {
                 log::debug!("no texture info for material {:?}", path);
                 radiance::rendering::SimpleMaterialDef::create2("missing", None, true)
@@ -10,6 +10,7 @@ use mini_fs::MiniFs;
 pub mod anm;
 pub mod bsp;
 pub mod dff;
+pub mod nif;
 pub mod smp;
 
 pub trait TextureResolver: Send + Sync {
@@ -117,3 +118,31 @@ impl TextureResolver for Swd5TextureResolver {
         Some(data)
     }
 }
+
+/// Texture paths in Gujian models are relative to the game root, but the
+/// textures are usually found next to the model.
+pub struct GujianTextureResolver;
+impl TextureResolver for GujianTextureResolver {
+    fn resolve_texture(
+        &self,
+        vfs: &MiniFs,
+        model_path: &Path,
+        texture_name: &str,
+    ) -> Option<Vec<u8>> {
+        let texture_name = texture_name.replace('\\', "/");
+        let file_name = Path::new(&texture_name).file_name()?;
+        let tex_paths = [
+            model_path.parent()?.join(file_name),
+            PathBuf::from("/").join(&texture_name),
+        ];
+
+        let mut data = vec![];
+        let _ = vfs
+            .try_open_files(&tex_paths)
+            .ok()?
+            .read_to_end(&mut data)
+            .ok()?;
+
+        Some(data)
+    }
+}
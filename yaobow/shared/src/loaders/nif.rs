@@ -0,0 +1,502 @@
+use std::{collections::HashMap, io::Cursor, path::Path, rc::Rc};
+
+use common::store_ext::StoreExt2;
+use crosscom::ComRc;
+use fileformats::{
+    binrw::BinRead,
+    nif::{
+        blocks::{
+            MeshPrimitiveType, NiAVObject, NiMesh, NiNode, NiSkinningMeshModifier, NiSourceTexture,
+            NiTexturingProperty, NiTransformController, NiTransformData, NiTransformInterpolator,
+        },
+        mesh::NiSubMeshData,
+        Matrix33, NifModel, Vector3,
+    },
+};
+use mini_fs::MiniFs;
+use radiance::{
+    comdef::{
+        IArmatureComponent, IComponent, IEntity, IHAnimBoneComponent, ISkinnedMeshComponent,
+        IStaticMeshComponent,
+    },
+    components::mesh::{
+        skinned_mesh::{AnimKeyFrame, ArmatureComponent, HAnimBoneComponent, SkinnedMeshComponent},
+        Geometry, StaticMeshComponent, TexCoord,
+    },
+    math::{Mat44, Quaternion, Vec3},
+    rendering::{ComponentFactory, MaterialDef, SimpleMaterialDef},
+    scene::CoreEntity,
+};
+
+use super::TextureResolver;
+
+pub struct NifLoaderConfig<'a> {
+    pub texture_resolver: &'a dyn TextureResolver,
+}
+
+/// Creates an entity for each NiNode and NiMesh reachable from the roots.
+/// Skinned meshes get an armature, and the keyframes of their bones are
+/// played in a loop when the model has transform controllers.
+pub fn create_entity_from_nif_model<P: AsRef<Path>>(
+    component_factory: &Rc<dyn ComponentFactory>,
+    vfs: &MiniFs,
+    path: P,
+    name: String,
+    visible: bool,
+    config: &NifLoaderConfig,
+) -> anyhow::Result<ComRc<IEntity>> {
+    let data = vfs.read_to_end(&path)?;
+    let nif = NifModel::read(&mut Cursor::new(data))?;
+
+    let entity = CoreEntity::create(name, visible);
+    let mut loader = NifLoader {
+        nif: &nif,
+        component_factory,
+        vfs,
+        path: path.as_ref(),
+        config,
+        bones: HashMap::new(),
+    };
+
+    for root in &nif.footer.roots {
+        if let Some(child) = loader.load_block(*root, &entity.name(), 0) {
+            entity.attach(child);
+        }
+    }
+
+    Ok(entity)
+}
+
+// Guards against reference cycles in corrupted files
+const MAX_DEPTH: usize = 64;
+
+struct NifLoader<'a> {
+    nif: &'a NifModel,
+    component_factory: &'a Rc<dyn ComponentFactory>,
+    vfs: &'a MiniFs,
+    path: &'a Path,
+    config: &'a NifLoaderConfig<'a>,
+    bones: HashMap<i32, ComRc<IEntity>>,
+}
+
+impl<'a> NifLoader<'a> {
+    fn load_block(&mut self, index: i32, parent: &str, depth: usize) -> Option<ComRc<IEntity>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        if let Some(node) = self.nif.block::<NiNode>(index) {
+            let entity = self.create_entity(&node.av_object, parent, index);
+            for child in &node.children {
+                if let Some(child) = self.load_block(*child as i32, &entity.name(), depth + 1) {
+                    entity.attach(child);
+                }
+            }
+
+            Some(entity)
+        } else if let Some(mesh) = self.nif.block::<NiMesh>(index) {
+            let entity = self.create_entity(&mesh.render_object.av_object, parent, index);
+            self.load_mesh(entity.clone(), mesh);
+            Some(entity)
+        } else {
+            None
+        }
+    }
+
+    fn create_entity(&self, av_object: &NiAVObject, parent: &str, index: i32) -> ComRc<IEntity> {
+        let name = self
+            .nif
+            .string(av_object.object_net.name as i32)
+            .unwrap_or(format!("{}_{}", parent, index));
+        let entity = CoreEntity::create(name, true);
+        entity
+            .transform()
+            .as_ref()
+            .borrow_mut()
+            .set_matrix(create_matrix(
+                &av_object.rotation,
+                &av_object.translation,
+                av_object.scale,
+            ));
+
+        entity
+    }
+
+    fn load_mesh(&mut self, entity: ComRc<IEntity>, mesh: &NiMesh) {
+        if mesh.primitive_type.0 != MeshPrimitiveType::MESH_PRIMITIVE_TRIANGLES.0 {
+            log::warn!("Unsupported nif primitive type {}", mesh.primitive_type.0);
+            return;
+        }
+
+        let sub_meshes: Vec<NiSubMeshData> = self
+            .nif
+            .sub_meshes(mesh)
+            .into_iter()
+            .filter(|data| data.is_valid())
+            .collect();
+        let texture_name = self.texture_name(&mesh.render_object.av_object);
+
+        let skin = mesh
+            .modifiers
+            .iter()
+            .find_map(|m| self.nif.block::<NiSkinningMeshModifier>(*m));
+        let armature = skin.and_then(|skin| self.create_armature(entity.clone(), skin));
+
+        let mut static_geometries = vec![];
+        for data in sub_meshes {
+            let vertex_count = data.positions.len();
+            let skinned =
+                data.bone_indices.len() == vertex_count && data.bone_weights.len() == vertex_count;
+            let geometry = self.create_geometry(&data, texture_name.as_deref());
+
+            match &armature {
+                Some(armature) if skinned => {
+                    let child = CoreEntity::create(format!("{}_geom", entity.name()), true);
+                    let bone_ids = data
+                        .bone_indices
+                        .iter()
+                        .map(|id| id.map(|i| i as usize))
+                        .collect();
+                    let mesh_component = SkinnedMeshComponent::new(
+                        child.clone(),
+                        self.component_factory.clone(),
+                        geometry,
+                        armature.clone(),
+                        bone_ids,
+                        data.bone_weights,
+                    );
+
+                    child.add_component(
+                        ISkinnedMeshComponent::uuid(),
+                        ComRc::from_object(mesh_component),
+                    );
+                    entity.attach(child);
+                }
+                _ => static_geometries.push(geometry),
+            }
+        }
+
+        if !static_geometries.is_empty() {
+            let mesh_component = StaticMeshComponent::new(
+                entity.clone(),
+                static_geometries,
+                self.component_factory.clone(),
+            );
+            entity.add_component(
+                IStaticMeshComponent::uuid(),
+                ComRc::from_object(mesh_component),
+            );
+        }
+    }
+
+    fn create_geometry(&self, data: &NiSubMeshData, texture_name: Option<&str>) -> Geometry {
+        let vertices: Vec<Vec3> = data
+            .positions
+            .iter()
+            .map(|p| Vec3::new(p[0], p[1], p[2]))
+            .collect();
+        let texcoords: Vec<TexCoord> = if data.texcoords.len() == vertices.len() {
+            data.texcoords
+                .iter()
+                .map(|t| TexCoord::new(t[0], t[1]))
+                .collect()
+        } else {
+            vertices.iter().map(|_| TexCoord::new(0., 0.)).collect()
+        };
+
+        Geometry::new(
+            &vertices,
+            None,
+            &[texcoords],
+            data.indices.clone(),
+            self.create_material(texture_name),
+            1,
+        )
+    }
+
+    fn create_material(&self, texture_name: Option<&str>) -> MaterialDef {
+        match texture_name {
+            Some(name) => {
+                let data = self
+                    .config
+                    .texture_resolver
+                    .resolve_texture(self.vfs, self.path, name);
+                if data.is_none() {
+                    log::warn!("Failed to resolve texture {} for {:?}", name, self.path);
+                }
+
+                SimpleMaterialDef::create2(name, data, true)
+            }
+            None => {
+                log::debug!("no texture info for material {:?}", self.path);
+                SimpleMaterialDef::create2("missing", None, true)
+            }
+        }
+    }
+
+    /// The file name of the base texture in the texturing property.
+    fn texture_name(&self, av_object: &NiAVObject) -> Option<String> {
+        let property = av_object
+            .properties
+            .iter()
+            .find_map(|p| self.nif.block::<NiTexturingProperty>(*p as i32))?;
+        let source = property.base_texture.texture.as_ref()?.source;
+        let texture = self.nif.block::<NiSourceTexture>(source)?;
+        self.nif.string(texture.file_name)
+    }
+
+    fn create_armature(
+        &mut self,
+        entity: ComRc<IEntity>,
+        skin: &NiSkinningMeshModifier,
+    ) -> Option<ComRc<IArmatureComponent>> {
+        let root_bone = self.load_bone(skin.root_node, 0)?;
+        let bones: Vec<ComRc<IEntity>> = skin
+            .bones
+            .iter()
+            .map(|b| self.load_bone(*b, 0))
+            .collect::<Option<_>>()?;
+
+        for (bone, transform) in bones.iter().zip(&skin.bone_transforms) {
+            bone.get_component(IHAnimBoneComponent::uuid())
+                .unwrap()
+                .query_interface::<IHAnimBoneComponent>()
+                .unwrap()
+                .set_bond_pose(create_matrix(
+                    &transform.rotation,
+                    &transform.translation,
+                    transform.scale,
+                ));
+        }
+
+        let armature = ComRc::<IArmatureComponent>::from_object(ArmatureComponent::new(
+            entity.clone(),
+            root_bone,
+            bones,
+        ));
+        entity.add_component(
+            IArmatureComponent::uuid(),
+            armature.clone().query_interface::<IComponent>().unwrap(),
+        );
+
+        let keyframes: Vec<Option<Vec<AnimKeyFrame>>> =
+            skin.bones.iter().map(|b| self.bone_keyframes(*b)).collect();
+        if keyframes.iter().any(|k| k.is_some()) {
+            let keyframes = keyframes
+                .into_iter()
+                .zip(&skin.bones)
+                .map(|(k, b)| k.unwrap_or_else(|| self.rest_keyframes(*b)))
+                .collect();
+            armature.set_animation(keyframes, vec![]);
+            armature.set_looping(true);
+        }
+
+        Some(armature)
+    }
+
+    /// Creates the bone entities of the node tree. Bones are shared by all
+    /// the skinned meshes of the model.
+    fn load_bone(&mut self, index: i32, depth: usize) -> Option<ComRc<IEntity>> {
+        if let Some(bone) = self.bones.get(&index) {
+            return Some(bone.clone());
+        }
+
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let node = self.nif.block::<NiNode>(index)?;
+        let name = self
+            .nif
+            .string(node.av_object.object_net.name as i32)
+            .unwrap_or_default();
+        let bone = CoreEntity::create(format!("{}_bone", name), false);
+        let bone_component =
+            ComRc::<IComponent>::from_object(HAnimBoneComponent::new(bone.clone(), index as u32));
+        bone.add_component(IHAnimBoneComponent::uuid(), bone_component);
+        bone.transform()
+            .as_ref()
+            .borrow_mut()
+            .set_matrix(create_matrix(
+                &node.av_object.rotation,
+                &node.av_object.translation,
+                node.av_object.scale,
+            ));
+        self.bones.insert(index, bone.clone());
+
+        for child in &node.children {
+            let child = *child as i32;
+            if self.bones.contains_key(&child) {
+                continue;
+            }
+
+            if let Some(child) = self.load_bone(child, depth + 1) {
+                bone.attach(child);
+            }
+        }
+
+        Some(bone)
+    }
+
+    /// Samples the transform controller of the node at every key time.
+    fn bone_keyframes(&self, index: i32) -> Option<Vec<AnimKeyFrame>> {
+        let node = self.nif.block::<NiNode>(index)?;
+        let interpolator = self.transform_interpolator(&node.av_object)?;
+        let data = self.nif.block::<NiTransformData>(interpolator.data)?;
+
+        let (rest_rotation, rest_position) = self.rest_pose(index);
+        let t = &interpolator.transform;
+        let rest_rotation = if t.rotation.w != -f32::MAX {
+            Quaternion::new(t.rotation.x, t.rotation.y, t.rotation.z, t.rotation.w)
+        } else {
+            rest_rotation
+        };
+        let rest_position = if t.translation.x != -f32::MAX {
+            Vec3::new(t.translation.x, t.translation.y, t.translation.z)
+        } else {
+            rest_position
+        };
+
+        let rotations: Vec<(f32, Quaternion)> = data
+            .quaternion_keys
+            .iter()
+            .map(|k| {
+                let q = &k.value;
+                (k.time, Quaternion::new(q.x, q.y, q.z, q.w))
+            })
+            .collect();
+        let positions: Vec<(f32, Vec3)> = data
+            .translations
+            .keys
+            .iter()
+            .map(|k| (k.time, Vec3::new(k.value.x, k.value.y, k.value.z)))
+            .collect();
+
+        let mut times: Vec<f32> = rotations
+            .iter()
+            .map(|r| r.0)
+            .chain(positions.iter().map(|p| p.0))
+            .collect();
+        times.sort_by(|a, b| a.total_cmp(b));
+        times.dedup();
+        if times.is_empty() {
+            times.push(0.);
+        }
+
+        Some(
+            times
+                .into_iter()
+                .map(|timestamp| AnimKeyFrame {
+                    rotation: sample(&rotations, timestamp, Quaternion::slerp)
+                        .unwrap_or(rest_rotation),
+                    position: sample(&positions, timestamp, Vec3::lerp).unwrap_or(rest_position),
+                    timestamp,
+                })
+                .collect(),
+        )
+    }
+
+    fn rest_keyframes(&self, index: i32) -> Vec<AnimKeyFrame> {
+        let (rotation, position) = self.rest_pose(index);
+        vec![AnimKeyFrame {
+            rotation,
+            position,
+            timestamp: 0.,
+        }]
+    }
+
+    fn rest_pose(&self, index: i32) -> (Quaternion, Vec3) {
+        match self.nif.block::<NiNode>(index) {
+            Some(node) => {
+                let t = &node.av_object.translation;
+                (
+                    matrix_to_quaternion(&node.av_object.rotation),
+                    Vec3::new(t.x, t.y, t.z),
+                )
+            }
+            None => (Quaternion::new(0., 0., 0., 1.), Vec3::new(0., 0., 0.)),
+        }
+    }
+
+    /// Only transform controllers are parsed, so the controller chain isn't
+    /// followed past other controller types.
+    fn transform_interpolator(&self, av_object: &NiAVObject) -> Option<&NiTransformInterpolator> {
+        let controller = self
+            .nif
+            .block::<NiTransformController>(av_object.object_net.controller as i32)?;
+        self.nif.block(controller.interpolator)
+    }
+}
+
+/// Linearly interpolates between the keys around `time`.
+fn sample<T: Copy>(keys: &[(f32, T)], time: f32, lerp: fn(&T, &T, f32) -> T) -> Option<T> {
+    let next = keys.iter().position(|k| k.0 >= time);
+    match next {
+        None => keys.last().map(|k| k.1),
+        Some(0) => keys.first().map(|k| k.1),
+        Some(i) => {
+            let (t0, v0) = &keys[i - 1];
+            let (t1, v1) = &keys[i];
+            let pct = if t1 > t0 { (time - t0) / (t1 - t0) } else { 0. };
+            Some(lerp(v0, v1, pct))
+        }
+    }
+}
+
+fn create_matrix(rotation: &Matrix33, translation: &Vector3, scale: f32) -> Mat44 {
+    let mut mat = Mat44::new_identity();
+    let m = mat.floats_mut();
+    m[0][0] = rotation.m11 * scale;
+    m[0][1] = rotation.m12 * scale;
+    m[0][2] = rotation.m13 * scale;
+    m[1][0] = rotation.m21 * scale;
+    m[1][1] = rotation.m22 * scale;
+    m[1][2] = rotation.m23 * scale;
+    m[2][0] = rotation.m31 * scale;
+    m[2][1] = rotation.m32 * scale;
+    m[2][2] = rotation.m33 * scale;
+    m[0][3] = translation.x;
+    m[1][3] = translation.y;
+    m[2][3] = translation.z;
+
+    mat
+}
+
+fn matrix_to_quaternion(m: &Matrix33) -> Quaternion {
+    let trace = m.m11 + m.m22 + m.m33;
+    let q = if trace > 0. {
+        let s = 0.5 / (trace + 1.).sqrt();
+        Quaternion::new(
+            (m.m32 - m.m23) * s,
+            (m.m13 - m.m31) * s,
+            (m.m21 - m.m12) * s,
+            0.25 / s,
+        )
+    } else if m.m11 > m.m22 && m.m11 > m.m33 {
+        let s = 2. * (1. + m.m11 - m.m22 - m.m33).sqrt();
+        Quaternion::new(
+            0.25 * s,
+            (m.m12 + m.m21) / s,
+            (m.m13 + m.m31) / s,
+            (m.m32 - m.m23) / s,
+        )
+    } else if m.m22 > m.m33 {
+        let s = 2. * (1. + m.m22 - m.m11 - m.m33).sqrt();
+        Quaternion::new(
+            (m.m12 + m.m21) / s,
+            0.25 * s,
+            (m.m23 + m.m32) / s,
+            (m.m13 - m.m31) / s,
+        )
+    } else {
+        let s = 2. * (1. + m.m33 - m.m11 - m.m22).sqrt();
+        Quaternion::new(
+            (m.m13 + m.m31) / s,
+            (m.m23 + m.m32) / s,
+            0.25 * s,
+            (m.m21 - m.m12) / s,
+        )
+    };
+
+    Quaternion::normalized(&q)
+}
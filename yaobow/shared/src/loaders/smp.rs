@@ -1,7 +1,9 @@
+use std::sync::Arc;
+
 use crate::fs::cpk::CpkArchive;
 
 pub fn load_smp(data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
-    let mut cpk = CpkArchive::load(Box::new(std::io::Cursor::new(data)))?;
+    let cpk = CpkArchive::load(Arc::new(data))?;
     let mut content = cpk.open_first()?.content();
     let size = content.len() & 0xFFFFFFFC;
     content.resize(size, 0);
@@ -1,3 +1,4 @@
+use anyhow::Context;
 use common::store_ext::StoreExt2;
 use crosscom::ComRc;
 use encoding::{types::Encoding, DecoderTrap};
@@ -10,8 +11,9 @@ use radiance::scene::CoreScene;
 use radiance::utils::SeekRead;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
-use std::{io, rc::Rc};
+use std::{io, rc::Rc, sync::Arc};
 
+use super::combat::MonsterTable;
 use super::comdef::IScnSceneComponent;
 use super::loaders::nav_loader::nav_load_from_file;
 use super::loaders::nav_loader::NavFile;
@@ -23,6 +25,8 @@ use super::scene::create_animated_mesh_from_mv3;
 use super::scene::create_entity_from_cvd_model;
 use super::scene::create_mv3_entity;
 use super::scene::ScnScene;
+use super::states::inventory::ItemTable;
+use super::states::party::RoleTable;
 
 pub struct AssetManager {
     factory: Rc<dyn ComponentFactory>,
@@ -33,11 +37,11 @@ pub struct AssetManager {
     movie_effect_path: PathBuf,
     snd_path: PathBuf,
     basedata_path: PathBuf,
-    vfs: Rc<MiniFs>,
+    vfs: Arc<MiniFs>,
 }
 
 impl AssetManager {
-    pub fn new(factory: Rc<dyn ComponentFactory>, vfs: Rc<MiniFs>) -> Self {
+    pub fn new(factory: Rc<dyn ComponentFactory>, vfs: Arc<MiniFs>) -> Self {
         Self {
             factory,
             basedata_path: PathBuf::from("/basedata/basedata"),
@@ -59,38 +63,46 @@ impl AssetManager {
         self.factory.clone()
     }
 
-    pub fn load_scn(self: &Rc<Self>, cpk_name: &str, scn_name: &str) -> ComRc<IScene> {
+    pub fn load_scn(
+        self: &Rc<Self>,
+        cpk_name: &str,
+        scn_name: &str,
+    ) -> anyhow::Result<ComRc<IScene>> {
         let scene_base = self.scene_path.join(cpk_name).join(scn_name);
         let scene_path = scene_base.with_extension("scn");
 
-        let scn_file = scn_load_from_file(&self.vfs, scene_path);
-        let nav_file = self.load_nav(&scn_file.cpk_name, &scn_file.scn_base_name);
+        let scn_file = scn_load_from_file(&self.vfs, &scene_path)
+            .with_context(|| format!("Cannot load scn {:?}", scene_path))?;
+        let nav_file = self.load_nav(&scn_file.cpk_name, &scn_file.scn_base_name)?;
 
         let scene = CoreScene::create();
         let component = ScnScene::new(scene.clone(), &self, cpk_name, scn_name, scn_file, nav_file);
         scene.add_component(IScnSceneComponent::uuid(), ComRc::from_object(component));
-        scene
+        Ok(scene)
     }
 
-    pub fn load_sce(&self, cpk_name: &str) -> SceFile {
+    pub fn load_sce(&self, cpk_name: &str) -> anyhow::Result<SceFile> {
         let scene_base = self.scene_path.join(cpk_name).join(cpk_name);
         let sce_path = scene_base.with_extension("sce");
-        sce_load_from_file(&self.vfs, sce_path)
+        sce_load_from_file(&self.vfs, &sce_path)
+            .with_context(|| format!("Cannot load sce {:?}", sce_path))
     }
 
-    pub fn load_init_sce(&self) -> SceFile {
+    pub fn load_init_sce(&self) -> anyhow::Result<SceFile> {
         let init_sce = self.basedata_path.join("init.sce");
-        sce_load_from_file(&self.vfs, init_sce)
+        sce_load_from_file(&self.vfs, &init_sce)
+            .with_context(|| format!("Cannot load sce {:?}", init_sce))
     }
 
-    pub fn load_nav(&self, cpk_name: &str, scn_name: &str) -> NavFile {
+    pub fn load_nav(&self, cpk_name: &str, scn_name: &str) -> anyhow::Result<NavFile> {
         let nav_path = self
             .scene_path
             .join(cpk_name)
             .join(scn_name)
             .join(scn_name)
             .with_extension("nav");
-        nav_load_from_file(&self.vfs, nav_path)
+        nav_load_from_file(&self.vfs, &nav_path)
+            .with_context(|| format!("Cannot load nav {:?}", nav_path))
     }
 
     pub fn load_role(
@@ -259,6 +271,63 @@ impl AssetManager {
         }
     }
 
+    /// A missing or empty table is logged and left empty so the game still
+    /// starts, every item is then rejected by `ItemTable::is_valid`.
+    pub fn load_item_table(&self) -> ItemTable {
+        let path = self.basedata_path.join("datascript").join("item.txt");
+        let table = match self.vfs.read_to_end_from_gbk(&path) {
+            Ok(content) => ItemTable::parse(&content),
+            Err(e) => {
+                log::error!("Cannot load item table {:?}: {}", path, e);
+                return ItemTable::default();
+            }
+        };
+
+        if table.is_empty() {
+            log::error!("No items found in item table {:?}", path);
+        }
+
+        table
+    }
+
+    /// A missing or empty table is logged and left empty so the game still
+    /// starts, party commands then ignore the roles they can't find.
+    pub fn load_role_table(&self) -> RoleTable {
+        let path = self.basedata_path.join("datascript").join("role.txt");
+        let table = match self.vfs.read_to_end_from_gbk(&path) {
+            Ok(content) => RoleTable::parse(&content),
+            Err(e) => {
+                log::error!("Cannot load role table {:?}: {}", path, e);
+                return RoleTable::default();
+            }
+        };
+
+        if table.is_empty() {
+            log::error!("No roles found in role table {:?}", path);
+        }
+
+        table
+    }
+
+    /// A missing or empty table is logged and left empty so the game still
+    /// starts, battles then have no monster to fight and are skipped.
+    pub fn load_monster_table(&self) -> MonsterTable {
+        let path = self.basedata_path.join("datascript").join("monster.txt");
+        let table = match self.vfs.read_to_end_from_gbk(&path) {
+            Ok(content) => MonsterTable::parse(&content),
+            Err(e) => {
+                log::error!("Cannot load monster table {:?}: {}", path, e);
+                return MonsterTable::default();
+            }
+        };
+
+        if table.is_empty() {
+            log::error!("No monsters found in monster table {:?}", path);
+        }
+
+        table
+    }
+
     pub fn load_music_data(&self, music_name: &str) -> Vec<u8> {
         let path = self.music_path.join(music_name).with_extension("mp3");
         self.vfs.read_to_end(path).unwrap()
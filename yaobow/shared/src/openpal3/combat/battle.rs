@@ -0,0 +1,353 @@
+use crate::openpal3::states::party::RoleAttributes;
+
+use super::{rng::CombatRng, CombatOptions, CombatResult};
+
+/// A combatant acts when its gauge gets full. Every tick the gauge grows by
+/// the combatant's speed, so faster combatants act more often.
+pub const GAUGE_MAX: i32 = 1000;
+
+/// A combatant of speed 10 acts once per round.
+pub const TICKS_PER_ROUND: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Party,
+    Monster,
+}
+
+#[derive(Debug, Clone)]
+pub struct Combatant {
+    pub id: i32,
+    pub name: String,
+    pub side: Side,
+    pub attributes: RoleAttributes,
+    pub hp: i32,
+    pub mp: i32,
+    gauge: i32,
+    defending: bool,
+}
+
+impl Combatant {
+    pub fn new(
+        id: i32,
+        name: String,
+        side: Side,
+        attributes: RoleAttributes,
+        hp: i32,
+        mp: i32,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            side,
+            attributes,
+            hp,
+            mp,
+            gauge: 0,
+            defending: false,
+        }
+    }
+
+    pub fn alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    pub fn gauge(&self) -> i32 {
+        self.gauge
+    }
+
+    pub fn defending(&self) -> bool {
+        self.defending
+    }
+}
+
+/// Combatants are referred to by their index in `Battle::combatants`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BattleAction {
+    Attack(usize),
+    Defend,
+    Flee,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BattleEvent {
+    RoundStarted(u32),
+    Attack {
+        actor: usize,
+        target: usize,
+        damage: i32,
+        critical: bool,
+    },
+    Defend {
+        actor: usize,
+    },
+    FleeFailed {
+        actor: usize,
+    },
+    Fled,
+    Defeated {
+        target: usize,
+    },
+}
+
+/// Turn based battle between the party and the monsters. It has no
+/// dependency on rendering or input, the same seed and actions always give
+/// the same battle.
+pub struct Battle {
+    combatants: Vec<Combatant>,
+    options: CombatOptions,
+    rng: CombatRng,
+    round: u32,
+    ticks: u32,
+    current: Option<usize>,
+    result: Option<CombatResult>,
+    events: Vec<BattleEvent>,
+}
+
+impl Battle {
+    pub fn new(
+        party: Vec<Combatant>,
+        monsters: Vec<Combatant>,
+        options: CombatOptions,
+        seed: u64,
+    ) -> Self {
+        let mut battle = Self {
+            combatants: party.into_iter().chain(monsters).collect(),
+            options,
+            rng: CombatRng::new(seed),
+            round: 1,
+            ticks: 0,
+            current: None,
+            result: None,
+            events: vec![],
+        };
+
+        battle.check_result();
+        battle
+    }
+
+    pub fn combatants(&self) -> &[Combatant] {
+        &self.combatants
+    }
+
+    pub fn options(&self) -> &CombatOptions {
+        &self.options
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn result(&self) -> Option<CombatResult> {
+        self.result
+    }
+
+    pub fn events(&self) -> &[BattleEvent] {
+        &self.events
+    }
+
+    pub fn current_actor(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Fills the gauges until a combatant is ready and returns it. The fullest
+    /// gauge acts first and the party wins a tie. Returns `None` once the
+    /// battle is over.
+    pub fn next_actor(&mut self) -> Option<usize> {
+        while self.result.is_none() {
+            if self.current.is_some() {
+                return self.current;
+            }
+
+            let ready = self
+                .combatants
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.alive() && c.gauge >= GAUGE_MAX)
+                .max_by(|(i1, c1), (i2, c2)| c1.gauge.cmp(&c2.gauge).then(i2.cmp(i1)))
+                .map(|(i, _)| i);
+
+            match ready {
+                Some(actor) => {
+                    self.combatants[actor].defending = false;
+                    self.current = Some(actor);
+                }
+                None => self.tick(),
+            }
+        }
+
+        None
+    }
+
+    /// Performs the action of the current actor. An attack on a target that
+    /// cannot be attacked goes to the automatic target instead.
+    pub fn act(&mut self, action: BattleAction) {
+        let actor = match self.current.take() {
+            Some(actor) => actor,
+            None => return,
+        };
+
+        self.combatants[actor].gauge -= GAUGE_MAX;
+        match action {
+            BattleAction::Attack(target) => {
+                let target = if self.can_attack(actor, target) {
+                    Some(target)
+                } else {
+                    self.auto_target(actor)
+                };
+
+                if let Some(target) = target {
+                    self.attack(actor, target);
+                }
+            }
+            BattleAction::Defend => {
+                self.combatants[actor].defending = true;
+                self.events.push(BattleEvent::Defend { actor });
+            }
+            BattleAction::Flee => self.flee(actor),
+        }
+
+        self.check_result();
+    }
+
+    /// The living opponent with the lowest hp.
+    pub fn auto_target(&self, actor: usize) -> Option<usize> {
+        self.combatants
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.can_attack(actor, *i))
+            .min_by_key(|(i, c)| (c.hp, *i))
+            .map(|(i, _)| i)
+    }
+
+    pub fn auto_action(&self, actor: usize) -> BattleAction {
+        self.auto_target(actor)
+            .map_or(BattleAction::Defend, BattleAction::Attack)
+    }
+
+    /// Runs the battle to the end with automatic actions on both sides.
+    pub fn run_headless(&mut self) -> CombatResult {
+        while let Some(actor) = self.next_actor() {
+            let action = self.auto_action(actor);
+            self.act(action);
+        }
+
+        self.result.unwrap()
+    }
+
+    fn can_attack(&self, actor: usize, target: usize) -> bool {
+        self.combatants
+            .get(target)
+            .is_some_and(|t| t.alive() && t.side != self.combatants[actor].side)
+    }
+
+    fn tick(&mut self) {
+        if self.ticks == self.round * TICKS_PER_ROUND {
+            if self.options.max_round.is_some_and(|max| self.round >= max) {
+                self.result = Some(if self.options.must_fail {
+                    CombatResult::Lost
+                } else {
+                    CombatResult::Won
+                });
+                return;
+            }
+
+            self.round += 1;
+            self.events.push(BattleEvent::RoundStarted(self.round));
+        }
+
+        for combatant in self.combatants.iter_mut().filter(|c| c.alive()) {
+            combatant.gauge += combatant.attributes.speed.max(1);
+        }
+
+        self.ticks += 1;
+    }
+
+    fn attack(&mut self, actor: usize, target: usize) {
+        let attacker = &self.combatants[actor].attributes;
+        let defender = &self.combatants[target];
+        let base = (attacker.attack as i64 * 2 - defender.attributes.defense as i64).max(1);
+        let luck = attacker.luck;
+        let defending = defender.defending;
+
+        let mut damage = base * self.rng.range(90, 111) as i64 / 100;
+        let critical = self.rng.range(0, 100) < luck / 4;
+        if critical {
+            damage = damage * 3 / 2;
+        }
+
+        if defending {
+            damage /= 2;
+        }
+
+        let damage = damage.clamp(1, i32::MAX as i64) as i32;
+        let target_side = self.combatants[target].side;
+        let min_hp = if self.options.must_fail && target_side == Side::Monster {
+            1
+        } else {
+            0
+        };
+
+        let defender = &mut self.combatants[target];
+        defender.hp = defender.hp.saturating_sub(damage).max(min_hp);
+        self.events.push(BattleEvent::Attack {
+            actor,
+            target,
+            damage,
+            critical,
+        });
+
+        if !defender.alive() {
+            defender.gauge = 0;
+            self.events.push(BattleEvent::Defeated { target });
+        }
+    }
+
+    fn flee(&mut self, actor: usize) {
+        if self.options.boss || self.combatants[actor].side != Side::Party {
+            self.events.push(BattleEvent::FleeFailed { actor });
+            return;
+        }
+
+        let chance = (50 + self.average_speed(Side::Party) - self.average_speed(Side::Monster))
+            .clamp(10, 90);
+        if self.rng.range(0, 100) < chance {
+            self.events.push(BattleEvent::Fled);
+            self.result = Some(CombatResult::Fled);
+        } else {
+            self.events.push(BattleEvent::FleeFailed { actor });
+        }
+    }
+
+    fn average_speed(&self, side: Side) -> i32 {
+        let speeds: Vec<i32> = self
+            .combatants
+            .iter()
+            .filter(|c| c.side == side && c.alive())
+            .map(|c| c.attributes.speed)
+            .collect();
+
+        if speeds.is_empty() {
+            0
+        } else {
+            speeds.iter().sum::<i32>() / speeds.len() as i32
+        }
+    }
+
+    fn check_result(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+
+        let alive = |side| self.combatants.iter().any(|c| c.side == side && c.alive());
+        if !alive(Side::Monster) {
+            self.result = Some(CombatResult::Won);
+        } else if !alive(Side::Party) {
+            self.result = Some(CombatResult::Lost);
+        }
+
+        if self.result.is_some() {
+            self.current = None;
+        }
+    }
+}
@@ -0,0 +1,126 @@
+mod battle;
+mod monster;
+mod rng;
+
+pub use battle::{Battle, BattleAction, BattleEvent, Combatant, Side, GAUGE_MAX, TICKS_PER_ROUND};
+pub use monster::{MonsterTable, MAX_ENCOUNTER_MONSTERS};
+
+use super::states::party::{Party, RoleTable};
+
+/// Set by the `Combat*` commands and used by the next battle only.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CombatOptions {
+    /// The battle ends after this round, the party wins unless it must fail.
+    pub max_round: Option<u32>,
+
+    /// Story battles the party cannot win: monsters keep at least 1 hp.
+    pub must_fail: bool,
+
+    /// Losing the battle goes on with the script instead of the game over
+    /// screen.
+    pub not_game_over: bool,
+
+    /// The party cannot flee from a boss.
+    pub boss: bool,
+}
+
+impl CombatOptions {
+    /// Result reported for a battle that can't be fought because none of
+    /// its monsters is known. It counts as won so that scripts go on with the
+    /// story, or as lost when the party must fail.
+    pub fn skipped_result(&self) -> CombatResult {
+        if self.must_fail {
+            CombatResult::Lost
+        } else {
+            CombatResult::Won
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CombatRequest {
+    pub monster_ids: Vec<i32>,
+    pub options: CombatOptions,
+    pub seed: u64,
+}
+
+impl CombatRequest {
+    /// Builds the battle from the current party members. Members and
+    /// monsters missing from their table stay out of the battle.
+    pub fn new_battle(
+        &self,
+        party: &mut Party,
+        roles: &RoleTable,
+        monsters: &MonsterTable,
+    ) -> Battle {
+        let party_combatants = party
+            .members()
+            .to_vec()
+            .into_iter()
+            .filter_map(|role_id| {
+                let name = roles
+                    .name(role_id)
+                    .map_or_else(|| format!("角色{}", role_id), |n| n.to_string());
+                let role = match party.role_mut(role_id, roles) {
+                    Some(role) => role,
+                    None => {
+                        log::error!("Role {} is not in the role table", role_id);
+                        return None;
+                    }
+                };
+
+                Some(Combatant::new(
+                    role_id,
+                    name,
+                    Side::Party,
+                    role.attributes.clone(),
+                    role.hp,
+                    role.mp,
+                ))
+            })
+            .collect();
+
+        let monster_combatants = self
+            .monster_ids
+            .iter()
+            .filter_map(|id| monsters.combatant(*id))
+            .collect();
+
+        Battle::new(
+            party_combatants,
+            monster_combatants,
+            self.options.clone(),
+            self.seed,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombatResult {
+    Won,
+    Lost,
+    Fled,
+}
+
+impl CombatResult {
+    /// The value `GetCombat` reports to the scripts.
+    pub fn sce_value(&self) -> i32 {
+        match self {
+            CombatResult::Won => 1,
+            CombatResult::Lost | CombatResult::Fled => 0,
+        }
+    }
+}
+
+/// Writes the hp and mp left after the battle back to the party. Knocked out
+/// members get up with 1 hp.
+pub fn update_party(battle: &Battle, party: &mut Party, roles: &RoleTable) {
+    for combatant in battle.combatants() {
+        if combatant.side == Side::Party {
+            if let Some(role) = party.role_mut(combatant.id, roles) {
+                role.hp = combatant.hp.max(1);
+                role.mp = combatant.mp;
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::openpal3::states::party::{RoleAttributes, RoleTable};
+
+use super::{
+    battle::{Combatant, Side},
+    rng::CombatRng,
+};
+
+pub const MAX_ENCOUNTER_MONSTERS: usize = 3;
+
+/// Monster attributes from `datascript/monster.txt` in basedata. The records
+/// are assumed to share the layout of the role table (id, name, level, max
+/// hp, max mp, attack, defense, speed and luck), which hasn't been checked
+/// against the retail file yet.
+#[derive(Debug, Default)]
+pub struct MonsterTable {
+    monsters: RoleTable,
+}
+
+impl MonsterTable {
+    pub fn parse(content: &str) -> Self {
+        Self {
+            monsters: RoleTable::parse(content),
+        }
+    }
+
+    pub fn name(&self, monster_id: i32) -> String {
+        self.monsters
+            .name(monster_id)
+            .map_or_else(|| format!("怪物{}", monster_id), |n| n.to_string())
+    }
+
+    pub fn get(&self, monster_id: i32) -> Option<RoleAttributes> {
+        self.monsters.get(monster_id)
+    }
+
+    /// Returns `None` for a monster missing from the table.
+    pub fn combatant(&self, monster_id: i32) -> Option<Combatant> {
+        let attributes = self.get(monster_id)?;
+        Some(Combatant::new(
+            monster_id,
+            self.name(monster_id),
+            Side::Monster,
+            attributes.clone(),
+            attributes.max_hp,
+            attributes.max_mp,
+        ))
+    }
+
+    /// Leaves out the monsters missing from the table.
+    pub fn known_monsters(&self, monster_ids: Vec<i32>) -> Vec<i32> {
+        monster_ids
+            .into_iter()
+            .filter(|id| {
+                let known = self.get(*id).is_some();
+                if !known {
+                    log::error!("Monster {} is not in the monster table", id);
+                }
+
+                known
+            })
+            .collect()
+    }
+
+    /// Picks 1 to `MAX_ENCOUNTER_MONSTERS` monsters that are at most 2 levels
+    /// above `level`, or any monster when none is weak enough. Returns no
+    /// monster for an empty table.
+    pub fn random_encounter(&self, level: u32, seed: u64) -> Vec<i32> {
+        let mut ids = self.monsters.ids();
+        if ids.is_empty() {
+            return vec![];
+        }
+
+        let weak: Vec<i32> = ids
+            .iter()
+            .copied()
+            .filter(|id| self.get(*id).is_some_and(|m| m.level <= level + 2))
+            .collect();
+        if !weak.is_empty() {
+            ids = weak;
+        }
+
+        let mut rng = CombatRng::new(seed);
+        let count = rng.range(1, MAX_ENCOUNTER_MONSTERS as i32 + 1);
+        (0..count)
+            .map(|_| ids[rng.range(0, ids.len() as i32) as usize])
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.monsters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.monsters.is_empty()
+    }
+}
@@ -0,0 +1,35 @@
+/// Xorshift64* generator. Battles only depend on their seed so that they can
+/// be replayed, whatever version of `rand` is in use.
+#[derive(Debug, Clone)]
+pub(crate) struct CombatRng {
+    state: u64,
+}
+
+impl CombatRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 {
+                0x9e37_79b9_7f4a_7c15
+            } else {
+                seed
+            },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A value in `low..high`, `low` for an empty range.
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        if high <= low {
+            return low;
+        }
+
+        let span = (high as i64 - low as i64) as u64;
+        (low as i64 + (self.next_u64() % span) as i64) as i32
+    }
+}
@@ -159,6 +159,7 @@ macro_rules! ComObject_RoleController {
             use radiance::comdef::ISkinnedMeshComponentImpl;
             use radiance::comdef::IStaticMeshComponentImpl;
             use shared::openpal3::comdef::IAdventureDirectorImpl;
+            use shared::openpal3::comdef::ICombatDirectorImpl;
             use shared::openpal3::comdef::ICvdModelImpl;
             use shared::openpal3::comdef::IRoleControllerImpl;
             use shared::openpal3::comdef::IScnSceneComponentImpl;
@@ -450,6 +451,7 @@ macro_rules! ComObject_CvdModel {
             use radiance::comdef::ISkinnedMeshComponentImpl;
             use radiance::comdef::IStaticMeshComponentImpl;
             use shared::openpal3::comdef::IAdventureDirectorImpl;
+            use shared::openpal3::comdef::ICombatDirectorImpl;
             use shared::openpal3::comdef::ICvdModelImpl;
             use shared::openpal3::comdef::IRoleControllerImpl;
             use shared::openpal3::comdef::IScnSceneComponentImpl;
@@ -737,6 +739,7 @@ macro_rules! ComObject_ScnSceneComponent {
             use radiance::comdef::ISkinnedMeshComponentImpl;
             use radiance::comdef::IStaticMeshComponentImpl;
             use shared::openpal3::comdef::IAdventureDirectorImpl;
+            use shared::openpal3::comdef::ICombatDirectorImpl;
             use shared::openpal3::comdef::ICvdModelImpl;
             use shared::openpal3::comdef::IRoleControllerImpl;
             use shared::openpal3::comdef::IScnSceneComponentImpl;
@@ -1041,6 +1044,7 @@ macro_rules! ComObject_AdventureDirector {
             use radiance::comdef::ISkinnedMeshComponentImpl;
             use radiance::comdef::IStaticMeshComponentImpl;
             use shared::openpal3::comdef::IAdventureDirectorImpl;
+            use shared::openpal3::comdef::ICombatDirectorImpl;
             use shared::openpal3::comdef::ICvdModelImpl;
             use shared::openpal3::comdef::IRoleControllerImpl;
             use shared::openpal3::comdef::IScnSceneComponentImpl;
@@ -1189,3 +1193,311 @@ macro_rules! ComObject_AdventureDirector {
 }
 
 // pub use ComObject_AdventureDirector;
+// Interface ICombatDirector
+
+#[repr(C)]
+#[allow(non_snake_case)]
+pub struct ICombatDirectorVirtualTable {
+    pub query_interface: unsafe extern "system" fn(
+        this: *const *const std::os::raw::c_void,
+        guid: uuid::Uuid,
+        retval: &mut *const *const std::os::raw::c_void,
+    ) -> std::os::raw::c_long,
+    pub add_ref:
+        unsafe extern "system" fn(this: *const *const std::os::raw::c_void) -> std::os::raw::c_long,
+    pub release:
+        unsafe extern "system" fn(this: *const *const std::os::raw::c_void) -> std::os::raw::c_long,
+    pub activate: unsafe extern "system" fn(
+        this: *const *const std::os::raw::c_void,
+        scene_manager: *const *const std::os::raw::c_void,
+    ) -> (),
+    pub update: fn(
+        this: *const *const std::os::raw::c_void,
+        scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager>,
+        ui: &imgui::Ui,
+        delta_sec: f32,
+    ) -> Option<crosscom::ComRc<radiance::comdef::IDirector>>,
+    pub get: fn(
+        this: *const *const std::os::raw::c_void,
+    ) -> &'static shared::openpal3::directors::CombatDirector,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+pub struct ICombatDirectorVirtualTableCcw {
+    pub offset: isize,
+    pub vtable: ICombatDirectorVirtualTable,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+pub struct ICombatDirector {
+    pub vtable: *const ICombatDirectorVirtualTable,
+}
+
+#[allow(dead_code)]
+#[allow(non_snake_case)]
+#[allow(unused)]
+impl ICombatDirector {
+    pub fn query_interface<T: crosscom::ComInterface>(&self) -> Option<crosscom::ComRc<T>> {
+        let this = self as *const ICombatDirector as *const *const std::os::raw::c_void;
+        let mut raw = 0 as *const *const std::os::raw::c_void;
+        let guid = uuid::Uuid::from_bytes(T::INTERFACE_ID);
+        let ret_val = unsafe { ((*self.vtable).query_interface)(this, guid, &mut raw) };
+        if ret_val != 0 {
+            None
+        } else {
+            Some(unsafe { crosscom::ComRc::<T>::from_raw_pointer(raw) })
+        }
+    }
+
+    pub fn add_ref(&self) -> std::os::raw::c_long {
+        unsafe {
+            let this = self as *const ICombatDirector as *const *const std::os::raw::c_void;
+            let ret = ((*self.vtable).add_ref)(this);
+            let ret: std::os::raw::c_long = ret.into();
+
+            ret
+        }
+    }
+
+    pub fn release(&self) -> std::os::raw::c_long {
+        unsafe {
+            let this = self as *const ICombatDirector as *const *const std::os::raw::c_void;
+            let ret = ((*self.vtable).release)(this);
+            let ret: std::os::raw::c_long = ret.into();
+
+            ret
+        }
+    }
+
+    pub fn activate(&self, scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager>) -> () {
+        unsafe {
+            let this = self as *const ICombatDirector as *const *const std::os::raw::c_void;
+            let ret = ((*self.vtable).activate)(this, scene_manager.into());
+            let ret: () = ret.into();
+
+            ret
+        }
+    }
+
+    pub fn update(
+        &self,
+        scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager>,
+        ui: &imgui::Ui,
+        delta_sec: f32,
+    ) -> Option<crosscom::ComRc<radiance::comdef::IDirector>> {
+        unsafe {
+            let this = self as *const ICombatDirector as *const *const std::os::raw::c_void;
+            let ret =
+                ((*self.vtable).update)(this, scene_manager.into(), ui.into(), delta_sec.into());
+
+            ret
+        }
+    }
+
+    pub fn get(&self) -> &'static shared::openpal3::directors::CombatDirector {
+        unsafe {
+            let this = self as *const ICombatDirector as *const *const std::os::raw::c_void;
+            let ret = ((*self.vtable).get)(this);
+
+            ret
+        }
+    }
+
+    pub fn uuid() -> uuid::Uuid {
+        use crosscom::ComInterface;
+        uuid::Uuid::from_bytes(ICombatDirector::INTERFACE_ID)
+    }
+}
+
+pub trait ICombatDirectorImpl {
+    fn get(&self) -> &'static shared::openpal3::directors::CombatDirector;
+}
+
+impl crosscom::ComInterface for ICombatDirector {
+    // 5bf50f02-1f59-4f6c-a0af-66497d874591
+    const INTERFACE_ID: [u8; 16] = [
+        91u8, 245u8, 15u8, 2u8, 31u8, 89u8, 79u8, 108u8, 160u8, 175u8, 102u8, 73u8, 125u8, 135u8,
+        69u8, 145u8,
+    ];
+}
+
+// Class CombatDirector
+
+#[allow(unused)]
+#[macro_export]
+macro_rules! ComObject_CombatDirector {
+    ($impl_type: ty) => {
+        #[allow(dead_code)]
+        #[allow(non_snake_case)]
+        #[allow(unused)]
+        mod CombatDirector_crosscom_impl {
+            use crate as shared;
+            use crosscom::ComInterface;
+            use crosscom::IObjectArrayImpl;
+            use crosscom::IUnknownImpl;
+            use radiance::comdef::IAnimatedMeshComponentImpl;
+            use radiance::comdef::IAnimationEventObserverImpl;
+            use radiance::comdef::IApplicationImpl;
+            use radiance::comdef::IApplicationLoaderComponentImpl;
+            use radiance::comdef::IArmatureComponentImpl;
+            use radiance::comdef::IComponentContainerImpl;
+            use radiance::comdef::IComponentImpl;
+            use radiance::comdef::IDirectorImpl;
+            use radiance::comdef::IEntityImpl;
+            use radiance::comdef::IHAnimBoneComponentImpl;
+            use radiance::comdef::ISceneImpl;
+            use radiance::comdef::ISceneManagerImpl;
+            use radiance::comdef::ISkinnedMeshComponentImpl;
+            use radiance::comdef::IStaticMeshComponentImpl;
+            use shared::openpal3::comdef::IAdventureDirectorImpl;
+            use shared::openpal3::comdef::ICombatDirectorImpl;
+            use shared::openpal3::comdef::ICvdModelImpl;
+            use shared::openpal3::comdef::IRoleControllerImpl;
+            use shared::openpal3::comdef::IScnSceneComponentImpl;
+
+            #[repr(C)]
+            pub struct CombatDirectorCcw {
+                ICombatDirector: shared::openpal3::comdef::ICombatDirector,
+
+                ref_count: std::sync::atomic::AtomicU32,
+                pub inner: $impl_type,
+            }
+
+            unsafe extern "system" fn query_interface(
+                this: *const *const std::os::raw::c_void,
+                guid: uuid::Uuid,
+                retval: &mut *const *const std::os::raw::c_void,
+            ) -> std::os::raw::c_long {
+                let object = crosscom::get_object::<CombatDirectorCcw>(this);
+                match guid.as_bytes() {
+                    &crosscom::IUnknown::INTERFACE_ID => {
+                        *retval = (object as *const *const std::os::raw::c_void).offset(0);
+                        add_ref(object as *const *const std::os::raw::c_void);
+                        crosscom::ResultCode::Ok as std::os::raw::c_long
+                    }
+
+                    &radiance::comdef::IDirector::INTERFACE_ID => {
+                        *retval = (object as *const *const std::os::raw::c_void).offset(0);
+                        add_ref(object as *const *const std::os::raw::c_void);
+                        crosscom::ResultCode::Ok as std::os::raw::c_long
+                    }
+
+                    &shared::openpal3::comdef::ICombatDirector::INTERFACE_ID => {
+                        *retval = (object as *const *const std::os::raw::c_void).offset(0);
+                        add_ref(object as *const *const std::os::raw::c_void);
+                        crosscom::ResultCode::Ok as std::os::raw::c_long
+                    }
+
+                    _ => crosscom::ResultCode::ENoInterface as std::os::raw::c_long,
+                }
+            }
+
+            unsafe extern "system" fn add_ref(
+                this: *const *const std::os::raw::c_void,
+            ) -> std::os::raw::c_long {
+                let object = crosscom::get_object::<CombatDirectorCcw>(this);
+                let previous = (*object)
+                    .ref_count
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                (previous + 1) as std::os::raw::c_long
+            }
+
+            unsafe extern "system" fn release(
+                this: *const *const std::os::raw::c_void,
+            ) -> std::os::raw::c_long {
+                let object = crosscom::get_object::<CombatDirectorCcw>(this);
+
+                let previous = (*object)
+                    .ref_count
+                    .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                if previous - 1 == 0 {
+                    Box::from_raw(object as *mut CombatDirectorCcw);
+                }
+
+                (previous - 1) as std::os::raw::c_long
+            }
+
+            fn get(
+                this: *const *const std::os::raw::c_void,
+            ) -> &'static shared::openpal3::directors::CombatDirector {
+                unsafe {
+                    let __crosscom_object = crosscom::get_object::<CombatDirectorCcw>(this);
+                    (*__crosscom_object).inner.get()
+                }
+            }
+
+            unsafe extern "system" fn activate(
+                this: *const *const std::os::raw::c_void,
+                scene_manager: *const *const std::os::raw::c_void,
+            ) -> () {
+                let scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager> =
+                    scene_manager.into();
+
+                let __crosscom_object = crosscom::get_object::<CombatDirectorCcw>(this);
+                (*__crosscom_object)
+                    .inner
+                    .activate(scene_manager.into())
+                    .into()
+            }
+
+            fn update(
+                this: *const *const std::os::raw::c_void,
+                scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager>,
+                ui: &imgui::Ui,
+                delta_sec: f32,
+            ) -> Option<crosscom::ComRc<radiance::comdef::IDirector>> {
+                unsafe {
+                    let __crosscom_object = crosscom::get_object::<CombatDirectorCcw>(this);
+                    (*__crosscom_object)
+                        .inner
+                        .update(scene_manager, ui, delta_sec)
+                }
+            }
+
+            #[allow(non_upper_case_globals)]
+            pub const GLOBAL_ICombatDirectorVirtualTable_CCW_FOR_CombatDirector:
+                shared::openpal3::comdef::ICombatDirectorVirtualTableCcw =
+                shared::openpal3::comdef::ICombatDirectorVirtualTableCcw {
+                    offset: 0,
+                    vtable: shared::openpal3::comdef::ICombatDirectorVirtualTable {
+                        query_interface,
+                        add_ref,
+                        release,
+                        activate,
+                        update,
+                        get,
+                    },
+                };
+
+            impl crosscom::ComObject for $impl_type {
+                type CcwType = CombatDirectorCcw;
+
+                fn create_ccw(self) -> Self::CcwType {
+                    Self::CcwType {
+                        ICombatDirector: shared::openpal3::comdef::ICombatDirector {
+                            vtable: &GLOBAL_ICombatDirectorVirtualTable_CCW_FOR_CombatDirector
+                                .vtable
+                                as *const shared::openpal3::comdef::ICombatDirectorVirtualTable,
+                        },
+
+                        ref_count: std::sync::atomic::AtomicU32::new(0),
+                        inner: self,
+                    }
+                }
+
+                fn get_ccw(&self) -> &Self::CcwType {
+                    unsafe {
+                        let this = self as *const _ as *const u8;
+                        let this =
+                            this.offset(-(crosscom::offset_of!(CombatDirectorCcw, inner) as isize));
+                        &*(this as *const Self::CcwType)
+                    }
+                }
+            }
+        }
+    };
+}
+
+// pub use ComObject_CombatDirector;
@@ -6,12 +6,14 @@ use std::{
 use crate::{
     openpal3::{
         asset_manager::AssetManager,
-        comdef::IAdventureDirectorImpl,
-        directors::SceneManagerExtensions,
+        comdef::{IAdventureDirector, IAdventureDirectorImpl, IScnSceneComponent},
+        directors::{CombatDirector, SceneManagerExtensions},
         scene::{LadderTestResult, RoleController},
-        states::{global_state::GlobalState, persistent_state::PersistentState},
+        states::{
+            global_state::GlobalState, persistent_state::PersistentState, save_game::SceneSnapshot,
+        },
     },
-    scripting::sce::vm::{SceExecutionOptions, SceVm},
+    scripting::sce::vm::{SceExecutionOptions, SceProcHooks, SceVm},
     ComObject_AdventureDirector,
 };
 
@@ -37,13 +39,13 @@ impl AdventureDirector {
         audio_engine: Rc<dyn AudioEngine>,
         input_engine: Rc<RefCell<dyn InputEngine>>,
         sce_vm_options: Option<SceExecutionOptions>,
-    ) -> Self {
+    ) -> anyhow::Result<Self> {
         let p_state = Rc::new(RefCell::new(PersistentState::new(app_name.to_string())));
         let global_state = GlobalState::new(asset_mgr.clone(), audio_engine.clone(), p_state);
         let mut sce_vm = SceVm::new(
             audio_engine.clone(),
             input_engine.clone(),
-            asset_mgr.load_init_sce(),
+            asset_mgr.load_init_sce()?,
             "init".to_string(),
             asset_mgr.clone(),
             global_state,
@@ -51,14 +53,14 @@ impl AdventureDirector {
         );
         sce_vm.call_proc(51);
 
-        Self {
+        Ok(Self {
             props: RefCell::new(AdventureDirectorProps {
                 input_engine,
                 sce_vm,
                 camera_rotation: 0.,
                 layer_switch_triggered: false,
             }),
-        }
+        })
     }
 
     fn props_mut(&self) -> RefMut<AdventureDirectorProps> {
@@ -71,52 +73,73 @@ impl AdventureDirector {
         audio_engine: Rc<dyn AudioEngine>,
         input_engine: Rc<RefCell<dyn InputEngine>>,
         scene_manager: ComRc<ISceneManager>,
-        sce_vm_options: Option<SceExecutionOptions>,
+        mut sce_vm_options: Option<SceExecutionOptions>,
         slot: i32,
-    ) -> Option<Self> {
-        let p_state = PersistentState::load(app_name, slot);
-        let scene_name = p_state.scene_name();
-        let sub_scene_name = p_state.sub_scene_name();
-        if scene_name.is_none() || sub_scene_name.is_none() {
-            log::error!("Cannot load save {}: scene or sub_scene is empty", slot);
-            return None;
-        }
+    ) -> anyhow::Result<Self> {
+        let p_state = PersistentState::load(app_name, slot)?;
+        let (scene_name, sub_scene_name) = match (p_state.scene_name(), p_state.sub_scene_name()) {
+            (Some(scene_name), Some(sub_scene_name)) => (scene_name, sub_scene_name),
+            _ => anyhow::bail!("Cannot load save {}: scene or sub_scene is empty", slot),
+        };
 
-        let scene = asset_mgr.load_scn(
-            scene_name.as_ref().unwrap(),
-            sub_scene_name.as_ref().unwrap(),
-        );
+        let sce = asset_mgr.load_sce(&scene_name)?;
+        let scene = asset_mgr.load_scn(&scene_name, &sub_scene_name)?;
 
-        scene_manager.push_scene(scene);
+        let scene_state = p_state.scene_state().cloned();
+        let script = p_state.script().cloned();
+        let role_controlled = p_state.role_controlled();
+        let bgm = p_state.bgm().map(|b| b.to_string());
 
         let mut global_state = GlobalState::new(
             asset_mgr.clone(),
             audio_engine.clone(),
             Rc::new(RefCell::new(p_state)),
         );
+        global_state.set_role_controlled(role_controlled);
+        scene_manager.push_scene(scene);
 
-        // The role id should be saved in persistant state
-        let role_entity = scene_manager
-            .scn_scene()
-            .unwrap()
-            .get()
-            .get_role_entity(0)
-            .unwrap();
+        let scn_scene = scene_manager.scn_scene().unwrap();
+        if scene_state.is_none() {
+            // Saves without a scene snapshot only know the leader position
+            let role_entity = scn_scene.get().get_role_entity(0).unwrap();
+            let role = RoleController::get_role_controller(role_entity.clone()).unwrap();
+            role.get().set_active(true);
+            role_entity
+                .transform()
+                .borrow_mut()
+                .set_position(&global_state.persistent_state_mut().position());
+        }
 
-        let role = RoleController::get_role_controller(role_entity.clone()).unwrap();
-        role.get().set_active(true);
-        role_entity
-            .transform()
-            .borrow_mut()
-            .set_position(&global_state.persistent_state_mut().position());
+        match bgm {
+            Some(bgm) => global_state.play_bgm(&bgm),
+            None => global_state.play_default_bgm(),
+        }
 
-        global_state.play_default_bgm();
+        // The scene init proc runs first like when the scene is entered, and
+        // the saved changes to the scene are applied once it has finished.
+        let init_proc = format!("_{}_{}", scene_name, sub_scene_name);
+        let init_proc_id = sce
+            .proc_headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(&init_proc))
+            .map(|h| h.id);
+        if let Some(scene_state) = scene_state {
+            match init_proc_id {
+                Some(proc_id) => sce_vm_options
+                    .get_or_insert_with(|| SceExecutionOptions { proc_hooks: vec![] })
+                    .proc_hooks
+                    .push(Box::new(SceneRestoreHook {
+                        pending: RefCell::new(Some((proc_id, scn_scene, scene_state))),
+                    })),
+                None => scn_scene.get().restore(&scene_state),
+            }
+        }
 
         let mut sce_vm = SceVm::new(
             audio_engine.clone(),
             input_engine.clone(),
-            asset_mgr.load_sce(scene_name.as_ref().unwrap()),
-            scene_name.as_ref().unwrap().clone(),
+            sce,
+            scene_name.clone(),
             asset_mgr.clone(),
             global_state,
             sce_vm_options,
@@ -124,13 +147,14 @@ impl AdventureDirector {
 
         // don't draw curtain when loading a save
         sce_vm.state_mut().set_curtain(0.);
-        sce_vm.state_mut().try_call_proc_by_name(&format!(
-            "_{}_{}",
-            scene_name.as_ref().unwrap(),
-            sub_scene_name.as_ref().unwrap()
-        ));
 
-        Some(Self {
+        if let Some(script) = &script {
+            sce_vm.state_mut().context_mut().restore(script);
+        }
+
+        sce_vm.state_mut().try_call_proc_by_name(&init_proc);
+
+        Ok(Self {
             props: RefCell::new(AdventureDirectorProps {
                 input_engine,
                 sce_vm,
@@ -170,6 +194,24 @@ impl IAdventureDirectorImpl for AdventureDirector {
     }
 }
 
+/// Restores the scene snapshot of a save when the scene init proc ends, so
+/// that the init proc doesn't undo the saved changes.
+struct SceneRestoreHook {
+    pending: RefCell<Option<(u32, ComRc<IScnSceneComponent>, SceneSnapshot)>>,
+}
+
+impl SceProcHooks for SceneRestoreHook {
+    fn proc_begin(&self, _sce_name: &str, _proc_id: u32, _global_state: &mut GlobalState) {}
+
+    fn proc_end(&self, _sce_name: &str, proc_id: u32, _global_state: &mut GlobalState) {
+        let mut pending = self.pending.borrow_mut();
+        if pending.as_ref().is_some_and(|(id, _, _)| *id == proc_id) {
+            let (_, scn_scene, scene_state) = pending.take().unwrap();
+            scn_scene.get().restore(&scene_state);
+        }
+    }
+}
+
 struct AdventureDirectorProps {
     input_engine: Rc<RefCell<dyn InputEngine>>,
     sce_vm: SceVm,
@@ -178,24 +220,35 @@ struct AdventureDirectorProps {
 }
 
 impl AdventureDirectorProps {
-    fn test_save(&self) {
-        let input = self.input_engine.borrow_mut();
-        let save_slot = if input.get_key_state(Key::Num1).pressed() {
-            1
-        } else if input.get_key_state(Key::Num2).pressed() {
-            2
-        } else if input.get_key_state(Key::Num3).pressed() {
-            3
-        } else if input.get_key_state(Key::Num4).pressed() {
-            4
-        } else {
-            -1
+    fn test_save(&mut self, scene_manager: ComRc<ISceneManager>) {
+        let save_slot = {
+            let input = self.input_engine.borrow_mut();
+            if input.get_key_state(Key::Num1).pressed() {
+                1
+            } else if input.get_key_state(Key::Num2).pressed() {
+                2
+            } else if input.get_key_state(Key::Num3).pressed() {
+                3
+            } else if input.get_key_state(Key::Num4).pressed() {
+                4
+            } else {
+                -1
+            }
         };
 
-        self.sce_vm
-            .global_state()
-            .persistent_state()
-            .save(save_slot);
+        if save_slot < 0 {
+            return;
+        }
+
+        let scene_state = scene_manager.scn_scene().map(|s| s.get().snapshot());
+        let script = self.sce_vm.state().context().snapshot();
+        let global_state = self.sce_vm.global_state_mut();
+        let role_controlled = global_state.role_controlled();
+        let bgm = global_state.bgm_name().map(|b| b.to_string());
+
+        let mut p_state = global_state.persistent_state_mut();
+        p_state.set_snapshot(role_controlled, bgm, scene_state, script);
+        p_state.save(save_slot);
     }
 
     fn move_role(
@@ -308,6 +361,17 @@ impl AdventureDirectorProps {
         delta_sec: f32,
     ) -> Option<ComRc<IDirector>> {
         self.sce_vm.update(scene_manager.clone(), ui, delta_sec);
+        if let Some(request) = self.sce_vm.global_state_mut().take_combat_request() {
+            let adv_director = scene_manager
+                .director()
+                .and_then(|d| d.query_interface::<IAdventureDirector>())
+                .unwrap();
+            return Some(ComRc::from_object(CombatDirector::new(
+                adv_director,
+                request,
+            )));
+        }
+
         if !self.sce_vm.global_state().adv_input_enabled() {
             return None;
         }
@@ -316,7 +380,7 @@ impl AdventureDirectorProps {
             return None;
         }
 
-        self.test_save();
+        self.test_save(scene_manager.clone());
 
         let moving_direction = {
             let input = self.input_engine.borrow_mut();
@@ -0,0 +1,214 @@
+use std::cell::RefCell;
+
+use crate::{
+    openpal3::{
+        combat::{Battle, BattleAction, BattleEvent, CombatRequest, CombatResult, Side},
+        comdef::{IAdventureDirector, ICombatDirectorImpl},
+    },
+    ComObject_CombatDirector,
+};
+
+use crosscom::ComRc;
+use imgui::{Condition, Ui};
+use log::debug;
+use radiance::comdef::{IDirector, IDirectorImpl, ISceneManager};
+
+/// Runs a battle on top of the adventure scene, then hands the result over to
+/// the adventure director that started it.
+pub struct CombatDirector {
+    props: RefCell<CombatDirectorProps>,
+}
+
+ComObject_CombatDirector!(super::CombatDirector);
+
+impl CombatDirector {
+    pub fn new(adv_director: ComRc<IAdventureDirector>, request: CombatRequest) -> Self {
+        Self {
+            props: RefCell::new(CombatDirectorProps {
+                adv_director,
+                request,
+                battle: None,
+            }),
+        }
+    }
+}
+
+impl IDirectorImpl for CombatDirector {
+    fn activate(&self, _scene_manager: ComRc<ISceneManager>) {
+        debug!("CombatDirector activated");
+        self.props.borrow_mut().start_battle();
+    }
+
+    fn update(
+        &self,
+        _scene_manager: ComRc<ISceneManager>,
+        ui: &Ui,
+        _delta_sec: f32,
+    ) -> Option<ComRc<IDirector>> {
+        self.props.borrow_mut().do_update(ui)
+    }
+}
+
+impl ICombatDirectorImpl for CombatDirector {
+    fn get(&self) -> &'static CombatDirector {
+        unsafe { &*(self as *const _) }
+    }
+}
+
+struct CombatDirectorProps {
+    adv_director: ComRc<IAdventureDirector>,
+    request: CombatRequest,
+    battle: Option<Battle>,
+}
+
+impl CombatDirectorProps {
+    fn start_battle(&mut self) {
+        let battle = self
+            .adv_director
+            .get()
+            .sce_vm_mut()
+            .global_state_mut()
+            .new_battle(&self.request);
+        self.battle = Some(battle);
+    }
+
+    fn retry(&mut self) {
+        self.adv_director
+            .get()
+            .sce_vm_mut()
+            .global_state_mut()
+            .update_party(|party, _| party.restore_all());
+        self.start_battle();
+    }
+
+    fn finish(&mut self) -> Option<ComRc<IDirector>> {
+        if let Some(battle) = &self.battle {
+            self.adv_director
+                .get()
+                .sce_vm_mut()
+                .global_state_mut()
+                .finish_combat(battle);
+        }
+
+        self.adv_director.query_interface::<IDirector>()
+    }
+
+    fn do_update(&mut self, ui: &Ui) -> Option<ComRc<IDirector>> {
+        let battle = self.battle.as_mut()?;
+        while let Some(actor) = battle.next_actor() {
+            if battle.combatants()[actor].side == Side::Party {
+                break;
+            }
+
+            let action = battle.auto_action(actor);
+            battle.act(action);
+        }
+
+        let mut action = None;
+        let mut retry = false;
+        let mut finished = false;
+
+        let window_size = ui.io().display_size;
+        ui.window("战斗")
+            .position([window_size[0] * 0.25, 20.], Condition::Always)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(format!("第 {} 回合", battle.round()));
+                ui.separator();
+                for combatant in battle.combatants() {
+                    ui.text(format!(
+                        "{} HP {}/{} MP {}/{}",
+                        combatant.name,
+                        combatant.hp,
+                        combatant.attributes.max_hp,
+                        combatant.mp,
+                        combatant.attributes.max_mp,
+                    ));
+                }
+
+                ui.separator();
+                let events = battle.events();
+                for event in &events[events.len().saturating_sub(5)..] {
+                    ui.text(describe_event(battle, event));
+                }
+
+                ui.separator();
+                match battle.result() {
+                    None => {
+                        if let Some(actor) = battle.current_actor() {
+                            ui.text(format!("{} 的回合", battle.combatants()[actor].name));
+                            for (i, target) in battle.combatants().iter().enumerate() {
+                                if target.side == Side::Monster
+                                    && target.alive()
+                                    && ui.button(format!("攻击 {}##{}", target.name, i))
+                                {
+                                    action = Some(BattleAction::Attack(i));
+                                }
+                            }
+
+                            if ui.button("防御") {
+                                action = Some(BattleAction::Defend);
+                            }
+
+                            if !battle.options().boss && ui.button("逃跑") {
+                                action = Some(BattleAction::Flee);
+                            }
+                        }
+                    }
+                    Some(result) => {
+                        let options = battle.options();
+                        if result == CombatResult::Lost
+                            && !options.must_fail
+                            && !options.not_game_over
+                        {
+                            ui.text("胜败乃兵家常事，大侠请重新来过");
+                            retry = ui.button("重新战斗");
+                        } else {
+                            ui.text(match result {
+                                CombatResult::Won => "战斗胜利",
+                                CombatResult::Lost => "战斗失败",
+                                CombatResult::Fled => "逃跑成功",
+                            });
+                            finished = ui.button("继续");
+                        }
+                    }
+                }
+            });
+
+        if let Some(action) = action {
+            battle.act(action);
+        }
+
+        if retry {
+            self.retry();
+        } else if finished {
+            return self.finish();
+        }
+
+        None
+    }
+}
+
+fn describe_event(battle: &Battle, event: &BattleEvent) -> String {
+    let name = |index: usize| battle.combatants()[index].name.as_str();
+    match event {
+        BattleEvent::RoundStarted(round) => format!("第 {} 回合开始", round),
+        BattleEvent::Attack {
+            actor,
+            target,
+            damage,
+            critical,
+        } => format!(
+            "{} 攻击 {}，造成 {} 点伤害{}",
+            name(*actor),
+            name(*target),
+            damage,
+            if *critical { "（暴击）" } else { "" }
+        ),
+        BattleEvent::Defend { actor } => format!("{} 防御", name(*actor)),
+        BattleEvent::FleeFailed { actor } => format!("{} 逃跑失败", name(*actor)),
+        BattleEvent::Fled => "逃跑成功".to_string(),
+        BattleEvent::Defeated { target } => format!("{} 被击倒", name(*target)),
+    }
+}
@@ -1,6 +1,8 @@
 mod adv_director;
+mod combat_director;
 
 pub use adv_director::AdventureDirector;
+pub use combat_director::CombatDirector;
 use crosscom::ComRc;
 use radiance::comdef::{IEntity, ISceneManager};
 
@@ -1,4 +1,5 @@
 pub mod asset_manager;
+pub mod combat;
 pub mod comdef;
 pub mod directors;
 pub mod loaders;
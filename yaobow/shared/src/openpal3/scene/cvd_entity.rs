@@ -7,7 +7,7 @@ use crate::ComObject_CvdModel;
 use crosscom::ComRc;
 use mini_fs::{MiniFs, StoreExt};
 use radiance::comdef::{IComponentImpl, IEntity};
-use radiance::math::{Vec2, Vec3};
+use radiance::math::{Quaternion, Vec2, Vec3};
 use radiance::rendering::{
     ComponentFactory, MaterialDef, SimpleMaterialDef, VertexBuffer, VertexComponents,
 };
@@ -28,7 +28,14 @@ pub fn create_entity_from_cvd_model<P: AsRef<Path>>(
         crosscom::ComRc::from_object(mesh_component),
     );
 
-    let cvd = cvd_load_from_file(vfs, path.as_ref()).unwrap();
+    let cvd = match cvd_load_from_file(vfs, path.as_ref()) {
+        Ok(cvd) => cvd,
+        Err(e) => {
+            log::error!("Cannot load cvd {:?}: {}", path.as_ref(), e);
+            return entity;
+        }
+    };
+
     for (_i, node) in cvd.models.iter().enumerate() {
         entity.attach(new_from_cvd_model_node(
             component_factory.clone(),
@@ -188,7 +195,7 @@ impl CvdModelComponent {
             .position_keyframes
             .as_ref()
             .and_then(|frame| frame.frames.get(0))
-            .and_then(|f| Some(f.position))
+            .and_then(|f| Some(Vec3::new(f.position.x, f.position.y, f.position.z)))
         {
             entity.transform().borrow_mut().translate_local(&p);
         }
@@ -203,7 +210,7 @@ impl CvdModelComponent {
             .rotation_keyframes
             .as_ref()
             .and_then(|frame| frame.frames.get(0))
-            .and_then(|f| Some(f.quaternion))
+            .and_then(|f| Some(to_quaternion(&f.quaternion)))
         {
             entity.transform().borrow_mut().rotate_quaternion_local(&q);
         }
@@ -213,8 +220,8 @@ impl CvdModelComponent {
             .as_ref()
             .and_then(|frame| frame.frames.get(0))
         {
-            let scale = frame.scale;
-            let q2 = frame.quaternion;
+            let scale = Vec3::new(frame.scale.x, frame.scale.y, frame.scale.z);
+            let q2 = to_quaternion(&frame.quaternion);
             let mut q3 = q2;
             q3.inverse();
 
@@ -228,6 +235,10 @@ impl CvdModelComponent {
     }
 }
 
+fn to_quaternion(q: &fileformats::rwbs::Quaternion) -> Quaternion {
+    Quaternion::new(q.x, q.y, q.z, q.w)
+}
+
 struct CvdMesh {
     material: MaterialDef,
     vertices: VertexBuffer,
@@ -274,7 +285,7 @@ impl CvdMesh {
                     vert.position.z,
                 )),
                 None,
-                Some(&Vec2::new(vert.tex_coord.x, vert.tex_coord.y)),
+                Some(&Vec2::new(vert.tex_coord.u, vert.tex_coord.v)),
                 None,
             );
         }
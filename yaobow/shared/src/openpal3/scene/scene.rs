@@ -3,6 +3,7 @@ use crate::openpal3::comdef::{IRoleController, IScnSceneComponentImpl};
 use crate::openpal3::loaders::nav_loader::{NavFile, NavMapPoint};
 use crate::openpal3::loaders::scn_loader::ScnFile;
 use crate::openpal3::scene::RoleController;
+use crate::openpal3::states::save_game::{ObjectSnapshot, RoleSnapshot, SceneSnapshot};
 use crate::ComObject_ScnSceneComponent;
 use crosscom::ComRc;
 use radiance::comdef::{IComponentImpl, IEntity, IScene};
@@ -79,7 +80,8 @@ impl ScnScene {
     }
 
     pub fn nav_min_coord(&self, layer: usize) -> Vec3 {
-        self.nav.nav_file.maps[layer].min_coord
+        let min_coord = &self.nav.nav_file.maps[layer].min_coord;
+        Vec3::new(min_coord.x, min_coord.y, min_coord.z)
     }
 
     pub fn nav_block_size(&self, layer: usize) -> (f32, f32) {
@@ -332,6 +334,66 @@ impl ScnScene {
         }
     }
 
+    /// Captures the roles and objects that scripts may have changed.
+    pub fn snapshot(&self) -> SceneSnapshot {
+        let mut snapshot = SceneSnapshot::default();
+        for entity in self.scene.entities() {
+            let id = entity
+                .name()
+                .strip_prefix("ROLE_")
+                .and_then(|id| id.parse().ok());
+            let controller = RoleController::get_role_controller(entity.clone());
+            if let (Some(id), Some(controller)) = (id, controller) {
+                let controller = controller.get();
+                snapshot.roles.push(RoleSnapshot {
+                    id,
+                    transform: *entity.transform().borrow().matrix(),
+                    active: controller.is_active(),
+                    visible: entity.visible(),
+                    nav_layer: controller.nav_layer(),
+                    proc_id: controller.proc_id(),
+                });
+            }
+        }
+
+        for entity in self.scene.root_entities() {
+            let id = entity
+                .name()
+                .strip_prefix("OBJECT_")
+                .and_then(|id| id.parse().ok());
+            if let Some(id) = id {
+                snapshot.objects.push(ObjectSnapshot {
+                    id,
+                    visible: entity.visible(),
+                });
+            }
+        }
+
+        snapshot
+    }
+
+    pub fn restore(&self, snapshot: &SceneSnapshot) {
+        for role in &snapshot.roles {
+            if let Some(entity) = self.get_role_entity(role.id) {
+                if let Some(controller) = RoleController::get_role_controller(entity.clone()) {
+                    let controller = controller.get();
+                    controller.set_active(role.active);
+                    controller.set_nav_layer(role.nav_layer);
+                    controller.set_proc_id(role.proc_id);
+                }
+
+                entity.transform().borrow_mut().set_matrix(role.transform);
+                entity.set_visible(role.visible);
+            }
+        }
+
+        for object in &snapshot.objects {
+            if let Some(entity) = self.get_root_object(object.id) {
+                entity.set_visible(object.visible);
+            }
+        }
+    }
+
     fn test_sphere_aabb(s: &Vec3, r: f32, aabb1: &Vec3, aabb2: &Vec3) -> bool {
         macro_rules! dist_sqr {
             ($s: expr, $min: expr, $max: expr) => {
@@ -373,6 +435,7 @@ impl ScnScene {
         }
 
         for obj in &self.scn_file.nodes {
+            let position = Vec3::new(obj.position.x, obj.position.y, obj.position.z);
             let mut entity: Option<ComRc<IEntity>> = None;
             if obj.nav_trigger_coord_min.0 != 0
                 || obj.nav_trigger_coord_min.1 != 0
@@ -437,7 +500,7 @@ impl ScnScene {
             match obj.node_type {
                 ScnNodeTypes::LADDER | ScnNodeTypes::LADDER2 => {
                     self.ladder_triggers.borrow_mut().push(LadderTrigger {
-                        position: obj.position,
+                        position,
                         nav_coord1: obj.ladder_nav_coord1,
                         nav_coord2: obj.ladder_nav_coord2,
                         switch_layer: obj.ladder_switch_layer != obj.nav_layer as i32
@@ -450,15 +513,23 @@ impl ScnScene {
                 | ScnNodeTypes::ITEM_TRIGGER3
                 | ScnNodeTypes::TRIGGER_SOURCE => {
                     self.item_triggers.borrow_mut().push(SceItemTrigger {
-                        coord: obj.position,
+                        coord: position,
                         sce_proc_id: obj.sce_proc_id,
                     });
                 }
                 ScnNodeTypes::TRIGGER_TARGET => {}
                 ScnNodeTypes::AABB_TRIGGER => {
                     self.aabb_triggers.borrow_mut().push(SceAabbTrigger {
-                        aabb_coord2: obj.aabb_trigger_coord2,
-                        aabb_coord1: obj.aabb_trigger_coord1,
+                        aabb_coord2: Vec3::new(
+                            obj.aabb_trigger_coord2.x,
+                            obj.aabb_trigger_coord2.y,
+                            obj.aabb_trigger_coord2.z,
+                        ),
+                        aabb_coord1: Vec3::new(
+                            obj.aabb_trigger_coord1.x,
+                            obj.aabb_trigger_coord1.y,
+                            obj.aabb_trigger_coord1.z,
+                        ),
                         sce_proc_id: obj.sce_proc_id,
                     });
                 }
@@ -466,7 +537,7 @@ impl ScnScene {
             }
 
             if let Some(p) = entity {
-                Self::apply_position_rotation(p.clone(), &obj.position, obj.rotation.to_radians());
+                Self::apply_position_rotation(p.clone(), &position, obj.rotation.to_radians());
                 entities.push(p);
             }
         }
@@ -571,7 +642,12 @@ impl Nav {
     pub fn new(nav_file: NavFile) -> Self {
         let mut block_sizes = vec![];
         for i in 0..nav_file.maps.len() {
-            let area = Vec3::sub(&nav_file.maps[i].max_coord, &nav_file.maps[i].min_coord);
+            let (max_coord, min_coord) = (&nav_file.maps[i].max_coord, &nav_file.maps[i].min_coord);
+            let area = Vec3::new(
+                max_coord.x - min_coord.x,
+                max_coord.y - min_coord.y,
+                max_coord.z - min_coord.z,
+            );
             let width = nav_file.maps[i].width + 1;
             let height = nav_file.maps[i].height + 1;
             block_sizes.push((area.x / width as f32, area.z / height as f32))
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Affection of each role towards the leading role, it decides the ending.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FavorTable {
+    favors: BTreeMap<i32, i32>,
+}
+
+impl FavorTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, role_id: i32) -> i32 {
+        self.favors.get(&role_id).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, role_id: i32, value: i32) {
+        self.favors.insert(role_id, value);
+    }
+
+    pub fn add(&mut self, role_id: i32, delta: i32) {
+        self.set(role_id, self.get(role_id).saturating_add(delta));
+    }
+
+    /// The role with the highest favor, the smaller role id wins a tie.
+    /// Returns -1 before any favor has been given.
+    pub fn favorite(&self) -> i32 {
+        self.favors
+            .iter()
+            .rev()
+            .max_by_key(|(_, favor)| **favor)
+            .map_or(-1, |(role_id, _)| *role_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.favors
+            .iter()
+            .map(|(role_id, favor)| (*role_id, *favor))
+    }
+}
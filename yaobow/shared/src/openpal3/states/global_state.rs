@@ -4,9 +4,16 @@ use std::{
     rc::Rc,
 };
 
-use crate::openpal3::asset_manager::AssetManager;
+use crate::openpal3::{
+    asset_manager::AssetManager,
+    combat::{self, Battle, CombatOptions, CombatRequest, CombatResult, MonsterTable},
+};
 
-use super::persistent_state::PersistentState;
+use super::{
+    inventory::ItemTable,
+    party::{Party, RoleTable},
+    persistent_state::PersistentState,
+};
 use common::store_ext::StoreExt2;
 use radiance::{
     audio::{AudioEngine, AudioMemorySource, AudioSource, AudioSourceState, Codec as AudioCodec},
@@ -24,9 +31,18 @@ pub struct GlobalState {
 
     asset_mgr: Rc<AssetManager>,
     bgm_source: Box<dyn AudioMemorySource>,
+    bgm_name: Option<String>,
     sound_sources: Vec<Rc<RefCell<Box<dyn AudioMemorySource>>>>,
     default_scene_bgm: HashMap<String, String>,
     video_player: Box<VideoPlayer>,
+    item_table: ItemTable,
+    role_table: RoleTable,
+    monster_table: MonsterTable,
+
+    combat_options: CombatOptions,
+    combat_request: Option<CombatRequest>,
+    combat_running: bool,
+    combat_result: Option<CombatResult>,
 
     pass_through_wall: bool,
 }
@@ -43,6 +59,9 @@ impl GlobalState {
         let music_path = "/basedata/basedata/datascript/music.txt";
         let default_scene_bgm =
             parse_music_mapping(asset_mgr.vfs().read_to_end_from_gbk(music_path).unwrap());
+        let item_table = asset_mgr.load_item_table();
+        let role_table = asset_mgr.load_role_table();
+        let monster_table = asset_mgr.load_monster_table();
 
         Self {
             persistent_state,
@@ -52,9 +71,17 @@ impl GlobalState {
             role_controlled: 0,
             asset_mgr,
             bgm_source,
+            bgm_name: None,
             sound_sources,
             default_scene_bgm,
             video_player,
+            item_table,
+            role_table,
+            monster_table,
+            combat_options: CombatOptions::default(),
+            combat_request: None,
+            combat_running: false,
+            combat_result: None,
             pass_through_wall: false,
         }
     }
@@ -79,6 +106,16 @@ impl GlobalState {
         let data = self.asset_mgr.load_music_data(name);
         self.bgm_source.set_data(data, AudioCodec::Mp3);
         self.bgm_source.play(true);
+        self.bgm_name = Some(name.to_string());
+    }
+
+    /// Name of the bgm that is playing, `None` after it is stopped.
+    pub fn bgm_name(&self) -> Option<&str> {
+        if self.bgm_source.state() == AudioSourceState::Stopped {
+            None
+        } else {
+            self.bgm_name.as_deref()
+        }
     }
 
     pub fn play_default_bgm(&mut self) {
@@ -140,6 +177,100 @@ impl GlobalState {
         self.persistent_state.borrow_mut()
     }
 
+    pub fn item_table(&self) -> &ItemTable {
+        &self.item_table
+    }
+
+    pub fn role_table(&self) -> &RoleTable {
+        &self.role_table
+    }
+
+    /// Gives access to the party together with the role table that seeds the
+    /// roles joining for the first time.
+    pub fn update_party<T>(&mut self, f: impl FnOnce(&mut Party, &RoleTable) -> T) -> T {
+        f(
+            self.persistent_state.borrow_mut().party_mut(),
+            &self.role_table,
+        )
+    }
+
+    pub fn monster_table(&self) -> &MonsterTable {
+        &self.monster_table
+    }
+
+    /// Options for the next battle.
+    pub fn combat_options_mut(&mut self) -> &mut CombatOptions {
+        &mut self.combat_options
+    }
+
+    /// Queues a battle for the director, the pending options are used up.
+    /// When none of the monsters is in the monster table the battle is
+    /// skipped and reported with `CombatOptions::skipped_result`, so
+    /// `GetCombat` doesn't send scripted fights down their losing branch.
+    pub fn start_combat(&mut self, monster_ids: Vec<i32>, boss: bool) {
+        let mut options = std::mem::take(&mut self.combat_options);
+        self.combat_result = None;
+        let monster_ids = self.monster_table.known_monsters(monster_ids);
+        if monster_ids.is_empty() {
+            log::error!("No monster to fight, the battle is skipped");
+            self.combat_result = Some(options.skipped_result());
+            return;
+        }
+
+        options.boss = boss;
+        self.combat_request = Some(CombatRequest {
+            monster_ids,
+            options,
+            seed: rand::random(),
+        });
+        self.combat_running = true;
+    }
+
+    /// Queues a battle against monsters that fit the level of the party.
+    pub fn start_random_combat(&mut self) {
+        let level = self
+            .persistent_state
+            .borrow()
+            .party()
+            .average_level(-1, &self.role_table);
+        let monster_ids = self.monster_table.random_encounter(level, rand::random());
+        self.start_combat(monster_ids, false);
+    }
+
+    pub fn take_combat_request(&mut self) -> Option<CombatRequest> {
+        self.combat_request.take()
+    }
+
+    /// True from `start_combat` until the battle has finished.
+    pub fn combat_running(&self) -> bool {
+        self.combat_running
+    }
+
+    pub fn new_battle(&mut self, request: &CombatRequest) -> Battle {
+        request.new_battle(
+            self.persistent_state.borrow_mut().party_mut(),
+            &self.role_table,
+            &self.monster_table,
+        )
+    }
+
+    /// Writes the party state back and reports the result to the scripts.
+    pub fn finish_combat(&mut self, battle: &Battle) {
+        combat::update_party(
+            battle,
+            self.persistent_state.borrow_mut().party_mut(),
+            &self.role_table,
+        );
+        self.combat_running = false;
+        self.combat_result = Some(battle.result().unwrap_or(CombatResult::Lost));
+    }
+
+    /// Result of the last battle, `None` when no battle has been fought since
+    /// the last `start_combat`.
+    pub fn combat_result(&self) -> Option<CombatResult> {
+        self.combat_result
+    }
+
     pub fn add_sound_source(&mut self, source: Rc<RefCell<Box<dyn AudioMemorySource>>>) {
         self.sound_sources.push(source);
     }
@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, HashMap};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_ITEM_COUNT: u32 = 99;
+pub const MAX_MONEY: i32 = 9_999_999;
+
+/// Items, money and clothes owned by the team.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    money: i32,
+    items: BTreeMap<i32, u32>,
+    clothes: Vec<i16>,
+
+    #[serde(default)]
+    untracked: bool,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inventory of saves made before items were tracked. The items picked
+    /// up before the save are unknown, so every item is reported as owned.
+    pub fn untracked() -> Self {
+        Self {
+            untracked: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_untracked(&self) -> bool {
+        self.untracked
+    }
+
+    pub fn money(&self) -> i32 {
+        self.money
+    }
+
+    /// Money never goes below zero or above `MAX_MONEY`, a negative amount
+    /// takes the money away.
+    pub fn add_money(&mut self, amount: i32) {
+        self.money = self.money.saturating_add(amount).clamp(0, MAX_MONEY);
+    }
+
+    pub fn item_count(&self, item_id: i32) -> u32 {
+        self.items.get(&item_id).copied().unwrap_or(0)
+    }
+
+    pub fn have_item(&self, item_id: i32) -> bool {
+        self.untracked || self.item_count(item_id) > 0
+    }
+
+    /// A negative count removes the items instead. The count of each item is
+    /// capped at `MAX_ITEM_COUNT`.
+    pub fn add_item(&mut self, item_id: i32, count: i32) {
+        let current = self.item_count(item_id) as i64;
+        let count = (current + count as i64).clamp(0, MAX_ITEM_COUNT as i64) as u32;
+        if count == 0 {
+            self.items.remove(&item_id);
+        } else {
+            self.items.insert(item_id, count);
+        }
+    }
+
+    pub fn remove_item(&mut self, item_id: i32) {
+        self.add_item(item_id, -1);
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (i32, u32)> + '_ {
+        self.items.iter().map(|(id, count)| (*id, *count))
+    }
+
+    pub fn give_cloth(&mut self, cloth_id: i16) {
+        if !self.clothes.contains(&cloth_id) {
+            self.clothes.push(cloth_id);
+        }
+    }
+
+    pub fn clothes(&self) -> &[i16] {
+        &self.clothes
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemInfo {
+    pub id: i32,
+    pub name: String,
+}
+
+/// Item definitions from `datascript/item.txt` in basedata. Every record line
+/// starts with the item id followed by its name, other columns are ignored.
+#[derive(Debug, Default)]
+pub struct ItemTable {
+    items: HashMap<i32, ItemInfo>,
+}
+
+impl ItemTable {
+    pub fn parse(content: &str) -> Self {
+        let record_regex = Regex::new(r"^\s*(\d+)[\s,]+([^\s,]+)").unwrap();
+        let items = content
+            .lines()
+            .filter_map(|line| record_regex.captures(line))
+            .filter_map(|c| {
+                let id = c[1].parse().ok()?;
+                let name = c[2].to_string();
+                Some((id, ItemInfo { id, name }))
+            })
+            .collect();
+
+        Self { items }
+    }
+
+    pub fn get(&self, item_id: i32) -> Option<&ItemInfo> {
+        self.items.get(&item_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Only ids listed in the table are valid, so an empty table, as left
+    /// when item.txt can't be loaded, rejects every item.
+    pub fn is_valid(&self, item_id: i32) -> bool {
+        self.items.contains_key(&item_id)
+    }
+}
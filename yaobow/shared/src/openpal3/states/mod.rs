@@ -1,2 +1,6 @@
+pub mod favor;
 pub mod global_state;
+pub mod inventory;
+pub mod party;
 pub mod persistent_state;
+pub mod save_game;
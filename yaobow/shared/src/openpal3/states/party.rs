@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+pub const LEADER_ROLE_ID: i32 = 0;
+pub const MAX_LEVEL: u32 = 99;
+
+/// Base attributes of a playable role.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoleAttributes {
+    pub level: u32,
+    pub max_hp: i32,
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub speed: i32,
+    pub luck: i32,
+}
+
+/// State of a role that has been in the party at least once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartyRole {
+    pub attributes: RoleAttributes,
+    pub hp: i32,
+    pub mp: i32,
+    pub skills: BTreeSet<i32>,
+}
+
+impl PartyRole {
+    pub fn new(attributes: RoleAttributes) -> Self {
+        Self {
+            hp: attributes.max_hp,
+            mp: attributes.max_mp,
+            attributes,
+            skills: BTreeSet::new(),
+        }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.attributes.level
+    }
+
+    pub fn restore(&mut self) {
+        self.hp = self.attributes.max_hp;
+        self.mp = self.attributes.max_mp;
+    }
+}
+
+/// Party members in joining order, the first one leads the team. Roles get
+/// their state from the role table the first time they are touched, and keep
+/// it when they leave and join again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Party {
+    members: Vec<i32>,
+    team_open: bool,
+    roles: BTreeMap<i32, PartyRole>,
+
+    #[serde(default)]
+    roster_known: bool,
+}
+
+impl Default for Party {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Party {
+    pub fn new() -> Self {
+        Self {
+            members: vec![LEADER_ROLE_ID],
+            team_open: true,
+            roles: BTreeMap::new(),
+            roster_known: true,
+        }
+    }
+
+    /// Party of saves made before the roster was tracked. Every role counts
+    /// as a member until a role joins or leaves the team.
+    pub fn unknown() -> Self {
+        Self {
+            roster_known: false,
+            ..Self::new()
+        }
+    }
+
+    pub fn roster_known(&self) -> bool {
+        self.roster_known
+    }
+
+    pub fn members(&self) -> &[i32] {
+        &self.members
+    }
+
+    pub fn in_team(&self, role_id: i32) -> bool {
+        !self.roster_known || self.members.contains(&role_id)
+    }
+
+    pub fn join(&mut self, role_id: i32) {
+        self.roster_known = true;
+        if !self.members.contains(&role_id) {
+            self.members.push(role_id);
+        }
+    }
+
+    pub fn leave(&mut self, role_id: i32) {
+        self.roster_known = true;
+        self.members.retain(|r| *r != role_id);
+    }
+
+    pub fn team_open(&self) -> bool {
+        self.team_open
+    }
+
+    pub fn set_team_open(&mut self, team_open: bool) {
+        self.team_open = team_open;
+    }
+
+    pub fn role(&self, role_id: i32) -> Option<&PartyRole> {
+        self.roles.get(&role_id)
+    }
+
+    /// Returns `None` for a role that is neither in the party state nor in
+    /// the role table.
+    pub fn role_mut(&mut self, role_id: i32, table: &RoleTable) -> Option<&mut PartyRole> {
+        if !self.roles.contains_key(&role_id) {
+            let role = PartyRole::new(table.get(role_id)?);
+            self.roles.insert(role_id, role);
+        }
+
+        self.roles.get_mut(&role_id)
+    }
+
+    fn update_role(&mut self, role_id: i32, table: &RoleTable, f: impl FnOnce(&mut PartyRole)) {
+        match self.role_mut(role_id, table) {
+            Some(role) => f(role),
+            None => log::error!("Role {} is not in the role table", role_id),
+        }
+    }
+
+    pub fn restore(&mut self, role_id: i32, table: &RoleTable) {
+        self.update_role(role_id, table, PartyRole::restore);
+    }
+
+    pub fn restore_all(&mut self) {
+        for role_id in &self.members {
+            if let Some(role) = self.roles.get_mut(role_id) {
+                role.restore();
+            }
+        }
+    }
+
+    pub fn level(&self, role_id: i32, table: &RoleTable) -> Option<u32> {
+        match self.roles.get(&role_id) {
+            Some(role) => Some(role.level()),
+            None => table.get(role_id).map(|a| a.level),
+        }
+    }
+
+    /// Average level of the members other than `except`, 1 for an empty team.
+    /// Members missing from the role table are left out.
+    pub fn average_level(&self, except: i32, table: &RoleTable) -> u32 {
+        let levels: Vec<u32> = self
+            .members
+            .iter()
+            .filter(|r| **r != except)
+            .filter_map(|r| self.level(*r, table))
+            .collect();
+
+        if levels.is_empty() {
+            1
+        } else {
+            levels.iter().sum::<u32>() / levels.len() as u32
+        }
+    }
+
+    /// Raises the role to the average level of the rest of the team plus
+    /// `bonus`, a role that is already stronger keeps its level.
+    pub fn level_up_to_average(&mut self, role_id: i32, bonus: i32, table: &RoleTable) {
+        let target = (self.average_level(role_id, table) as i64 + bonus as i64)
+            .clamp(1, MAX_LEVEL as i64) as u32;
+        self.update_role(role_id, table, |role| {
+            if role.attributes.level < target {
+                role.attributes.level = target;
+                role.restore();
+            }
+        });
+    }
+
+    pub fn add_skill(&mut self, role_id: i32, skill_id: i32, table: &RoleTable) {
+        self.update_role(role_id, table, |role| {
+            role.skills.insert(skill_id);
+        });
+    }
+}
+
+/// Base role attributes from `datascript/role.txt` in basedata. Every record
+/// line is assumed to hold the role id, name, level, max hp, max mp, attack,
+/// defense, speed and luck; the column order hasn't been checked against the
+/// retail file yet.
+#[derive(Debug, Default)]
+pub struct RoleTable {
+    roles: HashMap<i32, (String, RoleAttributes)>,
+}
+
+impl RoleTable {
+    pub fn parse(content: &str) -> Self {
+        let record_regex = Regex::new(r"^\s*(\d+)[\s,]+([^\s,]+)((?:[\s,]+-?\d+){7})").unwrap();
+        let roles = content
+            .lines()
+            .filter_map(|line| record_regex.captures(line))
+            .filter_map(|c| {
+                let values: Vec<i32> = c[3]
+                    .split(|ch: char| ch == ',' || ch.is_whitespace())
+                    .filter(|v| !v.is_empty())
+                    .map(|v| v.parse().ok())
+                    .collect::<Option<_>>()?;
+                let attributes = RoleAttributes {
+                    level: values[0].clamp(1, MAX_LEVEL as i32) as u32,
+                    max_hp: values[1],
+                    max_mp: values[2],
+                    attack: values[3],
+                    defense: values[4],
+                    speed: values[5],
+                    luck: values[6],
+                };
+                Some((c[1].parse().ok()?, (c[2].to_string(), attributes)))
+            })
+            .collect();
+
+        Self { roles }
+    }
+
+    pub fn name(&self, role_id: i32) -> Option<&str> {
+        self.roles.get(&role_id).map(|(name, _)| name.as_str())
+    }
+
+    pub fn get(&self, role_id: i32) -> Option<RoleAttributes> {
+        self.roles
+            .get(&role_id)
+            .map(|(_, attributes)| attributes.clone())
+    }
+
+    /// Role ids in ascending order.
+    pub fn ids(&self) -> Vec<i32> {
+        let mut ids: Vec<i32> = self.roles.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn len(&self) -> usize {
+        self.roles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roles.is_empty()
+    }
+}
@@ -1,27 +1,75 @@
+use anyhow::Context;
 use radiance::math::Vec3;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 use crate::ydirs;
 
+use super::{
+    favor::FavorTable,
+    inventory::Inventory,
+    party::Party,
+    save_game::{self, SaveGameError, SceneSnapshot, ScriptSnapshot, SAVE_VERSION},
+};
+
 #[derive(Serialize, Deserialize)]
 pub struct PersistentState {
+    #[serde(default)]
+    version: u32,
     app_name: String,
     global_vars: HashMap<i16, i32>,
     position: Vec3,
     scene: Option<String>,
     sub_scene: Option<String>,
+
+    #[serde(default)]
+    inventory: Inventory,
+
+    #[serde(default = "Party::unknown")]
+    party: Party,
+
+    #[serde(default)]
+    favors: FavorTable,
+
+    #[serde(default)]
+    role_controlled: i32,
+
+    #[serde(default)]
+    bgm: Option<String>,
+
+    #[serde(default)]
+    scene_state: Option<SceneSnapshot>,
+
+    #[serde(default)]
+    script: Option<ScriptSnapshot>,
+}
+
+/// Summary of a save slot shown in the slot browser.
+pub struct SaveSlotInfo {
+    pub slot: i32,
+    pub scene: Option<String>,
+    pub sub_scene: Option<String>,
+    pub modified: Option<SystemTime>,
 }
 
 impl PersistentState {
     pub fn new(app_name: String) -> Self {
         Self {
+            version: SAVE_VERSION,
             app_name,
             global_vars: HashMap::new(),
             position: Vec3::new(0., 0., 0.),
             scene: None,
             sub_scene: None,
+            inventory: Inventory::new(),
+            party: Party::new(),
+            favors: FavorTable::new(),
+            role_controlled: 0,
+            bgm: None,
+            scene_state: None,
+            script: None,
         }
     }
 
@@ -29,12 +77,45 @@ impl PersistentState {
         ydirs::save_dir().join(app_name)
     }
 
-    pub fn load(app_name: &str, slot: i32) -> Self {
-        let path = Self::get_data_dir(app_name)
+    fn get_slot_path(app_name: &str, slot: i32) -> PathBuf {
+        Self::get_data_dir(app_name)
             .join("Save")
-            .join(format!("{}.json", slot));
-        let content = std::fs::read_to_string(path).unwrap();
-        serde_json::from_str(&content).unwrap()
+            .join(format!("{}.json", slot))
+    }
+
+    pub fn load(app_name: &str, slot: i32) -> anyhow::Result<Self> {
+        let path = Self::get_slot_path(app_name, slot);
+        if !path.exists() {
+            return Err(SaveGameError::EmptySlot(slot).into());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read save {:?}", path))?;
+        Self::from_json(&content).with_context(|| format!("Cannot load save {:?}", path))
+    }
+
+    /// Parses a save of any supported version.
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        let save = save_game::migrate(serde_json::from_str(content)?)?;
+        Ok(serde_json::from_value(save)?)
+    }
+
+    /// Returns `None` for an empty slot.
+    pub fn slot_info(app_name: &str, slot: i32) -> Option<SaveSlotInfo> {
+        let path = Self::get_slot_path(app_name, slot);
+        let modified = std::fs::metadata(&path).ok()?.modified().ok();
+        let state = Self::load(app_name, slot);
+        let (scene, sub_scene) = match &state {
+            Ok(state) => (state.scene_name(), state.sub_scene_name()),
+            Err(_) => (None, None),
+        };
+
+        Some(SaveSlotInfo {
+            slot,
+            scene,
+            sub_scene,
+            modified,
+        })
     }
 
     pub fn save(&self, slot: i32) {
@@ -91,4 +172,63 @@ impl PersistentState {
     pub fn sub_scene_name(&self) -> Option<String> {
         self.sub_scene.clone()
     }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    pub fn party(&self) -> &Party {
+        &self.party
+    }
+
+    pub fn party_mut(&mut self) -> &mut Party {
+        &mut self.party
+    }
+
+    pub fn favors(&self) -> &FavorTable {
+        &self.favors
+    }
+
+    pub fn favors_mut(&mut self) -> &mut FavorTable {
+        &mut self.favors
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn role_controlled(&self) -> i32 {
+        self.role_controlled
+    }
+
+    pub fn bgm(&self) -> Option<&str> {
+        self.bgm.as_deref()
+    }
+
+    pub fn scene_state(&self) -> Option<&SceneSnapshot> {
+        self.scene_state.as_ref()
+    }
+
+    pub fn script(&self) -> Option<&ScriptSnapshot> {
+        self.script.as_ref()
+    }
+
+    /// Records the state that isn't kept up to date while playing, right
+    /// before saving.
+    pub fn set_snapshot(
+        &mut self,
+        role_controlled: i32,
+        bgm: Option<String>,
+        scene_state: Option<SceneSnapshot>,
+        script: Option<ScriptSnapshot>,
+    ) {
+        self.role_controlled = role_controlled;
+        self.bgm = bgm;
+        self.scene_state = scene_state;
+        self.script = script;
+    }
 }
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use radiance::math::Mat44;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{inventory::Inventory, party::Party};
+
+/// Version 1 saves only have the global vars, the leader position and the
+/// scene names. Version 2 adds the inventory, party, favors and the snapshots
+/// below. The items and party members of version 1 saves are unknown.
+pub const SAVE_VERSION: u32 = 2;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SaveGameError {
+    #[error("Save slot {0} is empty")]
+    EmptySlot(i32),
+
+    #[error("Save is not a json object")]
+    InvalidFormat,
+
+    #[error("Save version {0} is newer than the supported version {SAVE_VERSION}")]
+    UnsupportedVersion(u64),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleSnapshot {
+    pub id: i32,
+    pub transform: Mat44,
+    pub active: bool,
+    pub visible: bool,
+    pub nav_layer: usize,
+    pub proc_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectSnapshot {
+    pub id: i32,
+    pub visible: bool,
+}
+
+/// Roles and objects of the current scene.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneSnapshot {
+    pub roles: Vec<RoleSnapshot>,
+    pub objects: Vec<ObjectSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceProcSnapshot {
+    pub proc_id: u32,
+    pub program_counter: usize,
+    pub local_vars: HashMap<i16, i32>,
+    pub dlgsel: i32,
+}
+
+/// Procs still running in the scene sce, innermost last. Commands that were
+/// in progress are not saved, a resumed proc continues after them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptSnapshot {
+    pub sce_name: String,
+    pub procs: Vec<SceProcSnapshot>,
+}
+
+/// Upgrades a save of any older version to `SAVE_VERSION`.
+pub fn migrate(mut save: Value) -> Result<Value, SaveGameError> {
+    let object = save.as_object_mut().ok_or(SaveGameError::InvalidFormat)?;
+    let version = object.get("version").and_then(Value::as_u64).unwrap_or(1);
+    if version > SAVE_VERSION as u64 {
+        return Err(SaveGameError::UnsupportedVersion(version));
+    }
+
+    if version < 2 {
+        for key in ["scene_state", "script", "bgm"] {
+            object.entry(key).or_insert(Value::Null);
+        }
+
+        object.entry("role_controlled").or_insert(Value::from(0));
+        object
+            .entry("inventory")
+            .or_insert_with(|| serde_json::to_value(Inventory::untracked()).unwrap());
+        object
+            .entry("party")
+            .or_insert_with(|| serde_json::to_value(Party::unknown()).unwrap());
+    }
+
+    object.insert("version".to_string(), Value::from(SAVE_VERSION));
+    Ok(save)
+}
@@ -225,7 +225,13 @@ impl Pal4AppContext {
         if let Some(data) = &self.camera_data {
             let camera_data = data.get_camera_data(name);
             if let Some(camera_data) = camera_data {
-                let position = camera_data.get_position();
+                let position = match camera_data.get_position() {
+                    Some(position) => position,
+                    None => {
+                        log::warn!("run_camera: {} has no position", name);
+                        return;
+                    }
+                };
                 let look_at = camera_data.get_look_at();
                 log::debug!("camera_data: {:?} {:?}", position, look_at);
                 // if camera_data.is_instant() {
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, io::Cursor, rc::Rc};
+use std::{collections::HashMap, io::Cursor, rc::Rc, sync::Arc};
 
 use common::store_ext::StoreExt2;
 use crosscom::ComRc;
@@ -16,14 +16,14 @@ use crate::loaders::{
 };
 
 pub struct AssetLoader {
-    vfs: Rc<MiniFs>,
+    vfs: Arc<MiniFs>,
     component_factory: Rc<dyn ComponentFactory>,
     pub index: HashMap<u32, AssetItem>,
     texture_resolver: Pal5TextureResolver,
 }
 
 impl AssetLoader {
-    pub fn new(component_factory: Rc<dyn ComponentFactory>, vfs: Rc<MiniFs>) -> Rc<Self> {
+    pub fn new(component_factory: Rc<dyn ComponentFactory>, vfs: Arc<MiniFs>) -> Rc<Self> {
         let index = load_index(&vfs);
         Rc::new(Self {
             component_factory,
@@ -18,7 +18,7 @@ use self::scripting::create_lua_vm;
 
 pub fn test() {
     let game = GameType::SWDHC;
-    let vfs = init_virtual_fs("F:\\SteamLibrary\\steamapps\\common\\SWDHC", None);
+    let (vfs, _) = init_virtual_fs("F:\\SteamLibrary\\steamapps\\common\\SWDHC", None);
     let asset_loader = asset_loader::AssetLoader::new(vfs, game);
     // let script = asset_loader.load_main_script().unwrap();
     // let vm = Lua5032Vm::new(script, "initiatelua").unwrap();
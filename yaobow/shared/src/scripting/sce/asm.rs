@@ -0,0 +1,879 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write as _,
+    io::{Cursor, Read},
+};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use encoding::{DecoderTrap, EncoderTrap, Encoding};
+
+use crate::openpal3::loaders::sce_loader::{
+    SceFile, SceLocalVar, SceProc, SceProcHeader, SceWriteError,
+};
+
+use super::opcodes::{SceOpcode, SceParamType};
+
+/// Textual form of sce scripts, one command per line:
+///
+/// ```text
+/// proc 1000 "main"
+///     local 1 x"0000"
+/// L_0000:
+///     Dlg "..."
+///     GEQ2 3, 4
+///     Goto L_0000
+/// end
+/// ```
+///
+/// Strings that don't survive a GBK round trip are written as raw bytes
+/// `x"..."`, non-finite floats as `f#<bits>`, and a flag other than the
+/// default one as `Command/<flag>`. Bytes that can't be decoded as commands
+/// are kept in a `.bytes` line, so `assemble(disassemble(sce))` always
+/// reproduces the original proc bodies. Proc and header names whose stored
+/// bytes differ from a plain NUL terminated encoding are followed by those
+/// bytes, e.g. `proc 1000 "main" x"6d61696e00ff"`. Procs are listed in the
+/// order their bodies are stored; when the header table uses another order it
+/// is given by a leading `headers 1001, 1000` line.
+#[derive(thiserror::Error, Debug)]
+pub enum SceAsmError {
+    #[error("Line {0}: {1}")]
+    Syntax(usize, String),
+
+    #[error("Line {0}: unknown command {1}")]
+    UnknownCommand(usize, String),
+
+    #[error("Line {0}: undefined label {1}")]
+    UndefinedLabel(usize, String),
+
+    #[error("Line {0}: duplicated label {1}")]
+    DuplicatedLabel(usize, String),
+
+    #[error("Line {0}: {1} expects {2} argument(s)")]
+    ArgumentCount(usize, String, usize),
+
+    #[error("Line {0}: unable to encode {1} in GBK")]
+    Encoding(usize, String),
+
+    #[error("Line {0}: {1} is too long")]
+    TooLong(usize, String),
+
+    #[error(transparent)]
+    Write(#[from] SceWriteError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceString {
+    Text(String),
+    Raw(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceArg {
+    I16(i16),
+    I32(i32),
+    U32(u32),
+    F32(f32),
+    Str(SceString),
+    List(Vec<(u8, SceString)>),
+    Label(u32),
+}
+
+#[derive(Debug, Clone)]
+pub enum SceLine {
+    Command {
+        offset: usize,
+        opcode: &'static SceOpcode,
+        flag: i16,
+        args: Vec<SceArg>,
+    },
+    Bytes {
+        offset: usize,
+        data: Vec<u8>,
+    },
+}
+
+pub fn disassemble(sce: &SceFile) -> String {
+    let mut output = String::new();
+    let layout = sce.layout_order();
+    if layout
+        .iter()
+        .zip(&sce.proc_headers)
+        .any(|(a, b)| a.id != b.id)
+    {
+        let ids: Vec<String> = sce.proc_headers.iter().map(|h| h.id.to_string()).collect();
+        let _ = writeln!(output, "headers {}\n", ids.join(", "));
+    }
+
+    for header in layout {
+        if let Some(proc) = sce.procs.get(&header.id) {
+            disassemble_proc(proc, Some(header), &mut output);
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+pub fn disassemble_proc(proc: &SceProc, header: Option<&SceProcHeader>, output: &mut String) {
+    let lines = decode_proc(&proc.inst);
+    let starts: BTreeSet<usize> = lines
+        .iter()
+        .map(|l| match l {
+            SceLine::Command { offset, .. } | SceLine::Bytes { offset, .. } => *offset,
+        })
+        .collect();
+
+    let mut targets = BTreeSet::new();
+    for line in &lines {
+        if let SceLine::Command { args, .. } = line {
+            for arg in args {
+                if let SceArg::Label(target) = arg {
+                    if starts.contains(&(*target as usize)) {
+                        targets.insert(*target as usize);
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = writeln!(
+        output,
+        "proc {} {}{}",
+        proc.id,
+        format_text(&proc.name),
+        format_raw_name(proc.name_bytes().ok(), proc.default_name_bytes().ok())
+    );
+    if let Some(header) = header {
+        let raw_name = format_raw_name(header.name_bytes().ok(), header.default_name_bytes().ok());
+        if header.name != proc.name || !raw_name.is_empty() {
+            let _ = writeln!(
+                output,
+                "    header {}{}",
+                format_text(&header.name),
+                raw_name
+            );
+        }
+    }
+
+    for var in &proc.local_vars {
+        let _ = writeln!(
+            output,
+            "    local {} {}",
+            var.unknown,
+            format_raw(&var.unknown_vec)
+        );
+    }
+
+    for line in &lines {
+        match line {
+            SceLine::Command {
+                offset,
+                opcode,
+                flag,
+                args,
+            } => {
+                if targets.contains(offset) {
+                    let _ = writeln!(output, "{}:", label_name(*offset));
+                }
+
+                let _ = write!(output, "    {}", opcode.name);
+                if *flag != opcode.default_flag() {
+                    let _ = write!(output, "/{}", flag);
+                }
+
+                let args: Vec<String> = args.iter().map(|arg| format_arg(arg, &targets)).collect();
+                if !args.is_empty() {
+                    let _ = write!(output, " {}", args.join(", "));
+                }
+
+                output.push('\n');
+            }
+            SceLine::Bytes { offset, data } => {
+                if targets.contains(offset) {
+                    let _ = writeln!(output, "{}:", label_name(*offset));
+                }
+
+                let _ = writeln!(output, "    .bytes {}", format_raw(data));
+            }
+        }
+    }
+
+    output.push_str("end\n");
+}
+
+/// Decodes the proc body into commands. Decoding stops at the first unknown
+/// command and the rest of the body is returned as raw bytes.
+pub fn decode_proc(inst: &[u8]) -> Vec<SceLine> {
+    let mut lines = vec![];
+    let mut offset = 0;
+    while offset < inst.len() {
+        let mut cursor = Cursor::new(&inst[offset..]);
+        match decode_command(&mut cursor) {
+            Some((opcode, flag, args)) => {
+                lines.push(SceLine::Command {
+                    offset,
+                    opcode,
+                    flag,
+                    args,
+                });
+                offset += cursor.position() as usize;
+            }
+            None => {
+                lines.push(SceLine::Bytes {
+                    offset,
+                    data: inst[offset..].to_vec(),
+                });
+                break;
+            }
+        }
+    }
+
+    lines
+}
+
+fn decode_command(cursor: &mut Cursor<&[u8]>) -> Option<(&'static SceOpcode, i16, Vec<SceArg>)> {
+    let id = cursor.read_i16::<LittleEndian>().ok()?;
+    let flag = cursor.read_i16::<LittleEndian>().ok()?;
+    let opcode = SceOpcode::find(id, flag)?;
+    let mut args = vec![];
+    for param in opcode.params {
+        let arg = match param {
+            SceParamType::I16 => SceArg::I16(cursor.read_i16::<LittleEndian>().ok()?),
+            SceParamType::I32 => SceArg::I32(cursor.read_i32::<LittleEndian>().ok()?),
+            SceParamType::U32 => SceArg::U32(cursor.read_u32::<LittleEndian>().ok()?),
+            SceParamType::F32 => SceArg::F32(cursor.read_f32::<LittleEndian>().ok()?),
+            SceParamType::Label => SceArg::Label(cursor.read_u32::<LittleEndian>().ok()?),
+            SceParamType::Str => SceArg::Str(decode_string(cursor)?),
+            SceParamType::List => {
+                let count = cursor.read_u16::<LittleEndian>().ok()?;
+                let mut items = vec![];
+                for _ in 0..count {
+                    let prefix = cursor.read_u8().ok()?;
+                    items.push((prefix, decode_string(cursor)?));
+                }
+
+                SceArg::List(items)
+            }
+        };
+
+        args.push(arg);
+    }
+
+    Some((opcode, flag, args))
+}
+
+fn decode_string(cursor: &mut Cursor<&[u8]>) -> Option<SceString> {
+    let len = cursor.read_u16::<LittleEndian>().ok()?;
+    let mut data = vec![0; len as usize];
+    cursor.read_exact(&mut data).ok()?;
+
+    // Strings are stored with a terminating NUL which is counted in the length
+    if let Some((0, text)) = data.split_last() {
+        if !text.contains(&0) {
+            if let Ok(decoded) = encoding::all::GBK.decode(text, DecoderTrap::Strict) {
+                let encoded = encoding::all::GBK.encode(&decoded, EncoderTrap::Strict);
+                if encoded.as_deref() == Ok(text) {
+                    return Some(SceString::Text(decoded));
+                }
+            }
+        }
+    }
+
+    Some(SceString::Raw(data))
+}
+
+fn label_name(offset: usize) -> String {
+    format!("L_{:04X}", offset)
+}
+
+fn format_arg(arg: &SceArg, targets: &BTreeSet<usize>) -> String {
+    match arg {
+        SceArg::I16(v) => v.to_string(),
+        SceArg::I32(v) => v.to_string(),
+        SceArg::U32(v) => v.to_string(),
+        SceArg::F32(v) if v.is_finite() => format!("{:?}", v),
+        SceArg::F32(v) => format!("f#{:08x}", v.to_bits()),
+        SceArg::Str(s) => format_string(s),
+        SceArg::List(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|(prefix, s)| format!("{} {}", prefix, format_string(s)))
+                .collect();
+            format!("[{}]", items.join(", "))
+        }
+        SceArg::Label(target) if targets.contains(&(*target as usize)) => {
+            label_name(*target as usize)
+        }
+        SceArg::Label(target) => target.to_string(),
+    }
+}
+
+fn format_string(s: &SceString) -> String {
+    match s {
+        SceString::Text(text) => format_text(text),
+        SceString::Raw(data) => format_raw(data),
+    }
+}
+
+fn format_text(text: &str) -> String {
+    let mut output = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(output, "\\u{{{:x}}}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+    output
+}
+
+/// Formats the stored name bytes as a trailing raw string, or nothing when
+/// they are the plain encoding of the name.
+fn format_raw_name(stored: Option<Vec<u8>>, default: Option<Vec<u8>>) -> String {
+    match stored {
+        Some(stored) if Some(&stored) != default.as_ref() => format!(" {}", format_raw(&stored)),
+        _ => String::new(),
+    }
+}
+
+fn format_raw(data: &[u8]) -> String {
+    let mut output = String::from("x\"");
+    for b in data {
+        let _ = write!(output, "{:02x}", b);
+    }
+
+    output.push('"');
+    output
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Text(String),
+    Raw(Vec<u8>),
+    FloatBits(u32),
+    Comma,
+    Colon,
+    Slash,
+    LBracket,
+    RBracket,
+}
+
+fn tokenize(line_no: usize, line: &str) -> Result<Vec<Token>, SceAsmError> {
+    let syntax = |msg: &str| SceAsmError::Syntax(line_no, msg.to_string());
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ';' => break,
+            c if c.is_whitespace() => i += 1,
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            '/' => {
+                tokens.push(Token::Slash);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            '"' => {
+                let (text, next) = parse_quoted(&chars, i + 1).ok_or(syntax("bad string"))?;
+                tokens.push(Token::Text(text));
+                i = next;
+            }
+            'x' if chars.get(i + 1) == Some(&'"') => {
+                let end = (i + 2..chars.len())
+                    .find(|&j| chars[j] == '"')
+                    .ok_or(syntax("unterminated bytes"))?;
+                let hex: String = chars[i + 2..end].iter().collect();
+                tokens.push(Token::Raw(parse_hex(&hex).ok_or(syntax("bad bytes"))?));
+                i = end + 1;
+            }
+            'f' if chars.get(i + 1) == Some(&'#') => {
+                let end = (i + 2..chars.len())
+                    .find(|&j| !chars[j].is_ascii_hexdigit())
+                    .unwrap_or(chars.len());
+                let hex: String = chars[i + 2..end].iter().collect();
+                let bits = u32::from_str_radix(&hex, 16).map_err(|_| syntax("bad float"))?;
+                tokens.push(Token::FloatBits(bits));
+                i = end;
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !(chars[j].is_ascii_alphanumeric() || "._-+".contains(chars[j])))
+                    .unwrap_or(chars.len());
+                tokens.push(Token::Number(chars[i..end].iter().collect()));
+                i = end;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '.' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '_'))
+                    .unwrap_or(chars.len());
+                tokens.push(Token::Ident(chars[i..end].iter().collect()));
+                i = end;
+            }
+            _ => return Err(syntax(&format!("unexpected character {}", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_quoted(chars: &[char], mut i: usize) -> Option<(String, usize)> {
+    let mut text = String::new();
+    while i < chars.len() {
+        match chars[i] {
+            '"' => return Some((text, i + 1)),
+            '\\' => {
+                i += 1;
+                match chars.get(i)? {
+                    'n' => text.push('\n'),
+                    'r' => text.push('\r'),
+                    't' => text.push('\t'),
+                    'u' => {
+                        let end = (i..chars.len()).find(|&j| chars[j] == '}')?;
+                        let hex: String = chars[i + 2..end].iter().collect();
+                        text.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                        i = end;
+                    }
+                    c => text.push(*c),
+                }
+            }
+            c => text.push(c),
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+enum AsmArg {
+    Value(SceArg),
+    Label(String),
+}
+
+enum AsmItem {
+    Label(usize, String),
+    Command {
+        line_no: usize,
+        opcode: &'static SceOpcode,
+        flag: i16,
+        args: Vec<AsmArg>,
+    },
+    Bytes(Vec<u8>),
+}
+
+struct AsmProc {
+    id: u32,
+    name: String,
+    raw_name: Vec<u8>,
+    header_name: Option<(String, Vec<u8>)>,
+    local_vars: Vec<SceLocalVar>,
+    items: Vec<AsmItem>,
+}
+
+pub fn assemble(text: &str) -> Result<SceFile, SceAsmError> {
+    let mut procs: Vec<AsmProc> = vec![];
+    let mut header_ids: Option<(usize, Vec<u32>)> = None;
+    let mut current: Option<AsmProc> = None;
+    let mut last_line = 0;
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        last_line = line_no;
+        let tokens = tokenize(line_no, line)?;
+        let syntax = |msg: &str| SceAsmError::Syntax(line_no, msg.to_string());
+        match tokens.as_slice() {
+            [] => continue,
+            [Token::Ident(kw), Token::Number(id), Token::Text(name), raw @ ..] if kw == "proc" => {
+                if current.is_some() {
+                    return Err(syntax("missing end"));
+                }
+
+                current = Some(AsmProc {
+                    id: id.parse().map_err(|_| syntax("bad proc id"))?,
+                    name: name.clone(),
+                    raw_name: parse_raw_name(line_no, raw)?,
+                    header_name: None,
+                    local_vars: vec![],
+                    items: vec![],
+                });
+                continue;
+            }
+            [Token::Ident(kw), rest @ ..] if kw == "headers" && current.is_none() => {
+                let mut ids = vec![];
+                for (i, token) in rest.iter().enumerate() {
+                    match token {
+                        Token::Comma if i % 2 == 1 => {}
+                        Token::Number(id) if i % 2 == 0 => {
+                            ids.push(id.parse().map_err(|_| syntax("bad proc id"))?)
+                        }
+                        _ => return Err(syntax("bad headers")),
+                    }
+                }
+
+                header_ids = Some((line_no, ids));
+                continue;
+            }
+            [Token::Ident(kw)] if kw == "end" => {
+                procs.push(current.take().ok_or(syntax("unexpected end"))?);
+                continue;
+            }
+            _ => {}
+        }
+
+        let proc = current.as_mut().ok_or(syntax("expected proc"))?;
+        match tokens.as_slice() {
+            [Token::Ident(kw), Token::Text(name), raw @ ..] if kw == "header" => {
+                proc.header_name = Some((name.clone(), parse_raw_name(line_no, raw)?));
+            }
+            [Token::Ident(kw), Token::Number(unknown), Token::Raw(data)] if kw == "local" => {
+                proc.local_vars.push(SceLocalVar {
+                    unknown: unknown.parse().map_err(|_| syntax("bad local"))?,
+                    unknown_vec: data.clone(),
+                });
+            }
+            [Token::Ident(kw), Token::Raw(data)] if kw == ".bytes" => {
+                proc.items.push(AsmItem::Bytes(data.clone()));
+            }
+            [Token::Ident(label), Token::Colon] => {
+                proc.items.push(AsmItem::Label(line_no, label.clone()));
+            }
+            [Token::Ident(name), rest @ ..] => {
+                let opcode = SceOpcode::find_by_name(name)
+                    .ok_or(SceAsmError::UnknownCommand(line_no, name.clone()))?;
+                let (flag, rest) = match rest {
+                    [Token::Slash, Token::Number(flag), rest @ ..] => {
+                        (flag.parse().map_err(|_| syntax("bad flag"))?, rest)
+                    }
+                    rest => (opcode.default_flag(), rest),
+                };
+
+                let args = parse_args(line_no, opcode, rest)?;
+                proc.items.push(AsmItem::Command {
+                    line_no,
+                    opcode,
+                    flag,
+                    args,
+                });
+            }
+            _ => return Err(syntax("unexpected tokens")),
+        }
+    }
+
+    if current.is_some() {
+        return Err(SceAsmError::Syntax(last_line, "missing end".to_string()));
+    }
+
+    let mut sce = SceFile {
+        proc_num: u16::try_from(procs.len())
+            .map_err(|_| SceAsmError::TooLong(last_line, "proc list".to_string()))?,
+        proc_headers: vec![],
+        procs: HashMap::new(),
+    };
+
+    // The provisional offsets keep the bodies in the order they are listed
+    for (i, proc) in procs.into_iter().enumerate() {
+        let inst = encode_items(&proc.items)?;
+        let (header_name, header_raw_name) = proc
+            .header_name
+            .unwrap_or_else(|| (proc.name.clone(), vec![]));
+        sce.proc_headers.push(SceProcHeader {
+            id: proc.id,
+            offset: i as u32,
+            name: header_name,
+            raw_name: header_raw_name,
+        });
+        sce.procs.insert(
+            proc.id,
+            SceProc {
+                id: proc.id,
+                name: proc.name,
+                raw_name: proc.raw_name,
+                local_vars: proc.local_vars,
+                inst,
+            },
+        );
+    }
+
+    if let Some((line_no, ids)) = header_ids {
+        let mut headers = vec![];
+        for id in ids {
+            let index = sce
+                .proc_headers
+                .iter()
+                .position(|h| h.id == id)
+                .ok_or(SceAsmError::Syntax(line_no, format!("unknown proc {}", id)))?;
+            headers.push(sce.proc_headers.remove(index));
+        }
+
+        if !sce.proc_headers.is_empty() {
+            return Err(SceAsmError::Syntax(
+                line_no,
+                "headers doesn't list every proc".to_string(),
+            ));
+        }
+
+        sce.proc_headers = headers;
+    }
+
+    let offsets = sce.proc_offsets()?;
+    for (header, offset) in sce.proc_headers.iter_mut().zip(offsets) {
+        header.offset = offset;
+    }
+
+    Ok(sce)
+}
+
+fn parse_raw_name(line_no: usize, tokens: &[Token]) -> Result<Vec<u8>, SceAsmError> {
+    match tokens {
+        [] => Ok(vec![]),
+        [Token::Raw(data)] => Ok(data.clone()),
+        _ => Err(SceAsmError::Syntax(line_no, "bad name".to_string())),
+    }
+}
+
+fn parse_args(
+    line_no: usize,
+    opcode: &'static SceOpcode,
+    mut tokens: &[Token],
+) -> Result<Vec<AsmArg>, SceAsmError> {
+    let count_error =
+        || SceAsmError::ArgumentCount(line_no, opcode.name.to_string(), opcode.params.len());
+    let syntax = |msg: &str| SceAsmError::Syntax(line_no, msg.to_string());
+
+    let mut args = vec![];
+    for (i, param) in opcode.params.iter().enumerate() {
+        if i > 0 {
+            match tokens {
+                [Token::Comma, rest @ ..] => tokens = rest,
+                _ => return Err(count_error()),
+            }
+        }
+
+        let (arg, rest) = match (param, tokens) {
+            (SceParamType::I16, [Token::Number(n), rest @ ..]) => (
+                AsmArg::Value(SceArg::I16(n.parse().map_err(|_| syntax("bad i16"))?)),
+                rest,
+            ),
+            (SceParamType::I32, [Token::Number(n), rest @ ..]) => (
+                AsmArg::Value(SceArg::I32(n.parse().map_err(|_| syntax("bad i32"))?)),
+                rest,
+            ),
+            (SceParamType::U32, [Token::Number(n), rest @ ..]) => (
+                AsmArg::Value(SceArg::U32(n.parse().map_err(|_| syntax("bad u32"))?)),
+                rest,
+            ),
+            (SceParamType::F32, [Token::Number(n), rest @ ..]) => (
+                AsmArg::Value(SceArg::F32(n.parse().map_err(|_| syntax("bad f32"))?)),
+                rest,
+            ),
+            (SceParamType::F32, [Token::FloatBits(bits), rest @ ..]) => {
+                (AsmArg::Value(SceArg::F32(f32::from_bits(*bits))), rest)
+            }
+            (SceParamType::Label, [Token::Number(n), rest @ ..]) => (
+                AsmArg::Value(SceArg::Label(n.parse().map_err(|_| syntax("bad offset"))?)),
+                rest,
+            ),
+            (SceParamType::Label, [Token::Ident(label), rest @ ..]) => {
+                (AsmArg::Label(label.clone()), rest)
+            }
+            (SceParamType::Str, [Token::Text(text), rest @ ..]) => (
+                AsmArg::Value(SceArg::Str(SceString::Text(text.clone()))),
+                rest,
+            ),
+            (SceParamType::Str, [Token::Raw(data), rest @ ..]) => (
+                AsmArg::Value(SceArg::Str(SceString::Raw(data.clone()))),
+                rest,
+            ),
+            (SceParamType::List, [Token::LBracket, rest @ ..]) => {
+                let (items, rest) = parse_list(line_no, rest)?;
+                (AsmArg::Value(SceArg::List(items)), rest)
+            }
+            (_, []) => return Err(count_error()),
+            _ => return Err(syntax(&format!("bad argument {}", i + 1))),
+        };
+
+        args.push(arg);
+        tokens = rest;
+    }
+
+    if tokens.is_empty() {
+        Ok(args)
+    } else {
+        Err(count_error())
+    }
+}
+
+fn parse_list(
+    line_no: usize,
+    mut tokens: &[Token],
+) -> Result<(Vec<(u8, SceString)>, &[Token]), SceAsmError> {
+    let syntax = |msg: &str| SceAsmError::Syntax(line_no, msg.to_string());
+    let mut items = vec![];
+    loop {
+        match tokens {
+            [Token::RBracket, rest @ ..] => return Ok((items, rest)),
+            [Token::Comma, rest @ ..] if !items.is_empty() => tokens = rest,
+            [Token::Number(prefix), s, rest @ ..] => {
+                let prefix = prefix.parse().map_err(|_| syntax("bad list item"))?;
+                let s = match s {
+                    Token::Text(text) => SceString::Text(text.clone()),
+                    Token::Raw(data) => SceString::Raw(data.clone()),
+                    _ => return Err(syntax("bad list item")),
+                };
+
+                items.push((prefix, s));
+                tokens = rest;
+            }
+            _ => return Err(syntax("bad list")),
+        }
+    }
+}
+
+fn encode_items(items: &[AsmItem]) -> Result<Vec<u8>, SceAsmError> {
+    // The commands have fixed sizes once parsed, so labels can be resolved
+    // before anything is written
+    let mut labels = HashMap::new();
+    let mut offset = 0;
+    for item in items {
+        match item {
+            AsmItem::Label(line_no, label) => {
+                if labels.insert(label.clone(), offset as u32).is_some() {
+                    return Err(SceAsmError::DuplicatedLabel(*line_no, label.clone()));
+                }
+            }
+            AsmItem::Command { line_no, args, .. } => {
+                offset += 4;
+                for arg in args {
+                    offset += arg_size(*line_no, arg)?;
+                }
+            }
+            AsmItem::Bytes(data) => offset += data.len(),
+        }
+    }
+
+    let mut output = vec![];
+    for item in items {
+        match item {
+            AsmItem::Label(..) => {}
+            AsmItem::Bytes(data) => output.extend_from_slice(data),
+            AsmItem::Command {
+                line_no,
+                opcode,
+                flag,
+                args,
+            } => {
+                output.write_i16::<LittleEndian>(opcode.id).unwrap();
+                output.write_i16::<LittleEndian>(*flag).unwrap();
+                for arg in args {
+                    let arg = match arg {
+                        AsmArg::Value(v) => v.clone(),
+                        AsmArg::Label(label) => SceArg::Label(
+                            *labels
+                                .get(label)
+                                .ok_or(SceAsmError::UndefinedLabel(*line_no, label.clone()))?,
+                        ),
+                    };
+
+                    encode_arg(*line_no, &arg, &mut output)?;
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn arg_size(line_no: usize, arg: &AsmArg) -> Result<usize, SceAsmError> {
+    Ok(match arg {
+        AsmArg::Label(_) => 4,
+        AsmArg::Value(SceArg::I16(_)) => 2,
+        AsmArg::Value(SceArg::I32(_) | SceArg::U32(_) | SceArg::F32(_) | SceArg::Label(_)) => 4,
+        AsmArg::Value(SceArg::Str(s)) => 2 + string_bytes(line_no, s)?.len(),
+        AsmArg::Value(SceArg::List(items)) => {
+            let mut size = 2;
+            for (_, s) in items {
+                size += 3 + string_bytes(line_no, s)?.len();
+            }
+
+            size
+        }
+    })
+}
+
+fn encode_arg(line_no: usize, arg: &SceArg, output: &mut Vec<u8>) -> Result<(), SceAsmError> {
+    match arg {
+        SceArg::I16(v) => output.write_i16::<LittleEndian>(*v).unwrap(),
+        SceArg::I32(v) => output.write_i32::<LittleEndian>(*v).unwrap(),
+        SceArg::U32(v) | SceArg::Label(v) => output.write_u32::<LittleEndian>(*v).unwrap(),
+        SceArg::F32(v) => output.write_f32::<LittleEndian>(*v).unwrap(),
+        SceArg::Str(s) => encode_string(line_no, s, output)?,
+        SceArg::List(items) => {
+            let len = u16::try_from(items.len())
+                .map_err(|_| SceAsmError::TooLong(line_no, "list".to_string()))?;
+            output.write_u16::<LittleEndian>(len).unwrap();
+            for (prefix, s) in items {
+                output.push(*prefix);
+                encode_string(line_no, s, output)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_string(line_no: usize, s: &SceString, output: &mut Vec<u8>) -> Result<(), SceAsmError> {
+    let data = string_bytes(line_no, s)?;
+    let len = u16::try_from(data.len())
+        .map_err(|_| SceAsmError::TooLong(line_no, "string".to_string()))?;
+    output.write_u16::<LittleEndian>(len).unwrap();
+    output.extend_from_slice(&data);
+    Ok(())
+}
+
+fn string_bytes(line_no: usize, s: &SceString) -> Result<Vec<u8>, SceAsmError> {
+    match s {
+        SceString::Raw(data) => Ok(data.clone()),
+        SceString::Text(text) => {
+            let mut data = encoding::all::GBK
+                .encode(text, EncoderTrap::Strict)
+                .map_err(|_| SceAsmError::Encoding(line_no, text.clone()))?;
+            data.push(0);
+            Ok(data)
+        }
+    }
+}
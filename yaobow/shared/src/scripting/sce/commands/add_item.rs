@@ -0,0 +1,39 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use log::warn;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandAddItem {
+    item_id: i32,
+    count: i32,
+}
+
+impl SceCommand for SceCommandAddItem {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        if !state.global_state().item_table().is_valid(self.item_id) {
+            warn!("AddItem: unknown item {}", self.item_id);
+            return true;
+        }
+
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .inventory_mut()
+            .add_item(self.item_id, self.count);
+        true
+    }
+}
+
+impl SceCommandAddItem {
+    pub fn new(item_id: i32, count: i32) -> Self {
+        Self { item_id, count }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandAddMoney {
+    amount: i32,
+}
+
+impl SceCommand for SceCommandAddMoney {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .inventory_mut()
+            .add_money(self.amount);
+        true
+    }
+}
+
+impl SceCommandAddMoney {
+    pub fn new(amount: i32) -> Self {
+        Self { amount }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandAddSkill {
+    role_id: i32,
+    skill_id: i32,
+}
+
+impl SceCommand for SceCommandAddSkill {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        let (role_id, skill_id) = (self.role_id, self.skill_id);
+        state
+            .global_state_mut()
+            .update_party(|party, table| party.add_skill(role_id, skill_id, table));
+        true
+    }
+}
+
+impl SceCommandAddSkill {
+    pub fn new(role_id: i32, skill_id: i32) -> Self {
+        Self { role_id, skill_id }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandAverageLv {
+    role_id: i32,
+    bonus: i32,
+}
+
+impl SceCommand for SceCommandAverageLv {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        let (role_id, bonus) = (self.role_id, self.bonus);
+        state
+            .global_state_mut()
+            .update_party(|party, table| party.level_up_to_average(role_id, bonus, table));
+        true
+    }
+}
+
+impl SceCommandAverageLv {
+    pub fn new(role_id: i32, bonus: i32) -> Self {
+        Self { role_id, bonus }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandCombatBoss {
+    monster_ids: [i32; 6],
+    started: bool,
+}
+
+impl SceCommand for SceCommandCombatBoss {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        if !self.started {
+            let monster_ids = self
+                .monster_ids
+                .iter()
+                .copied()
+                .filter(|id| *id > 0)
+                .collect();
+            state.global_state_mut().start_combat(monster_ids, true);
+            self.started = true;
+        }
+
+        !state.global_state().combat_running()
+    }
+}
+
+impl SceCommandCombatBoss {
+    pub fn new(
+        monster1: i32,
+        monster2: i32,
+        monster3: i32,
+        monster4: i32,
+        monster5: i32,
+        monster6: i32,
+    ) -> Self {
+        Self {
+            monster_ids: [monster1, monster2, monster3, monster4, monster5, monster6],
+            started: false,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandCombatMaxRound {
+    round: i32,
+}
+
+impl SceCommand for SceCommandCombatMaxRound {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state.global_state_mut().combat_options_mut().max_round =
+            (self.round > 0).then_some(self.round as u32);
+        true
+    }
+}
+
+impl SceCommandCombatMaxRound {
+    pub fn new(round: i32) -> Self {
+        Self { round }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandCombatNotGameOver {}
+
+impl SceCommand for SceCommandCombatNotGameOver {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state.global_state_mut().combat_options_mut().not_game_over = true;
+        true
+    }
+}
+
+impl SceCommandCombatNotGameOver {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
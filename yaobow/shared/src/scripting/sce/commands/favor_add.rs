@@ -0,0 +1,33 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandFavorAdd {
+    role_id: i32,
+    delta: i32,
+}
+
+impl SceCommand for SceCommandFavorAdd {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .favors_mut()
+            .add(self.role_id, self.delta);
+        true
+    }
+}
+
+impl SceCommandFavorAdd {
+    pub fn new(role_id: i32, delta: i32) -> Self {
+        Self { role_id, delta }
+    }
+}
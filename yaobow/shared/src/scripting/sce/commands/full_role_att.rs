@@ -0,0 +1,32 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandFullRoleAtt {
+    role_id: i32,
+    _unknown: i32,
+}
+
+impl SceCommand for SceCommandFullRoleAtt {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        let role_id = self.role_id;
+        state
+            .global_state_mut()
+            .update_party(|party, table| party.restore(role_id, table));
+        true
+    }
+}
+
+impl SceCommandFullRoleAtt {
+    pub fn new(role_id: i32, _unknown: i32) -> Self {
+        Self { role_id, _unknown }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::scripting::sce::{SceCommand, SceState};
 use crosscom::ComRc;
 use imgui::Ui;
+use log::warn;
 use radiance::comdef::ISceneManager;
 
 #[derive(Debug, Clone)]
@@ -16,10 +17,22 @@ impl SceCommand for SceCommandGetCombat {
         state: &mut SceState,
         _delta_sec: f32,
     ) -> bool {
-        state
-            .context_mut()
-            .current_proc_context_mut()
-            .set_local(self.var, 1);
+        let value = match state.global_state().combat_result() {
+            Some(result) => result.sce_value(),
+            None => {
+                warn!("GetCombat: no battle has been fought");
+                0
+            }
+        };
+        if self.var < 0 {
+            state
+                .global_state_mut()
+                .persistent_state_mut()
+                .set_global(self.var, value)
+        } else {
+            state.context_mut().set_local(self.var, value)
+        }
+
         true
     }
 }
@@ -0,0 +1,42 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandGetFavor {
+    var: i16,
+    role_id: i32,
+}
+
+impl SceCommand for SceCommandGetFavor {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        let favor = state
+            .global_state()
+            .persistent_state()
+            .favors()
+            .get(self.role_id);
+        if self.var < 0 {
+            state
+                .global_state_mut()
+                .persistent_state_mut()
+                .set_global(self.var, favor)
+        } else {
+            state.context_mut().set_local(self.var, favor)
+        }
+
+        true
+    }
+}
+
+impl SceCommandGetFavor {
+    pub fn new(var: i16, role_id: i32) -> Self {
+        Self { var, role_id }
+    }
+}
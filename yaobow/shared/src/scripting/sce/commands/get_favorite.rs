@@ -0,0 +1,37 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandGetFavorite {
+    var: i16,
+}
+
+impl SceCommand for SceCommandGetFavorite {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        let role_id = state.global_state().persistent_state().favors().favorite();
+        if self.var < 0 {
+            state
+                .global_state_mut()
+                .persistent_state_mut()
+                .set_global(self.var, role_id)
+        } else {
+            state.context_mut().set_local(self.var, role_id)
+        }
+
+        true
+    }
+}
+
+impl SceCommandGetFavorite {
+    pub fn new(var: i16) -> Self {
+        Self { var }
+    }
+}
@@ -16,10 +16,16 @@ impl SceCommand for SceCommandGetMoney {
         state: &mut SceState,
         _delta_sec: f32,
     ) -> bool {
-        state
-            .context_mut()
-            .current_proc_context_mut()
-            .set_local(self.var, 99999);
+        let money = state.global_state().persistent_state().inventory().money();
+        if self.var < 0 {
+            state
+                .global_state_mut()
+                .persistent_state_mut()
+                .set_global(self.var, money)
+        } else {
+            state.context_mut().set_local(self.var, money)
+        }
+
         true
     }
 }
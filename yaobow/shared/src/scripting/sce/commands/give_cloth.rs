@@ -0,0 +1,32 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandGiveCloth {
+    cloth_id: i16,
+}
+
+impl SceCommand for SceCommandGiveCloth {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .inventory_mut()
+            .give_cloth(self.cloth_id);
+        true
+    }
+}
+
+impl SceCommandGiveCloth {
+    pub fn new(cloth_id: i16) -> Self {
+        Self { cloth_id }
+    }
+}
@@ -3,7 +3,6 @@ use crosscom::ComRc;
 use imgui::Ui;
 use radiance::comdef::ISceneManager;
 
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct SceCommandHaveItem {
     item_id: i32,
@@ -17,8 +16,15 @@ impl SceCommand for SceCommandHaveItem {
         state: &mut SceState,
         _delta_sec: f32,
     ) -> bool {
-        // TODO
-        state.global_state_mut().fop_state_mut().push_value(true);
+        let have_item = state
+            .global_state()
+            .persistent_state()
+            .inventory()
+            .have_item(self.item_id);
+        state
+            .global_state_mut()
+            .fop_state_mut()
+            .push_value(have_item);
         true
     }
 }
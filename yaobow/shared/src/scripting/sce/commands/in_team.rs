@@ -0,0 +1,35 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandInTeam {
+    role_id: i32,
+    in_team: i32,
+}
+
+impl SceCommand for SceCommandInTeam {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        let mut persistent_state = state.global_state_mut().persistent_state_mut();
+        let party = persistent_state.party_mut();
+        if self.in_team != 0 {
+            party.join(self.role_id);
+        } else {
+            party.leave(self.role_id);
+        }
+        true
+    }
+}
+
+impl SceCommandInTeam {
+    pub fn new(role_id: i32, in_team: i32) -> Self {
+        Self { role_id, in_team }
+    }
+}
@@ -27,8 +27,28 @@ impl SceCommand for SceCommandLoadScene {
             .or(Some(true))
             .unwrap();
 
+        let sce = if cpk_changed {
+            match state.asset_mgr().load_sce(&self.name) {
+                Ok(sce) => Some(sce),
+                Err(e) => {
+                    log::error!("Cannot load sce {}: {:?}", self.name, e);
+                    return true;
+                }
+            }
+        } else {
+            None
+        };
+
+        let scene = match state.asset_mgr().load_scn(&self.name, &self.sub_name) {
+            Ok(scene) => scene,
+            Err(e) => {
+                log::error!("Cannot load scene {} {}: {:?}", self.name, self.sub_name, e);
+                return true;
+            }
+        };
+
         scene_manager.pop_scene();
-        scene_manager.push_scene(state.asset_mgr().load_scn(&self.name, &self.sub_name));
+        scene_manager.push_scene(scene);
         let e = scene_manager.get_resolved_role(state, -1).unwrap();
         let r = RoleController::get_role_controller(e.clone()).unwrap();
         r.get().set_active(true);
@@ -37,9 +57,8 @@ impl SceCommand for SceCommandLoadScene {
             .global_state_mut()
             .persistent_state_mut()
             .set_scene_name(self.name.clone(), self.sub_name.clone());
-        if cpk_changed {
-            let sce = Rc::new(state.asset_mgr().load_sce(&self.name));
-            state.context_mut().set_sce(sce, self.name.clone());
+        if let Some(sce) = sce {
+            state.context_mut().set_sce(Rc::new(sce), self.name.clone());
             state.global_state_mut().bgm_source().stop();
             state.global_state_mut().play_default_bgm();
         }
@@ -1,4 +1,8 @@
 mod _let;
+mod add_item;
+mod add_money;
+mod add_skill;
+mod average_lv;
 mod between;
 mod call;
 mod camera_default;
@@ -6,6 +10,10 @@ mod camera_move;
 mod camera_rotate;
 mod camera_set;
 mod cmp;
+mod combat_boss;
+mod combat_max_round;
+mod combat_must_fail;
+mod combat_not_game_over;
 mod dlg;
 mod dlg_face;
 mod dlg_sel;
@@ -15,17 +23,24 @@ mod fade_in;
 mod fade_in_white;
 mod fade_out;
 mod fade_out_white;
+mod favor_add;
 mod fop;
+mod full_role_att;
+mod full_team_att;
 mod get_appr;
 mod get_combat;
 mod get_dlg_sel;
+mod get_favor;
+mod get_favorite;
 mod get_money;
 mod get_time_sel;
+mod give_cloth;
 mod goto;
 mod have_item;
 mod hy_fly;
 mod idle;
 mod if_in_team;
+mod in_team;
 mod load_scene;
 mod movie;
 mod music;
@@ -33,6 +48,7 @@ mod nop;
 mod object_active;
 mod play_sound;
 mod quake;
+mod remove_item;
 mod rnd;
 mod role_act_auto_stand;
 mod role_active;
@@ -56,9 +72,15 @@ mod set_bigmap_element;
 mod show_chat_rest;
 mod start_hidefight;
 mod stop_music;
+mod team_close;
+mod team_open;
 mod testgoto;
 
 pub use _let::SceCommandLet;
+pub use add_item::SceCommandAddItem;
+pub use add_money::SceCommandAddMoney;
+pub use add_skill::SceCommandAddSkill;
+pub use average_lv::SceCommandAverageLv;
 pub use between::SceCommandBetween;
 pub use call::SceCommandCall;
 pub use camera_default::SceCommandCameraDefault;
@@ -69,6 +91,10 @@ pub use cmp::{
     SceCommandEq, SceCommandGeq, SceCommandGeq2, SceCommandGt, SceCommandLeq, SceCommandLs,
     SceCommandNeq,
 };
+pub use combat_boss::SceCommandCombatBoss;
+pub use combat_max_round::SceCommandCombatMaxRound;
+pub use combat_must_fail::SceCommandCombatMustFail;
+pub use combat_not_game_over::SceCommandCombatNotGameOver;
 pub use dlg::SceCommandDlg;
 pub use dlg_face::SceCommandDlgFace;
 pub use dlg_sel::SceCommandDlgSel;
@@ -78,17 +104,24 @@ pub use fade_in::SceCommandFadeIn;
 pub use fade_in_white::SceCommandFadeInWhite;
 pub use fade_out::SceCommandFadeOut;
 pub use fade_out_white::SceCommandFadeOutWhite;
+pub use favor_add::SceCommandFavorAdd;
 pub use fop::SceCommandFop;
+pub use full_role_att::SceCommandFullRoleAtt;
+pub use full_team_att::SceCommandFullTeamAtt;
 pub use get_appr::SceCommandGetAppr;
 pub use get_combat::SceCommandGetCombat;
 pub use get_dlg_sel::SceCommandGetDlgSel;
+pub use get_favor::SceCommandGetFavor;
+pub use get_favorite::SceCommandGetFavorite;
 pub use get_money::SceCommandGetMoney;
 pub use get_time_sel::SceCommandGetTimeSel;
+pub use give_cloth::SceCommandGiveCloth;
 pub use goto::SceCommandGoto;
 pub use have_item::SceCommandHaveItem;
 pub use hy_fly::SceCommandHyFly;
 pub use idle::SceCommandIdle;
 pub use if_in_team::SceCommandIfInTeam;
+pub use in_team::SceCommandInTeam;
 pub use load_scene::SceCommandLoadScene;
 pub use movie::SceCommandMovie;
 pub use music::SceCommandMusic;
@@ -96,6 +129,7 @@ pub use nop::SceCommandNop;
 pub use object_active::SceCommandObjectActive;
 pub use play_sound::SceCommandPlaySound;
 pub use quake::SceCommandQuake;
+pub use remove_item::SceCommandRemoveItem;
 pub use rnd::SceCommandRnd;
 pub use role_act_auto_stand::SceCommandRoleActAutoStand;
 pub use role_active::SceCommandRoleActive;
@@ -119,6 +153,8 @@ pub use set_bigmap_element::SceCommandSetBigMapElement;
 pub use show_chat_rest::SceCommandShowChatRest;
 pub use start_hidefight::SceCommandStartHideFight;
 pub use stop_music::SceCommandStopMusic;
+pub use team_close::SceCommandTeamClose;
+pub use team_open::SceCommandTeamOpen;
 pub use testgoto::SceCommandTestGoto;
 
 use radiance::math::Vec3;
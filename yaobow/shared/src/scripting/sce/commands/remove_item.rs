@@ -0,0 +1,32 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandRemoveItem {
+    item_id: i32,
+}
+
+impl SceCommand for SceCommandRemoveItem {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .inventory_mut()
+            .remove_item(self.item_id);
+        true
+    }
+}
+
+impl SceCommandRemoveItem {
+    pub fn new(item_id: i32) -> Self {
+        Self { item_id }
+    }
+}
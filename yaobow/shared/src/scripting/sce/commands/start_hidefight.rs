@@ -4,7 +4,9 @@ use imgui::Ui;
 use radiance::comdef::ISceneManager;
 
 #[derive(Debug, Clone)]
-pub struct SceCommandStartHideFight {}
+pub struct SceCommandStartHideFight {
+    started: bool,
+}
 
 impl SceCommand for SceCommandStartHideFight {
     fn update(
@@ -14,6 +16,15 @@ impl SceCommand for SceCommandStartHideFight {
         state: &mut SceState,
         _delta_sec: f32,
     ) -> bool {
+        if !self.started {
+            state.global_state_mut().start_random_combat();
+            self.started = true;
+        }
+
+        if state.global_state().combat_running() {
+            return false;
+        }
+
         state.call_proc(1701);
         true
     }
@@ -21,6 +32,6 @@ impl SceCommand for SceCommandStartHideFight {
 
 impl SceCommandStartHideFight {
     pub fn new() -> Self {
-        Self {}
+        Self { started: false }
     }
 }
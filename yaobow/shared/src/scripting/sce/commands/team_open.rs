@@ -0,0 +1,30 @@
+use crate::scripting::sce::{SceCommand, SceState};
+use crosscom::ComRc;
+use imgui::Ui;
+use radiance::comdef::ISceneManager;
+
+#[derive(Debug, Clone)]
+pub struct SceCommandTeamOpen {}
+
+impl SceCommand for SceCommandTeamOpen {
+    fn update(
+        &mut self,
+        _scene_manager: ComRc<ISceneManager>,
+        _ui: &Ui,
+        state: &mut SceState,
+        _delta_sec: f32,
+    ) -> bool {
+        state
+            .global_state_mut()
+            .persistent_state_mut()
+            .party_mut()
+            .set_team_open(true);
+        true
+    }
+}
+
+impl SceCommandTeamOpen {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
@@ -16,7 +16,9 @@ use crate::openpal3::{
 
 use self::vm::{SceExecutionContext, SceExecutionOptions};
 
+pub mod asm;
 pub mod commands;
+pub mod opcodes;
 pub mod vm;
 
 pub trait SceCommandDebug {
@@ -90,6 +92,10 @@ impl SceState {
         &mut self.global_state
     }
 
+    pub fn context(&self) -> &SceExecutionContext {
+        &self.context
+    }
+
     pub fn context_mut(&mut self) -> &mut SceExecutionContext {
         &mut self.context
     }
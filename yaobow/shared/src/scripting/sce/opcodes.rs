@@ -0,0 +1,259 @@
+use SceParamType::{Label, List, Str, F32, I16, I32, U32};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceParamType {
+    I16,
+    I32,
+    U32,
+    F32,
+    Str,
+    List,
+
+    /// An offset inside the current proc, used by the jump commands
+    Label,
+}
+
+/// Layout of a sce command. Every command starts with its `id` as i16
+/// followed by an i16 `flag`, then the parameters. A few commands use the
+/// flag to select between parameter layouts.
+#[derive(Debug)]
+pub struct SceOpcode {
+    pub id: i16,
+    pub name: &'static str,
+    pub flag: Option<i16>,
+    pub params: &'static [SceParamType],
+}
+
+impl SceOpcode {
+    pub fn find(id: i16, flag: i16) -> Option<&'static SceOpcode> {
+        SCE_OPCODES
+            .iter()
+            .find(|op| op.id == id && op.flag.unwrap_or(flag) == flag)
+    }
+
+    pub fn find_by_name(name: &str) -> Option<&'static SceOpcode> {
+        SCE_OPCODES
+            .iter()
+            .find(|op| op.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn default_flag(&self) -> i16 {
+        self.flag.unwrap_or(0)
+    }
+}
+
+const fn op(
+    id: i16,
+    name: &'static str,
+    flag: Option<i16>,
+    params: &'static [SceParamType],
+) -> SceOpcode {
+    SceOpcode {
+        id,
+        name,
+        flag,
+        params,
+    }
+}
+
+/// Every command known to the vm, written as
+/// `id [if flag] => Name(params) [=> SceCommandType];`. Commands with a type
+/// are decoded into it, the others only have their parameters skipped and
+/// list bare types. Both the vm decoder and `SCE_OPCODES` are generated from
+/// this list by passing a macro that takes the entries as `$callback`.
+macro_rules! sce_commands {
+    ($callback: ident) => {
+        $callback! {
+            1 => Idle(length: f32) => SceCommandIdle;
+            2 => ScriptRunMode(mode: i32) => SceCommandScriptRunMode;
+            3 => Goto(offset: label) => SceCommandGoto;
+            5 => FOP(op: i32) => SceCommandFop;
+            6 => GT(var: i16, value: i32) => SceCommandGt;
+            7 => LS(var: i16, value: i32) => SceCommandLs;
+            8 => EQ(var: i16, value: i32) => SceCommandEq;
+            9 => NEQ(var: i16, value: i32) => SceCommandNeq;
+            10 if 1 => GEQ(var: i16, value: i32) => SceCommandGeq;
+            10 if 3 => GEQ2(var: i16, var2: i16) => SceCommandGeq2;
+            10 => GeqNotSupported();
+            11 => LEQ(var: i16, value: i32) => SceCommandLeq;
+            12 => TestGoto(offset: label) => SceCommandTestGoto;
+            13 => Let(var: i16, value: i32) => SceCommandLet;
+            16 => Call(proc_id: u32) => SceCommandCall;
+            17 => Rnd(var: i16, value: i32) => SceCommandRnd;
+            19 => Between(var: i16, lb: i32, rh: i32) => SceCommandBetween;
+            20 => RolePathTo(role_id: i32, x: i32, y: i32, unknown: i32) => SceCommandRolePathTo;
+            21 => RoleSetPos(role_id: i32, x: i32, y: i32) => SceCommandRoleSetPos;
+            22 => RoleShowAction(
+                role_id: i32, action_name: string, repeat_mode: i32,
+            ) => SceCommandRoleShowAction;
+            23 => RoleSetFace(role_id: i32, direction: i32) => SceCommandRoleSetFace;
+            24 => RoleTurnFace(role_id: i32, degree: i32) => SceCommandRoleTurnFace;
+            25 => TeamOpen() => SceCommandTeamOpen;
+            26 => TeamClose() => SceCommandTeamClose;
+            27 => RoleInput(enable_input: i32) => SceCommandRoleInput;
+            28 => RoleActive(role: i32, active: i32) => SceCommandRoleActive;
+            29 => RoleScript(role: i32, proc_id: i32) => SceCommandRoleScript;
+            30 => CameraFocusRole(i32);
+            31 => CameraFocusPoint(f32, f32, f32);
+            32 => CameraPush(f32, f32, i32);
+            33 => CameraRotate(
+                to_rot_x: f32, to_rot_y: f32, duration: f32, _unknown: i32,
+            ) => SceCommandCameraRotate;
+            34 => CameraMove(
+                position_x: f32, position_y: f32, position_z: f32, unknown_1: f32, unknown_2: f32,
+            ) => SceCommandCameraMove;
+            35 => CameraWag(f32, f32, f32, i32);
+            36 => CameraSet(
+                y_rot: f32, x_rot: f32, unknown: f32, x: f32, y: f32, z: f32,
+            ) => SceCommandCameraSet;
+            37 => CameraDefault(unknown: i32) => SceCommandCameraDefault;
+            38 => CameraPushState();
+            39 => CameraPopState();
+            42 => LK_Ghost(i32);
+            43 => FavorAdd(role_id: i32, delta: i32) => SceCommandFavorAdd;
+            46 => AddItem(item_id: i32, count: i32) => SceCommandAddItem;
+            47 => RemoveItem(item_id: i32) => SceCommandRemoveItem;
+            48 => AddMoney(amount: i32) => SceCommandAddMoney;
+            49 => GetMoney(var: i16) => SceCommandGetMoney;
+            50 => GetFavor(var: i16, role_id: i32) => SceCommandGetFavor;
+            51 => AddSkill(role_id: i32, skill_id: i32) => SceCommandAddSkill;
+            52 => GetFavorite(var: i16) => SceCommandGetFavorite;
+            54 => FullRoleAtt(role_id: i32, _unknown: i32) => SceCommandFullRoleAtt;
+            62 => Dlg(text: string) => SceCommandDlg;
+            63 => LoadScene(name: string, sub_name: string) => SceCommandLoadScene;
+            65 => DlgSel(list: list) => SceCommandDlgSel;
+            66 => GetDlgSel(var: i16) => SceCommandGetDlgSel;
+            67 => DlgFace(id: i32, face_name: string, left_or_right: i32) => SceCommandDlgFace;
+            68 => Note(string);
+            69 => FadeOut() => SceCommandFadeOut;
+            70 => FadeIn() => SceCommandFadeIn;
+            71 => RoleStop(role_id: i32) => SceCommandRoleStop;
+            72 => RoleEmote(i32, i32);
+            74 => Climb(i32, i32);
+            76 => DlgTime(text: string) => SceCommandDlgTime;
+            77 => GetTimeSel(var: i16) => SceCommandGetTimeSel;
+            78 => HaveItem(item_id: i32) => SceCommandHaveItem;
+            79 => PlaySound(name: string, repeat: i32) => SceCommandPlaySound;
+            80 => CombatBoss(
+                monster1: i32,
+                monster2: i32,
+                monster3: i32,
+                monster4: i32,
+                monster5: i32,
+                monster6: i32,
+            ) => SceCommandCombatBoss;
+            81 => FadeOutWhite() => SceCommandFadeOutWhite;
+            82 => CombatMaxRound(round: i32) => SceCommandCombatMaxRound;
+            83 => CombatMustFail() => SceCommandCombatMustFail;
+            85 => ObjectActive(object_id: i32, active: i32) => SceCommandObjectActive;
+            86 => Caption(string, i32);
+            87 => OpenDoor(i32);
+            88 => HY_Mode(i32);
+            89 => HY_FLY(position_x: f32, position_y: f32, position_z: f32) => SceCommandHyFly;
+            90 => ObjectMove(i32, f32, f32, f32, f32);
+            91 => FadeInWhite() => SceCommandFadeInWhite;
+            102 => SwitchRS(i32);
+            104 => APPR_Entry();
+            106 => ENCAMP_Entry(i32);
+            107 => SKEE_Entry(i32);
+            108 => GetAppr(var: i16) => SceCommandGetAppr;
+            109 => Enable_Sword(i32);
+            111 => Specify_Compos(i32);
+            113 => Start_HideFight() => SceCommandStartHideFight;
+            115 => Movie(name: string) => SceCommandMovie;
+            116 => SetRoleTexture(i32, string);
+            117 => Rotate(i32, i32, i32);
+            118 => Quake(duration: f32, amplitude: f32) => SceCommandQuake;
+            119 => ShowChatRest(
+                config_file: string,
+                enough_money_proc: u32,
+                not_enough_money_proc: u32,
+                after_rest_proc: u32,
+            ) => SceCommandShowChatRest;
+            124 => Trigger(i32);
+            125 => SetBigMapElement(id: i32, option: i32) => SceCommandSetBigMapElement;
+            126 => GetSwitch(string, i32, i16);
+            127 => EntryRow(id: i32, proc_id: i32) => SceCommandEntryRow;
+            128 => RotateInv(i32, i32, i32);
+            130 => Dist(i16, i16);
+            131 => CombatNotGameOver() => SceCommandCombatNotGameOver;
+            132 => GetCombat(var: i16) => SceCommandGetCombat;
+            133 => Music(name: string, unknown: i32) => SceCommandMusic;
+            134 => StopMusic() => SceCommandStopMusic;
+            135 => RoleFadeOut(i32);
+            136 => RoleFadeIn(i32);
+            137 => IfInTeam(role_id: i32) => SceCommandIfInTeam;
+            138 => Enable_SwordSkill(i32);
+            140 => Snow(i32);
+            141 => ScrEft(i32);
+            142 => CEft_Pos(f32, f32, f32);
+            143 => CEft(i32);
+            144 => CEft_Role(i32);
+            145 => AverageLv(role_id: i32, bonus: i32) => SceCommandAverageLv;
+            147 => Switch2Menu();
+            148 => CEft_Load(i32);
+            149 => GiveCloth(cloth_id: i16) => SceCommandGiveCloth;
+            150 => LoadAct(i32, string);
+            152 => WaterMagic(i32);
+            153 => FullTeamAtt() => SceCommandFullTeamAtt;
+            155 => CameraYaw(f32);
+            156 => XJ_Pic();
+            158 => ObjNotLoad(i32);
+            159 => InitFlower();
+            201 => RolePathOut(role_id: i32, x: i32, y: i32, unknown: i32) => SceCommandRolePathOut;
+            202 => InTeam(role_id: i32, in_team: i32) => SceCommandInTeam;
+            203 => RoleSetLayer(role_id: i32, layer: i32) => SceCommandRoleSetLayer;
+            204 => RoleCtrl(role_id: i32) => SceCommandRoleCtrl;
+            205 => RoleOverlap(i32, i32);
+            206 => RoleScale(i32, f32);
+            207 => RoleActAutoStand(
+                role_id: i32, auto_play_idle: i32,
+            ) => SceCommandRoleActAutoStand;
+            208 => RoleMoveBack(role_id: i32, speed: f32) => SceCommandRoleMoveBack;
+            209 => RoleFaceRole(role_id: i32, role_id2: i32) => SceCommandRoleFaceRole;
+            210 => RoleTurnFaceA(role_id: i32, direction: i32) => SceCommandRoleSetFace;
+            211 => TeamOpenA() => SceCommandTeamOpen;
+            212 => TeamCloseA() => SceCommandTeamClose;
+            214 => RoleMovTo(role_id: i32, x: i32, y: i32, unknown: i32) => SceCommandRoleMoveTo;
+            221 => RoleEndAction(role_id: i32) => SceCommandRoleEndAction;
+            250 => CameraFree(i32);
+            251 => ObjectMoveA(i32, f32, f32, f32, f32);
+        }
+    };
+}
+
+pub(crate) use sce_commands;
+
+macro_rules! sce_opcode_table {
+    (@flag) => {
+        None
+    };
+    (@flag $flag: literal) => {
+        Some($flag)
+    };
+    (@param $param: ident : $ty: ident) => {
+        sce_opcode_table!(@type $ty)
+    };
+    (@param $ty: ident) => {
+        sce_opcode_table!(@type $ty)
+    };
+    (@type i16) => { I16 };
+    (@type i32) => { I32 };
+    (@type u32) => { U32 };
+    (@type f32) => { F32 };
+    (@type string) => { Str };
+    (@type list) => { List };
+    (@type label) => { Label };
+    ($($id: literal $(if $flag: literal)? => $name: ident ($($param: ident $(: $ty: ident)?),* $(,)?) $(=> $cmd: ident)?;)*) => {
+        pub static SCE_OPCODES: &[SceOpcode] = &[
+            $(op(
+                $id,
+                stringify!($name),
+                sce_opcode_table!(@flag $($flag)?),
+                &[$(sce_opcode_table!(@param $param $(: $ty)?)),*],
+            ),)*
+        ];
+    };
+}
+
+sce_commands!(sce_opcode_table);
@@ -1,9 +1,10 @@
 use crate::openpal3::asset_manager::AssetManager;
 use crate::openpal3::loaders::sce_loader::SceFile;
 use crate::openpal3::states::global_state::GlobalState;
+use crate::openpal3::states::save_game::{SceProcSnapshot, ScriptSnapshot};
 use crate::scripting::sce::SceCommandDebug;
 
-use super::{commands::*, SceCommand, SceState};
+use super::{commands::*, opcodes::sce_commands, SceCommand, SceState};
 use crosscom::ComRc;
 use encoding::{DecoderTrap, Encoding};
 use imgui::*;
@@ -214,6 +215,34 @@ macro_rules! nop_command {
     };
 }
 
+macro_rules! sce_decoder {
+    (@decode $self: ident, $name: ident ($($param: ident : $ty: ident),*) => $cmd: ident) => {
+        command!($self, $cmd $(, $param : $ty)*)
+    };
+    (@decode $self: ident, $name: ident ($($ty: ident),*)) => {
+        nop_command!($self, $name $(, $ty)*)
+    };
+    ($($id: literal $(if $flag: literal)? => $name: ident ($($param: ident $(: $ty: ident)?),* $(,)?) $(=> $cmd: ident)?;)*) => {
+        impl SceProcContext {
+            /// Reads the parameters of the command and creates it. `flag` is the
+            /// i16 that follows the command id.
+            fn decode_command(&mut self, cmd: i16, flag: i16) -> Option<Box<dyn SceCommand>> {
+                $(
+                    if cmd == $id $(&& flag == $flag)? {
+                        return sce_decoder!(@decode self, $name ($($param $(: $ty)?),*) $(=> $cmd)?);
+                    }
+                )*
+
+                error!("Unsupported command: {}", cmd);
+                self.put(4);
+                panic!();
+            }
+        }
+    };
+}
+
+sce_commands!(sce_decoder);
+
 pub struct SceProcContext {
     sce: Rc<SceFile>,
     proc_id: u32,
@@ -239,6 +268,28 @@ impl SceProcContext {
             .and_then(|index| Some(Self::new(sce, index)))
     }
 
+    /// Returns `None` when the proc doesn't exist in `sce`.
+    pub fn new_from_snapshot(sce: Rc<SceFile>, snapshot: &SceProcSnapshot) -> Option<Self> {
+        let index = sce
+            .proc_headers
+            .iter()
+            .position(|h| h.id == snapshot.proc_id)?;
+        let mut context = Self::new(sce, index);
+        context.program_counter = snapshot.program_counter;
+        context.local_vars = snapshot.local_vars.clone();
+        context.dlgsel = snapshot.dlgsel;
+        Some(context)
+    }
+
+    pub fn snapshot(&self) -> SceProcSnapshot {
+        SceProcSnapshot {
+            proc_id: self.proc_id,
+            program_counter: self.program_counter,
+            local_vars: self.local_vars.clone(),
+            dlgsel: self.dlgsel,
+        }
+    }
+
     fn new(sce: Rc<SceFile>, index: usize) -> Self {
         let proc = &sce.proc_headers[index];
         let proc_id = proc.id;
@@ -278,597 +329,8 @@ impl SceProcContext {
         }
 
         let cmd = data_read::i16(self);
-        let access_local_var = data_read::i16(self);
-        match cmd {
-            1 => {
-                // Idle
-                command!(self, SceCommandIdle, length: f32)
-            }
-            2 => {
-                // ScriptRunMode
-                command!(self, SceCommandScriptRunMode, mode: i32)
-            }
-            3 => {
-                // Goto
-                command!(self, SceCommandGoto, offset: u32)
-            }
-            5 => {
-                // FOP
-                command!(self, SceCommandFop, op: i32)
-            }
-            6 => {
-                // GT
-                command!(self, SceCommandGt, var: i16, value: i32)
-            }
-            7 => {
-                // LS
-                command!(self, SceCommandLs, var: i16, value: i32)
-            }
-            8 => {
-                // EQ
-                command!(self, SceCommandEq, var: i16, value: i32)
-            }
-            9 => {
-                // NEQ
-                command!(self, SceCommandNeq, var: i16, value: i32)
-            }
-            10 => {
-                // GEQ
-                match access_local_var {
-                    1 => command!(self, SceCommandGeq, var: i16, value: i32),
-                    3 => command!(self, SceCommandGeq2, var: i16, var2: i16),
-                    _ => nop_command!(self, GeqNotSupported),
-                }
-            }
-            11 => {
-                // LEQ
-                command!(self, SceCommandLeq, var: i16, value: i32)
-            }
-            12 => {
-                // TestGoto
-                command!(self, SceCommandTestGoto, offset: u32)
-            }
-            13 => {
-                // Let
-                command!(self, SceCommandLet, var: i16, value: i32)
-            }
-            16 => {
-                //Call
-                command!(self, SceCommandCall, proc_id: u32)
-            }
-            17 => {
-                // Rnd
-                command!(self, SceCommandRnd, var: i16, value: i32)
-            }
-            19 => {
-                // Between
-                command!(self, SceCommandBetween, var: i16, lb: i32, rh: i32)
-            }
-            20 => {
-                // RolePathTo
-                command!(
-                    self,
-                    SceCommandRolePathTo,
-                    role_id: i32,
-                    x: i32,
-                    y: i32,
-                    unknown: i32
-                )
-            }
-            21 => {
-                // RoleSetPos
-                command!(self, SceCommandRoleSetPos, role_id: i32, x: i32, y: i32)
-            }
-            22 => {
-                // RoleShowAction
-                command!(
-                    self,
-                    SceCommandRoleShowAction,
-                    role_id: i32,
-                    action_name: string,
-                    repeat_mode: i32,
-                )
-            }
-            23 => {
-                // RoleSetFace
-                command!(self, SceCommandRoleSetFace, role_id: i32, direction: i32)
-            }
-            24 => {
-                // RoleTurnFace
-                command!(self, SceCommandRoleTurnFace, role_id: i32, degree: i32)
-            }
-            25 => {
-                // TeamOpen
-                nop_command!(self, TeamOpen)
-            }
-            26 => {
-                // TeamClose
-                nop_command!(self, TeamClose)
-            }
-            27 => {
-                // RoleInput
-                command!(self, SceCommandRoleInput, enable_input: i32)
-            }
-            28 => {
-                // RoleActive
-                command!(self, SceCommandRoleActive, role: i32, active: i32)
-            }
-            29 => {
-                // RoleScript
-                command!(self, SceCommandRoleScript, role: i32, proc_id: i32)
-            }
-            30 => {
-                // CameraFocusRole
-                nop_command!(self, CameraFocusRole, i32)
-            }
-            31 => {
-                // CameraFocusPoint
-                nop_command!(self, CameraFocusPoint, f32, f32, f32)
-            }
-            32 => {
-                // CameraPush
-                nop_command!(self, CameraPush, f32, f32, i32)
-            }
-            33 => {
-                // CameraRotate
-                command!(self, SceCommandCameraRotate, to_rot_x: f32, to_rot_y: f32, duration: f32, _unknown: i32)
-            }
-            34 => {
-                // CameraMove
-                command!(
-                    self,
-                    SceCommandCameraMove,
-                    position_x: f32,
-                    position_y: f32,
-                    position_z: f32,
-                    unknown_1: f32,
-                    unknown_2: f32
-                )
-            }
-            35 => {
-                //CameraWag
-                nop_command!(self, CameraWag, f32, f32, f32, i32)
-            }
-            36 => {
-                // CameraSet
-                command!(
-                    self,
-                    SceCommandCameraSet,
-                    y_rot: f32,
-                    x_rot: f32,
-                    unknown: f32,
-                    x: f32,
-                    y: f32,
-                    z: f32,
-                )
-            }
-            37 => {
-                // CameraDefault
-                command!(self, SceCommandCameraDefault, unknown: i32)
-            }
-            38 => {
-                // CameraPushState
-                nop_command!(self, CameraPushState)
-            }
-            39 => {
-                // CameraPopState
-                nop_command!(self, CameraPopState)
-            }
-            42 => {
-                // LK_Ghost
-                nop_command!(self, LK_Ghost, i32)
-            }
-            43 => {
-                // FavorAdd
-                nop_command!(self, FavorAdd, i32, i32)
-            }
-            46 => {
-                // AddItem
-                nop_command!(self, AddItem, i32, i32)
-            }
-            47 => {
-                // RemoveItem
-                nop_command!(self, RemoveItem, i32)
-            }
-            48 => {
-                // AddMoney
-                nop_command!(self, AddMoney, i32)
-            }
-            49 => {
-                // GetMoney
-                command!(self, SceCommandGetMoney, var: i16)
-            }
-            50 => {
-                // GetFavor
-                nop_command!(self, GetFavor, i16, i32)
-            }
-            51 => {
-                // AddSkill
-                nop_command!(self, AddSkill, i32, i32)
-            }
-            52 => {
-                // GetFavorite
-                nop_command!(self, GetFavorite, i16)
-            }
-            54 => {
-                // FullRoleAtt
-                nop_command!(self, FullRoleAtt, i32, i32)
-            }
-            62 => {
-                // Dlg
-                command!(self, SceCommandDlg, text: string)
-            }
-            63 => {
-                // LoadScene
-                command!(self, SceCommandLoadScene, name: string, sub_name: string)
-            }
-            65 => {
-                // DlgSel
-                command!(self, SceCommandDlgSel, list: list)
-            }
-            66 => {
-                // GetDlgSel
-                command!(self, SceCommandGetDlgSel, var: i16)
-            }
-            67 => {
-                // DlgFace
-                command!(self, SceCommandDlgFace, id: i32, face_name: string, left_or_right: i32)
-            }
-            68 => {
-                // Note
-                nop_command!(self, Note, string)
-            }
-            69 => {
-                // FadeOut
-                command!(self, SceCommandFadeOut)
-            }
-            70 => {
-                // FadeIn
-                command!(self, SceCommandFadeIn)
-            }
-            71 => {
-                // RoleStop
-                command!(self, SceCommandRoleStop, role_id: i32)
-            }
-            72 => {
-                // RoleEmote
-                nop_command!(self, RoleEmote, i32, i32)
-            }
-            74 => {
-                // Climb
-                nop_command!(self, Climb, i32, i32)
-            }
-            76 => {
-                // DlgTime
-                command!(self, SceCommandDlgTime, text: string)
-            }
-            77 => {
-                // GetTimeSel - temporarily use GetDlgSel
-                command!(self, SceCommandGetTimeSel, var: i16)
-            }
-            78 => {
-                command!(self, SceCommandHaveItem, item_id: i32)
-            }
-            79 => {
-                // PlaySound
-                command!(self, SceCommandPlaySound, name: string, repeat: i32)
-            }
-            80 => {
-                // CombatBoss
-                nop_command!(self, CombatBoss, i32, i32, i32, i32, i32, i32)
-            }
-            81 => {
-                // FadeOutWhite
-                command!(self, SceCommandFadeOutWhite)
-            }
-            82 => {
-                // CombatBoss
-                nop_command!(self, CombatMaxRound, i32)
-            }
-            83 => {
-                // CombatMustFail
-                nop_command!(self, CombatMustFail)
-            }
-            85 => {
-                // ObjectActive
-                command!(self, SceCommandObjectActive, object_id: i32, active: i32)
-            }
-            86 => {
-                // Caption
-                nop_command!(self, Caption, string, i32)
-            }
-            87 => {
-                // OpenDoor
-                nop_command!(self, OpenDoor, i32)
-            }
-            88 => {
-                // HY_Mode
-                nop_command!(self, HY_Mode, i32)
-            }
-            89 => {
-                // HY_FLY
-                command!(
-                    self,
-                    SceCommandHyFly,
-                    position_x: f32,
-                    position_y: f32,
-                    position_z: f32
-                )
-            }
-            90 => {
-                // ObjectMove
-                nop_command!(self, ObjectMove, i32, f32, f32, f32, f32)
-            }
-            91 => {
-                // FadeInWhite
-                command!(self, SceCommandFadeInWhite)
-            }
-            102 => {
-                // SwitchRS
-                nop_command!(self, SwitchRS, i32)
-            }
-            104 => {
-                // APPR Entry
-                nop_command!(self, APPREntry)
-            }
-            106 => {
-                // ENCAMP_Entry
-                nop_command!(self, ENCAMP_Entry, i32)
-            }
-            107 => {
-                // SKEE_Entry
-                nop_command!(self, SKEE_Entry, i32)
-            }
-            108 => {
-                // Get Appr
-                command!(self, SceCommandGetAppr, var: i16)
-            }
-            109 => {
-                // Enable_Sword
-                nop_command!(self, Enable_Sword, i32)
-            }
-            111 => {
-                // Specify_Compos
-                nop_command!(self, Specify_Compos, i32)
-            }
-            113 => {
-                // Start_HideFight
-                command!(self, SceCommandStartHideFight)
-            }
-            115 => {
-                // Movie
-                command!(self, SceCommandMovie, name: string)
-            }
-            116 => {
-                // SetRoleTexture
-                nop_command!(self, SetRoleTexture, i32, string)
-            }
-            117 => {
-                // Rotate
-                nop_command!(self, Rotate, i32, i32, i32)
-            }
-            118 => {
-                // Quake
-                command!(self, SceCommandQuake, duration: f32, amplitude: f32)
-            }
-            119 => {
-                // ShowChatRest
-                command!(
-                    self,
-                    SceCommandShowChatRest,
-                    config_file: string,
-                    enough_money_proc: u32,
-                    not_enough_money_proc: u32,
-                    after_rest_proc: u32
-                )
-            }
-            124 => {
-                // Trigger
-                nop_command!(self, Trigger, i32)
-            }
-            125 => {
-                // SetBigMapElement
-                command!(self, SceCommandSetBigMapElement, id: i32, option: i32)
-            }
-            126 => {
-                // GetSwitch
-                nop_command!(self, GetSwitch, string, i32, i16)
-            }
-            127 => {
-                command!(self, SceCommandEntryRow, id: i32, proc_id: i32)
-            }
-            128 => {
-                nop_command!(self, RotateInv, i32, i32, i32)
-            }
-            130 => {
-                // Dist
-                nop_command!(self, Dist, i16, i16)
-            }
-            131 => {
-                // GetSwitch
-                nop_command!(self, CombatNotGameOver)
-            }
-            132 => {
-                // GetCombat
-                command!(self, SceCommandGetCombat, var: i16)
-            }
-            133 => {
-                // Music
-                command!(self, SceCommandMusic, name: string, unknown: i32)
-            }
-            134 => {
-                // StopMusic
-                command!(self, SceCommandStopMusic)
-            }
-            135 => {
-                // RoleFadeOut
-                nop_command!(self, RoleFadeOut, i32)
-            }
-            136 => {
-                // RoleFadeIn
-                nop_command!(self, RoleFadeIn, i32)
-            }
-            137 => {
-                // IfInTeam
-                command!(self, SceCommandIfInTeam, role_id: i32)
-            }
-            138 => {
-                // Enable_SwordSkill
-                nop_command!(self, Enable_SwordSkill, i32)
-            }
-            140 => {
-                // Snow
-                nop_command!(self, Snow, i32)
-            }
-            141 => {
-                // ScrEft
-                nop_command!(self, ScrEft, i32)
-            }
-            142 => {
-                // CEft_Pos
-                nop_command!(self, CEft_Pos, f32, f32, f32)
-            }
-            143 => {
-                // CEft
-                nop_command!(self, CEft, i32)
-            }
-            144 => {
-                // CEft_Role
-                nop_command!(self, CEft, i32)
-            }
-            145 => {
-                // AverageLv
-                nop_command!(self, AverageLv, i32, i32)
-            }
-            147 => {
-                // Switch2Menu
-                nop_command!(self, Switch2Menu)
-            }
-            148 => {
-                // CEft_Load
-                nop_command!(self, CEft_Load, i32)
-            }
-            149 => {
-                // GiveCloth
-                nop_command!(self, GiveCloth, i16)
-            }
-            150 => {
-                // LoadAct
-                nop_command!(self, LoadAct, i32, string)
-            }
-            152 => {
-                // WaterMagic
-                nop_command!(self, WaterMagic, i32)
-            }
-            153 => {
-                // FullTeamAtt
-                nop_command!(self, FullTeamAtt)
-            }
-            155 => {
-                // CameraYaw
-                nop_command!(self, CameraYaw, f32)
-            }
-            156 => {
-                // XJ_Pic
-                nop_command!(self, XJ_Pic)
-            }
-            158 => {
-                // ObjNotLoad
-                nop_command!(self, ObjNotLoad, i32)
-            }
-            159 => {
-                // InitFlower
-                nop_command!(self, InitFlower)
-            }
-            201 => {
-                // RolePathOut
-                command!(
-                    self,
-                    SceCommandRolePathOut,
-                    role_id: i32,
-                    x: i32,
-                    y: i32,
-                    unknown: i32
-                )
-            }
-            202 => {
-                // InTeam
-                nop_command!(self, InTeam, i32, i32)
-            }
-            203 => {
-                // RoleSetLayer
-                command!(self, SceCommandRoleSetLayer, role_id: i32, layer: i32)
-            }
-            204 => {
-                // RoleCtrl
-                command!(self, SceCommandRoleCtrl, role_id: i32)
-            }
-            205 => {
-                // RoleOverlap
-                nop_command!(self, RoleOverlap, i32, i32)
-            }
-            206 => {
-                // RoleScale
-                nop_command!(self, RoleScale, i32, f32)
-            }
-            207 => {
-                // RoleActAutoStand
-                command!(
-                    self,
-                    SceCommandRoleActAutoStand,
-                    role_id: i32,
-                    auto_play_idle: i32
-                )
-            }
-            208 => {
-                // RoleMoveBack
-                command!(self, SceCommandRoleMoveBack, role_id: i32, speed: f32)
-            }
-            209 => {
-                // RoleFaceRole
-                command!(self, SceCommandRoleFaceRole, role_id: i32, role_id2: i32)
-            }
-            210 => {
-                // RoleTurnFaceA
-                command!(self, SceCommandRoleSetFace, role_id: i32, direction: i32)
-            }
-            211 => {
-                // TeamOpenA
-                nop_command!(self, TeamOpenA)
-            }
-            212 => {
-                // TeamCloseA
-                nop_command!(self, TeamCloseA)
-            }
-            214 => {
-                // RoleMovTo
-                command!(
-                    self,
-                    SceCommandRoleMoveTo,
-                    role_id: i32,
-                    x: i32,
-                    y: i32,
-                    unknown: i32
-                )
-            }
-            221 => {
-                // RoleEndAction
-                command!(self, SceCommandRoleEndAction, role_id: i32)
-            }
-            250 => {
-                // CameraFree
-                nop_command!(self, CameraFree, i32)
-            }
-            251 => {
-                // ObjectMove
-                nop_command!(self, ObjectMove, i32, f32, f32, f32, f32)
-            }
-            default => {
-                error!("Unsupported command: {}", default);
-                self.put(4);
-                panic!();
-            }
-        }
+        let flag = data_read::i16(self);
+        self.decode_command(cmd, flag)
     }
 
     fn jump_to(&mut self, addr: u32) {
@@ -920,6 +382,10 @@ mod data_read {
         context.read(4).read_u32::<LittleEndian>().unwrap()
     }
 
+    pub(super) fn label(context: &mut super::SceProcContext) -> u32 {
+        u32(context)
+    }
+
     pub(super) fn f32(context: &mut super::SceProcContext) -> f32 {
         context.read(4).read_f32::<LittleEndian>().unwrap()
     }
@@ -980,6 +446,44 @@ impl SceExecutionContext {
         self.proc_stack.last_mut().unwrap().jump_to(addr);
     }
 
+    /// Returns `None` when no proc is running.
+    pub fn snapshot(&self) -> Option<ScriptSnapshot> {
+        if self.proc_stack.is_empty() {
+            return None;
+        }
+
+        Some(ScriptSnapshot {
+            sce_name: self.sce_name.clone(),
+            procs: self.proc_stack.iter().map(|p| p.snapshot()).collect(),
+        })
+    }
+
+    /// Resumes the saved procs. A snapshot of another sce is ignored, and
+    /// false is returned when nothing is resumed.
+    pub fn restore(&mut self, snapshot: &ScriptSnapshot) -> bool {
+        if !snapshot.sce_name.eq_ignore_ascii_case(&self.sce_name) {
+            warn!(
+                "Cannot resume procs of {} in {}",
+                snapshot.sce_name, self.sce_name
+            );
+            return false;
+        }
+
+        let procs: Option<Vec<SceProcContext>> = snapshot
+            .procs
+            .iter()
+            .map(|p| SceProcContext::new_from_snapshot(self.sce.clone(), p))
+            .collect();
+
+        match procs {
+            Some(procs) if !procs.is_empty() => {
+                self.proc_stack = procs;
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn set_local(&mut self, var: i16, value: i32) {
         self.proc_stack.last_mut().unwrap().set_local(var, value);
     }
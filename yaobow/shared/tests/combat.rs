@@ -0,0 +1,247 @@
+use shared::openpal3::{
+    combat::{
+        self, Battle, BattleAction, BattleEvent, CombatOptions, CombatRequest, CombatResult,
+        Combatant, MonsterTable, Side, MAX_ENCOUNTER_MONSTERS,
+    },
+    states::party::{Party, RoleAttributes, RoleTable},
+};
+
+fn attributes(hp: i32, attack: i32, defense: i32, speed: i32) -> RoleAttributes {
+    RoleAttributes {
+        level: 1,
+        max_hp: hp,
+        max_mp: 0,
+        attack,
+        defense,
+        speed,
+        luck: 0,
+    }
+}
+
+fn combatant(id: i32, side: Side, attributes: RoleAttributes) -> Combatant {
+    let hp = attributes.max_hp;
+    Combatant::new(id, format!("{}", id), side, attributes, hp, 0)
+}
+
+fn battle(party: RoleAttributes, monster: RoleAttributes, options: CombatOptions) -> Battle {
+    Battle::new(
+        vec![combatant(0, Side::Party, party)],
+        vec![combatant(100, Side::Monster, monster)],
+        options,
+        42,
+    )
+}
+
+fn monster_table() -> MonsterTable {
+    MonsterTable::parse(
+        "101, 野狗, 1, 30, 0, 8, 4, 8, 5\n\
+         102, 狼妖, 3, 60, 10, 12, 6, 12, 5\n\
+         103, 魔王, 40, 9000, 900, 300, 200, 30, 30\n",
+    )
+}
+
+#[test]
+fn monster_table_parse() {
+    let table = monster_table();
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.name(102), "狼妖");
+    assert_eq!(table.get(103).unwrap().max_hp, 9000);
+    assert_eq!(table.name(7), "怪物7");
+    assert_eq!(table.get(7), None);
+    assert!(table.combatant(7).is_none());
+}
+
+#[test]
+fn unknown_monsters_are_left_out() {
+    let table = monster_table();
+    assert_eq!(table.known_monsters(vec![7, 101, 8, 103]), [101, 103]);
+    assert!(table.known_monsters(vec![7]).is_empty());
+    assert!(MonsterTable::default().known_monsters(vec![101]).is_empty());
+}
+
+#[test]
+fn skipped_battles_count_as_won_unless_they_must_fail() {
+    assert_eq!(CombatOptions::default().skipped_result(), CombatResult::Won);
+
+    let options = CombatOptions {
+        must_fail: true,
+        ..Default::default()
+    };
+    assert_eq!(options.skipped_result(), CombatResult::Lost);
+}
+
+#[test]
+fn random_encounters_fit_the_party_level() {
+    let table = monster_table();
+    for seed in 0..50 {
+        let ids = table.random_encounter(1, seed);
+        assert!((1..=MAX_ENCOUNTER_MONSTERS).contains(&ids.len()));
+        assert!(ids.iter().all(|id| *id == 101 || *id == 102));
+        assert_eq!(ids, table.random_encounter(1, seed));
+    }
+
+    assert!(MonsterTable::default().random_encounter(1, 0).is_empty());
+}
+
+#[test]
+fn faster_combatants_act_more_often() {
+    let mut battle = battle(
+        attributes(1000, 1, 1000, 20),
+        attributes(1000, 1, 1000, 10),
+        CombatOptions::default(),
+    );
+
+    let mut actors = vec![];
+    for _ in 0..6 {
+        let actor = battle.next_actor().unwrap();
+        actors.push(battle.combatants()[actor].side);
+        battle.act(BattleAction::Defend);
+    }
+
+    assert_eq!(
+        actors,
+        [
+            Side::Party,
+            Side::Party,
+            Side::Monster,
+            Side::Party,
+            Side::Party,
+            Side::Monster
+        ]
+    );
+}
+
+#[test]
+fn headless_battles_are_deterministic() {
+    let run = || {
+        let mut battle = battle(
+            attributes(100, 12, 5, 10),
+            attributes(80, 10, 5, 12),
+            CombatOptions::default(),
+        );
+        let result = battle.run_headless();
+        (result, battle.events().to_vec())
+    };
+
+    let (result, events) = run();
+    assert_eq!(run(), (result, events.clone()));
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, BattleEvent::Defeated { .. })));
+}
+
+#[test]
+fn stronger_side_wins() {
+    let mut won = battle(
+        attributes(500, 50, 20, 15),
+        attributes(50, 5, 5, 10),
+        CombatOptions::default(),
+    );
+    assert_eq!(won.run_headless(), CombatResult::Won);
+    assert!(won.combatants()[0].alive());
+
+    let mut lost = battle(
+        attributes(50, 5, 5, 10),
+        attributes(500, 50, 20, 15),
+        CombatOptions::default(),
+    );
+    assert_eq!(lost.run_headless(), CombatResult::Lost);
+}
+
+#[test]
+fn battles_end_after_max_round() {
+    let options = CombatOptions {
+        max_round: Some(3),
+        ..Default::default()
+    };
+    let mut survived = battle(
+        attributes(100_000, 1, 1000, 10),
+        attributes(100_000, 1, 1000, 10),
+        options.clone(),
+    );
+    assert_eq!(survived.run_headless(), CombatResult::Won);
+    assert_eq!(survived.round(), 3);
+
+    let mut failed = battle(
+        attributes(100_000, 1, 1000, 10),
+        attributes(100_000, 1, 1000, 10),
+        CombatOptions {
+            must_fail: true,
+            ..options
+        },
+    );
+    assert_eq!(failed.run_headless(), CombatResult::Lost);
+}
+
+#[test]
+fn must_fail_monsters_survive() {
+    let mut battle = battle(
+        attributes(200, 500, 10, 30),
+        attributes(10, 20, 0, 5),
+        CombatOptions {
+            must_fail: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(battle.run_headless(), CombatResult::Lost);
+    assert_eq!(battle.combatants()[1].hp, 1);
+}
+
+#[test]
+fn bosses_cannot_be_fled() {
+    let mut battle = battle(
+        attributes(100, 1, 1000, 10),
+        attributes(100, 1, 1000, 10),
+        CombatOptions {
+            boss: true,
+            ..Default::default()
+        },
+    );
+
+    battle.next_actor();
+    battle.act(BattleAction::Flee);
+    assert_eq!(battle.result(), None);
+    assert_eq!(battle.events(), [BattleEvent::FleeFailed { actor: 0 }]);
+}
+
+#[test]
+fn battles_use_and_update_the_party() {
+    let roles = RoleTable::parse("0, 景天, 5, 300, 80, 40, 15, 20, 10\n");
+    let mut party = Party::new();
+    party.role_mut(0, &roles).unwrap().hp = 120;
+
+    let request = CombatRequest {
+        monster_ids: vec![101, 101],
+        options: CombatOptions::default(),
+        seed: 7,
+    };
+    let mut battle = request.new_battle(&mut party, &roles, &monster_table());
+    assert_eq!(battle.combatants().len(), 3);
+    assert_eq!(battle.combatants()[0].name, "景天");
+    assert_eq!(battle.combatants()[0].hp, 120);
+    assert_eq!(battle.combatants()[2].name, "野狗");
+
+    assert_eq!(battle.run_headless(), CombatResult::Won);
+    combat::update_party(&battle, &mut party, &roles);
+    assert_eq!(party.role(0).unwrap().hp, battle.combatants()[0].hp);
+    assert_eq!(CombatResult::Won.sce_value(), 1);
+    assert_eq!(CombatResult::Fled.sce_value(), 0);
+}
+
+#[test]
+fn unknown_monsters_stay_out_of_battles() {
+    let roles = RoleTable::parse("0, 景天, 5, 300, 80, 40, 15, 20, 10\n");
+    let request = CombatRequest {
+        monster_ids: vec![7, 101],
+        options: CombatOptions::default(),
+        seed: 7,
+    };
+    let battle = request.new_battle(&mut Party::new(), &roles, &monster_table());
+    let names: Vec<&str> = battle
+        .combatants()
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, ["景天", "野狗"]);
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use shared::fs::cpk::{CpkArchive, CpkWriter};
+
+fn sample_writer(pal4: bool) -> CpkWriter {
+    let mut writer = CpkWriter::new(pal4);
+    writer.add_file("readme.txt", b"hello cpk".to_vec());
+    writer.add_file("scene/q01/q01.scn", vec![0x42; 4096]);
+    writer.add_file("scene/q01/空.bin", vec![]);
+    writer.add_file("scene/m01/data.bin", (0..=255u8).collect());
+    writer
+}
+
+fn write_to_vec(writer: &CpkWriter) -> Vec<u8> {
+    let mut buffer = vec![];
+    writer.write(&mut buffer).unwrap();
+    buffer
+}
+
+fn check_round_trip(pal4: bool) {
+    let writer = sample_writer(pal4);
+    let packed = write_to_vec(&writer);
+
+    let archive = CpkArchive::load(Arc::new(packed.clone())).unwrap();
+    assert_eq!(pal4, archive.is_pal4());
+    assert_eq!(
+        b"hello cpk".to_vec(),
+        archive.open_str("README.TXT").unwrap().content()
+    );
+    assert_eq!(
+        vec![0x42; 4096],
+        archive.open_str("scene\\q01\\q01.scn").unwrap().content()
+    );
+    assert_eq!(
+        (0..=255u8).collect::<Vec<u8>>(),
+        archive.open_str("scene\\m01\\data.bin").unwrap().content()
+    );
+
+    let root = archive.build_directory().unwrap();
+    let scene = root.ls("scene").unwrap();
+    let mut names: Vec<String> = scene.iter().map(|e| e.name().to_string()).collect();
+    names.sort();
+    assert_eq!(vec!["m01", "q01"], names);
+
+    let mut rewriter = CpkWriter::new(pal4);
+    rewriter.add_archive(&archive).unwrap();
+    assert_eq!(packed, write_to_vec(&rewriter));
+}
+
+#[test]
+fn test_cpk_round_trip_pal3() {
+    check_round_trip(false);
+}
+
+#[test]
+fn test_cpk_round_trip_pal4() {
+    check_round_trip(true);
+}
@@ -0,0 +1,38 @@
+use shared::openpal3::states::{favor::FavorTable, persistent_state::PersistentState};
+
+#[test]
+fn favors_accumulate() {
+    let mut favors = FavorTable::new();
+    assert_eq!(favors.get(1), 0);
+
+    favors.add(1, 5);
+    favors.add(1, -2);
+    favors.add(2, i32::MAX);
+    favors.add(2, 1);
+    assert_eq!(favors.get(1), 3);
+    assert_eq!(favors.get(2), i32::MAX);
+}
+
+#[test]
+fn favorite_prefers_the_smaller_role_id_on_tie() {
+    let mut favors = FavorTable::new();
+    assert_eq!(favors.favorite(), -1);
+
+    favors.set(3, 10);
+    favors.set(2, 10);
+    favors.set(1, 4);
+    assert_eq!(favors.favorite(), 2);
+
+    favors.add(3, 1);
+    assert_eq!(favors.favorite(), 3);
+}
+
+#[test]
+fn favors_are_saved() {
+    let mut state = PersistentState::new("OpenPAL3".to_string());
+    state.favors_mut().add(2, 7);
+
+    let json = serde_json::to_string(&state).unwrap();
+    let state: PersistentState = serde_json::from_str(&json).unwrap();
+    assert_eq!(state.favors().iter().collect::<Vec<_>>(), [(2, 7)]);
+}
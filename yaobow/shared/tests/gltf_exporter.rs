@@ -0,0 +1,251 @@
+use fileformats::{
+    mv3::{Mv3File, Mv3Frame, Mv3Mesh, Mv3Model, Mv3Texture, Mv3Triangle, Mv3Vertex},
+    rwbs::TexCoord,
+    utils::SizedString,
+};
+use serde_json::Value;
+use shared::exporters::{
+    gltf_exporter::{GltfAnimationPath, GltfBuilder, GltfChannel, GltfNode, GltfPrimitive},
+    mv3_gltf_exporter::export_mv3_to_gltf,
+};
+
+fn parse_glb(glb: &[u8]) -> (Value, Vec<u8>) {
+    let u32_at = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+    assert_eq!(&glb[0..4], b"glTF");
+    assert_eq!(u32_at(4), 2);
+    assert_eq!(u32_at(8) as usize, glb.len());
+
+    let json_len = u32_at(12) as usize;
+    assert_eq!(&glb[16..20], b"JSON");
+    assert_eq!(json_len % 4, 0);
+    let json = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+
+    let bin_offset = 20 + json_len;
+    let bin_len = u32_at(bin_offset) as usize;
+    assert_eq!(&glb[bin_offset + 4..bin_offset + 8], b"BIN\0");
+    let bin = glb[bin_offset + 8..bin_offset + 8 + bin_len].to_vec();
+    (json, bin)
+}
+
+fn read_floats(json: &Value, bin: &[u8], accessor: usize) -> Vec<f32> {
+    let accessor = &json["accessors"][accessor];
+    let view = &json["bufferViews"][accessor["bufferView"].as_u64().unwrap() as usize];
+    let offset = view["byteOffset"].as_u64().unwrap() as usize;
+    let len = view["byteLength"].as_u64().unwrap() as usize;
+    bin[offset..offset + len]
+        .chunks(4)
+        .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_gltf_builder_morph_targets_and_animation() {
+    let mut builder = GltfBuilder::new();
+    let material = builder.add_material("missing.dds", None, false);
+    assert_eq!(material, builder.add_material("missing.dds", None, false));
+
+    let mesh = builder.add_mesh(
+        "triangle",
+        &[GltfPrimitive {
+            positions: vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+            texcoords: Some(vec![[0., 0.], [1., 0.], [0., 1.]]),
+            indices: vec![0, 1, 2],
+            material: Some(material),
+            morph_targets: vec![vec![[0., 0., 1.], [1., 0., 1.], [0., 1., 1.]]],
+            ..Default::default()
+        }],
+    );
+    let node = builder.add_node(GltfNode {
+        name: "triangle".to_string(),
+        mesh: Some(mesh),
+        ..Default::default()
+    });
+    builder.add_root(node);
+    builder.add_animation(
+        "move",
+        &[GltfChannel {
+            node,
+            path: GltfAnimationPath::Translation,
+            times: vec![0., 1., 1., 2.],
+            values: vec![0., 0., 0., 1., 0., 0., 9., 9., 9., 2., 0., 0.],
+            step: false,
+        }],
+    );
+
+    let (json, bin) = parse_glb(&builder.to_glb());
+    assert_eq!(json["asset"]["version"], "2.0");
+    assert_eq!(json["scenes"][0]["nodes"][0], node);
+    assert_eq!(json["images"][0]["uri"], "missing.dds");
+    assert_eq!(json["meshes"][0]["weights"].as_array().unwrap().len(), 1);
+
+    let primitive = &json["meshes"][0]["primitives"][0];
+    let target = primitive["targets"][0]["POSITION"].as_u64().unwrap() as usize;
+    assert_eq!(
+        read_floats(&json, &bin, target),
+        vec![0., 0., 1., 0., 0., 1., 0., 0., 1.]
+    );
+
+    let position = primitive["attributes"]["POSITION"].as_u64().unwrap() as usize;
+    assert_eq!(json["accessors"][position]["max"][0], 1.0);
+
+    // The keyframe that doesn't move time forward is dropped
+    let sampler = &json["animations"][0]["samplers"][0];
+    let input = sampler["input"].as_u64().unwrap() as usize;
+    let output = sampler["output"].as_u64().unwrap() as usize;
+    assert_eq!(read_floats(&json, &bin, input), vec![0., 1., 2.]);
+    assert_eq!(
+        read_floats(&json, &bin, output),
+        vec![0., 0., 0., 1., 0., 0., 2., 0., 0.]
+    );
+}
+
+fn vertex(x: i16, y: i16, z: i16) -> Mv3Vertex {
+    Mv3Vertex {
+        x,
+        y,
+        z,
+        normal_phi: 0,
+        normal_theta: 0,
+    }
+}
+
+#[test]
+fn test_mv3_to_gltf_keeps_frames() {
+    let frames = vec![
+        Mv3Frame {
+            timestamp: 0,
+            vertices: vec![vertex(0, 0, 0), vertex(64, 0, 0), vertex(0, 64, 0)],
+        },
+        Mv3Frame {
+            timestamp: 4580,
+            vertices: vec![vertex(0, 0, 64), vertex(64, 0, 64), vertex(0, 64, 64)],
+        },
+    ];
+
+    let mv3 = Mv3File {
+        version: 0,
+        duration: 4580,
+        texture_count: 1,
+        unknown_data_count: 0,
+        model_count: 1,
+        action_count: 0,
+        action_desc: vec![],
+        unknown_data: vec![],
+        textures: vec![Mv3Texture {
+            unknown: vec![0.; 17],
+            names: vec![
+                SizedString::from("role.tga"),
+                SizedString::from(""),
+                SizedString::from(""),
+                SizedString::from(""),
+            ],
+        }],
+        models: vec![Mv3Model {
+            unknown: vec![0; 64],
+            vertex_per_frame: 3,
+            aabb_min: [0.; 3],
+            aabb_max: [1.; 3],
+            frame_count: 2,
+            frames,
+            texcoord_count: 1,
+            texcoords: vec![TexCoord { u: 0.5, v: 0.5 }],
+            mesh_count: 1,
+            meshes: vec![Mv3Mesh {
+                unknown: 0,
+                triangle_count: 1,
+                triangles: vec![Mv3Triangle {
+                    indices: [0, 1, 2],
+                    texcoord_indices: [0, 0, 0],
+                }],
+                unknown_data_count: 0,
+                unknown_data: vec![],
+            }],
+        }],
+    };
+
+    let builder = export_mv3_to_gltf(&mv3, "role", &|name| {
+        assert_eq!(name, "role.tga");
+        None
+    });
+    let (json, bin) = parse_glb(&builder.to_glb());
+
+    assert_eq!(json["materials"][0]["name"], "role.tga");
+    let primitive = &json["meshes"][0]["primitives"][0];
+    assert_eq!(primitive["targets"].as_array().unwrap().len(), 1);
+
+    let channel = &json["animations"][0]["channels"][0];
+    assert_eq!(channel["target"]["path"], "weights");
+    let sampler = &json["animations"][0]["samplers"][0];
+    let input = sampler["input"].as_u64().unwrap() as usize;
+    let output = sampler["output"].as_u64().unwrap() as usize;
+    assert_eq!(read_floats(&json, &bin, input), vec![0., 1.]);
+    assert_eq!(read_floats(&json, &bin, output), vec![0., 1.]);
+}
+
+fn create_mv3(frames: Vec<Mv3Frame>, triangles: Vec<Mv3Triangle>) -> Mv3File {
+    Mv3File {
+        version: 0,
+        duration: 0,
+        texture_count: 0,
+        unknown_data_count: 0,
+        model_count: 1,
+        action_count: 0,
+        action_desc: vec![],
+        unknown_data: vec![],
+        textures: vec![],
+        models: vec![Mv3Model {
+            unknown: vec![0; 64],
+            vertex_per_frame: 3,
+            aabb_min: [0.; 3],
+            aabb_max: [1.; 3],
+            frame_count: frames.len() as u32,
+            frames,
+            texcoord_count: 0,
+            texcoords: vec![],
+            mesh_count: 1,
+            meshes: vec![Mv3Mesh {
+                unknown: 0,
+                triangle_count: triangles.len() as u32,
+                triangles,
+                unknown_data_count: 0,
+                unknown_data: vec![],
+            }],
+        }],
+    }
+}
+
+#[test]
+fn test_mv3_to_gltf_skips_invalid_triangles() {
+    let frames = vec![Mv3Frame {
+        timestamp: 0,
+        vertices: vec![vertex(0, 0, 0), vertex(64, 0, 0), vertex(0, 64, 0)],
+    }];
+    let triangles = vec![
+        Mv3Triangle {
+            indices: [0, 1, 2],
+            texcoord_indices: [0, 0, 0],
+        },
+        Mv3Triangle {
+            indices: [0, 1, 3],
+            texcoord_indices: [0, 0, 0],
+        },
+    ];
+
+    let builder = export_mv3_to_gltf(&create_mv3(frames, triangles), "role", &|_| None);
+    let (json, _) = parse_glb(&builder.to_glb());
+    let primitive = &json["meshes"][0]["primitives"][0];
+    let indices = primitive["indices"].as_u64().unwrap() as usize;
+    assert_eq!(json["accessors"][indices]["count"], 3);
+}
+
+#[test]
+fn test_mv3_to_gltf_without_frames() {
+    let triangles = vec![Mv3Triangle {
+        indices: [0, 1, 2],
+        texcoord_indices: [0, 0, 0],
+    }];
+
+    let builder = export_mv3_to_gltf(&create_mv3(vec![], triangles), "role", &|_| None);
+    let (json, _) = parse_glb(&builder.to_glb());
+    assert_eq!(0, json["meshes"].as_array().map_or(0, Vec::len));
+}
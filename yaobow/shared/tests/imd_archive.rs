@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use shared::fs::{
+    imd::imd_archive::{ImdArchive, ImdReadError},
+    plain_fs::PlainArchive,
+};
+
+struct TestTexture {
+    name: &'static str,
+    file_type: u32,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+fn build_imd(textures: &[TestTexture]) -> Vec<u8> {
+    let mut output = vec![];
+    output.extend_from_slice(&0x20444d49u32.to_le_bytes());
+    for texture in textures {
+        let mut unknown12 = [0u32; 12];
+        unknown12[1] = texture.file_type;
+        unknown12[4] = texture.width;
+        unknown12[5] = texture.height;
+        unknown12[8] = texture.data.len() as u32;
+
+        let chunk_size = 4 + 48 + 4 + texture.name.len() + texture.data.len();
+        output.extend_from_slice(&(chunk_size as u32).to_le_bytes());
+        for value in unknown12 {
+            output.extend_from_slice(&value.to_le_bytes());
+        }
+
+        output.extend_from_slice(&(texture.name.len() as u32).to_le_bytes());
+        output.extend_from_slice(texture.name.as_bytes());
+        output.extend_from_slice(&texture.data);
+    }
+
+    output.extend_from_slice(&(textures.len() as u32).to_le_bytes());
+    output
+}
+
+fn words(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn dwords(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn with_size(width: u32, height: u32, pixels: &[[u8; 4]]) -> Vec<u8> {
+    let mut output = vec![];
+    output.extend_from_slice(&width.to_le_bytes());
+    output.extend_from_slice(&height.to_le_bytes());
+    for pixel in pixels {
+        output.extend_from_slice(pixel);
+    }
+
+    output
+}
+
+fn load(textures: &[TestTexture]) -> ImdArchive {
+    ImdArchive::load(Arc::new(build_imd(textures))).unwrap()
+}
+
+#[test]
+fn test_imd_rgb565_rle() {
+    // A literal run of red and green, then blue repeated twice
+    let archive = load(&[TestTexture {
+        name: "a.tga",
+        file_type: 3,
+        width: 2,
+        height: 2,
+        data: words(&[0x0001, 0xf800, 0x07e0, 0x8001, 0x001f]),
+    }]);
+
+    assert_eq!(
+        with_size(
+            2,
+            2,
+            &[
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [0, 0, 255, 255],
+                [0, 0, 255, 255]
+            ]
+        ),
+        archive.open("a.tga").unwrap().content()
+    );
+}
+
+#[test]
+fn test_imd_bgra8888_rle() {
+    // Three repeated pixels followed by one literal pixel
+    let archive = load(&[TestTexture {
+        name: "b.tga",
+        file_type: 5,
+        width: 4,
+        height: 1,
+        data: dwords(&[0x80000002, 0x80112233, 0x00000000, 0x7f445566]),
+    }]);
+
+    assert_eq!(
+        with_size(
+            4,
+            1,
+            &[
+                [0x11, 0x22, 0x33, 0x80],
+                [0x11, 0x22, 0x33, 0x80],
+                [0x11, 0x22, 0x33, 0x80],
+                [0x44, 0x55, 0x66, 0x7f]
+            ]
+        ),
+        archive.open("b.tga").unwrap().content()
+    );
+}
+
+#[test]
+fn test_imd_raw_images() {
+    let archive = load(&[
+        TestTexture {
+            name: "c.dds",
+            file_type: 7,
+            width: 0,
+            height: 0,
+            data: b"DDS raw".to_vec(),
+        },
+        TestTexture {
+            name: "d.tga",
+            file_type: 9,
+            width: 0,
+            height: 0,
+            data: b"TGA raw".to_vec(),
+        },
+    ]);
+
+    assert_eq!(
+        b"DDS raw".to_vec(),
+        archive.open("c.dds").unwrap().content()
+    );
+    assert_eq!(
+        b"TGA raw".to_vec(),
+        archive.open("d.tga").unwrap().content()
+    );
+}
+
+#[test]
+fn test_imd_unsupported_type() {
+    let archive = load(&[TestTexture {
+        name: "e.tga",
+        file_type: 42,
+        width: 1,
+        height: 1,
+        data: vec![0; 4],
+    }]);
+
+    let error = archive.open("e.tga").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ImdReadError>(),
+        Some(ImdReadError::UnsupportedFileType(42))
+    ));
+}
+
+#[test]
+fn test_imd_truncated_rle() {
+    // The run claims 4 literal pixels but only one follows
+    let archive = load(&[TestTexture {
+        name: "f.tga",
+        file_type: 3,
+        width: 2,
+        height: 2,
+        data: words(&[0x0003, 0xffff]),
+    }]);
+
+    let error = archive.open("f.tga").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ImdReadError>(),
+        Some(ImdReadError::CorruptedData(_))
+    ));
+}
+
+#[test]
+fn test_imd_rle_overrun() {
+    // A 2x2 texture whose repeated run claims 0x7fffffff + 1 pixels
+    let archive = load(&[TestTexture {
+        name: "g.tga",
+        file_type: 5,
+        width: 2,
+        height: 2,
+        data: dwords(&[0xffffffff, 0x80112233]),
+    }]);
+
+    let error = archive.open("g.tga").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ImdReadError>(),
+        Some(ImdReadError::TooManyPixels(4))
+    ));
+
+    // One literal pixel too many for a 1x1 texture
+    let archive = load(&[TestTexture {
+        name: "h.tga",
+        file_type: 3,
+        width: 1,
+        height: 1,
+        data: words(&[0x0001, 0xf800, 0x07e0]),
+    }]);
+
+    let error = archive.open("h.tga").unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<ImdReadError>(),
+        Some(ImdReadError::TooManyPixels(1))
+    ));
+}
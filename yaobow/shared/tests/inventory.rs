@@ -0,0 +1,92 @@
+use shared::openpal3::states::{
+    inventory::{Inventory, ItemTable, MAX_ITEM_COUNT, MAX_MONEY},
+    persistent_state::PersistentState,
+};
+
+#[test]
+fn items_are_counted_and_removed() {
+    let mut inventory = Inventory::new();
+    assert!(!inventory.have_item(101));
+
+    inventory.add_item(101, 2);
+    inventory.add_item(102, 1);
+    assert_eq!(inventory.item_count(101), 2);
+
+    inventory.remove_item(101);
+    inventory.remove_item(102);
+    assert!(inventory.have_item(101));
+    assert!(!inventory.have_item(102));
+    assert_eq!(inventory.items().collect::<Vec<_>>(), [(101, 1)]);
+
+    inventory.add_item(101, -5);
+    assert_eq!(inventory.items().count(), 0);
+
+    inventory.add_item(103, 1000);
+    assert_eq!(inventory.item_count(103), MAX_ITEM_COUNT);
+}
+
+#[test]
+fn money_is_clamped() {
+    let mut inventory = Inventory::new();
+    inventory.add_money(300);
+    inventory.add_money(-100);
+    assert_eq!(inventory.money(), 200);
+
+    inventory.add_money(-1000);
+    assert_eq!(inventory.money(), 0);
+
+    inventory.add_money(i32::MAX);
+    assert_eq!(inventory.money(), MAX_MONEY);
+}
+
+#[test]
+fn clothes_are_not_duplicated() {
+    let mut inventory = Inventory::new();
+    inventory.give_cloth(3);
+    inventory.give_cloth(1);
+    inventory.give_cloth(3);
+    assert_eq!(inventory.clothes(), [3, 1]);
+}
+
+#[test]
+fn item_table_parse() {
+    let table = ItemTable::parse("// id name price\n101, 止血草, 10\n  102\t金创药\t50\nEND\n");
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.get(102).unwrap().name, "金创药");
+    assert!(table.is_valid(101));
+    assert!(!table.is_valid(103));
+    assert!(!ItemTable::default().is_valid(101));
+}
+
+#[test]
+fn inventory_is_saved() {
+    let mut state = PersistentState::new("OpenPAL3".to_string());
+    state.inventory_mut().add_money(50);
+    state.inventory_mut().add_item(101, 3);
+
+    let json = serde_json::to_string(&state).unwrap();
+    let state: PersistentState = serde_json::from_str(&json).unwrap();
+    assert_eq!(state.inventory().money(), 50);
+    assert_eq!(state.inventory().item_count(101), 3);
+}
+
+#[test]
+fn old_saves_have_untracked_inventory() {
+    let json = r#"{
+        "app_name": "OpenPAL3",
+        "global_vars": {"1": 2},
+        "position": {"x": 0.0, "y": 0.0, "z": 0.0},
+        "scene": "q01",
+        "sub_scene": "q01a"
+    }"#;
+
+    let mut state = PersistentState::from_json(json).unwrap();
+    assert_eq!(state.get_global(1), Some(2));
+    assert_eq!(state.inventory(), &Inventory::untracked());
+    assert!(state.inventory().have_item(101));
+
+    state.inventory_mut().add_money(10);
+    let state = PersistentState::from_json(&serde_json::to_string(&state).unwrap()).unwrap();
+    assert!(state.inventory().is_untracked());
+    assert_eq!(state.inventory().money(), 10);
+}
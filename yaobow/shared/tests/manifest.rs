@@ -0,0 +1,102 @@
+use std::io::Cursor;
+
+use mini_fs::{LocalFs, MiniFs};
+use shared::fs::manifest::{detect_edition, load_editions, Manifest, ManifestEntry};
+
+fn create_vfs(name: &str, files: &[(&str, &[u8])]) -> (MiniFs, std::path::PathBuf) {
+    let root = std::env::temp_dir().join(format!("yaobow_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for (path, data) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    let vfs = MiniFs::new(false).mount("/", LocalFs::new(&root));
+    (vfs, root)
+}
+
+#[test]
+fn test_manifest_build_and_round_trip() {
+    let (vfs, root) = create_vfs(
+        "manifest",
+        &[("readme.txt", b"hello"), ("scene/q01.scn", b"")],
+    );
+
+    let manifest = Manifest::build(&vfs, "/");
+    assert_eq!(
+        Some(&ManifestEntry {
+            size: 5,
+            md5: "5d41402abc4b2a76b9719d911017c592".to_string()
+        }),
+        manifest.entries.get("/readme.txt")
+    );
+    assert_eq!(
+        Some(&ManifestEntry {
+            size: 0,
+            md5: "d41d8cd98f00b204e9800998ecf8427e".to_string()
+        }),
+        manifest.entries.get("/scene/q01.scn")
+    );
+    assert!(manifest.check(&vfs).is_empty());
+
+    let mut buffer = vec![];
+    manifest.write(&mut buffer).unwrap();
+    let loaded = Manifest::read(&mut Cursor::new(buffer)).unwrap();
+    assert_eq!(manifest, loaded);
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn test_manifest_diff() {
+    let old = Manifest::read(&mut Cursor::new(
+        "# edition a\naa\t1\t/a.bin\nbb\t2\t/b.bin\ncc\t3\t/c.bin\n",
+    ))
+    .unwrap();
+    let new = Manifest::read(&mut Cursor::new(
+        "aa\t1\t/a.bin\nbc\t2\t/b.bin\ndd\t4\t/d.bin\n",
+    ))
+    .unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(vec!["/d.bin"], diff.added);
+    assert_eq!(vec!["/c.bin"], diff.removed);
+    assert_eq!(vec!["/b.bin"], diff.changed);
+    assert!(old.diff(&old).is_empty());
+    assert!(Manifest::read(&mut Cursor::new("aa\tbig\t/a.bin\n")).is_err());
+}
+
+#[test]
+fn test_detect_edition() {
+    let (vfs, root) = create_vfs("edition", &[("readme.txt", b"hello")]);
+    let manifests =
+        std::env::temp_dir().join(format!("yaobow_edition_manifests_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&manifests);
+    std::fs::create_dir_all(&manifests).unwrap();
+    std::fs::write(
+        manifests.join("cd.manifest"),
+        "d41d8cd98f00b204e9800998ecf8427e\t0\t/readme.txt\n",
+    )
+    .unwrap();
+    std::fs::write(
+        manifests.join("steam.manifest"),
+        "5d41402abc4b2a76b9719d911017c592\t5\t/readme.txt\n",
+    )
+    .unwrap();
+    std::fs::write(manifests.join("notes.txt"), "not a manifest").unwrap();
+
+    let editions = load_editions(&manifests);
+    assert_eq!(
+        vec!["cd", "steam"],
+        editions
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(Some("steam"), detect_edition(&vfs, &editions));
+    assert_eq!(None, detect_edition(&vfs, &editions[..1]));
+
+    let _ = std::fs::remove_dir_all(manifests);
+    let _ = std::fs::remove_dir_all(root);
+}
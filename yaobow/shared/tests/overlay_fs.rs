@@ -0,0 +1,120 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use mini_fs::{MiniFs, Store};
+use shared::fs::{
+    memory_file::MemoryFile,
+    overlay_fs::OverlayFs,
+    plain_fs::{PlainArchive, PlainFileInfo, PlainFs},
+};
+
+struct TestArchive {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl PlainArchive for TestArchive {
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let data = self
+            .files
+            .get(path.as_ref().to_str().unwrap())
+            .ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        Ok(MemoryFile::new(Cursor::new(data.clone())))
+    }
+
+    fn files(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        vec![]
+    }
+}
+
+fn create_layer(files: &[(&str, &[u8])]) -> MiniFs {
+    let files = files
+        .iter()
+        .map(|(name, data)| (name.to_string(), data.to_vec()))
+        .collect();
+    MiniFs::new(false).mount("/", PlainFs::new(TestArchive { files }))
+}
+
+fn create_fs() -> OverlayFs {
+    OverlayFs::new()
+        .push_layer(
+            "mod",
+            create_layer(&[("basedata/role.txt", b"mod"), ("mod.txt", b"new")]),
+        )
+        .push_layer(
+            "game",
+            create_layer(&[
+                ("basedata/role.txt", b"game"),
+                ("basedata/item.txt", b"item"),
+            ]),
+        )
+}
+
+fn read(fs: &OverlayFs, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    fs.open_path(Path::new(path))
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    content
+}
+
+fn list(fs: &OverlayFs, path: &str) -> Vec<OsString> {
+    let mut names: Vec<OsString> = fs
+        .entries_path(Path::new(path))
+        .unwrap()
+        .map(|e| e.unwrap().name)
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_overlay_fs_first_layer_wins() {
+    let fs = create_fs();
+    assert_eq!(vec!["mod", "game"], fs.layer_names());
+    assert_eq!(b"mod".to_vec(), read(&fs, "/basedata/role.txt"));
+}
+
+#[test]
+fn test_overlay_fs_falls_through() {
+    let fs = create_fs();
+    assert_eq!(b"item".to_vec(), read(&fs, "/basedata/item.txt"));
+    assert_eq!(b"new".to_vec(), read(&fs, "/mod.txt"));
+    assert_eq!(
+        std::io::ErrorKind::NotFound,
+        fs.open_path(Path::new("/basedata/missing.txt"))
+            .err()
+            .unwrap()
+            .kind()
+    );
+}
+
+#[test]
+fn test_overlay_fs_entries() {
+    let fs = create_fs();
+    assert_eq!(
+        vec![OsString::from("basedata"), OsString::from("mod.txt")],
+        list(&fs, "/")
+    );
+    assert_eq!(
+        vec![OsString::from("item.txt"), OsString::from("role.txt")],
+        list(&fs, "/basedata")
+    );
+    assert!(fs.entries_path(Path::new("/music")).is_err());
+}
+
+#[test]
+fn test_overlay_fs_empty() {
+    let fs = OverlayFs::default();
+    assert!(fs.layer_names().is_empty());
+    assert!(fs.open_path(Path::new("/basedata/role.txt")).is_err());
+    assert!(fs.entries_path(Path::new("/")).is_err());
+}
@@ -0,0 +1,142 @@
+use shared::openpal3::states::{
+    party::{Party, RoleTable, LEADER_ROLE_ID},
+    persistent_state::PersistentState,
+};
+
+fn role_table() -> RoleTable {
+    RoleTable::parse(
+        "// id name level hp mp attack defense speed luck\n\
+         0, 景天, 1, 200, 80, 20, 15, 12, 10\n\
+         1\t雪见\t3\t150\t120\t14\t12\t15\t20\n\
+         2, 龙葵, 12, 180, 150\n",
+    )
+}
+
+#[test]
+fn role_table_parse() {
+    let table = role_table();
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.name(1), Some("雪见"));
+    assert_eq!(table.get(1).unwrap().max_mp, 120);
+    assert_eq!(table.get(2), None);
+}
+
+#[test]
+fn members_join_and_leave() {
+    let mut party = Party::new();
+    assert_eq!(party.members(), [LEADER_ROLE_ID]);
+
+    party.join(1);
+    party.join(2);
+    party.join(1);
+    assert_eq!(party.members(), [0, 1, 2]);
+
+    party.leave(1);
+    assert!(!party.in_team(1));
+    assert_eq!(party.members(), [0, 2]);
+
+    party.set_team_open(false);
+    assert!(!party.team_open());
+}
+
+#[test]
+fn roles_start_from_the_table_and_keep_their_state() {
+    let table = role_table();
+    let mut party = Party::new();
+    assert!(party.role(1).is_none());
+    assert_eq!(party.level(1, &table), Some(3));
+
+    party.join(1);
+    party.role_mut(1, &table).unwrap().hp = 10;
+    party.add_skill(1, 7, &table);
+    party.leave(1);
+    party.join(1);
+
+    let role = party.role(1).unwrap();
+    assert_eq!(role.hp, 10);
+    assert_eq!(role.mp, 120);
+    assert!(role.skills.contains(&7));
+
+    party.restore_all();
+    assert_eq!(party.role(1).unwrap().hp, 150);
+}
+
+#[test]
+fn average_level() {
+    let table = role_table();
+    let mut party = Party::new();
+    party.join(1);
+    party.join(1);
+    party.role_mut(1, &table).unwrap().attributes.level = 9;
+    assert_eq!(party.average_level(0, &table), 9);
+    assert_eq!(party.average_level(1, &table), 1);
+
+    party.role_mut(0, &table).unwrap().attributes.level = 5;
+    party.level_up_to_average(0, 2, &table);
+    assert_eq!(party.level(0, &table), Some(11));
+
+    party.level_up_to_average(0, -5, &table);
+    assert_eq!(party.level(0, &table), Some(11));
+}
+
+#[test]
+fn roles_missing_from_the_table_are_skipped() {
+    let table = role_table();
+    let mut party = Party::new();
+    party.join(3);
+    assert!(party.role_mut(3, &table).is_none());
+    assert_eq!(party.level(3, &table), None);
+    assert_eq!(party.average_level(-1, &table), 1);
+
+    party.add_skill(3, 7, &table);
+    party.level_up_to_average(3, 2, &table);
+    assert!(party.role(3).is_none());
+}
+
+#[test]
+fn party_is_saved() {
+    let table = role_table();
+    let mut state = PersistentState::new("OpenPAL3".to_string());
+    state.party_mut().join(1);
+    state.party_mut().add_skill(1, 7, &table);
+
+    let json = serde_json::to_string(&state).unwrap();
+    let state: PersistentState = serde_json::from_str(&json).unwrap();
+    assert_eq!(state.party().members(), [0, 1]);
+    assert!(state.party().role(1).unwrap().skills.contains(&7));
+}
+
+#[test]
+fn unknown_roster_keeps_everyone_in_team() {
+    let mut party = Party::unknown();
+    assert!(party.in_team(1));
+    assert!(party.in_team(4));
+
+    party.join(1);
+    assert!(party.roster_known());
+    assert!(party.in_team(1));
+    assert!(!party.in_team(4));
+
+    let mut party = Party::unknown();
+    party.leave(4);
+    assert!(!party.in_team(4));
+    assert!(party.in_team(LEADER_ROLE_ID));
+}
+
+#[test]
+fn old_saves_have_an_unknown_roster() {
+    let json = r#"{
+        "app_name": "OpenPAL3",
+        "global_vars": {},
+        "position": {"x": 0.0, "y": 0.0, "z": 0.0},
+        "scene": null,
+        "sub_scene": null
+    }"#;
+
+    let state: PersistentState = serde_json::from_str(json).unwrap();
+    assert_eq!(state.party(), &Party::unknown());
+    assert!(state.party().in_team(1));
+    assert!(PersistentState::new("OpenPAL3".to_string())
+        .party()
+        .roster_known());
+}
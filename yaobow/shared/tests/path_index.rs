@@ -0,0 +1,46 @@
+use shared::fs::path_index::PathIndex;
+
+fn create_index() -> PathIndex {
+    PathIndex::new([
+        "Basedata\\Role.txt",
+        "/music/P01.mp3",
+        "map/q01/Q01.cpk",
+        "BASEDATA/role.txt",
+    ])
+}
+
+#[test]
+fn test_path_index_ignores_case() {
+    let index = create_index();
+    assert_eq!(Some(0), index.get("basedata/role.txt"));
+    assert_eq!(Some(0), index.get("BASEDATA\\ROLE.TXT"));
+    assert_eq!(Some(2), index.get("Map/Q01/q01.CPK"));
+}
+
+#[test]
+fn test_path_index_normalizes_separators() {
+    let index = create_index();
+    assert_eq!(Some(1), index.get("music\\p01.mp3"));
+    assert_eq!(Some(1), index.get("/music/p01.mp3"));
+    assert_eq!(Some(1), index.get("\\\\music\\p01.mp3"));
+    assert_eq!(Some(2), index.get("map\\q01/q01.cpk"));
+    assert_eq!(None, index.get("music"));
+    assert_eq!(None, index.get("music/p02.mp3"));
+}
+
+#[test]
+fn test_path_index_keeps_first_duplicate() {
+    let index = create_index();
+    assert_eq!(3, index.len());
+    assert_eq!(Some(0), index.get("basedata/role.txt"));
+}
+
+#[test]
+fn test_path_index_normalize() {
+    assert_eq!(
+        "map/q01/q01.cpk",
+        PathIndex::normalize("\\Map\\Q01\\Q01.cpk")
+    );
+    assert_eq!("", PathIndex::normalize("/"));
+    assert!(PathIndex::default().is_empty());
+}
@@ -0,0 +1,91 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use mini_fs::{EntryKind, Store};
+use shared::fs::{
+    memory_file::MemoryFile,
+    plain_fs::{PlainArchive, PlainFileInfo, PlainFs},
+};
+
+struct TestArchive {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl PlainArchive for TestArchive {
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let data = self
+            .files
+            .get(path.as_ref().to_str().unwrap())
+            .ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        Ok(MemoryFile::new(Cursor::new(data.clone())))
+    }
+
+    fn files(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        vec![]
+    }
+}
+
+fn create_fs() -> PlainFs<TestArchive> {
+    let mut files = HashMap::new();
+    files.insert("role_00.bin".to_string(), b"root".to_vec());
+    files.insert("map/q01/q01_0_0.nod".to_string(), b"nod".to_vec());
+    files.insert("map\\q01\\q01_sky.dff".to_string(), b"sky".to_vec());
+    PlainFs::new(TestArchive { files })
+}
+
+fn read(fs: &PlainFs<TestArchive>, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    fs.open_path(Path::new(path))
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    content
+}
+
+fn list(fs: &PlainFs<TestArchive>, path: &str) -> Vec<(OsString, bool)> {
+    fs.entries_path(Path::new(path))
+        .unwrap()
+        .map(|e| {
+            let e = e.unwrap();
+            (e.name, matches!(e.kind, EntryKind::Dir))
+        })
+        .collect()
+}
+
+#[test]
+fn test_plain_fs_underscore_names() {
+    let fs = create_fs();
+    assert_eq!(b"root".to_vec(), read(&fs, "role_00.bin"));
+    assert_eq!(b"nod".to_vec(), read(&fs, "map/q01/q01_0_0.nod"));
+    assert_eq!(b"sky".to_vec(), read(&fs, "map/q01/q01_sky.dff"));
+    assert!(fs.open_path(Path::new("map/q01/q01/0/0.nod")).is_err());
+}
+
+#[test]
+fn test_plain_fs_entries() {
+    let fs = create_fs();
+    assert_eq!(
+        vec![
+            (OsString::from("map"), true),
+            (OsString::from("role_00.bin"), false)
+        ],
+        list(&fs, "")
+    );
+    assert_eq!(vec![(OsString::from("q01"), true)], list(&fs, "map"));
+    assert_eq!(
+        vec![
+            (OsString::from("q01_0_0.nod"), false),
+            (OsString::from("q01_sky.dff"), false)
+        ],
+        list(&fs, "map/q01")
+    );
+    assert!(fs.entries_path(Path::new("model")).is_err());
+}
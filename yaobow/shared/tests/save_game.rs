@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use shared::openpal3::states::{
+    party::LEADER_ROLE_ID,
+    persistent_state::PersistentState,
+    save_game::{
+        migrate, ObjectSnapshot, SaveGameError, SceProcSnapshot, SceneSnapshot, ScriptSnapshot,
+        SAVE_VERSION,
+    },
+};
+
+const V1_SAVE: &str = r#"{
+    "app_name": "OpenPAL3",
+    "global_vars": {"-32768": 1},
+    "position": {"x": 1.0, "y": 2.0, "z": 3.0},
+    "scene": "q01",
+    "sub_scene": "q01"
+}"#;
+
+#[test]
+fn v1_saves_are_migrated() {
+    let state = PersistentState::from_json(V1_SAVE).unwrap();
+    assert_eq!(state.version(), SAVE_VERSION);
+    assert_eq!(state.role_controlled(), 0);
+    assert_eq!(state.scene_name().as_deref(), Some("q01"));
+    assert_eq!(state.get_global(-32768), Some(1));
+    assert!(state.bgm().is_none());
+    assert!(state.scene_state().is_none());
+    assert!(state.script().is_none());
+    assert!(state.inventory().is_untracked());
+    assert!(!state.party().roster_known());
+}
+
+#[test]
+fn newer_saves_are_rejected() {
+    let save = serde_json::json!({ "version": 99 });
+    assert!(matches!(
+        migrate(save),
+        Err(SaveGameError::UnsupportedVersion(99))
+    ));
+}
+
+#[test]
+fn non_object_saves_are_rejected() {
+    assert!(matches!(
+        migrate(serde_json::json!([1, 2])),
+        Err(SaveGameError::InvalidFormat)
+    ));
+    assert!(PersistentState::from_json("[]").is_err());
+}
+
+#[test]
+fn snapshots_round_trip() {
+    let mut state = PersistentState::from_json(V1_SAVE).unwrap();
+    let script = ScriptSnapshot {
+        sce_name: "q01".to_string(),
+        procs: vec![SceProcSnapshot {
+            proc_id: 1201,
+            program_counter: 42,
+            local_vars: HashMap::from([(3, 7)]),
+            dlgsel: 1,
+        }],
+    };
+    let scene_state = SceneSnapshot {
+        roles: vec![],
+        objects: vec![ObjectSnapshot {
+            id: 5,
+            visible: false,
+        }],
+    };
+
+    state.set_snapshot(
+        2,
+        Some("P02".to_string()),
+        Some(scene_state),
+        Some(script.clone()),
+    );
+
+    let json = serde_json::to_string(&state).unwrap();
+    let state = PersistentState::from_json(&json).unwrap();
+    assert_eq!(state.version(), SAVE_VERSION);
+    assert_eq!(state.role_controlled(), 2);
+    assert_eq!(state.bgm(), Some("P02"));
+    assert_eq!(state.script(), Some(&script));
+    assert_eq!(
+        state.scene_state().unwrap().objects,
+        [ObjectSnapshot {
+            id: 5,
+            visible: false
+        }]
+    );
+}
+
+#[test]
+fn saves_are_loaded_from_their_slot() {
+    let app_name = format!("OpenPAL3-test-{}", std::process::id());
+    let mut state = PersistentState::new(app_name.clone());
+    state.set_scene_name("q01".to_string(), "q01a".to_string());
+    state.set_global(-3, 5);
+    state.party_mut().join(1);
+    state.save(7);
+
+    let loaded = PersistentState::load(&app_name, 7);
+    let empty = PersistentState::load(&app_name, 8);
+    let slot_info = PersistentState::slot_info(&app_name, 7);
+    std::fs::remove_dir_all(shared::ydirs::save_dir().join(&app_name)).unwrap();
+
+    let loaded = loaded.unwrap();
+    assert_eq!(loaded.sub_scene_name().as_deref(), Some("q01a"));
+    assert_eq!(loaded.get_global(-3), Some(5));
+    assert_eq!(loaded.party().members(), [LEADER_ROLE_ID, 1]);
+    assert!(loaded.party().roster_known());
+    assert!(matches!(
+        empty.unwrap_err().downcast_ref::<SaveGameError>(),
+        Some(SaveGameError::EmptySlot(8))
+    ));
+    assert_eq!(slot_info.unwrap().scene.as_deref(), Some("q01"));
+}
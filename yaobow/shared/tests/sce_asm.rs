@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use encoding::{EncoderTrap, Encoding};
+use shared::{
+    openpal3::loaders::sce_loader::{
+        read_sce, write_sce, SceFile, SceLocalVar, SceProc, SceProcHeader, SceWriteError,
+    },
+    scripting::sce::asm::{assemble, disassemble, SceAsmError},
+};
+
+fn command(inst: &mut Vec<u8>, id: i16, flag: i16) {
+    inst.extend_from_slice(&id.to_le_bytes());
+    inst.extend_from_slice(&flag.to_le_bytes());
+}
+
+fn string(inst: &mut Vec<u8>, text: &str) {
+    let mut data = encoding::all::GBK
+        .encode(text, EncoderTrap::Strict)
+        .unwrap();
+    data.push(0);
+    inst.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    inst.extend_from_slice(&data);
+}
+
+fn create_sce() -> SceFile {
+    let mut inst = vec![];
+
+    // Idle 1.5
+    command(&mut inst, 1, 0);
+    inst.extend_from_slice(&1.5f32.to_le_bytes());
+
+    // Dlg "..."
+    command(&mut inst, 62, 0);
+    string(&mut inst, "仙剑奇侠传\"三\"");
+
+    // DlgSel [0 "是", 0 "否"]
+    command(&mut inst, 65, 0);
+    inst.extend_from_slice(&2u16.to_le_bytes());
+    inst.push(0);
+    string(&mut inst, "是");
+    inst.push(0);
+    string(&mut inst, "否");
+
+    // Dlg with a string that isn't valid GBK
+    command(&mut inst, 62, 0);
+    inst.extend_from_slice(&3u16.to_le_bytes());
+    inst.extend_from_slice(&[0xff, 0xfe, 0]);
+
+    // GEQ2 3, 4
+    command(&mut inst, 10, 3);
+    inst.extend_from_slice(&3i16.to_le_bytes());
+    inst.extend_from_slice(&4i16.to_le_bytes());
+
+    // Idle NaN
+    command(&mut inst, 1, 0);
+    inst.extend_from_slice(&0x7fc00001u32.to_le_bytes());
+
+    // Goto 0
+    command(&mut inst, 3, 0);
+    inst.extend_from_slice(&0u32.to_le_bytes());
+
+    // Unknown trailing data
+    inst.extend_from_slice(&[0x34, 0x12, 0, 0, 1]);
+
+    let mut procs = HashMap::new();
+    procs.insert(
+        1000,
+        SceProc {
+            id: 1000,
+            name: "main".to_string(),
+            raw_name: vec![],
+            local_vars: vec![SceLocalVar {
+                unknown: 1,
+                unknown_vec: vec![0, 1, 2],
+            }],
+            inst,
+        },
+    );
+    procs.insert(
+        1001,
+        SceProc {
+            id: 1001,
+            name: "空".to_string(),
+            raw_name: vec![],
+            local_vars: vec![],
+            inst: vec![],
+        },
+    );
+
+    SceFile {
+        proc_num: 2,
+        proc_headers: vec![
+            SceProcHeader {
+                id: 1000,
+                offset: 0,
+                name: "main_header".to_string(),
+                raw_name: vec![],
+            },
+            SceProcHeader {
+                id: 1001,
+                offset: 0,
+                name: "空".to_string(),
+                raw_name: vec![],
+            },
+        ],
+        procs,
+    }
+}
+
+fn assert_same_procs(expected: &SceFile, actual: &SceFile) {
+    assert_eq!(expected.proc_headers.len(), actual.proc_headers.len());
+    for (e, a) in expected.proc_headers.iter().zip(&actual.proc_headers) {
+        assert_eq!(e.id, a.id);
+        assert_eq!(e.name, a.name);
+
+        let e = &expected.procs[&e.id];
+        let a = &actual.procs[&a.id];
+        assert_eq!(e.name, a.name);
+        assert_eq!(e.inst, a.inst);
+        assert_eq!(e.local_vars.len(), a.local_vars.len());
+        for (ev, av) in e.local_vars.iter().zip(&a.local_vars) {
+            assert_eq!(ev.unknown, av.unknown);
+            assert_eq!(ev.unknown_vec, av.unknown_vec);
+        }
+    }
+}
+
+#[test]
+fn test_sce_disassemble_round_trip() {
+    let sce = create_sce();
+    let text = disassemble(&sce);
+
+    assert!(text.contains("L_0000:"));
+    assert!(text.contains("Goto L_0000"));
+    assert!(text.contains("GEQ2 3, 4"));
+    assert!(text.contains("Dlg \"仙剑奇侠传\\\"三\\\"\""));
+    assert!(text.contains("DlgSel [0 \"是\", 0 \"否\"]"));
+    assert!(text.contains("Dlg x\"fffe00\""));
+    assert!(text.contains("Idle f#7fc00001"));
+    assert!(text.contains(".bytes x\"3412000001\""));
+    assert!(text.contains("header \"main_header\""));
+
+    let assembled = assemble(&text).unwrap();
+    assert_same_procs(&sce, &assembled);
+    assert_eq!(disassemble(&assembled), text);
+}
+
+#[test]
+fn test_sce_write_read_round_trip() {
+    let mut sce = create_sce();
+    let offsets = sce.proc_offsets().unwrap();
+    for (header, offset) in sce.proc_headers.iter_mut().zip(offsets) {
+        header.offset = offset;
+    }
+
+    let mut data = vec![];
+    write_sce(&sce, &mut data).unwrap();
+    let read = read_sce(&mut Cursor::new(&data)).unwrap();
+
+    assert_same_procs(&sce, &read);
+    for (e, a) in sce.proc_headers.iter().zip(&read.proc_headers) {
+        assert_eq!(e.offset, a.offset);
+    }
+}
+
+/// The procs of `create_sce` laid out like a retail file: names are NUL
+/// terminated, header names are padded with leftover bytes rather than zeros,
+/// and the proc bodies aren't stored in header order.
+fn create_sce_bytes() -> Vec<u8> {
+    let sce = create_sce();
+    let mut data = b"SCE\0".to_vec();
+    data.push(1);
+    data.extend_from_slice(&2u16.to_le_bytes());
+
+    let header_size = 7 + 2 * (8 + 64);
+    let mut body = vec![];
+    let mut offsets = HashMap::new();
+    for id in [1001, 1000] {
+        offsets.insert(id, (header_size + body.len()) as u32);
+        let proc = &sce.procs[&id];
+        let mut name = encoding::all::GBK
+            .encode(&proc.name, EncoderTrap::Strict)
+            .unwrap();
+        name.push(0);
+        body.extend_from_slice(&proc.id.to_le_bytes());
+        body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        body.extend_from_slice(&name);
+        body.extend_from_slice(&(proc.local_vars.len() as u16).to_le_bytes());
+        for var in &proc.local_vars {
+            body.push(var.unknown);
+            body.extend_from_slice(&(var.unknown_vec.len() as u16).to_le_bytes());
+            body.extend_from_slice(&var.unknown_vec);
+        }
+
+        body.extend_from_slice(&(proc.inst.len() as u32).to_le_bytes());
+        body.extend_from_slice(&proc.inst);
+    }
+
+    for header in &sce.proc_headers {
+        let mut name = encoding::all::GBK
+            .encode(&header.name, EncoderTrap::Strict)
+            .unwrap();
+        name.push(0);
+        name.resize(64, 0xcd);
+        data.extend_from_slice(&header.id.to_le_bytes());
+        data.extend_from_slice(&offsets[&header.id].to_le_bytes());
+        data.extend_from_slice(&name);
+    }
+
+    data.extend_from_slice(&body);
+    data
+}
+
+#[test]
+fn test_sce_byte_exact_round_trip() {
+    let data = create_sce_bytes();
+    let sce = read_sce(&mut Cursor::new(&data)).unwrap();
+    assert_eq!("main_header", sce.proc_headers[0].name);
+    assert_eq!(b"main\0", sce.procs[&1000].raw_name.as_slice());
+
+    let mut written = vec![];
+    write_sce(&sce, &mut written).unwrap();
+    assert_eq!(data, written);
+
+    // Through the assembler as well
+    let text = disassemble(&sce);
+    assert!(text.starts_with("headers 1000, 1001\n"));
+    let assembled = assemble(&text).unwrap();
+    let mut written = vec![];
+    write_sce(&assembled, &mut written).unwrap();
+    assert_eq!(data, written);
+}
+
+#[test]
+fn test_sce_write_renamed_proc() {
+    let mut sce = read_sce(&mut Cursor::new(create_sce_bytes())).unwrap();
+    sce.proc_headers[0].name = "renamed".to_string();
+    sce.procs.get_mut(&1000).unwrap().name = "renamed".to_string();
+
+    let mut written = vec![];
+    write_sce(&sce, &mut written).unwrap();
+    let read = read_sce(&mut Cursor::new(&written)).unwrap();
+    assert_eq!("renamed", read.proc_headers[0].name);
+    assert_eq!(b"renamed\0", read.procs[&1000].raw_name.as_slice());
+
+    let mut header_name = b"renamed".to_vec();
+    header_name.resize(64, 0);
+    assert_eq!(header_name, read.proc_headers[0].raw_name);
+}
+
+#[test]
+fn test_sce_write_long_header_name() {
+    let mut sce = create_sce();
+    sce.proc_headers[0].name = "a".repeat(64);
+    assert!(write_sce(&sce, &mut vec![]).is_ok());
+
+    // 33 GBK characters take 66 bytes
+    sce.proc_headers[0].name = "空".repeat(33);
+    assert!(matches!(
+        write_sce(&sce, &mut vec![]),
+        Err(SceWriteError::NameTooLong(_))
+    ));
+}
+
+#[test]
+fn test_sce_assemble_errors() {
+    let text = "proc 1 \"a\"\n    Goto L_0010\nend\n";
+    assert!(matches!(
+        assemble(text),
+        Err(SceAsmError::UndefinedLabel(2, _))
+    ));
+
+    let text = "proc 1 \"a\"\n    NotACommand 1\nend\n";
+    assert!(matches!(
+        assemble(text),
+        Err(SceAsmError::UnknownCommand(2, _))
+    ));
+
+    let text = "proc 1 \"a\"\n    GEQ2 1\nend\n";
+    assert!(matches!(
+        assemble(text),
+        Err(SceAsmError::ArgumentCount(2, _, 2))
+    ));
+
+    let text = "proc 1 \"a\"\nL_0000:\nL_0000:\n    Idle 1.0\nend\n";
+    assert!(matches!(
+        assemble(text),
+        Err(SceAsmError::DuplicatedLabel(3, _))
+    ));
+
+    let text = "headers 1, 2\nproc 1 \"a\"\nend\n";
+    assert!(matches!(assemble(text), Err(SceAsmError::Syntax(1, _))));
+
+    let text = format!("proc 1 \"a\"\n    Dlg x\"{}\"\nend\n", "00".repeat(0x10000));
+    assert!(matches!(assemble(&text), Err(SceAsmError::TooLong(2, _))));
+}
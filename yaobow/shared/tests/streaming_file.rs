@@ -0,0 +1,109 @@
+use std::{
+    io::{Read, Seek, SeekFrom},
+    sync::Arc,
+};
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use shared::fs::{
+    shared_reader::SharedReader,
+    streaming_file::{InflateFile, StreamingFile},
+};
+
+fn content() -> Vec<u8> {
+    (0..200_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+fn create_streaming_file() -> StreamingFile {
+    let mut data = b"header".to_vec();
+    data.extend(content());
+    data.extend(b"trailer");
+    let reader: SharedReader = Arc::new(data);
+    StreamingFile::new(reader, 6, content().len() as u64)
+}
+
+fn create_inflate_file() -> InflateFile {
+    let compressed = compress_to_vec_zlib(&content(), 6);
+    let len = compressed.len() as u64;
+    let reader: SharedReader = Arc::new(compressed);
+    InflateFile::new(StreamingFile::new(reader, 0, len), content().len() as u64)
+}
+
+fn read_at<F: Read + Seek>(file: &mut F, pos: SeekFrom, size: usize) -> Vec<u8> {
+    file.seek(pos).unwrap();
+    let mut buf = vec![0; size];
+    file.read_exact(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_streaming_file_stays_in_window() {
+    let mut file = create_streaming_file();
+    let mut data = vec![];
+    file.read_to_end(&mut data).unwrap();
+    assert_eq!(content(), data);
+
+    let mut buf = [0; 4];
+    assert_eq!(0, file.read(&mut buf).unwrap());
+}
+
+#[test]
+fn test_streaming_file_seek() {
+    let expected = content();
+    let mut file = create_streaming_file();
+    assert_eq!(
+        expected[1000..1010],
+        read_at(&mut file, SeekFrom::Start(1000), 10)
+    );
+    assert_eq!(
+        expected[1020..1030],
+        read_at(&mut file, SeekFrom::Current(10), 10)
+    );
+    assert_eq!(
+        expected[expected.len() - 5..],
+        read_at(&mut file, SeekFrom::End(-5), 5)
+    );
+    assert!(file.seek(SeekFrom::Current(-1_000_000)).is_err());
+}
+
+#[test]
+fn test_inflate_file_reads_all() {
+    let mut file = create_inflate_file();
+    let mut data = vec![];
+    file.read_to_end(&mut data).unwrap();
+    assert_eq!(content(), data);
+}
+
+#[test]
+fn test_inflate_file_seek() {
+    let expected = content();
+    let mut file = create_inflate_file();
+    assert_eq!(
+        expected[150_000..150_100],
+        read_at(&mut file, SeekFrom::Start(150_000), 100)
+    );
+
+    // Seeking backward restarts the stream
+    assert_eq!(
+        expected[10..20],
+        read_at(&mut file, SeekFrom::Start(10), 10)
+    );
+    assert_eq!(
+        expected[70_020..70_030],
+        read_at(&mut file, SeekFrom::Current(70_000), 10)
+    );
+    assert_eq!(
+        expected[expected.len() - 8..],
+        read_at(&mut file, SeekFrom::End(-8), 8)
+    );
+    assert!(file.seek(SeekFrom::Current(-1_000_000)).is_err());
+}
+
+#[test]
+fn test_inflate_file_truncated_stream() {
+    let compressed = compress_to_vec_zlib(&content(), 6);
+    let len = compressed.len() as u64 / 2;
+    let reader: SharedReader = Arc::new(compressed);
+    let mut file = InflateFile::new(StreamingFile::new(reader, 0, len), content().len() as u64);
+    let mut data = vec![];
+    assert!(file.read_to_end(&mut data).is_err());
+}
@@ -0,0 +1,64 @@
+use std::{io::Read, path::Path, sync::Arc};
+
+use mini_fs::Store;
+use shared::fs::{
+    cpk::{CpkFs, CpkWriter},
+    fmb::fmb_fs::FmbFs,
+    imd::imd_fs::ImdFs,
+    pkg::pkg_fs::PkgFs,
+    sfb::sfb_fs::SfbFs,
+    streaming_file::PackedFile,
+    zpk::zpk_fs::ZpkFs,
+    zpkg::zpkg_fs::ZpkgFs,
+};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn test_archive_stores_are_send_sync() {
+    assert_send_sync::<CpkFs>();
+    assert_send_sync::<PkgFs>();
+    assert_send_sync::<FmbFs>();
+    assert_send_sync::<ImdFs>();
+    assert_send_sync::<SfbFs>();
+    assert_send_sync::<ZpkFs>();
+    assert_send_sync::<ZpkgFs>();
+    assert_send_sync::<PackedFile>();
+}
+
+#[test]
+fn test_cpk_concurrent_reads() {
+    let mut writer = CpkWriter::new(false);
+    for i in 0..8u8 {
+        writer.add_file(&format!("data/{}.bin", i), vec![i; 8192]);
+    }
+
+    let path = std::env::temp_dir().join(format!("yaobow_threads_{}.cpk", std::process::id()));
+    let mut file = std::fs::File::create(&path).unwrap();
+    writer.write(&mut file).unwrap();
+    drop(file);
+
+    let fs = Arc::new(CpkFs::new(&path).unwrap());
+    let handles: Vec<_> = (0..8u8)
+        .map(|i| {
+            let fs = fs.clone();
+            std::thread::spawn(move || {
+                for _ in 0..16 {
+                    let mut content = vec![];
+                    fs.open_path(Path::new(&format!("data/{}.bin", i)))
+                        .unwrap()
+                        .read_to_end(&mut content)
+                        .unwrap();
+                    assert_eq!(vec![i; 8192], content);
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    drop(fs);
+    let _ = std::fs::remove_file(&path);
+}
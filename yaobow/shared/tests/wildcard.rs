@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use mini_fs::MiniFs;
+use shared::fs::{
+    memory_file::MemoryFile,
+    plain_fs::{PlainArchive, PlainFileInfo, PlainFs},
+    wildcard::{expand, wildcard_match},
+};
+
+struct TestArchive {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl PlainArchive for TestArchive {
+    fn open<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<MemoryFile> {
+        let data = self
+            .files
+            .get(path.as_ref().to_str().unwrap())
+            .ok_or(std::io::Error::from(std::io::ErrorKind::NotFound))?;
+        Ok(MemoryFile::new(Cursor::new(data.clone())))
+    }
+
+    fn files(&self) -> Vec<String> {
+        self.files.keys().cloned().collect()
+    }
+
+    fn file_infos(&self) -> Vec<PlainFileInfo> {
+        vec![]
+    }
+}
+
+fn create_fs() -> MiniFs {
+    let files = [
+        "basedata/role.txt",
+        "basedata/item.txt",
+        "ROLE/101/101.mv3",
+        "role/102/102.MV3",
+        "role/102/102.tga",
+        "scene/m01/1/m01.pol",
+    ]
+    .iter()
+    .map(|name| (name.to_string(), vec![]))
+    .collect();
+    MiniFs::new(false).mount("/", PlainFs::new(TestArchive { files }))
+}
+
+fn paths(paths: &[&str]) -> Vec<PathBuf> {
+    paths.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn test_wildcard_match() {
+    assert!(wildcard_match("*.mv3", "101.MV3"));
+    assert!(wildcard_match("1?1.mv3", "101.mv3"));
+    assert!(wildcard_match("*", ""));
+    assert!(wildcard_match("a*b*c", "aXbYbZc"));
+    assert!(wildcard_match("**", "role"));
+    assert!(!wildcard_match("*.mv3", "101.mv3.bak"));
+    assert!(!wildcard_match("?", ""));
+    assert!(!wildcard_match("role", "roles"));
+}
+
+#[test]
+fn test_expand_without_wildcards() {
+    let fs = create_fs();
+    assert_eq!(
+        paths(&["/missing/file.pol"]),
+        expand(&fs, "missing\\file.pol")
+    );
+    assert_eq!(
+        paths(&["/basedata/role.txt"]),
+        expand(&fs, "/basedata/role.txt")
+    );
+}
+
+#[test]
+fn test_expand_wildcards() {
+    let fs = create_fs();
+    assert_eq!(
+        paths(&["/basedata/item.txt", "/basedata/role.txt"]),
+        expand(&fs, "basedata/*.txt")
+    );
+    assert_eq!(
+        paths(&["/ROLE/101/101.mv3", "/role/102/102.MV3"]),
+        expand(&fs, "role/*/*.mv3")
+    );
+    assert!(expand(&fs, "role/*.mv3").is_empty());
+}
+
+#[test]
+fn test_expand_recursive() {
+    let fs = create_fs();
+    assert_eq!(
+        paths(&["/ROLE/101/101.mv3", "/role/102/102.MV3"]),
+        expand(&fs, "**/*.mv3")
+    );
+    assert_eq!(
+        paths(&["/scene/m01/1/m01.pol"]),
+        expand(&fs, "scene/**/*.pol")
+    );
+}
+
+#[test]
+fn test_expand_trailing_recursive() {
+    let fs = create_fs();
+    assert_eq!(
+        paths(&[
+            "/ROLE/101/101.mv3",
+            "/role/102/102.MV3",
+            "/role/102/102.tga"
+        ]),
+        expand(&fs, "role/**")
+    );
+    assert_eq!(
+        paths(&[
+            "/ROLE/101/101.mv3",
+            "/basedata/item.txt",
+            "/basedata/role.txt",
+            "/role/102/102.MV3",
+            "/role/102/102.tga",
+            "/scene/m01/1/m01.pol",
+        ]),
+        expand(&fs, "**")
+    );
+}
@@ -2,6 +2,7 @@ use std::{
     cell::RefCell,
     path::{Path, PathBuf},
     rc::Rc,
+    sync::Arc,
     time::Duration,
 };
 
@@ -26,7 +27,7 @@ pub struct TitleSelectionDirector {
     audio: Rc<dyn AudioEngine>,
     input: Rc<RefCell<dyn InputEngine>>,
     selected_game: Rc<RefCell<Option<GameType>>>,
-    vfs: Option<Rc<MiniFs>>,
+    vfs: Option<Arc<MiniFs>>,
     dpi_scale: f32,
     props: RefCell<Option<Props>>,
 }
@@ -139,7 +140,7 @@ impl TitleSelectionDirector {
         }
     }
 
-    fn load_vfs() -> Option<Rc<MiniFs>> {
+    fn load_vfs() -> Option<Arc<MiniFs>> {
         let mut vfs = MiniFs::new(false);
         let zip = PathBuf::from(ASSET_PATH);
         let local1 = PathBuf::from("./yaobow/yaobow-assets");
@@ -148,15 +149,15 @@ impl TitleSelectionDirector {
         if Path::exists(&zip) {
             let local = ZipFs::new(std::fs::File::open(zip).unwrap());
             vfs = vfs.mount(PathBuf::from("/"), local);
-            Some(Rc::new(vfs))
+            Some(Arc::new(vfs))
         } else if Path::exists(&local1) {
             let local = LocalFs::new(&local1);
             vfs = vfs.mount(PathBuf::from("/"), local);
-            Some(Rc::new(vfs))
+            Some(Arc::new(vfs))
         } else if Path::exists(&local2) {
             let local = LocalFs::new(&local2);
             vfs = vfs.mount(PathBuf::from("/"), local);
-            Some(Rc::new(vfs))
+            Some(Arc::new(vfs))
         } else {
             None
         }
@@ -176,7 +177,7 @@ struct UiProps {
 
 impl TitleUi {
     fn new(
-        vfs: Rc<MiniFs>,
+        vfs: Arc<MiniFs>,
         factory: Rc<dyn ComponentFactory>,
         logo: &RgbaImage,
         outlines: Vec<Vec<Frame>>,
@@ -263,7 +264,7 @@ impl TitleUi {
 }
 
 struct GamePic {
-    vfs: Rc<MiniFs>,
+    vfs: Arc<MiniFs>,
     outlines: TitleOutlines,
     hovered_game: Rc<RefCell<Option<usize>>>,
     factory: Rc<dyn ComponentFactory>,
@@ -283,7 +284,7 @@ enum GamePicState {
 
 impl GamePic {
     fn new(
-        vfs: Rc<MiniFs>,
+        vfs: Arc<MiniFs>,
         factory: Rc<dyn ComponentFactory>,
         outlines: Vec<Vec<Frame>>,
         hovered_game: Rc<RefCell<Option<usize>>>,
@@ -507,6 +507,175 @@ IApplicationLoaderComponent: radiance::comdef::IApplicationLoaderComponent {
 
 // pub use ComObject_OpenPal5ApplicationLoaderComponent;
 
+// Class OpenGujianApplicationLoaderComponent
+
+#[allow(unused)]
+#[macro_export]
+macro_rules! ComObject_OpenGujianApplicationLoaderComponent {
+    ($impl_type: ty) => {
+
+#[allow(dead_code)]
+#[allow(non_snake_case)]
+#[allow(unused)]
+mod OpenGujianApplicationLoaderComponent_crosscom_impl {
+    use crate as yaobow;
+    use crosscom::ComInterface;
+use crosscom::IUnknownImpl;
+use crosscom::IObjectArrayImpl;
+use radiance::comdef::IComponentImpl;
+use radiance::comdef::IComponentContainerImpl;
+use radiance::comdef::IApplicationImpl;
+use radiance::comdef::IApplicationLoaderComponentImpl;
+use radiance::comdef::ISceneImpl;
+use radiance::comdef::IEntityImpl;
+use radiance::comdef::IStaticMeshComponentImpl;
+use radiance::comdef::IAnimatedMeshComponentImpl;
+use radiance::comdef::IDirectorImpl;
+use radiance::comdef::ISceneManagerImpl;
+use radiance::comdef::IArmatureComponentImpl;
+use radiance::comdef::ISkinnedMeshComponentImpl;
+use radiance::comdef::IHAnimBoneComponentImpl;
+use radiance::comdef::IAnimationEventObserverImpl;
+
+
+    #[repr(C)]
+    pub struct OpenGujianApplicationLoaderComponentCcw {
+        IApplicationLoaderComponent: radiance::comdef::IApplicationLoaderComponent,
+
+        ref_count: std::sync::atomic::AtomicU32,
+        pub inner: $impl_type,
+    }
+
+    unsafe extern "system" fn query_interface(
+        this: *const *const std::os::raw::c_void,
+        guid: uuid::Uuid,
+        retval: &mut *const *const std::os::raw::c_void,
+    ) -> std::os::raw::c_long {
+        let object = crosscom::get_object::<OpenGujianApplicationLoaderComponentCcw>(this);
+        match guid.as_bytes() {
+
+&crosscom::IUnknown::INTERFACE_ID => {
+    *retval = (object as *const *const std::os::raw::c_void).offset(0);
+    add_ref(object as *const *const std::os::raw::c_void);
+    crosscom::ResultCode::Ok as std::os::raw::c_long
+}
+
+
+&radiance::comdef::IComponent::INTERFACE_ID => {
+    *retval = (object as *const *const std::os::raw::c_void).offset(0);
+    add_ref(object as *const *const std::os::raw::c_void);
+    crosscom::ResultCode::Ok as std::os::raw::c_long
+}
+
+
+&radiance::comdef::IApplicationLoaderComponent::INTERFACE_ID => {
+    *retval = (object as *const *const std::os::raw::c_void).offset(0);
+    add_ref(object as *const *const std::os::raw::c_void);
+    crosscom::ResultCode::Ok as std::os::raw::c_long
+}
+
+
+            _ => crosscom::ResultCode::ENoInterface as std::os::raw::c_long,
+        }
+    }
+
+    unsafe extern "system" fn add_ref(this: *const *const std::os::raw::c_void) -> std::os::raw::c_long {
+        let object = crosscom::get_object::<OpenGujianApplicationLoaderComponentCcw>(this);
+        let previous = (*object).ref_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        (previous + 1) as std::os::raw::c_long
+    }
+
+    unsafe extern "system" fn release(this: *const *const std::os::raw::c_void) -> std::os::raw::c_long {
+        let object = crosscom::get_object::<OpenGujianApplicationLoaderComponentCcw>(this);
+
+        let previous = (*object).ref_count.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        if previous - 1 == 0 {
+            Box::from_raw(object as *mut OpenGujianApplicationLoaderComponentCcw);
+        }
+
+        (previous - 1) as std::os::raw::c_long
+    }
+
+
+
+    unsafe extern "system" fn on_loading (this: *const *const std::os::raw::c_void, ) -> () {
+
+        let __crosscom_object = crosscom::get_object::<OpenGujianApplicationLoaderComponentCcw>(this);
+        (*__crosscom_object).inner.on_loading().into()
+    }
+
+
+
+    unsafe extern "system" fn on_updating (this: *const *const std::os::raw::c_void, delta_sec: std::os::raw::c_float,
+) -> () {
+        let delta_sec: f32 = delta_sec.into()
+;
+
+        let __crosscom_object = crosscom::get_object::<OpenGujianApplicationLoaderComponentCcw>(this);
+        (*__crosscom_object).inner.on_updating(delta_sec.into()).into()
+    }
+
+
+
+    unsafe extern "system" fn on_unloading (this: *const *const std::os::raw::c_void, ) -> () {
+
+        let __crosscom_object = crosscom::get_object::<OpenGujianApplicationLoaderComponentCcw>(this);
+        (*__crosscom_object).inner.on_unloading().into()
+    }
+
+
+
+
+
+
+#[allow(non_upper_case_globals)]
+pub const GLOBAL_IApplicationLoaderComponentVirtualTable_CCW_FOR_OpenGujianApplicationLoaderComponent: radiance::comdef::IApplicationLoaderComponentVirtualTableCcw
+    = radiance::comdef::IApplicationLoaderComponentVirtualTableCcw {
+    offset: 0,
+    vtable: radiance::comdef::IApplicationLoaderComponentVirtualTable {
+        query_interface,
+add_ref,
+release,
+on_loading,
+on_updating,
+on_unloading,
+
+    },
+};
+
+
+
+
+    impl crosscom::ComObject for $impl_type {
+        type CcwType = OpenGujianApplicationLoaderComponentCcw;
+
+        fn create_ccw(self) -> Self::CcwType {
+            Self::CcwType {
+
+IApplicationLoaderComponent: radiance::comdef::IApplicationLoaderComponent {
+    vtable: &GLOBAL_IApplicationLoaderComponentVirtualTable_CCW_FOR_OpenGujianApplicationLoaderComponent.vtable
+        as *const radiance::comdef::IApplicationLoaderComponentVirtualTable,
+},
+
+                ref_count: std::sync::atomic::AtomicU32::new(0),
+                inner: self,
+            }
+        }
+
+        fn get_ccw(&self) -> &Self::CcwType {
+            unsafe {
+                let this = self as *const _ as *const u8;
+                let this = this.offset(-(crosscom::offset_of!(OpenGujianApplicationLoaderComponentCcw, inner) as isize));
+                &*(this as *const Self::CcwType)
+            }
+        }
+    }
+}
+    }
+}
+
+// pub use ComObject_OpenGujianApplicationLoaderComponent;
+
 // Class YaobowApplicationLoader
 
 #[allow(unused)]
@@ -991,3 +1160,160 @@ macro_rules! ComObject_MainMenuDirector {
 }
 
 // pub use ComObject_MainMenuDirector;
+
+// Class OpenGujianDirector
+
+#[allow(unused)]
+#[macro_export]
+macro_rules! ComObject_OpenGujianDirector {
+    ($impl_type: ty) => {
+        #[allow(dead_code)]
+        #[allow(non_snake_case)]
+        #[allow(unused)]
+        mod OpenGujianDirector_crosscom_impl {
+            use crate as yaobow;
+            use crosscom::ComInterface;
+            use crosscom::IObjectArrayImpl;
+            use crosscom::IUnknownImpl;
+            use radiance::comdef::IAnimatedMeshComponentImpl;
+            use radiance::comdef::IAnimationEventObserverImpl;
+            use radiance::comdef::IApplicationImpl;
+            use radiance::comdef::IApplicationLoaderComponentImpl;
+            use radiance::comdef::IArmatureComponentImpl;
+            use radiance::comdef::IComponentContainerImpl;
+            use radiance::comdef::IComponentImpl;
+            use radiance::comdef::IDirectorImpl;
+            use radiance::comdef::IEntityImpl;
+            use radiance::comdef::IHAnimBoneComponentImpl;
+            use radiance::comdef::ISceneImpl;
+            use radiance::comdef::ISceneManagerImpl;
+            use radiance::comdef::ISkinnedMeshComponentImpl;
+            use radiance::comdef::IStaticMeshComponentImpl;
+
+            #[repr(C)]
+            pub struct OpenGujianDirectorCcw {
+                IDirector: radiance::comdef::IDirector,
+
+                ref_count: std::sync::atomic::AtomicU32,
+                pub inner: $impl_type,
+            }
+
+            unsafe extern "system" fn query_interface(
+                this: *const *const std::os::raw::c_void,
+                guid: uuid::Uuid,
+                retval: &mut *const *const std::os::raw::c_void,
+            ) -> std::os::raw::c_long {
+                let object = crosscom::get_object::<OpenGujianDirectorCcw>(this);
+                match guid.as_bytes() {
+                    &crosscom::IUnknown::INTERFACE_ID => {
+                        *retval = (object as *const *const std::os::raw::c_void).offset(0);
+                        add_ref(object as *const *const std::os::raw::c_void);
+                        crosscom::ResultCode::Ok as std::os::raw::c_long
+                    }
+
+                    &radiance::comdef::IDirector::INTERFACE_ID => {
+                        *retval = (object as *const *const std::os::raw::c_void).offset(0);
+                        add_ref(object as *const *const std::os::raw::c_void);
+                        crosscom::ResultCode::Ok as std::os::raw::c_long
+                    }
+
+                    _ => crosscom::ResultCode::ENoInterface as std::os::raw::c_long,
+                }
+            }
+
+            unsafe extern "system" fn add_ref(
+                this: *const *const std::os::raw::c_void,
+            ) -> std::os::raw::c_long {
+                let object = crosscom::get_object::<OpenGujianDirectorCcw>(this);
+                let previous = (*object)
+                    .ref_count
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                (previous + 1) as std::os::raw::c_long
+            }
+
+            unsafe extern "system" fn release(
+                this: *const *const std::os::raw::c_void,
+            ) -> std::os::raw::c_long {
+                let object = crosscom::get_object::<OpenGujianDirectorCcw>(this);
+
+                let previous = (*object)
+                    .ref_count
+                    .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                if previous - 1 == 0 {
+                    Box::from_raw(object as *mut OpenGujianDirectorCcw);
+                }
+
+                (previous - 1) as std::os::raw::c_long
+            }
+
+            unsafe extern "system" fn activate(
+                this: *const *const std::os::raw::c_void,
+                scene_manager: *const *const std::os::raw::c_void,
+            ) -> () {
+                let scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager> =
+                    scene_manager.into();
+
+                let __crosscom_object = crosscom::get_object::<OpenGujianDirectorCcw>(this);
+                (*__crosscom_object)
+                    .inner
+                    .activate(scene_manager.into())
+                    .into()
+            }
+
+            fn update(
+                this: *const *const std::os::raw::c_void,
+                scene_manager: crosscom::ComRc<radiance::comdef::ISceneManager>,
+                ui: &imgui::Ui,
+                delta_sec: f32,
+            ) -> Option<crosscom::ComRc<radiance::comdef::IDirector>> {
+                unsafe {
+                    let __crosscom_object = crosscom::get_object::<OpenGujianDirectorCcw>(this);
+                    (*__crosscom_object)
+                        .inner
+                        .update(scene_manager, ui, delta_sec)
+                }
+            }
+
+            #[allow(non_upper_case_globals)]
+            pub const GLOBAL_IDirectorVirtualTable_CCW_FOR_OpenGujianDirector:
+                radiance::comdef::IDirectorVirtualTableCcw =
+                radiance::comdef::IDirectorVirtualTableCcw {
+                    offset: 0,
+                    vtable: radiance::comdef::IDirectorVirtualTable {
+                        query_interface,
+                        add_ref,
+                        release,
+                        activate,
+                        update,
+                    },
+                };
+
+            impl crosscom::ComObject for $impl_type {
+                type CcwType = OpenGujianDirectorCcw;
+
+                fn create_ccw(self) -> Self::CcwType {
+                    Self::CcwType {
+                        IDirector: radiance::comdef::IDirector {
+                            vtable: &GLOBAL_IDirectorVirtualTable_CCW_FOR_OpenGujianDirector.vtable
+                                as *const radiance::comdef::IDirectorVirtualTable,
+                        },
+
+                        ref_count: std::sync::atomic::AtomicU32::new(0),
+                        inner: self,
+                    }
+                }
+
+                fn get_ccw(&self) -> &Self::CcwType {
+                    unsafe {
+                        let this = self as *const _ as *const u8;
+                        let this = this
+                            .offset(-(crosscom::offset_of!(OpenGujianDirectorCcw, inner) as isize));
+                        &*(this as *const Self::CcwType)
+                    }
+                }
+            }
+        }
+    };
+}
+
+// pub use ComObject_OpenGujianDirector;
@@ -40,7 +40,7 @@ pub fn main() {
                 run_openpal5();
             }
             "--gujian" => {
-                run_opengujian();
+                run_opengujian(args.get(2).map(|s| s.as_str()));
             }
             "--test" => {
                 run_test();
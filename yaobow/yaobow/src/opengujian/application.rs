@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+
+use crosscom::ComRc;
+use radiance::{
+    application::Application,
+    comdef::{IApplication, IApplicationLoaderComponent, IComponentImpl},
+    scene::CoreScene,
+};
+use shared::{
+    config::YaobowConfig,
+    fs::init_virtual_fs,
+    loaders::{
+        nif::{create_entity_from_nif_model, NifLoaderConfig},
+        GujianTextureResolver,
+    },
+};
+
+use crate::ComObject_OpenGujianApplicationLoaderComponent;
+
+use super::director::OpenGujianDirector;
+
+pub struct OpenGujianApplicationLoader {
+    app: ComRc<IApplication>,
+    root_path: PathBuf,
+    model_path: String,
+}
+
+ComObject_OpenGujianApplicationLoaderComponent!(super::OpenGujianApplicationLoader);
+
+impl IComponentImpl for OpenGujianApplicationLoader {
+    fn on_loading(&self) {
+        self.app.set_title("OpenGujian - Project Yaobow");
+
+        let component_factory = self.app.engine().borrow().rendering_component_factory();
+        let input_engine = self.app.engine().borrow().input_engine();
+        let scene_manager = self.app.engine().borrow().scene_manager().clone();
+
+        let (vfs, _) = init_virtual_fs(&self.root_path, None);
+        let scene = CoreScene::create();
+        match create_entity_from_nif_model(
+            &component_factory,
+            &vfs,
+            Path::new("/").join(&self.model_path),
+            self.model_path.clone(),
+            true,
+            &NifLoaderConfig {
+                texture_resolver: &GujianTextureResolver,
+            },
+        ) {
+            Ok(entity) => scene.add_entity(entity),
+            Err(e) => log::error!("Cannot load nif {}: {:?}", self.model_path, e),
+        }
+
+        scene_manager.push_scene(scene);
+        scene_manager.set_director(ComRc::from_object(OpenGujianDirector::new(input_engine)));
+    }
+
+    fn on_unloading(&self) {}
+
+    fn on_updating(&self, _delta_sec: f32) {}
+}
+
+impl OpenGujianApplicationLoader {
+    pub fn create_application(config: &YaobowConfig, model_path: &str) -> ComRc<IApplication> {
+        let app = ComRc::<IApplication>::from_object(Application::new());
+        app.add_component(
+            IApplicationLoaderComponent::uuid(),
+            ComRc::from_object(Self {
+                app: app.clone(),
+                root_path: PathBuf::from(&config.asset_path),
+                model_path: model_path.to_string(),
+            }),
+        );
+
+        app
+    }
+}
@@ -0,0 +1,52 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crosscom::ComRc;
+use radiance::{
+    comdef::{IDirector, IDirectorImpl, ISceneManager},
+    input::InputEngine,
+    math::Vec3,
+    utils::free_view::FreeViewController,
+};
+
+use crate::ComObject_OpenGujianDirector;
+
+/// Shows the loaded scene with a free camera.
+pub struct OpenGujianDirector {
+    control: FreeViewController,
+}
+
+ComObject_OpenGujianDirector!(super::OpenGujianDirector);
+
+impl OpenGujianDirector {
+    pub fn new(input: Rc<RefCell<dyn InputEngine>>) -> Self {
+        Self {
+            control: FreeViewController::new(input),
+        }
+    }
+}
+
+impl IDirectorImpl for OpenGujianDirector {
+    fn activate(&self, scene_manager: ComRc<ISceneManager>) {
+        if let Some(scene) = scene_manager.scene() {
+            scene
+                .camera()
+                .borrow_mut()
+                .transform_mut()
+                .set_position(&Vec3::new(0., 200., 200.))
+                .look_at(&Vec3::new(0., 0., 0.));
+        }
+    }
+
+    fn update(
+        &self,
+        scene_manager: ComRc<ISceneManager>,
+        _ui: &imgui::Ui,
+        delta_sec: f32,
+    ) -> Option<ComRc<IDirector>> {
+        if let Some(scene) = scene_manager.scene() {
+            self.control.update(scene, delta_sec);
+        }
+
+        None
+    }
+}
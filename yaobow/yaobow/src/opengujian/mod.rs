@@ -1,21 +1,23 @@
-use std::io::Cursor;
+use application::OpenGujianApplicationLoader;
+use shared::config::YaobowConfig;
 
-use fileformats::{binrw::BinRead, nif::NifModel, npc::NpcInfoFile};
+mod application;
+mod director;
 
-pub fn test_nif(data: &[u8]) {
-    let model = NifModel::read(&mut Cursor::new(data));
+/// Renders a nif model of the game, the path is relative to the game folder
+/// set in `opengujian.toml`.
+pub fn run_opengujian(model_path: Option<&str>) {
+    let model_path = match model_path {
+        Some(path) => path,
+        None => {
+            log::error!("Usage: yaobow --gujian <model.nif>");
+            return;
+        }
+    };
 
-    // println!("{}", UnknownBlock::read(&mut cursor));
-    println!("{:?}", model);
-}
-
-pub fn run_opengujian() {
-    // zpk::zpk_test().unwrap();
-    // shared::fs::zpkg::zpkg_test();
-    // let data = std::fs::read("F:\\gujian_extracted\\101\\101.nif").unwrap();
-    // test_nif(&data);
-    let data =
-        std::fs::read("F:\\PAL4\\gamedata\\scenedata\\scenedata\\q01\\N01\\npcInfo.npc").unwrap();
-    let cam = NpcInfoFile::read(&mut Cursor::new(data));
-    println!("cam: {:?}", cam);
+    let config = YaobowConfig::load("opengujian.toml", "OPENGUJIAN").unwrap();
+    let app = OpenGujianApplicationLoader::create_application(&config, model_path);
+    log::info!("initializing with config {:?}", config);
+    app.initialize();
+    app.run();
 }
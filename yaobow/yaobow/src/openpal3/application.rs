@@ -7,13 +7,17 @@ use crosscom::ComRc;
 use radiance::application::Application;
 use radiance::comdef::{IApplication, IApplicationLoaderComponent, IComponentImpl};
 use shared::config::YaobowConfig;
+use shared::fs::manifest;
 use shared::openpal3::asset_manager::AssetManager;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 
 pub struct OpenPal3ApplicationLoader {
     app: ComRc<IApplication>,
     root_path: PathBuf,
+    mod_paths: Vec<PathBuf>,
+    edition_manifest_path: Option<PathBuf>,
     app_name: String,
 }
 
@@ -26,10 +30,19 @@ impl IComponentImpl for OpenPal3ApplicationLoader {
 
         let input_engine = self.app.engine().borrow().input_engine();
         let audio_engine = self.app.engine().borrow().audio_engine();
-        let vfs = shared::fs::init_virtual_fs(&self.root_path, None);
+        let (vfs, mount_report) =
+            shared::fs::init_virtual_fs_with_overlays(&self.root_path, &self.mod_paths, None);
+        if let Some(path) = &self.edition_manifest_path {
+            let editions = manifest::load_editions(self.root_path.join(path));
+            match manifest::detect_edition(&vfs, &editions) {
+                Some(edition) => log::info!("Detected game edition: {}", edition),
+                None => log::warn!("The game data doesn't match any known edition"),
+            }
+        }
+
         let asset_mgr = Rc::new(AssetManager::new(
             self.app.engine().borrow().rendering_component_factory(),
-            Rc::new(vfs),
+            Arc::new(vfs),
         ));
 
         let debug_layer = OpenPal3DebugLayer::new(input_engine.clone(), audio_engine.clone());
@@ -42,6 +55,7 @@ impl IComponentImpl for OpenPal3ApplicationLoader {
             asset_mgr.clone(),
             audio_engine,
             input_engine,
+            mount_report,
         );
         self.app
             .engine()
@@ -79,6 +93,8 @@ impl OpenPal3ApplicationLoader {
         Self {
             app,
             root_path,
+            mod_paths: config.mod_paths(),
+            edition_manifest_path: config.edition_manifest_path.as_ref().map(PathBuf::from),
             app_name: app_name.to_owned(),
         }
     }
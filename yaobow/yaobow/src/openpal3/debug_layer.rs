@@ -14,6 +14,8 @@ use shared::openpal3::{
     comdef::IAdventureDirector, directors::SceneManagerExtensions, scene::RoleController,
 };
 
+const FAVOR_ROLE_IDS: [i32; 5] = [1, 2, 3, 4, 5];
+
 pub struct OpenPal3DebugLayer {
     input_engine: Rc<RefCell<dyn InputEngine>>,
 
@@ -59,6 +61,7 @@ impl OpenPal3DebugLayer {
             TabBar::new("##debug_tab_bar").build(ui, || {
                 Self::build_nav_tab(scene_manager.clone(), ui, coord.as_ref());
                 Self::build_sce_tab(scene_manager.clone(), ui);
+                Self::build_favor_tab(scene_manager.clone(), ui);
             });
         });
     }
@@ -173,6 +176,38 @@ impl OpenPal3DebugLayer {
             }
         });
     }
+
+    fn build_favor_tab(scene_manager: ComRc<ISceneManager>, ui: &Ui) {
+        TabItem::new("Favor").build(ui, || {
+            if let Some(d) = scene_manager.director().as_ref() {
+                if let Some(d) = d.query_interface::<IAdventureDirector>() {
+                    let d = d.get();
+                    let mut sce_vm = d.sce_vm_mut();
+                    let global_state = sce_vm.global_state_mut();
+                    let names: Vec<String> = FAVOR_ROLE_IDS
+                        .iter()
+                        .map(|id| {
+                            global_state
+                                .role_table()
+                                .name(*id)
+                                .map_or_else(|| format!("Role {}", id), |n| n.to_string())
+                        })
+                        .collect();
+
+                    let mut persistent_state = global_state.persistent_state_mut();
+                    let favors = persistent_state.favors_mut();
+                    for (id, name) in FAVOR_ROLE_IDS.iter().zip(names) {
+                        let mut favor = favors.get(*id);
+                        if ui.input_int(&name, &mut favor).build() {
+                            favors.set(*id, favor);
+                        }
+                    }
+
+                    ui.text(format!("Favorite: {}", favors.favorite()));
+                }
+            }
+        });
+    }
 }
 
 impl DebugLayer for OpenPal3DebugLayer {
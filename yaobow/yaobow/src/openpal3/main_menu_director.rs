@@ -10,7 +10,12 @@ use radiance::{
     scene::CoreScene,
 };
 use shared::{
-    openpal3::{asset_manager::AssetManager, directors::AdventureDirector},
+    fs::mount_report::{MountReport, MountStatus},
+    openpal3::{
+        asset_manager::AssetManager,
+        directors::AdventureDirector,
+        states::persistent_state::{PersistentState, SaveSlotInfo},
+    },
     scripting::sce::vm::SceExecutionOptions,
 };
 
@@ -23,8 +28,14 @@ pub struct MainMenuDirector {
     audio_engine: Rc<dyn AudioEngine>,
     input_engine: Rc<RefCell<dyn InputEngine>>,
     main_theme_source: RefCell<Box<dyn AudioMemorySource>>,
+    mount_report: MountReport,
+    save_slots: RefCell<Vec<SaveSlotInfo>>,
+    load_error: RefCell<Option<String>>,
 }
 
+const APP_NAME: &str = "OpenPAL3";
+const SAVE_SLOTS: std::ops::Range<i32> = 1..5;
+
 ComObject_MainMenuDirector!(super::MainMenuDirector);
 
 impl MainMenuDirector {
@@ -32,6 +43,7 @@ impl MainMenuDirector {
         asset_mgr: Rc<AssetManager>,
         audio_engine: Rc<dyn AudioEngine>,
         input_engine: Rc<RefCell<dyn InputEngine>>,
+        mount_report: MountReport,
     ) -> Self {
         let data = asset_mgr.load_music_data("PI01");
         let mut main_theme_source = audio_engine.create_source();
@@ -43,8 +55,48 @@ impl MainMenuDirector {
             audio_engine,
             input_engine,
             main_theme_source: RefCell::new(main_theme_source),
+            mount_report,
+            save_slots: RefCell::new(vec![]),
+            load_error: RefCell::new(None),
+        }
+    }
+
+    fn refresh_save_slots(&self) {
+        *self.save_slots.borrow_mut() = SAVE_SLOTS
+            .filter_map(|slot| PersistentState::slot_info(APP_NAME, slot))
+            .collect();
+    }
+
+    fn save_slot_label(&self, slot: i32) -> String {
+        let slots = self.save_slots.borrow();
+        match slots.iter().find(|info| info.slot == slot) {
+            Some(SaveSlotInfo {
+                scene: Some(scene),
+                sub_scene: Some(sub_scene),
+                ..
+            }) => format!("存档 {}: {} {}", slot, scene, sub_scene),
+            Some(_) => format!("存档 {} (损坏)", slot),
+            None => format!("存档 {} (空)", slot),
         }
     }
+
+    fn render_mount_failures(&self, ui: &Ui) {
+        if !self.mount_report.has_failures() {
+            return;
+        }
+
+        ui.text_colored(
+            [1.0, 0.4, 0.4, 1.0],
+            "以下资源包无法加载，游戏可能无法正常运行：",
+        );
+        for record in self.mount_report.failed() {
+            if let MountStatus::Failed(reason) = &record.status {
+                ui.text_wrapped(format!("{}: {}", record.path.to_string_lossy(), reason));
+            }
+        }
+
+        ui.separator();
+    }
 }
 
 impl IDirectorImpl for MainMenuDirector {
@@ -52,6 +104,7 @@ impl IDirectorImpl for MainMenuDirector {
         debug!("MainMenuDirector activated");
         scene_manager.push_scene(CoreScene::create());
         self.main_theme_source.borrow_mut().restart();
+        self.refresh_save_slots();
     }
 
     fn update(
@@ -75,31 +128,55 @@ impl IDirectorImpl for MainMenuDirector {
             .always_auto_resize(true);
 
         if let Some(Some(director)) = window.build(|| {
+            self.render_mount_failures(ui);
+
             if ui.button("开始游戏") {
-                return Some(ComRc::from_object(AdventureDirector::new(
-                    "OpenPAL3",
+                match AdventureDirector::new(
+                    APP_NAME,
                     self.asset_mgr.clone(),
                     self.audio_engine.clone(),
                     self.input_engine.clone(),
                     Some(sce_options),
-                )));
+                ) {
+                    Ok(director) => {
+                        self.load_error.replace(None);
+                        return Some(ComRc::from_object(director));
+                    }
+                    Err(e) => {
+                        log::error!("Cannot start a new game: {:?}", e);
+                        self.load_error
+                            .replace(Some(format!("无法开始游戏: {}", e)));
+                        return None;
+                    }
+                }
             } else {
-                for i in 1..5 {
-                    if ui.button(&format!("存档 {}", i)) {
-                        let director = ComRc::from_object(
-                            AdventureDirector::load(
-                                "OpenPAL3",
-                                self.asset_mgr.clone(),
-                                self.audio_engine.clone(),
-                                self.input_engine.clone(),
-                                scene_manager,
-                                Some(sce_options),
-                                i,
-                            )
-                            .unwrap(),
-                        );
-
-                        return Some(director);
+                if let Some(error) = self.load_error.borrow().as_ref() {
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], error);
+                }
+
+                for i in SAVE_SLOTS {
+                    if ui.button(&self.save_slot_label(i)) {
+                        match AdventureDirector::load(
+                            APP_NAME,
+                            self.asset_mgr.clone(),
+                            self.audio_engine.clone(),
+                            self.input_engine.clone(),
+                            scene_manager.clone(),
+                            Some(sce_options),
+                            i,
+                        ) {
+                            Ok(director) => {
+                                self.load_error.replace(None);
+                                return Some(ComRc::from_object(director));
+                            }
+                            Err(e) => {
+                                log::error!("Cannot load save {}: {:?}", i, e);
+                                self.load_error
+                                    .replace(Some(format!("无法读取存档 {}: {}", i, e)));
+                                self.refresh_save_slots();
+                                return None;
+                            }
+                        }
                     }
                 }
                 None
@@ -13,11 +13,15 @@ pub fn run_openpal3() {
     #[cfg(android)]
     let config = YaobowConfig {
         asset_path: "/sdcard/Games/PAL3".to_string(),
+        mod_paths: vec![],
+        edition_manifest_path: None,
     };
 
     #[cfg(vita)]
     let config = YaobowConfig {
         asset_path: "ux0:games/PAL3".to_string(),
+        mod_paths: vec![],
+        edition_manifest_path: None,
     };
 
     let app = OpenPal3ApplicationLoader::create_application(&config, "OpenPAL3");
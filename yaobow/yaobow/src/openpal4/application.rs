@@ -33,7 +33,7 @@ impl IComponentImpl for OpenPal4ApplicationLoader {
         let scene_manager = self.app.engine().borrow().scene_manager().clone();
         let ui = self.app.engine().borrow().ui_manager();
 
-        let vfs = init_virtual_fs(self.root_path.to_str().unwrap(), None);
+        let (vfs, _) = init_virtual_fs(self.root_path.to_str().unwrap(), None);
         let loader = AssetLoader::new(
             self.app.engine().borrow().rendering_component_factory(),
             vfs,
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, rc::Rc};
+use std::{path::PathBuf, sync::Arc};
 
 use crosscom::ComRc;
 use radiance::{
@@ -31,10 +31,10 @@ impl IComponentImpl for OpenPal5ApplicationLoader {
         let scene_manager = self.app.engine().borrow().scene_manager().clone();
         let ui = self.app.engine().borrow().ui_manager();
 
-        let vfs = init_virtual_fs(self.root_path.to_str().unwrap(), None);
+        let (vfs, _) = init_virtual_fs(self.root_path.to_str().unwrap(), None);
         let loader = AssetLoader::new(
             self.app.engine().borrow().rendering_component_factory(),
-            Rc::new(vfs),
+            Arc::new(vfs),
         );
 
         let scene = Pal5Scene::load(&loader, "kuangfengzhai").unwrap();
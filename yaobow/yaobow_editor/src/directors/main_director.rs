@@ -153,11 +153,18 @@ impl IDirectorImpl for DevToolsDirector {
         let state = self.main_window(ui);
         match state {
             Some(DevToolsState::PreviewScene { cpk_name, scn_name }) => {
-                scene_manager.pop_scene();
-
-                let scene = self
+                let scene = match self
                     .asset_mgr
-                    .load_scn(cpk_name.as_str(), scn_name.as_str());
+                    .load_scn(cpk_name.as_str(), scn_name.as_str())
+                {
+                    Ok(scene) => scene,
+                    Err(e) => {
+                        log::error!("Cannot preview scene {} {}: {:?}", cpk_name, scn_name, e);
+                        return None;
+                    }
+                };
+
+                scene_manager.pop_scene();
                 scene
                     .camera()
                     .borrow_mut()
@@ -5,6 +5,7 @@ mod preview;
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use crosscom::ComRc;
 use directors::welcome_page::WelcomePageDirector;
@@ -47,7 +48,12 @@ impl SceneViewResourceView {
         };
 
         let factory = app.engine().borrow().rendering_component_factory();
-        let vfs = Rc::new(shared::fs::init_virtual_fs(&config.asset_path, pkg_key));
+        let (vfs, _) = shared::fs::init_virtual_fs_with_overlays(
+            &config.asset_path,
+            &config.mod_paths(),
+            pkg_key,
+        );
+        let vfs = Arc::new(vfs);
         let asset_mgr = AssetManager::new(factory.clone(), vfs.clone());
         let audio_engine = app.engine().borrow().audio_engine();
         let ui = Some(DevToolsDirector::new(
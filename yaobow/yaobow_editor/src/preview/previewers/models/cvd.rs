@@ -26,7 +26,7 @@ impl ModelLoader for CvdModelLoader {
     fn load_text(&self, vfs: &MiniFs, path: &Path) -> String {
         cvd_load_from_file(vfs, path)
             .map(|f| jsonify(&f))
-            .unwrap_or("Unsupported".to_string())
+            .unwrap_or_else(|e| e.to_string())
     }
 
     fn is_supported(&self, path: &Path) -> bool {
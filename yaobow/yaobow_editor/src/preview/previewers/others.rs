@@ -43,9 +43,9 @@ impl TextContentLoader for OthersTextContentLoader {
 fn try_load(vfs: &mini_fs::MiniFs, path: &std::path::Path) -> anyhow::Result<String> {
     let extension = get_extension(path);
     let text = match extension.as_deref() {
-        Some("scn") => jsonify(&scn_load_from_file(vfs, path)),
-        Some("nav") => jsonify(&nav_load_from_file(vfs, path)),
-        Some("sce") => jsonify(&sce_load_from_file(vfs, path)),
+        Some("scn") => jsonify(&scn_load_from_file(vfs, path)?),
+        Some("nav") => jsonify(&nav_load_from_file(vfs, path)?),
+        Some("sce") => jsonify(&sce_load_from_file(vfs, path)?),
         Some("anm") => jsonify(&load_anm(vfs, path)?),
         Some("nod") => jsonify(&NodFile::read(&mut vfs.open(path)?)?),
         _ => "Unsupported".to_string(),